@@ -0,0 +1,39 @@
+// Inputs of the glwe_decrypt guest: any words the guest reads as parameters and a GLWE decryption
+// input either fail a check of `zkfhe_core::checks` or rebuild into entities tfhe-rs decrypts
+// without panicking.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::checks::{
+    validate_encoding_for, validate_glwe_ciphertext, validate_glwe_secret_key,
+    validate_native_modulus,
+};
+use zkfhe_core::io::GlweDecryptionInput;
+use zkfhe_core::params::ProofParams;
+
+fuzz_target!(|words: Vec<u32>| {
+    let Ok((params, input)) =
+        risc0_zkvm::serde::from_slice::<(ProofParams, GlweDecryptionInput), _>(&words)
+    else {
+        return;
+    };
+    let GlweDecryptionInput { glwe_sk, ciphertext } = input;
+    let checks = validate_encoding_for(&params.encoding, u64::BITS)
+        .and_then(|()| validate_native_modulus(&params))
+        .and_then(|()| validate_glwe_secret_key(&glwe_sk, &params))
+        .and_then(|()| validate_glwe_ciphertext(&ciphertext, &params, 0));
+    if checks.is_err() {
+        return;
+    }
+
+    let glwe_sk =
+        GlweSecretKey::from_container(glwe_sk.data, PolynomialSize(glwe_sk.polynomial_size));
+    let ciphertext = GlweCiphertext::from_container(
+        ciphertext.data,
+        PolynomialSize(ciphertext.polynomial_size),
+        CiphertextModulus::new_native(),
+    );
+    let mut plaintexts = PlaintextList::new(0u64, PlaintextCount(params.polynomial_size));
+    decrypt_glwe_ciphertext(&glwe_sk, &ciphertext, &mut plaintexts);
+});
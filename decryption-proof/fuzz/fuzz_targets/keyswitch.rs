@@ -0,0 +1,30 @@
+// Inputs of the keyswitch guest: any words the guest reads as a keyswitch proof input either fail
+// a check of `zkfhe_core::checks` or rebuild into entities tfhe-rs keyswitches without panicking.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::checks::validate_keyswitch;
+use zkfhe_core::io::KeyswitchProofInput;
+
+fuzz_target!(|words: Vec<u32>| {
+    let Ok(KeyswitchProofInput { ksk, input, output }) =
+        risc0_zkvm::serde::from_slice::<KeyswitchProofInput, _>(&words)
+    else {
+        return;
+    };
+    if validate_keyswitch(&ksk, &input, &output).is_err() {
+        return;
+    }
+
+    let ksk = LweKeyswitchKey::from_container(
+        ksk.data,
+        DecompositionBaseLog(ksk.decomposition_base_log),
+        DecompositionLevelCount(ksk.decomposition_level_count),
+        LweSize(ksk.output_lwe_size),
+        CiphertextModulus::new(ksk.ciphertext_modulus),
+    );
+    let input = LweCiphertext::from_container(input.data, CiphertextModulus::new_native());
+    let mut keyswitched = LweCiphertext::new(0u64, ksk.output_lwe_size(), ksk.ciphertext_modulus());
+    keyswitch_lwe_ciphertext(&ksk, &input, &mut keyswitched);
+});
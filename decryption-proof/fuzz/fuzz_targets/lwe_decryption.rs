@@ -0,0 +1,49 @@
+// Inputs of the decrypt guest: any words the guest reads as parameters, a request ID and a secret
+// key, with any bytes it reads as the public input frame, either fail the decoding of the
+// ciphertext or a check of `zkfhe_core::checks`, or rebuild into entities tfhe-rs decrypts without
+// panicking.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::canonical::decode_lwe_ciphertext;
+use zkfhe_core::checks::{
+    checked_big_lwe_dimension, validate_ciphertext_modulus, validate_encoding_for,
+    validate_lwe_ciphertext, validate_lwe_secret_key,
+};
+use zkfhe_core::io::LweSecretKeyData;
+use zkfhe_core::params::ProofParams;
+
+fuzz_target!(|input: (Vec<u32>, Vec<u8>)| {
+    let (words, public_input) = input;
+    // The guest reads the parameters, the request ID it only echoes, then the secret key, the same
+    // words as the triple
+    let Ok((params, _, lwe_sk)) = risc0_zkvm::serde::from_slice::<
+        (ProofParams, Option<[u8; 32]>, LweSecretKeyData),
+        _,
+    >(&words) else {
+        return;
+    };
+    let Some(ciphertext) = decode_lwe_ciphertext(&public_input) else {
+        return;
+    };
+    let Some(big_lwe_dimension) = checked_big_lwe_dimension(&params) else {
+        return;
+    };
+    let checks = validate_encoding_for(&params.encoding, u64::BITS)
+        .and_then(|()| validate_ciphertext_modulus(&params))
+        .and_then(|()| validate_lwe_secret_key(&lwe_sk, big_lwe_dimension))
+        .and_then(|()| {
+            validate_lwe_ciphertext(&ciphertext, big_lwe_dimension, params.ciphertext_modulus)
+        });
+    if checks.is_err() {
+        return;
+    }
+
+    let lwe_sk = LweSecretKey::from_container(lwe_sk.data);
+    let ciphertext = LweCiphertext::from_container(
+        ciphertext.data,
+        CiphertextModulus::new(ciphertext.ciphertext_modulus),
+    );
+    decrypt_lwe_ciphertext(&lwe_sk, &ciphertext);
+});
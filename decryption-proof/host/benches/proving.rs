@@ -0,0 +1,181 @@
+// Proving cost of the batch decryption guest in keyswitch mode across parameter sizes.
+//
+// Every combination of polynomial size, LWE dimension and keyswitch decomposition level of the
+// sweep is executed without a proof, and proven as well when `BENCH_MODES` contains `prove`. One
+// CSV row per run is printed and written to `BENCH_CSV` (default `proving.csv`):
+//
+//     BENCH_MODES=execute,prove BENCH_BATCH=4 cargo bench -p host --bench proving
+//
+// The keys are generated from a fixed seed, and the parameters only size the inputs, they are not
+// meant to be secure.
+use std::error::Error;
+use std::fmt::Write as _;
+use std::time::Instant;
+
+use host::io::{lwe_ciphertext_data, lwe_keyswitch_key_data, lwe_secret_key_data};
+use host::params::{self, TfheParams};
+use host::proof_kind::ProofKind;
+use host::seed::DeterministicSeeder;
+use risc0_zkvm::{default_executor, default_prover, ExecutorEnv};
+use tfhe::core_crypto::commons::math::random::Seeder;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::{BatchItem, DecryptionInput, KeyswitchInput};
+use zkfhe_core::params::ProofParams;
+
+const POLYNOMIAL_SIZES: [usize; 3] = [512, 1024, 2048];
+const LWE_DIMENSIONS: [usize; 3] = [512, 742, 1024];
+/// Keyswitch decomposition levels, each with the base log keeping `base_log * level` at 15 bits
+const KS_DECOMPOSITIONS: [(usize, usize); 3] = [(1, 15), (3, 5), (5, 3)];
+
+const CSV_HEADER: &str = "mode,polynomial_size,lwe_dimension,ks_level,ks_base_log,batch_size,\
+                          segments,user_cycles,total_cycles,wall_time_ms";
+
+struct Run {
+    segments: usize,
+    user_cycles: u64,
+    total_cycles: u64,
+}
+
+fn sweep_params(
+    polynomial_size: usize,
+    lwe_dimension: usize,
+    ks_level: usize,
+    ks_base_log: usize,
+) -> ProofParams {
+    ProofParams {
+        name: format!("bench_n{lwe_dimension}_N{polynomial_size}_ks{ks_level}"),
+        lwe_dimension,
+        polynomial_size,
+        ks_base_log,
+        ks_level,
+        ..params::toy()
+    }
+}
+
+/// The environment of a batch of `batch_size` ciphertexts under the big key, keyswitched and
+/// decrypted by the guest.
+fn batch_env(
+    params: &ProofParams,
+    batch_size: u32,
+) -> Result<ExecutorEnv<'static>, Box<dyn Error>> {
+    let mut seeder = DeterministicSeeder::new([7; 32]);
+    let mut secret_generator =
+        SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+    let mut encryption_generator =
+        EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), &mut seeder);
+
+    let small_lwe_sk =
+        LweSecretKey::generate_new_binary(params.lwe_dimension(), &mut secret_generator);
+    let big_lwe_sk = GlweSecretKey::generate_new_binary(
+        params.glwe_dimension(),
+        params.polynomial_size(),
+        &mut secret_generator,
+    )
+    .into_lwe_secret_key();
+    let ksk = allocate_and_generate_new_lwe_keyswitch_key(
+        &big_lwe_sk,
+        &small_lwe_sk,
+        params.ks_base_log(),
+        params.ks_level(),
+        params.lwe_noise(),
+        params.ciphertext_modulus(),
+        &mut encryption_generator,
+    );
+
+    let encoding = params.encoding;
+    let mut builder = ExecutorEnv::builder();
+    builder.write(params)?.write(&DecryptionInput {
+        big_lwe_sk: lwe_secret_key_data(&big_lwe_sk),
+        keyswitch: Some(KeyswitchInput {
+            ksk: lwe_keyswitch_key_data(&ksk),
+            small_lwe_sk: lwe_secret_key_data(&small_lwe_sk),
+        }),
+        seed_commitment: None,
+        circuit_digest: None,
+        batch_size,
+    })?;
+    for index in 0..u64::from(batch_size) {
+        let message = index % encoding.message_space();
+        let ciphertext = allocate_and_encrypt_new_lwe_ciphertext(
+            &big_lwe_sk,
+            Plaintext(message * encoding.delta()),
+            params.glwe_noise(),
+            params.ciphertext_modulus(),
+            &mut encryption_generator,
+        );
+        builder.write(&BatchItem {
+            cleartext_multiplication_result: message,
+            pbs_multiplication_ct: lwe_ciphertext_data(&ciphertext),
+        })?;
+    }
+    Ok(builder.build()?)
+}
+
+fn execute(env: ExecutorEnv<'_>) -> Result<Run, Box<dyn Error>> {
+    let session = default_executor().execute(env, ProofKind::BatchDecrypt.elf())?;
+    let user_cycles = session
+        .segments
+        .iter()
+        .map(|segment| u64::from(segment.cycles))
+        .sum();
+    let total_cycles = session
+        .segments
+        .iter()
+        .map(|segment| 1u64 << segment.po2)
+        .sum();
+    Ok(Run {
+        segments: session.segments.len(),
+        user_cycles,
+        total_cycles,
+    })
+}
+
+fn prove(env: ExecutorEnv<'_>) -> Result<Run, Box<dyn Error>> {
+    let prove_info = default_prover().prove(env, ProofKind::BatchDecrypt.elf())?;
+    Ok(Run {
+        segments: prove_info.stats.segments,
+        user_cycles: prove_info.stats.user_cycles,
+        total_cycles: prove_info.stats.total_cycles,
+    })
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let modes = std::env::var("BENCH_MODES").unwrap_or_else(|_| "execute".to_string());
+    let modes: Vec<&str> = modes.split(',').map(str::trim).collect();
+    if let Some(mode) = modes.iter().find(|mode| !matches!(**mode, "execute" | "prove")) {
+        return Err(format!("unknown mode {mode}, expected execute or prove").into());
+    }
+    let batch_size: u32 = std::env::var("BENCH_BATCH").map_or(Ok(1), |batch| batch.parse())?;
+    let csv_path = std::env::var("BENCH_CSV").unwrap_or_else(|_| "proving.csv".to_string());
+
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    println!("{CSV_HEADER}");
+    for polynomial_size in POLYNOMIAL_SIZES {
+        for lwe_dimension in LWE_DIMENSIONS {
+            for (ks_level, ks_base_log) in KS_DECOMPOSITIONS {
+                let params = sweep_params(polynomial_size, lwe_dimension, ks_level, ks_base_log);
+                for &mode in &modes {
+                    let env = batch_env(&params, batch_size)?;
+                    let start = Instant::now();
+                    let run = match mode {
+                        "execute" => execute(env)?,
+                        _ => prove(env)?,
+                    };
+                    let wall_time_ms = start.elapsed().as_millis();
+                    let row = format!(
+                        "{mode},{polynomial_size},{lwe_dimension},{ks_level},{ks_base_log},\
+                         {batch_size},{},{},{},{wall_time_ms}",
+                        run.segments, run.user_cycles, run.total_cycles
+                    );
+                    println!("{row}");
+                    writeln!(csv, "{row}")?;
+                }
+            }
+        }
+    }
+
+    std::fs::write(&csv_path, csv)?;
+    println!("Results written to {csv_path}");
+    Ok(())
+}
@@ -0,0 +1,59 @@
+// End to end flow of an FHE application: a client encrypts two numbers with the shortint API, a
+// server adds them homomorphically without the secret key, and the client proves the decryption
+// of the sum, which anyone holding the ciphertext can check against the receipt:
+//
+//     cargo run -p host --features dev-mode --example shortint_add -- 2 3
+//
+// The operands default to 2 and 3, an optional third argument selects the `PARAM_MESSAGE_*`
+// preset, `PARAM_MESSAGE_2_CARRY_2_KS_PBS` by default. Drop `dev-mode` for a real proof.
+use std::error::Error;
+
+use host::params::shortint_preset;
+use host::shortint::{
+    check_shortint_decryption, prove_shortint_decryption, shortint_secret_key_id,
+};
+use tfhe::shortint::gen_keys;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // Fake receipts are only produced, and accepted, by a host built with the dev-mode feature
+    #[cfg(feature = "dev-mode")]
+    std::env::set_var("RISC0_DEV_MODE", "1");
+
+    let mut args = std::env::args().skip(1);
+    let lhs: u64 = args.next().map_or(Ok(2), |arg| arg.parse())?;
+    let rhs: u64 = args.next().map_or(Ok(3), |arg| arg.parse())?;
+    let preset = args.next().unwrap_or_else(|| "PARAM_MESSAGE_2_CARRY_2_KS_PBS".to_string());
+    let shortint_params =
+        shortint_preset(&preset).ok_or_else(|| format!("{preset} is not a shortint preset"))?;
+
+    let message_modulus = shortint_params.message_modulus.0 as u64;
+    if lhs >= message_modulus || rhs >= message_modulus {
+        return Err(format!("{preset} encrypts messages below {message_modulus}").into());
+    }
+
+    // Client: generate the keys and encrypt the operands
+    let (client_key, server_key) = gen_keys(shortint_params);
+    let lhs_ct = client_key.encrypt(lhs);
+    let rhs_ct = client_key.encrypt(rhs);
+
+    // Server: add the ciphertexts, the default operations clear the carries of the result, which
+    // holds the sum modulo the message modulus
+    let sum_ct = server_key.add(&lhs_ct, &rhs_ct);
+
+    // Client: prove the decryption of the sum the server returned
+    println!("Proving the decryption of {lhs} + {rhs} under {preset}…");
+    let receipt = prove_shortint_decryption(&client_key, &sum_ct)?;
+
+    // Verifier: check the receipt against the ciphertext of the sum and the ID of the client key,
+    // which the client publishes with its ciphertexts
+    let secret_key_id = shortint_secret_key_id(&client_key, &sum_ct);
+    let sum = check_shortint_decryption(&receipt, &sum_ct, &secret_key_id)?;
+    let expected = (lhs + rhs) % message_modulus;
+    if sum != expected || sum != client_key.decrypt(&sum_ct) {
+        return Err(format!("proven sum {sum}, expected {expected}").into());
+    }
+
+    println!("Proven: {lhs} + {rhs} = {sum} (mod {message_modulus})");
+    println!("Journal: {} bytes", receipt.journal.bytes.len());
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::{default_prover, ExecutorEnv, InnerReceipt, ProverOpts, Receipt};
+use zkfhe_core::io::{AggregatedReceipt, AggregationInput};
+use zkfhe_core::journal::AggregationJournal;
+
+use crate::error::ProofError;
+use crate::hashing::{aggregated_receipt_leaf, merkle_root};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+pub(crate) fn image_id_words(image_id: &Digest) -> [u32; 8] {
+    let mut words = [0u32; 8];
+    words.copy_from_slice(image_id.as_words());
+    words
+}
+
+/// Prove that every receipt verifies against its image ID, in a single succinct receipt committing
+/// a Merkle root of their journals.
+///
+/// The inner receipts are the assumptions of the aggregation guest, composite receipts are first
+/// compressed to succinct ones so the prover can resolve them.
+pub fn prove_aggregation(receipts: &[(Digest, Receipt)]) -> Result<Receipt, ProofError> {
+    let prover = default_prover();
+    let opts = ProverOpts::succinct();
+
+    let mut env_builder = ExecutorEnv::builder();
+    for (_, receipt) in receipts {
+        let receipt = match &receipt.inner {
+            InnerReceipt::Composite(_) => {
+                prover.compress(&opts, receipt).map_err(ProofError::proving)?
+            }
+            _ => receipt.clone(),
+        };
+        env_builder.add_assumption(receipt);
+    }
+    let env = env_builder
+        .write(&AggregationInput {
+            receipts: receipts
+                .iter()
+                .map(|(image_id, receipt)| AggregatedReceipt {
+                    image_id: image_id_words(image_id),
+                    journal: receipt.journal.bytes.clone(),
+                })
+                .collect(),
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = prover
+        .prove_with_opts(env, ProofKind::Aggregate.elf(), &opts)
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify an aggregated receipt and check that it covers exactly the given journals, in order,
+/// each produced by the guest of its image ID.
+pub fn check_aggregation(
+    receipt: &Receipt,
+    journals: &[(Digest, &[u8])],
+) -> Result<AggregationJournal, ProofError> {
+    verify_receipt(receipt, ProofKind::Aggregate.image_id())?;
+    let journal: AggregationJournal = decode_payload(receipt, ProofKind::Aggregate)?;
+
+    if journal.receipt_count as usize != journals.len() {
+        return Err(ProofError::Verification(format!(
+            "aggregated {} receipts, expected {}",
+            journal.receipt_count,
+            journals.len()
+        )));
+    }
+    let leaves: Vec<[u8; 32]> = journals
+        .iter()
+        .map(|(image_id, journal)| aggregated_receipt_leaf(&image_id_words(image_id), journal))
+        .collect();
+    if journal.journals_root != merkle_root(&leaves) {
+        return Err(ProofError::Verification("aggregated journals root mismatch".to_string()));
+    }
+
+    Ok(journal)
+}
@@ -0,0 +1,187 @@
+//! Audit bundles: everything needed to re-execute the guest of a proof and reproduce the journal
+//! of its receipt.
+//!
+//! A bundle directory holds the exact bytes every shard's guest read from its stdin, the parameter
+//! set, every receipt and a [`MANIFEST_FILE`] recording the guest, its image ID and the SHA-256 of
+//! every file and journal. The guests are deterministic, so executing the recorded stdin with the
+//! guest of the recorded image ID commits the journal of the receipt byte for byte.
+//!
+//! The recorded inputs hold the secret key the guest decrypted with, keep the directory private.
+
+use std::fs;
+use std::path::Path;
+
+use risc0_zkvm::{default_executor, ExecutorEnv, Receipt};
+use serde::{Deserialize, Serialize};
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::hashing::{params_digest, sha256_bytes};
+use crate::io::append_framed_container;
+use crate::proof_kind::ProofKind;
+use crate::verification::verify_receipt;
+
+/// Name of the manifest in a bundle directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Name of the parameter set in a bundle directory, loadable with `--params`.
+pub const PARAMS_FILE: &str = "params.json";
+
+/// The bytes a guest reads from its stdin, serialized as `ExecutorEnvBuilder::write` and
+/// `write_slice` would, so the same bytes are proven and recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GuestStdin {
+    bytes: Vec<u8>,
+}
+
+impl GuestStdin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `value` in the risc0 serde format the guests read with `env::read`.
+    pub fn write<T: Serialize>(&mut self, value: &T) -> Result<&mut Self, ProofError> {
+        let words = risc0_zkvm::serde::to_vec(value).map_err(ProofError::serialization)?;
+        self.write_words(&words);
+        Ok(self)
+    }
+
+    pub fn write_words(&mut self, words: &[u32]) -> &mut Self {
+        self.bytes.extend(words.iter().flat_map(|word| word.to_le_bytes()));
+        self
+    }
+
+    /// Append a raw container in frames of at most [`zkfhe_core::io::FRAME_LEN`] values, encoded in
+    /// parallel, see [`crate::io::append_framed_container`].
+    pub fn write_framed_container(&mut self, container: &[u64]) -> &mut Self {
+        append_framed_container(&mut self.bytes, container);
+        self
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// One receipt of a bundle and the stdin its guest was proven on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditShard {
+    pub stdin: String,
+    pub stdin_sha256: String,
+    pub receipt: String,
+    pub receipt_sha256: String,
+    pub journal_sha256: String,
+}
+
+/// The manifest of a bundle, every digest hex encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditManifest {
+    pub guest: String,
+    pub image_id: String,
+    pub params: String,
+    pub params_digest: String,
+    pub shards: Vec<AuditShard>,
+}
+
+/// Write the bundle of the receipts of `kind` proven on `shards` under `params` to `dir`.
+pub fn write_audit_bundle(
+    dir: &Path,
+    kind: ProofKind,
+    params: &ProofParams,
+    shards: &[(&GuestStdin, &Receipt)],
+) -> Result<AuditManifest, ProofError> {
+    fs::create_dir_all(dir)?;
+    let params_json = serde_json::to_vec_pretty(params).map_err(ProofError::serialization)?;
+    fs::write(dir.join(PARAMS_FILE), params_json)?;
+
+    let mut audit_shards = Vec::with_capacity(shards.len());
+    for (index, (stdin, receipt)) in shards.iter().enumerate() {
+        let stdin_file = format!("stdin.{index}.bin");
+        let receipt_file = format!("receipt.{index}.bin");
+        let receipt_bytes = bincode::serialize(receipt).map_err(ProofError::serialization)?;
+        fs::write(dir.join(&stdin_file), stdin.bytes())?;
+        fs::write(dir.join(&receipt_file), &receipt_bytes)?;
+        audit_shards.push(AuditShard {
+            stdin: stdin_file,
+            stdin_sha256: hex::encode(sha256_bytes(stdin.bytes())),
+            receipt: receipt_file,
+            receipt_sha256: hex::encode(sha256_bytes(&receipt_bytes)),
+            journal_sha256: hex::encode(sha256_bytes(&receipt.journal.bytes)),
+        });
+    }
+
+    let manifest = AuditManifest {
+        guest: kind.guest_name().to_string(),
+        image_id: hex::encode(kind.image_id().as_bytes()),
+        params: PARAMS_FILE.to_string(),
+        params_digest: hex::encode(params_digest(params)),
+        shards: audit_shards,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(ProofError::serialization)?;
+    fs::write(dir.join(MANIFEST_FILE), manifest_json)?;
+    Ok(manifest)
+}
+
+/// Read `file` of a bundle and check it against its recorded digest.
+fn read_checked(dir: &Path, file: &str, sha256: &str) -> Result<Vec<u8>, ProofError> {
+    let bytes = fs::read(dir.join(file))?;
+    if hex::encode(sha256_bytes(&bytes)) != sha256 {
+        return Err(ProofError::Verification(format!("{file} does not match its digest")));
+    }
+    Ok(bytes)
+}
+
+/// Re-execute every shard of the bundle in `dir` with the guest of this build and check it
+/// commits the journal of the recorded receipt, which must verify against the recorded image ID.
+pub fn replay_audit_bundle(dir: &Path) -> Result<AuditManifest, ProofError> {
+    let manifest: AuditManifest = serde_json::from_slice(&fs::read(dir.join(MANIFEST_FILE))?)
+        .map_err(ProofError::serialization)?;
+    let kind = ProofKind::from_guest_name(&manifest.guest)
+        .ok_or_else(|| ProofError::Verification(format!("unknown guest {}", manifest.guest)))?;
+    let image_id = hex::encode(kind.image_id().as_bytes());
+    if image_id != manifest.image_id {
+        return Err(ProofError::Verification(format!(
+            "{} is {image_id} in this build, the bundle was proven with {}",
+            manifest.guest, manifest.image_id
+        )));
+    }
+
+    let params: ProofParams = serde_json::from_slice(&fs::read(dir.join(&manifest.params))?)
+        .map_err(ProofError::serialization)?;
+    if hex::encode(params_digest(&params)) != manifest.params_digest {
+        return Err(ProofError::Verification(format!(
+            "{} does not match its digest",
+            manifest.params
+        )));
+    }
+
+    for shard in &manifest.shards {
+        let stdin = read_checked(dir, &shard.stdin, &shard.stdin_sha256)?;
+        let receipt: Receipt =
+            bincode::deserialize(&read_checked(dir, &shard.receipt, &shard.receipt_sha256)?)
+                .map_err(ProofError::serialization)?;
+        verify_receipt(&receipt, kind.image_id())?;
+        if hex::encode(sha256_bytes(&receipt.journal.bytes)) != shard.journal_sha256 {
+            return Err(ProofError::Verification(format!(
+                "journal of {} does not match its digest",
+                shard.receipt
+            )));
+        }
+
+        let env = ExecutorEnv::builder()
+            .write_slice(&stdin)
+            .build()
+            .map_err(ProofError::proving)?;
+        let session = default_executor()
+            .execute(env, kind.elf())
+            .map_err(ProofError::proving)?;
+        if session.journal.bytes != receipt.journal.bytes {
+            return Err(ProofError::Verification(format!(
+                "executing {} does not reproduce the journal of {}",
+                shard.stdin, shard.receipt
+            )));
+        }
+    }
+
+    Ok(manifest)
+}
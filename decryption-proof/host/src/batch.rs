@@ -0,0 +1,96 @@
+//! Inputs of the batch proofs, split in shards proven in their own receipts.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use zkfhe_core::budget::{batch_budget, pbs_budget};
+use zkfhe_core::io::{BatchItem, DecryptionInput, PbsBatchItem, PbsDecryptionInput};
+use zkfhe_core::params::ProofParams;
+
+use crate::audit::GuestStdin;
+use crate::error::ProofError;
+use crate::proof_kind::ProofKind;
+
+/// Input of a batch proof, for the decryption-only guest or the PBS guest.
+pub enum BatchInput {
+    Decryption {
+        input: DecryptionInput,
+        batch_items: Vec<BatchItem>,
+    },
+    Pbs {
+        input: PbsDecryptionInput,
+        bsk_container: Arc<Vec<u64>>,
+        batch_items: Vec<PbsBatchItem>,
+    },
+}
+
+impl BatchInput {
+    pub fn proof_kind(&self) -> ProofKind {
+        match self {
+            Self::Decryption { .. } => ProofKind::BatchDecrypt,
+            Self::Pbs { .. } => ProofKind::PbsAndDecrypt,
+        }
+    }
+
+    /// The input of the batch items in `range`, proven in its own receipt. The keys are shared by
+    /// every shard, the bootstrapping key container is not copied.
+    pub fn shard(&self, range: Range<usize>) -> Self {
+        match self {
+            Self::Decryption { input, batch_items } => Self::Decryption {
+                input: DecryptionInput {
+                    batch_size: range.len() as u32,
+                    ..input.clone()
+                },
+                batch_items: batch_items[range].to_vec(),
+            },
+            Self::Pbs {
+                input,
+                bsk_container,
+                batch_items,
+            } => Self::Pbs {
+                input: PbsDecryptionInput {
+                    batch_size: range.len() as u32,
+                    ..input.clone()
+                },
+                bsk_container: bsk_container.clone(),
+                batch_items: batch_items[range].to_vec(),
+            },
+        }
+    }
+
+    /// Memory the guest reserves for this input under `params`.
+    pub fn guest_memory(&self, params: &ProofParams) -> usize {
+        match self {
+            Self::Decryption { input, .. } => {
+                batch_budget(params, input.keyswitch.is_some(), input.batch_size)
+            }
+            Self::Pbs { input, .. } => {
+                pbs_budget(params, input.bsk.container_len(), input.batch_size)
+            }
+        }
+    }
+
+    /// Write the input, then the batch items one by one so the guest streams them.
+    pub fn write(&self, stdin: &mut GuestStdin) -> Result<(), ProofError> {
+        match self {
+            Self::Decryption { input, batch_items } => {
+                stdin.write(input)?;
+                for batch_item in batch_items {
+                    stdin.write(batch_item)?;
+                }
+            }
+            Self::Pbs {
+                input,
+                bsk_container,
+                batch_items,
+            } => {
+                stdin.write(input)?;
+                stdin.write_framed_container(bsk_container);
+                for batch_item in batch_items {
+                    stdin.write(batch_item)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
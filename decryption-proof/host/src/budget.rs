@@ -0,0 +1,207 @@
+//! Pre-flight budget of the guest inputs, checked before proving.
+//!
+//! A batch guest reserves the memory of its inputs, as budgeted by [`zkfhe_core::budget`], and
+//! halts with [`OutOfMemory`](zkfhe_core::validation::ValidationFailure::OutOfMemory) when the
+//! guest memory cannot hold them. That only happens once the prover executes the guest, after the
+//! keys are generated and the inputs serialized. The host computes the same budget and the
+//! serialized size of every input up front, and refuses an input over the limits before any
+//! proving work.
+//!
+//! The PBS guest also needs a blind rotation stack growing with the polynomial size, the host gives
+//! it a [`MemoryLayout`] from the [`MemoryPreset`]s and checks the parameters fit in it.
+
+use clap::ValueEnum;
+use risc0_zkvm_platform::memory::GUEST_MAX_MEM;
+use tfhe::core_crypto::commons::math::ntt::ntt64::Ntt64;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::budget::{multi_bit_scratch_budget, pbs_budget, MemoryLayout};
+use zkfhe_core::checks::NTT64_CIPHERTEXT_MODULUS;
+use zkfhe_core::io::PbsBootstrapKeyHeader;
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+
+/// Default limit on the serialized input of a guest.
+pub const DEFAULT_MAX_INPUT_BYTES: usize = 1 << 30;
+
+/// Memory the guest heap ends at, the most a guest can ever reserve.
+pub const GUEST_MEMORY_BYTES: usize = GUEST_MAX_MEM;
+
+const MIB: f64 = (1 << 20) as f64;
+const KIB: f64 = (1 << 10) as f64;
+
+/// Limits every guest input is checked against before proving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputLimits {
+    /// Largest serialized stdin of a guest
+    pub max_input_bytes: usize,
+    /// Largest memory a guest may reserve, the guest memory itself by default
+    pub max_guest_memory_bytes: usize,
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        Self {
+            max_input_bytes: DEFAULT_MAX_INPUT_BYTES,
+            max_guest_memory_bytes: GUEST_MEMORY_BYTES,
+        }
+    }
+}
+
+/// The serialized size of a guest input and the memory its guest reserves for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputBudget {
+    pub input_bytes: usize,
+    pub guest_memory_bytes: usize,
+}
+
+impl InputBudget {
+    /// Fail with [`ProofError::InputBudget`] when the input or its guest memory is over `limits`.
+    pub fn check(&self, limits: &InputLimits) -> Result<(), ProofError> {
+        if self.input_bytes > limits.max_input_bytes {
+            return Err(ProofError::InputBudget(format!(
+                "the guest input is {:.1} MiB, over the limit of {:.1} MiB",
+                self.input_bytes as f64 / MIB,
+                limits.max_input_bytes as f64 / MIB
+            )));
+        }
+        let max_guest_memory_bytes = limits.max_guest_memory_bytes.min(GUEST_MEMORY_BYTES);
+        if self.guest_memory_bytes > max_guest_memory_bytes {
+            return Err(ProofError::InputBudget(format!(
+                "the guest would reserve {:.1} MiB for its inputs, over the limit of {:.1} MiB",
+                self.guest_memory_bytes as f64 / MIB,
+                max_guest_memory_bytes as f64 / MIB
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Memory layouts of the PBS guest, from the smallest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum MemoryPreset {
+    /// 256 MiB of heap and a 192 KiB stack, enough for accumulators of up to 2048 coefficients
+    #[default]
+    Standard,
+    /// 1 GiB of heap and a 1 MiB stack, for polynomial sizes of 4096 and 8192
+    Large,
+    /// The whole guest memory as heap and a 4 MiB stack
+    Huge,
+}
+
+impl MemoryPreset {
+    pub const ALL: [Self; 3] = [Self::Standard, Self::Large, Self::Huge];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Large => "large",
+            Self::Huge => "huge",
+        }
+    }
+
+    pub fn layout(self) -> MemoryLayout {
+        let (heap_bytes, stack_bytes) = match self {
+            Self::Standard => (256 << 20, 192 << 10),
+            Self::Large => (1 << 30, 1 << 20),
+            Self::Huge => (GUEST_MEMORY_BYTES as u64, 4 << 20),
+        };
+        MemoryLayout {
+            heap_bytes,
+            stack_bytes,
+        }
+    }
+
+    /// The smallest preset holding `required`, `None` when even the largest cannot.
+    pub fn holding(required: &MemoryLayout) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| fits(required, &preset.layout()))
+    }
+}
+
+fn fits(required: &MemoryLayout, layout: &MemoryLayout) -> bool {
+    required.heap_bytes <= layout.heap_bytes && required.stack_bytes <= layout.stack_bytes
+}
+
+/// Stack of the NTT blind rotation of the PBS guest under `params`, as tfhe-rs computes it.
+pub fn blind_rotation_stack_bytes(params: &ProofParams) -> Result<u64, ProofError> {
+    let polynomial_size = PolynomialSize(params.polynomial_size);
+    let ntt = Ntt64::new(CiphertextModulus::new(NTT64_CIPHERTEXT_MODULUS), polynomial_size);
+    let requirement = blind_rotate_ntt64_assign_mem_optimized_requirement(
+        GlweDimension(params.glwe_dimension).to_glwe_size(),
+        polynomial_size,
+        ntt.as_view(),
+    )
+    .map_err(|_| ProofError::InvalidParameters("the blind rotation stack overflows".to_string()))?;
+    Ok(requirement.unaligned_bytes_required() as u64)
+}
+
+/// The layout the PBS guest needs for a batch of `batch_size` under `params` with `bsk`: the
+/// stack of its NTT blind rotations, and a heap holding the inputs along with it. Multi-bit blind
+/// rotations use no stack, their scratch is counted in the heap.
+pub fn required_pbs_memory(
+    params: &ProofParams,
+    bsk: &PbsBootstrapKeyHeader,
+    batch_size: u32,
+) -> Result<MemoryLayout, ProofError> {
+    let input_bytes = pbs_budget(params, bsk.container_len(), batch_size) as u64;
+    Ok(match bsk {
+        PbsBootstrapKeyHeader::Ntt(_) => {
+            let stack_bytes = blind_rotation_stack_bytes(params)?;
+            MemoryLayout {
+                heap_bytes: input_bytes + stack_bytes,
+                stack_bytes,
+            }
+        }
+        PbsBootstrapKeyHeader::MultiBit(_) => MemoryLayout {
+            heap_bytes: input_bytes + multi_bit_scratch_budget(params, batch_size) as u64,
+            stack_bytes: 0,
+        },
+    })
+}
+
+/// Fail with [`ProofError::InputBudget`] when `required` does not fit in the layout of `preset`,
+/// naming the smallest preset it fits in. Polynomial sizes of 4096 do not fit in the standard one.
+pub fn check_memory_layout(
+    params: &ProofParams,
+    required: &MemoryLayout,
+    preset: MemoryPreset,
+) -> Result<(), ProofError> {
+    let layout = preset.layout();
+    if fits(required, &layout) {
+        return Ok(());
+    }
+    let advice = match MemoryPreset::holding(required) {
+        Some(holding) => format!("use the {} memory preset", holding.name()),
+        None => "no memory preset holds it".to_string(),
+    };
+    Err(ProofError::InputBudget(format!(
+        "PBS under PolynomialSize({}) needs a {:.1} KiB stack and {:.1} MiB of heap, the {} \
+         memory preset has a {:.1} KiB stack and {:.1} MiB of heap: {advice}",
+        params.polynomial_size,
+        required.stack_bytes as f64 / KIB,
+        required.heap_bytes as f64 / MIB,
+        preset.name(),
+        layout.stack_bytes as f64 / KIB,
+        layout.heap_bytes as f64 / MIB,
+    )))
+}
+
+/// The memory preset of the PBS guest, the requested one or the smallest holding the largest shard
+/// of `shard_size` items. A preset too small, or parameters no preset holds, are refused before
+/// proving with the preset to use.
+pub fn pbs_memory_preset(
+    params: &ProofParams,
+    bsk: &PbsBootstrapKeyHeader,
+    shard_size: u32,
+    requested: Option<MemoryPreset>,
+) -> Result<MemoryPreset, ProofError> {
+    let required = required_pbs_memory(params, bsk, shard_size)?;
+    let preset = requested
+        .or_else(|| MemoryPreset::holding(&required))
+        .unwrap_or(MemoryPreset::Huge);
+    check_memory_layout(params, &required, preset)?;
+    if requested.is_none() {
+        tracing::info!("Proving the PBS with the {} memory preset", preset.name());
+    }
+    Ok(preset)
+}
@@ -0,0 +1,82 @@
+//! Cooperative cancellation of long running proofs.
+//!
+//! A proof of a large batch runs for hours in `r0vm` processes the host does not otherwise
+//! control. A [`CancellationToken`] handed to [`crate::prover::prove_with_cancel`] stops the proof
+//! between its stages and attempts, and the prover waits on the token along with the running
+//! attempt: a cancelled local attempt gets its `r0vm` server killed, a remote one is abandoned like
+//! a timed out remote attempt.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::ProofError;
+
+/// Cancels the proofs holding a clone of it, on demand or once its wall clock timeout elapsed.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// A token only cancelled by [`CancellationToken::cancel`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token also cancelled once `timeout` elapsed from now.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            cancelled: Arc::default(),
+            deadline: Instant::now().checked_add(timeout),
+        }
+    }
+
+    /// Cancel every proof holding a clone of the token, from any thread.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    fn timed_out(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire) || self.timed_out()
+    }
+
+    /// [`ProofError::Cancelled`] once the token is cancelled or timed out.
+    pub fn check(&self) -> Result<(), ProofError> {
+        if self.cancelled.load(Ordering::Acquire) {
+            return Err(ProofError::Cancelled("the proof was cancelled".to_string()));
+        }
+        if self.timed_out() {
+            return Err(ProofError::Cancelled("the proof ran past its timeout".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_the_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(token.check().is_ok());
+        clone.cancel();
+        assert!(token.is_cancelled());
+        assert!(matches!(token.check(), Err(ProofError::Cancelled(_))));
+    }
+
+    #[test]
+    fn tokens_time_out() {
+        let token = CancellationToken::with_timeout(Duration::ZERO);
+        assert!(token.is_cancelled());
+        assert!(matches!(token.check(), Err(ProofError::Cancelled(_))));
+        assert!(!CancellationToken::with_timeout(Duration::from_secs(3600)).is_cancelled());
+    }
+}
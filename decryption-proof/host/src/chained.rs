@@ -0,0 +1,111 @@
+//! Chained decryption proofs: the decryption of a ciphertext conditioned on the receipt of the
+//! evaluation step that produced it.
+//!
+//! The guest verifies the evaluation receipt as a risc0 assumption and checks that its journal, of
+//! the `sample_extract` or the `keyswitch` guest, names the decrypted ciphertext as the output of
+//! the step. The prover resolves the assumption with the evaluation receipt, so the result is a
+//! single receipt that only verifies along with it, and whose journal links both statements.
+
+use risc0_zkvm::{default_prover, ExecutorEnv, InnerReceipt, ProverOpts, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::{AggregatedReceipt, ChainedDecryptionInput};
+use zkfhe_core::journal::ChainedDecryptionJournal;
+use zkfhe_core::params::ProofParams;
+
+use crate::aggregation::image_id_words;
+use crate::error::ProofError;
+use crate::hashing::{lwe_ciphertext_digest, params_digest, sha256_bytes};
+use crate::io::{lwe_ciphertext_data, lwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// The evaluation steps whose receipts a decryption can be chained to.
+pub const EVALUATION_KINDS: [ProofKind; 2] = [ProofKind::SampleExtract, ProofKind::Keyswitch];
+
+/// Prove the decryption of `ciphertext`, the output of the step `evaluation` attests, decoded
+/// with the encoding of `params`.
+///
+/// A composite evaluation receipt is first compressed to a succinct one so the prover can resolve
+/// the assumption.
+pub fn prove_chained_decryption(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    ciphertext: &LweCiphertextOwned<u64>,
+    evaluation_kind: ProofKind,
+    evaluation: &Receipt,
+) -> Result<Receipt, ProofError> {
+    if !EVALUATION_KINDS.contains(&evaluation_kind) {
+        return Err(ProofError::InvalidInput(format!(
+            "{} receipts attest no evaluation output",
+            evaluation_kind.guest_name()
+        )));
+    }
+    verify_receipt(evaluation, evaluation_kind.image_id())?;
+
+    let prover = default_prover();
+    let assumption = match &evaluation.inner {
+        InnerReceipt::Composite(_) => prover
+            .compress(&ProverOpts::succinct(), evaluation)
+            .map_err(ProofError::proving)?,
+        _ => evaluation.clone(),
+    };
+    let env = ExecutorEnv::builder()
+        .add_assumption(assumption)
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&ChainedDecryptionInput {
+            lwe_sk: lwe_secret_key_data(lwe_sk),
+            ciphertext: lwe_ciphertext_data(ciphertext),
+            evaluation: AggregatedReceipt {
+                image_id: image_id_words(&evaluation_kind.image_id()),
+                journal: evaluation.journal.bytes.clone(),
+            },
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = prover
+        .prove(env, ProofKind::ChainedDecrypt.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a chained decryption receipt for `ciphertext` under the secret key of ID
+/// `secret_key_id` and `expected_params`, chained to the `evaluation_kind` receipt committing
+/// `evaluation_journal`, and return the decoded value.
+pub fn check_chained_decryption(
+    receipt: &Receipt,
+    ciphertext: &LweCiphertextOwned<u64>,
+    secret_key_id: &[u8; 32],
+    evaluation_kind: ProofKind,
+    evaluation_journal: &[u8],
+    expected_params: &ProofParams,
+) -> Result<u64, ProofError> {
+    verify_receipt(receipt, ProofKind::ChainedDecrypt.image_id())?;
+    let journal: ChainedDecryptionJournal = decode_payload(receipt, ProofKind::ChainedDecrypt)?;
+
+    // The guest accepts any image ID, the evaluation step is only trusted for the known guests
+    if journal.evaluation_image_id != image_id_words(&evaluation_kind.image_id())
+        || journal.evaluation_kind != evaluation_kind.journal_kind()
+    {
+        return Err(ProofError::Verification(format!(
+            "the decryption is not chained to a {} receipt",
+            evaluation_kind.guest_name()
+        )));
+    }
+    if journal.evaluation_journal_digest != sha256_bytes(evaluation_journal) {
+        return Err(ProofError::Verification("evaluation journal digest mismatch".to_string()));
+    }
+    if journal.ct_digest != lwe_ciphertext_digest(ciphertext) {
+        return Err(ProofError::Verification("ciphertext digest mismatch".to_string()));
+    }
+    if &journal.secret_key_id != secret_key_id {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+
+    Ok(journal.plaintext)
+}
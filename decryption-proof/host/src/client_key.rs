@@ -0,0 +1,68 @@
+//! Adapters from the client keys of the tfhe-rs shortint, integer and high level APIs to the
+//! core_crypto secret keys and the parameters the proofs take, so users of those APIs do not reach
+//! into their internals to build proof inputs.
+//!
+//! Every client key holds the GLWE secret key, whose flattened LWE key is the big key, and the
+//! small LWE key. Ciphertexts rest under one or the other depending on the PBS order of the
+//! parameters, [`ProofKeys::decryption_key`] picks the one a decryption proof takes.
+
+use tfhe::core_crypto::prelude::*;
+use tfhe::shortint::parameters::ShortintParameterSet;
+
+use crate::params::{from_shortint_parameters, PbsOrder, ProofParams};
+
+/// The secret keys and parameters of a client key, as the proofs take them.
+#[derive(Debug, Clone)]
+pub struct ProofKeys {
+    pub params: ProofParams,
+    pub glwe_sk: GlweSecretKeyOwned<u64>,
+    /// The flattened GLWE secret key
+    pub big_lwe_sk: LweSecretKeyOwned<u64>,
+    pub small_lwe_sk: LweSecretKeyOwned<u64>,
+}
+
+impl ProofKeys {
+    fn new(
+        glwe_sk: GlweSecretKeyOwned<u64>,
+        small_lwe_sk: LweSecretKeyOwned<u64>,
+        params: &ShortintParameterSet,
+    ) -> Self {
+        Self {
+            params: from_shortint_parameters(params),
+            big_lwe_sk: glwe_sk.clone().into_lwe_secret_key(),
+            glwe_sk,
+            small_lwe_sk,
+        }
+    }
+
+    /// The LWE secret key the ciphertexts of the client key rest under: the big key when they are
+    /// keyswitched before their PBS, the small key otherwise.
+    pub fn decryption_key(&self) -> &LweSecretKeyOwned<u64> {
+        match self.params.pbs_order {
+            PbsOrder::KeyswitchBootstrap => &self.big_lwe_sk,
+            PbsOrder::BootstrapKeyswitch => &self.small_lwe_sk,
+        }
+    }
+}
+
+impl From<&tfhe::shortint::ClientKey> for ProofKeys {
+    fn from(client_key: &tfhe::shortint::ClientKey) -> Self {
+        let (glwe_sk, small_lwe_sk, params) = client_key.clone().into_raw_parts();
+        Self::new(glwe_sk, small_lwe_sk, &params)
+    }
+}
+
+impl From<&tfhe::integer::ClientKey> for ProofKeys {
+    fn from(client_key: &tfhe::integer::ClientKey) -> Self {
+        Self::from(client_key.as_ref())
+    }
+}
+
+/// The keys of the integer client key a high level client key wraps, its compact public key and
+/// compression keys are left out: no proof decrypts under them.
+impl From<&tfhe::ClientKey> for ProofKeys {
+    fn from(client_key: &tfhe::ClientKey) -> Self {
+        let (integer_key, ..) = client_key.clone().into_raw_parts();
+        Self::from(&integer_key)
+    }
+}
@@ -0,0 +1,81 @@
+//! Decryption proofs of LWE compact ciphertext lists, as produced by the compact public key
+//! encryption of tfhe-rs.
+//!
+//! A compact list shares one mask between as many ciphertexts as the LWE dimension, clients send it
+//! as is and the guest expands it before decrypting, so the journal commits the digest of the list
+//! the client produced and the prover never expands it. The expansion rotates the masks in
+//! `X^n + 1`, the key must be the flattened GLWE key, of a power of two dimension, on the native
+//! modulus.
+
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::CompactListDecryptionInput;
+use zkfhe_core::journal::CompactListDecryptionJournal;
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::hashing::{lwe_compact_ciphertext_list_digest, params_digest};
+use crate::io::{lwe_compact_ciphertext_list_data, lwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// Prove the decryption of every ciphertext `list` expands to under `lwe_sk`.
+pub fn prove_compact_list_decryption(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    list: &LweCompactCiphertextListOwned<u64>,
+) -> Result<Receipt, ProofError> {
+    if !list.ciphertext_modulus().is_native_modulus() {
+        return Err(ProofError::InvalidInput(
+            "compact lists are only expanded on the native modulus".to_string(),
+        ));
+    }
+
+    let env = ExecutorEnv::builder()
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&CompactListDecryptionInput {
+            lwe_sk: lwe_secret_key_data(lwe_sk),
+            list: lwe_compact_ciphertext_list_data(list),
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::CompactListDecrypt.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a compact list decryption receipt for `list` under the secret key of ID `secret_key_id`
+/// and `expected_params` and return the decoded value of every ciphertext of the list, in order.
+pub fn check_compact_list_decryption(
+    receipt: &Receipt,
+    list: &LweCompactCiphertextListOwned<u64>,
+    secret_key_id: &[u8; 32],
+    expected_params: &ProofParams,
+) -> Result<Vec<u64>, ProofError> {
+    verify_receipt(receipt, ProofKind::CompactListDecrypt.image_id())?;
+    let journal: CompactListDecryptionJournal =
+        decode_payload(receipt, ProofKind::CompactListDecrypt)?;
+
+    if journal.list_digest != lwe_compact_ciphertext_list_digest(list) {
+        return Err(ProofError::Verification("compact list digest mismatch".to_string()));
+    }
+    if &journal.secret_key_id != secret_key_id {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+    if journal.plaintexts.len() != list.lwe_ciphertext_count().0 {
+        return Err(ProofError::Verification(format!(
+            "journal has {} plaintexts, expected {}",
+            journal.plaintexts.len(),
+            list.lwe_ciphertext_count().0
+        )));
+    }
+
+    Ok(journal.plaintexts)
+}
@@ -0,0 +1,83 @@
+//! Consistency proofs: two ciphertexts under different secret keys decrypt to the same message,
+//! e.g. a PBS output and its keyswitch, or the same value encrypted for two parties. Both keys are
+//! private inputs, the journal only commits the digests of the ciphertexts, the IDs of the keys and
+//! whether the messages are equal.
+
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::{ConsistencyInput, LweDecryptionInput};
+use zkfhe_core::journal::ConsistencyJournal;
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::hashing::{lwe_ciphertext_digest, params_digest, secret_key_digest};
+use crate::io::{lwe_ciphertext_data, lwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+fn decryption_input(
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    ciphertext: &LweCiphertextOwned<u64>,
+) -> LweDecryptionInput {
+    LweDecryptionInput {
+        lwe_sk: lwe_secret_key_data(lwe_sk),
+        ciphertext: lwe_ciphertext_data(ciphertext),
+    }
+}
+
+/// Prove whether `left` under `left_sk` and `right` under `right_sk` decrypt to the same message,
+/// each key a big or a small key of `params`.
+pub fn prove_consistency(
+    params: &ProofParams,
+    left_sk: &LweSecretKeyOwned<u64>,
+    left: &LweCiphertextOwned<u64>,
+    right_sk: &LweSecretKeyOwned<u64>,
+    right: &LweCiphertextOwned<u64>,
+) -> Result<Receipt, ProofError> {
+    let env = ExecutorEnv::builder()
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&ConsistencyInput {
+            left: decryption_input(left_sk, left),
+            right: decryption_input(right_sk, right),
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::Consistency.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a consistency receipt for `left` and `right` under `expected_params` and return whether
+/// they decrypt to the same message. The key IDs are only checked when given.
+pub fn check_consistency(
+    receipt: &Receipt,
+    left: &LweCiphertextOwned<u64>,
+    right: &LweCiphertextOwned<u64>,
+    secret_keys: Option<(&LweSecretKeyOwned<u64>, &LweSecretKeyOwned<u64>)>,
+    expected_params: &ProofParams,
+) -> Result<bool, ProofError> {
+    verify_receipt(receipt, ProofKind::Consistency.image_id())?;
+    let journal: ConsistencyJournal = decode_payload(receipt, ProofKind::Consistency)?;
+
+    if journal.left_ct_digest != lwe_ciphertext_digest(left)
+        || journal.right_ct_digest != lwe_ciphertext_digest(right)
+    {
+        return Err(ProofError::Verification("ciphertext digest mismatch".to_string()));
+    }
+    if let Some((left_sk, right_sk)) = secret_keys {
+        if journal.left_secret_key_id != secret_key_digest(left_sk.as_ref())
+            || journal.right_secret_key_id != secret_key_digest(right_sk.as_ref())
+        {
+            return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+        }
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+
+    Ok(journal.equal)
+}
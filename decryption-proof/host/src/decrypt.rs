@@ -0,0 +1,236 @@
+use risc0_zkvm::{default_prover, ExecutorEnv, ProveInfo, Receipt, SessionStats};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::LweDecryptionInput;
+use zkfhe_core::journal::DecryptionJournal;
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::execution::{execute, UnprovenJournal};
+use crate::hashing::{
+    canonical_lwe_ciphertext, lwe_ciphertext_digest, lwe_ciphertext_u128_digest, params_digest,
+    secret_key_digest,
+};
+use crate::io::{lwe_ciphertext_data, lwe_secret_key_data, write_public_input};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// A checked single decryption proof: the decoded value and the noise budget the journal commits,
+/// the receipt and the statistics of the proving session.
+#[derive(Debug)]
+pub struct DecryptionOutput {
+    pub plaintext: u64,
+    pub noise_budget_bits: u32,
+    pub receipt: Receipt,
+    pub stats: SessionStats,
+}
+
+/// Prove the decryption of a single LWE ciphertext, decoded with the encoding of `params`. The
+/// journal echoes `request_id`, binding the receipt to the external request it answers.
+pub fn prove_decryption(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    ciphertext: &LweCiphertextOwned<u64>,
+    request_id: Option<[u8; 32]>,
+) -> Result<Receipt, ProofError> {
+    Ok(prove_decryption_session(params, lwe_sk, ciphertext, request_id)?.receipt)
+}
+
+/// Prove the decryption of a single LWE ciphertext and check the receipt with
+/// [`check_decryption`] and [`check_request_id`], so the returned plaintext is the one committed
+/// for `ciphertext` under `lwe_sk` and `params` and for `request_id`.
+pub fn prove_and_extract(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    ciphertext: &LweCiphertextOwned<u64>,
+    request_id: Option<[u8; 32]>,
+) -> Result<DecryptionOutput, ProofError> {
+    let prove_info = prove_decryption_session(params, lwe_sk, ciphertext, request_id)?;
+    let journal = check_decryption_journal(
+        &prove_info.receipt,
+        ciphertext,
+        &secret_key_digest(lwe_sk.as_ref()),
+        params,
+    )?;
+    check_request_id(&journal, request_id)?;
+    Ok(DecryptionOutput {
+        plaintext: journal.plaintext,
+        noise_budget_bits: journal.noise_budget_bits.ok_or_else(|| {
+            ProofError::Verification("the journal commits no noise budget".to_string())
+        })?,
+        receipt: prove_info.receipt,
+        stats: prove_info.stats,
+    })
+}
+
+/// Run the single decryption guest in the executor without proving, for correctness checks and
+/// CI. The journal is checked against `ciphertext`, `lwe_sk` and `params` like a receipt's, but
+/// nothing attests it.
+pub fn execute_decryption(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    ciphertext: &LweCiphertextOwned<u64>,
+    request_id: Option<[u8; 32]>,
+) -> Result<UnprovenJournal<DecryptionJournal>, ProofError> {
+    let env = decryption_env(params, lwe_sk, ciphertext, request_id)?;
+    let journal = execute(ProofKind::Decrypt, env)?;
+    check_decryption_digests(
+        journal.unproven_payload(),
+        ciphertext,
+        &secret_key_digest(lwe_sk.as_ref()),
+        params,
+    )?;
+    check_request_id(journal.unproven_payload(), request_id)?;
+    Ok(journal)
+}
+
+fn decryption_env(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    ciphertext: &LweCiphertextOwned<u64>,
+    request_id: Option<[u8; 32]>,
+) -> Result<ExecutorEnv<'static>, ProofError> {
+    // The ciphertext goes in the public input frame as the bytes the journal digest is taken over,
+    // the secret key is the only private input
+    let mut env_builder = ExecutorEnv::builder();
+    env_builder
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&request_id)
+        .map_err(ProofError::serialization)?;
+    write_public_input(&mut env_builder, &canonical_lwe_ciphertext(ciphertext))?;
+    env_builder
+        .write(&lwe_secret_key_data(lwe_sk))
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)
+}
+
+fn prove_decryption_session(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    ciphertext: &LweCiphertextOwned<u64>,
+    request_id: Option<[u8; 32]>,
+) -> Result<ProveInfo, ProofError> {
+    let env = decryption_env(params, lwe_sk, ciphertext, request_id)?;
+    default_prover()
+        .prove(env, ProofKind::Decrypt.elf())
+        .map_err(ProofError::proving)
+}
+
+/// Verify a single decryption receipt for `ciphertext` under the secret key of ID `secret_key_id`
+/// and `expected_params` and return the decoded value.
+pub fn check_decryption(
+    receipt: &Receipt,
+    ciphertext: &LweCiphertextOwned<u64>,
+    secret_key_id: &[u8; 32],
+    expected_params: &ProofParams,
+) -> Result<u64, ProofError> {
+    Ok(check_decryption_journal(receipt, ciphertext, secret_key_id, expected_params)?.plaintext)
+}
+
+/// Verify a single decryption receipt like [`check_decryption`] and return its whole journal.
+pub fn check_decryption_journal(
+    receipt: &Receipt,
+    ciphertext: &LweCiphertextOwned<u64>,
+    secret_key_id: &[u8; 32],
+    expected_params: &ProofParams,
+) -> Result<DecryptionJournal, ProofError> {
+    verify_receipt(receipt, ProofKind::Decrypt.image_id())?;
+    let journal: DecryptionJournal = decode_payload(receipt, ProofKind::Decrypt)?;
+    check_decryption_digests(&journal, ciphertext, secret_key_id, expected_params)?;
+    // The guest refuses to prove an overflow, a journal claiming one was not committed by it
+    if journal.overflow {
+        return Err(ProofError::Verification(
+            "the journal commits an overflowed plaintext".to_string(),
+        ));
+    }
+    Ok(journal)
+}
+
+/// Check that a single decryption journal commits `ciphertext`, the secret key of ID
+/// `secret_key_id` and `expected_params`, a tampered ciphertext is proven under another digest and
+/// refused here.
+pub fn check_decryption_digests(
+    journal: &DecryptionJournal,
+    ciphertext: &LweCiphertextOwned<u64>,
+    secret_key_id: &[u8; 32],
+    expected_params: &ProofParams,
+) -> Result<(), ProofError> {
+    if journal.ct_digest != lwe_ciphertext_digest(ciphertext) {
+        return Err(ProofError::Verification("ciphertext digest mismatch".to_string()));
+    }
+    if &journal.secret_key_id != secret_key_id {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// Check that a decryption journal answers the request `request_id`, when the verifier answers
+/// one. A receipt proven for another request, or for none, is refused: the request ID is what
+/// keeps it from being replayed.
+pub fn check_request_id<Plaintext>(
+    journal: &DecryptionJournal<Plaintext>,
+    request_id: Option<[u8; 32]>,
+) -> Result<(), ProofError> {
+    if request_id.is_some() && journal.request_id != request_id {
+        return Err(ProofError::Verification("the journal answers another request".to_string()));
+    }
+    Ok(())
+}
+
+/// Prove the decryption of a single LWE ciphertext on the native 128 bits modulus, decoded with the
+/// encoding of `params`, for the request `request_id`.
+pub fn prove_decryption_u128(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u128>,
+    ciphertext: &LweCiphertextOwned<u128>,
+    request_id: Option<[u8; 32]>,
+) -> Result<Receipt, ProofError> {
+    let env = ExecutorEnv::builder()
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&request_id)
+        .map_err(ProofError::serialization)?
+        .write(&LweDecryptionInput {
+            lwe_sk: lwe_secret_key_data(lwe_sk),
+            ciphertext: lwe_ciphertext_data(ciphertext),
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::DecryptU128.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a 128 bits decryption receipt for `ciphertext` under the secret key of ID
+/// `secret_key_id` and `expected_params` and, see [`check_request_id`], for `request_id`, and
+/// return the decoded value.
+pub fn check_decryption_u128(
+    receipt: &Receipt,
+    ciphertext: &LweCiphertextOwned<u128>,
+    secret_key_id: &[u8; 32],
+    expected_params: &ProofParams,
+    request_id: Option<[u8; 32]>,
+) -> Result<u128, ProofError> {
+    verify_receipt(receipt, ProofKind::DecryptU128.image_id())?;
+    let journal: DecryptionJournal<u128> = decode_payload(receipt, ProofKind::DecryptU128)?;
+
+    if journal.ct_digest != lwe_ciphertext_u128_digest(ciphertext) {
+        return Err(ProofError::Verification("ciphertext digest mismatch".to_string()));
+    }
+    if &journal.secret_key_id != secret_key_id {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+    check_request_id(&journal, request_id)?;
+
+    Ok(journal.plaintext)
+}
@@ -0,0 +1,111 @@
+//! Errors returned by the host library.
+
+use std::fmt::Display;
+
+use zkfhe_core::validation::{DecryptionFailure, ValidationFailure};
+
+/// What a failed proof or verification means for its caller, see [`ProofError::failure_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// The host refused the inputs or the parameters before running the guest
+    InvalidInput,
+    /// The guest rejected a malformed input before any crypto
+    MalformedInput(ValidationFailure),
+    /// A decryption check of the guest failed: the ciphertext or the claimed result was tampered
+    /// with, or the ciphertext is too noisy to be decrypted
+    FailedCheck(DecryptionFailure),
+    /// The receipt does not prove the expected statement: another ciphertext, key or parameter
+    /// set, or an invalid seal
+    Rejected,
+    /// Proving or decoding failed for a reason unrelated to the inputs, retrying may succeed
+    Internal,
+}
+
+/// Everything that can go wrong while proving a decryption or checking a receipt.
+#[derive(Debug, thiserror::Error)]
+pub enum ProofError {
+    /// An input could not be written to the guest, or a journal could not be decoded
+    #[error("serialization failed: {0}")]
+    Serialization(String),
+    /// The prover failed for another reason than a guest panic
+    #[error("proving failed: {0}")]
+    Proving(String),
+    /// The guest panicked, `message` is the panic message reported by the executor, which names
+    /// the failed check
+    #[error("guest panicked: {message}")]
+    GuestPanic { message: String },
+    /// The guest rejected its inputs before any crypto: it committed the code of the failed sanity
+    /// check in its journal and halted with it
+    #[error("guest rejected its inputs: {0}")]
+    GuestRejected(ValidationFailure),
+    /// The receipt does not verify, or does not commit to the expected ciphertexts or keys
+    #[error("verification failed: {0}")]
+    Verification(String),
+    /// The receipt was generated for other parameters or another encoding than the expected ones
+    #[error("parameter mismatch: {0}")]
+    ParameterMismatch(String),
+    /// An input cannot be lowered to the input of its guest
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    /// A guest input, or the guest memory it calls for, is over the limits checked before proving
+    #[error("input budget exceeded: {0}")]
+    InputBudget(String),
+    /// A parameter set could not be loaded or is invalid
+    #[error("invalid parameters: {0}")]
+    InvalidParameters(String),
+    /// Two executions of the same inputs committed different journals or ran different cycle
+    /// counts, the guest depends on something else than its inputs
+    #[error("nondeterministic guest: {0}")]
+    Nondeterminism(String),
+    /// The proof was cancelled through its [`crate::cancel::CancellationToken`] or ran past its
+    /// timeout, the `r0vm` server of a local attempt is killed
+    #[error("proof cancelled: {0}")]
+    Cancelled(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl ProofError {
+    pub fn serialization(error: impl Display) -> Self {
+        Self::Serialization(error.to_string())
+    }
+
+    /// Map a prover error, the executor reports guest panics as `Guest panicked: <message>`, they
+    /// are extracted into [`ProofError::GuestPanic`].
+    pub fn proving(error: impl Display) -> Self {
+        let error = error.to_string();
+        match error.split_once("Guest panicked: ") {
+            Some((_, message)) => Self::GuestPanic {
+                message: message.to_string(),
+            },
+            None => Self::Proving(error),
+        }
+    }
+
+    pub fn verification(error: impl Display) -> Self {
+        Self::Verification(error.to_string())
+    }
+
+    pub fn invalid_parameters(error: impl Display) -> Self {
+        Self::InvalidParameters(error.to_string())
+    }
+
+    /// Classify the error, a guest panic is a failed decryption check when its message carries
+    /// the code of a [`DecryptionFailure`].
+    pub fn failure_kind(&self) -> FailureKind {
+        match self {
+            Self::InvalidInput(_) | Self::InputBudget(_) | Self::InvalidParameters(_) => {
+                FailureKind::InvalidInput
+            }
+            Self::GuestRejected(failure) => FailureKind::MalformedInput(*failure),
+            Self::GuestPanic { message } => DecryptionFailure::from_panic_message(message)
+                .map_or(FailureKind::Internal, FailureKind::FailedCheck),
+            Self::Verification(_) | Self::ParameterMismatch(_) => FailureKind::Rejected,
+            Self::Serialization(_)
+            | Self::Proving(_)
+            | Self::Nondeterminism(_)
+            | Self::Cancelled(_)
+            | Self::Io(_) => FailureKind::Internal,
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Generation of the keys of a run: the binary secret keys, then the evaluation keys the proven
+//! computations need, the bootstrapping keys of the native and NTT PBS, the multi-bit
+//! bootstrapping key and the keyswitching key.
+//!
+//! Every evaluation key is loaded from the key cache when it holds it. In seeded mode the masks of
+//! the bootstrapping and keyswitching keys are drawn from a seeded CSPRNG, as a client would send
+//! the keys, and decompressed on the host, see [`crate::seeded`].
+
+use tfhe::core_crypto::commons::math::random::Seeder;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::checks::NTT64_CIPHERTEXT_MODULUS;
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::key_cache::{load_or_generate, CachedKeyKind, KeyCache};
+use crate::params::TfheParams;
+use crate::seeded::{container_size, decompress_lwe_bootstrap_key, decompress_lwe_keyswitch_key};
+
+/// Generate the small LWE secret key and the GLWE secret key of `params`, both binary. The big LWE
+/// key the PBS outputs are encrypted under is the flattened GLWE key.
+pub fn generate_secret_keys<Gen: ByteRandomGenerator>(
+    params: &ProofParams,
+    generator: &mut SecretRandomGenerator<Gen>,
+) -> (LweSecretKeyOwned<u64>, GlweSecretKeyOwned<u64>) {
+    let _keygen_span = tracing::info_span!("keygen").entered();
+    tracing::info!("Generating keys...");
+    let small_lwe_sk = LweSecretKey::generate_new_binary(params.lwe_dimension(), generator);
+    let glwe_sk = GlweSecretKey::generate_new_binary(
+        params.glwe_dimension(),
+        params.polynomial_size(),
+        generator,
+    );
+    (small_lwe_sk, glwe_sk)
+}
+
+/// The prime modulus of the NTT PBS, the guests only bootstrap with a 64 bits NTT on it.
+pub fn ntt_ciphertext_modulus() -> CiphertextModulus<u64> {
+    CiphertextModulus::new(NTT64_CIPHERTEXT_MODULUS)
+}
+
+/// Generator of the evaluation keys of the secret keys of a run.
+pub struct EvaluationKeyGenerator<'a, Gen: ByteRandomGenerator> {
+    params: &'a ProofParams,
+    small_lwe_sk: &'a LweSecretKeyOwned<u64>,
+    glwe_sk: &'a GlweSecretKeyOwned<u64>,
+    key_cache: Option<&'a KeyCache>,
+    /// Seeder of the seeded keys, `None` to generate the full keys with `encryption_generator`
+    seeder: Option<&'a mut dyn Seeder>,
+    encryption_generator: &'a mut EncryptionRandomGenerator<Gen>,
+}
+
+impl<'a, Gen: ByteRandomGenerator> EvaluationKeyGenerator<'a, Gen> {
+    pub fn new(
+        params: &'a ProofParams,
+        small_lwe_sk: &'a LweSecretKeyOwned<u64>,
+        glwe_sk: &'a GlweSecretKeyOwned<u64>,
+        key_cache: Option<&'a KeyCache>,
+        seeder: Option<&'a mut dyn Seeder>,
+        encryption_generator: &'a mut EncryptionRandomGenerator<Gen>,
+    ) -> Self {
+        Self {
+            params,
+            small_lwe_sk,
+            glwe_sk,
+            key_cache,
+            seeder,
+            encryption_generator,
+        }
+    }
+
+    /// The bootstrapping key from the small key to the GLWE key on the native modulus, in the
+    /// standard domain.
+    pub fn bootstrap_key(&mut self) -> Result<LweBootstrapKeyOwned<u64>, ProofError> {
+        let (params, key_cache) = (self.params, self.key_cache);
+        let (small_lwe_sk, glwe_sk) = (self.small_lwe_sk, self.glwe_sk);
+        let secret_keys = [small_lwe_sk.as_ref(), glwe_sk.as_ref()];
+        load_or_generate(key_cache, CachedKeyKind::BootstrapKey, params, &secret_keys, || {
+            self.generate_bootstrap_key(CiphertextModulus::new_native(), "bootstrapping key")
+        })
+    }
+
+    /// The bootstrapping key of [`Self::bootstrap_key`] on the [`ntt_ciphertext_modulus`],
+    /// converted to the NTT domain. The conversion is cached along with the key.
+    pub fn ntt_bootstrap_key(&mut self) -> Result<NttLweBootstrapKeyOwned<u64>, ProofError> {
+        let _keygen_span = tracing::info_span!("keygen").entered();
+        tracing::info!("Generating the NTT bootstrapping key...");
+        let (params, key_cache) = (self.params, self.key_cache);
+        let (small_lwe_sk, glwe_sk) = (self.small_lwe_sk, self.glwe_sk);
+        let secret_keys = [small_lwe_sk.as_ref(), glwe_sk.as_ref()];
+        load_or_generate(key_cache, CachedKeyKind::NttBootstrapKey, params, &secret_keys, || {
+            let std_bootstrapping_key =
+                self.generate_bootstrap_key(ntt_ciphertext_modulus(), "NTT bootstrapping key");
+            let mut ntt_bsk = NttLweBootstrapKey::new(
+                0u64,
+                std_bootstrapping_key.input_lwe_dimension(),
+                std_bootstrapping_key.glwe_size(),
+                std_bootstrapping_key.polynomial_size(),
+                std_bootstrapping_key.decomposition_base_log(),
+                std_bootstrapping_key.decomposition_level_count(),
+                std_bootstrapping_key.ciphertext_modulus(),
+            );
+            par_convert_standard_lwe_bootstrap_key_to_ntt64(&std_bootstrapping_key, &mut ntt_bsk);
+            ntt_bsk
+        })
+    }
+
+    /// The multi-bit bootstrapping key of `grouping_factor` on the native modulus. The small key
+    /// must split in groups of `grouping_factor` coefficients. Multi-bit keys are never seeded.
+    pub fn multi_bit_bootstrap_key(
+        &mut self,
+        grouping_factor: LweBskGroupingFactor,
+    ) -> Result<LweMultiBitBootstrapKeyOwned<u64>, ProofError> {
+        let lwe_dimension = self.small_lwe_sk.lwe_dimension();
+        if !lwe_dimension.0.is_multiple_of(grouping_factor.0) {
+            return Err(ProofError::InvalidInput(format!(
+                "the LWE dimension {} does not split in groups of {}",
+                lwe_dimension.0, grouping_factor.0
+            )));
+        }
+        let _keygen_span = tracing::info_span!("keygen").entered();
+        tracing::info!("Generating the multi-bit bootstrapping key...");
+        let (params, key_cache) = (self.params, self.key_cache);
+        let (small_lwe_sk, glwe_sk) = (self.small_lwe_sk, self.glwe_sk);
+        let secret_keys = [small_lwe_sk.as_ref(), glwe_sk.as_ref()];
+        let kind = CachedKeyKind::MultiBitBootstrapKey {
+            grouping_factor: grouping_factor.0,
+        };
+        load_or_generate(key_cache, kind, params, &secret_keys, || {
+            par_allocate_and_generate_new_lwe_multi_bit_bootstrap_key(
+                small_lwe_sk,
+                glwe_sk,
+                params.pbs_base_log(),
+                params.pbs_level(),
+                grouping_factor,
+                params.glwe_noise(),
+                CiphertextModulus::new_native(),
+                self.encryption_generator,
+            )
+        })
+    }
+
+    /// The keyswitching key from `big_lwe_sk`, the flattened GLWE key, back to the small key.
+    pub fn keyswitch_key(
+        &mut self,
+        big_lwe_sk: &LweSecretKeyOwned<u64>,
+    ) -> Result<LweKeyswitchKeyOwned<u64>, ProofError> {
+        let (params, key_cache, small_lwe_sk) = (self.params, self.key_cache, self.small_lwe_sk);
+        let secret_keys = [big_lwe_sk.as_ref(), small_lwe_sk.as_ref()];
+        load_or_generate(key_cache, CachedKeyKind::KeyswitchKey, params, &secret_keys, || {
+            match self.seeder.as_deref_mut() {
+                Some(seeder) => {
+                    let seeded_ksk = allocate_and_generate_new_seeded_lwe_keyswitch_key(
+                        big_lwe_sk,
+                        small_lwe_sk,
+                        params.ks_base_log(),
+                        params.ks_level(),
+                        params.lwe_noise(),
+                        CiphertextModulus::new_native(),
+                        seeder,
+                    );
+                    let seeded_len = seeded_ksk.as_ref().len();
+                    let ksk = decompress_lwe_keyswitch_key(seeded_ksk);
+                    report_decompression("keyswitching key", seeded_len, ksk.as_ref().len());
+                    ksk
+                }
+                None => allocate_and_generate_new_lwe_keyswitch_key(
+                    big_lwe_sk,
+                    small_lwe_sk,
+                    params.ks_base_log(),
+                    params.ks_level(),
+                    params.lwe_noise(),
+                    CiphertextModulus::new_native(),
+                    self.encryption_generator,
+                ),
+            }
+        })
+    }
+
+    /// Generate a standard bootstrapping key on `ciphertext_modulus`, with the parallel variant for
+    /// performance.
+    fn generate_bootstrap_key(
+        &mut self,
+        ciphertext_modulus: CiphertextModulus<u64>,
+        name: &str,
+    ) -> LweBootstrapKeyOwned<u64> {
+        let params = self.params;
+        match self.seeder.as_deref_mut() {
+            Some(seeder) => {
+                let seeded_bootstrapping_key =
+                    par_allocate_and_generate_new_seeded_lwe_bootstrap_key(
+                        self.small_lwe_sk,
+                        self.glwe_sk,
+                        params.pbs_base_log(),
+                        params.pbs_level(),
+                        params.glwe_noise(),
+                        ciphertext_modulus,
+                        seeder,
+                    );
+                let seeded_len = seeded_bootstrapping_key.as_ref().len();
+                let bootstrapping_key = decompress_lwe_bootstrap_key(seeded_bootstrapping_key);
+                report_decompression(name, seeded_len, bootstrapping_key.as_ref().len());
+                bootstrapping_key
+            }
+            None => par_allocate_and_generate_new_lwe_bootstrap_key(
+                self.small_lwe_sk,
+                self.glwe_sk,
+                params.pbs_base_log(),
+                params.pbs_level(),
+                params.glwe_noise(),
+                ciphertext_modulus,
+                self.encryption_generator,
+            ),
+        }
+    }
+}
+
+/// Report the size of a seeded key against its decompressed form.
+fn report_decompression(name: &str, seeded_len: usize, decompressed_len: usize) {
+    const MIB: f64 = (1 << 20) as f64;
+    tracing::info!(
+        "Decompressed the seeded {name} on the host: {:.1} MiB received instead of {:.1} MiB",
+        container_size(seeded_len) as f64 / MIB,
+        container_size(decompressed_len) as f64 / MIB
+    );
+}
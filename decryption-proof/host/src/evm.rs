@@ -0,0 +1,63 @@
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::Receipt;
+use serde::Serialize;
+use zkfhe_core::journal::{DecryptionJournal, DecryptionLeaf, ParamsSummary};
+
+use crate::error::ProofError;
+use crate::hashing::params_digest;
+use crate::journal::{check_journal, check_trivial, decode_journal};
+use crate::journal_abi::{encode_decryptions, encode_seal};
+
+/// A proof in the form expected by an EVM verifier contract: the image ID, the journal bytes whose
+/// digest the contract recomputes and the seal encoded by [`encode_seal`], all hex encoded.
+#[derive(Debug, Clone, Serialize)]
+pub struct EvmProof {
+    pub image_id: String,
+    pub journal: String,
+    pub seal: String,
+    /// The decrypted values with their ciphertext digests, secret key ID and parameter digest, ABI
+    /// encoded as `(uint64, bytes32, bytes32, bytes32, bytes32)[]`. The journal only commits their
+    /// Merkle root, they are checked against it before being encoded
+    pub decryptions: String,
+}
+
+/// Extract the on-chain verifiable parts of a Groth16 receipt, or of a fake receipt for the mock
+/// verifier, along with the decryptions of `leaves`, which must be those the journal commits.
+pub fn evm_proof(
+    receipt: &Receipt,
+    image_id: Digest,
+    leaves: &[DecryptionLeaf],
+) -> Result<EvmProof, ProofError> {
+    let seal = encode_seal(receipt)?;
+
+    let journal = decode_journal(receipt)?;
+    check_journal(&journal, leaves, &journal.params)?;
+    // Trivial receipts are pipeline checks, they are never exported for on-chain verification
+    check_trivial(&journal, false)?;
+    let params_digest = params_digest(&journal.params);
+    let params = ParamsSummary::from(&journal.params);
+    let decryptions: Vec<DecryptionJournal> = leaves
+        .iter()
+        .map(|leaf| DecryptionJournal {
+            ct_digest: leaf.ct_digest,
+            secret_key_id: journal.secret_key_id,
+            source_ct_digest: leaf.source_ct_digest,
+            plaintext: leaf.plaintext,
+            overflow: false,
+            params_digest,
+            params,
+            noise_budget_bits: None,
+            request_id: None,
+        })
+        .collect();
+
+    Ok(EvmProof {
+        image_id: format!("0x{}", hex::encode(image_id.as_bytes())),
+        journal: format!("0x{}", hex::encode(&receipt.journal.bytes)),
+        seal: format!("0x{}", hex::encode(seal)),
+        decryptions: format!(
+            "0x{}",
+            hex::encode(encode_decryptions(&decryptions, image_id))
+        ),
+    })
+}
@@ -0,0 +1,132 @@
+//! Execution of the guests without proving, for fast correctness checks and CI.
+//!
+//! The executor runs the same guest binaries and reports the same journal as the prover, but
+//! produces no seal: nothing attests the journal. It is returned as an [`UnprovenJournal`], a type
+//! distinct from a receipt that no verification function accepts, so an execution result cannot
+//! be mistaken for a proof.
+//!
+//! [`check_determinism`] executes a guest twice on the same inputs before proving: a journal or a
+//! cycle count depending on anything else, a floating point path or the allocator, would make the
+//! receipt irreproducible and the proving cost unpredictable.
+
+use risc0_zkvm::{default_executor, ExecutorEnv, ExitCode, Session};
+use serde::de::DeserializeOwned;
+
+use crate::error::ProofError;
+use crate::proof_kind::ProofKind;
+use crate::verification::{check_journal_kind, decode_envelope, decode_rejection};
+
+/// Cycle counts of an execution, those of the proof of the same inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionStats {
+    pub segments: usize,
+    /// Cycles spent in the guest itself
+    pub user_cycles: u64,
+    /// Cycles of the padded segments, what proving costs
+    pub total_cycles: u64,
+}
+
+/// The journal a guest committed when executed without proving. It is only what the guest
+/// computed on the host machine, never evidence for a third party.
+#[derive(Debug)]
+pub struct UnprovenJournal<Payload> {
+    kind: ProofKind,
+    payload: Payload,
+    pub stats: ExecutionStats,
+}
+
+impl<Payload> UnprovenJournal<Payload> {
+    /// The guest that was executed.
+    pub fn kind(&self) -> ProofKind {
+        self.kind
+    }
+
+    /// The committed journal, unproven.
+    pub fn unproven_payload(&self) -> &Payload {
+        &self.payload
+    }
+
+    pub fn into_unproven_payload(self) -> Payload {
+        self.payload
+    }
+}
+
+/// Execute the guest of `kind` in `env` and decode its journal, a guest halted by an input sanity
+/// check is reported as [`ProofError::GuestRejected`] and a guest panic as
+/// [`ProofError::GuestPanic`].
+pub fn execute<Payload: DeserializeOwned>(
+    kind: ProofKind,
+    env: ExecutorEnv<'_>,
+) -> Result<UnprovenJournal<Payload>, ProofError> {
+    let session = default_executor()
+        .execute(env, kind.elf())
+        .map_err(ProofError::proving)?;
+    match session.exit_code {
+        ExitCode::Halted(0) => {}
+        ExitCode::Halted(exit_code) => return Err(decode_rejection(exit_code, &session.journal)),
+        exit_code => return Err(ProofError::Proving(format!("guest stopped with {exit_code:?}"))),
+    }
+
+    let envelope = decode_envelope(&session.journal)?;
+    check_journal_kind(envelope.kind, &[kind.journal_kind()])?;
+    Ok(UnprovenJournal {
+        kind,
+        payload: envelope.payload,
+        stats: execution_stats(&session),
+    })
+}
+
+fn execution_stats(session: &Session) -> ExecutionStats {
+    ExecutionStats {
+        segments: session.segments.len(),
+        user_cycles: session
+            .segments
+            .iter()
+            .map(|segment| u64::from(segment.cycles))
+            .sum(),
+        total_cycles: session
+            .segments
+            .iter()
+            .map(|segment| 1u64 << segment.po2)
+            .sum(),
+    }
+}
+
+/// Execute the guest of `kind` twice, each time in a fresh environment from `build_env` holding
+/// the same inputs, and fail with [`ProofError::Nondeterminism`] unless both runs stop with the
+/// same exit code, commit the same journal bytes and run the same cycle counts. A rejected input
+/// is deterministic as well, the stats of the first run are returned either way.
+pub fn check_determinism<'a>(
+    kind: ProofKind,
+    mut build_env: impl FnMut() -> Result<ExecutorEnv<'a>, ProofError>,
+) -> Result<ExecutionStats, ProofError> {
+    let mut run = || -> Result<(ExitCode, Vec<u8>, ExecutionStats), ProofError> {
+        let session = default_executor()
+            .execute(build_env()?, kind.elf())
+            .map_err(ProofError::proving)?;
+        let stats = execution_stats(&session);
+        Ok((session.exit_code, session.journal.bytes, stats))
+    };
+    let (first_exit_code, first_journal, first_stats) = run()?;
+    let (second_exit_code, second_journal, second_stats) = run()?;
+
+    if first_exit_code != second_exit_code {
+        return Err(ProofError::Nondeterminism(format!(
+            "{} stopped with {first_exit_code:?}, then with {second_exit_code:?}",
+            kind.guest_name()
+        )));
+    }
+    if first_journal != second_journal {
+        return Err(ProofError::Nondeterminism(format!(
+            "{} committed two different journals",
+            kind.guest_name()
+        )));
+    }
+    if first_stats != second_stats {
+        return Err(ProofError::Nondeterminism(format!(
+            "{} ran {first_stats:?}, then {second_stats:?}",
+            kind.guest_name()
+        )));
+    }
+    Ok(first_stats)
+}
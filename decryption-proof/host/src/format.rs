@@ -0,0 +1,103 @@
+//! File formats of ciphertexts, keys and receipts.
+//!
+//! bincode is the native format of the host and the `verifier`. CBOR lets producers written in
+//! other languages hand over tfhe-rs entities with a standard decoder, and the hex and base64
+//! armors carry the bincode encoding as text, to embed a receipt or a ciphertext in a JSON API.
+//! Whitespace is ignored when reading armored text, so wrapped lines are accepted.
+
+use std::fmt;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use clap::ValueEnum;
+use risc0_zkvm::sha::Digest;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::ProofError;
+
+/// Encoding of a serialized entity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum FileFormat {
+    #[default]
+    Bincode,
+    Cbor,
+    /// Hex armored bincode
+    Hex,
+    /// Base64 armored bincode, standard alphabet with padding
+    Base64,
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FileFormat::Bincode => "bincode",
+            FileFormat::Cbor => "cbor",
+            FileFormat::Hex => "hex",
+            FileFormat::Base64 => "base64",
+        })
+    }
+}
+
+/// Serialize `value` in `format`, armored formats end with a newline.
+pub fn encode<T: Serialize>(value: &T, format: FileFormat) -> Result<Vec<u8>, ProofError> {
+    match format {
+        FileFormat::Bincode => bincode::serialize(value).map_err(ProofError::serialization),
+        FileFormat::Cbor => {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(value, &mut bytes).map_err(ProofError::serialization)?;
+            Ok(bytes)
+        }
+        FileFormat::Hex => armor(hex::encode(encode(value, FileFormat::Bincode)?)),
+        FileFormat::Base64 => armor(STANDARD.encode(encode(value, FileFormat::Bincode)?)),
+    }
+}
+
+fn armor(mut text: String) -> Result<Vec<u8>, ProofError> {
+    text.push('\n');
+    Ok(text.into_bytes())
+}
+
+/// Deserialize a value encoded in `format`.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8], format: FileFormat) -> Result<T, ProofError> {
+    match format {
+        FileFormat::Bincode => bincode::deserialize(bytes).map_err(ProofError::serialization),
+        FileFormat::Cbor => ciborium::from_reader(bytes).map_err(ProofError::serialization),
+        FileFormat::Hex => {
+            let text = unarmor(bytes);
+            let text = text.strip_prefix(b"0x").unwrap_or(&text);
+            decode(&hex::decode(text).map_err(ProofError::serialization)?, FileFormat::Bincode)
+        }
+        FileFormat::Base64 => {
+            let bytes = STANDARD.decode(unarmor(bytes)).map_err(ProofError::serialization)?;
+            decode(&bytes, FileFormat::Bincode)
+        }
+    }
+}
+
+fn unarmor(text: &[u8]) -> Vec<u8> {
+    text.iter().copied().filter(|byte| !byte.is_ascii_whitespace()).collect()
+}
+
+pub fn write_file<T: Serialize>(
+    path: &Path,
+    value: &T,
+    format: FileFormat,
+) -> Result<(), ProofError> {
+    std::fs::write(path, encode(value, format)?)?;
+    Ok(())
+}
+
+pub fn read_file<T: DeserializeOwned>(path: &Path, format: FileFormat) -> Result<T, ProofError> {
+    decode(&std::fs::read(path)?, format)
+}
+
+/// The `verifier` command checking the receipt written to `out`.
+pub fn verifier_command(out: &Path, image_id: Digest, format: FileFormat) -> String {
+    let format = match format {
+        FileFormat::Bincode => String::new(),
+        format => format!(" --format {format}"),
+    };
+    format!("cargo run --bin verifier -- {} --image-id {image_id}{format}", out.display())
+}
@@ -0,0 +1,66 @@
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::GlweDecryptionInput;
+use zkfhe_core::journal::GlweDecryptionJournal;
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::hashing::{glwe_ciphertext_digest, params_digest};
+use crate::io::{glwe_ciphertext_data, glwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// Prove the decryption of every coefficient of the plaintext polynomial of `ciphertext`.
+pub fn prove_glwe_decryption(
+    params: &ProofParams,
+    glwe_sk: &GlweSecretKeyOwned<u64>,
+    ciphertext: &GlweCiphertextOwned<u64>,
+) -> Result<Receipt, ProofError> {
+    let env = ExecutorEnv::builder()
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&GlweDecryptionInput {
+            glwe_sk: glwe_secret_key_data(glwe_sk),
+            ciphertext: glwe_ciphertext_data(ciphertext),
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::GlweDecrypt.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a GLWE decryption receipt for `ciphertext` under the secret key of ID `secret_key_id`,
+/// the ID of the flattened LWE secret key, and `expected_params` and return the decoded
+/// coefficients.
+pub fn check_glwe_decryption(
+    receipt: &Receipt,
+    ciphertext: &GlweCiphertextOwned<u64>,
+    secret_key_id: &[u8; 32],
+    expected_params: &ProofParams,
+) -> Result<Vec<u64>, ProofError> {
+    verify_receipt(receipt, ProofKind::GlweDecrypt.image_id())?;
+    let journal: GlweDecryptionJournal = decode_payload(receipt, ProofKind::GlweDecrypt)?;
+
+    if journal.ct_digest != glwe_ciphertext_digest(ciphertext) {
+        return Err(ProofError::Verification("GLWE ciphertext digest mismatch".to_string()));
+    }
+    if &journal.secret_key_id != secret_key_id {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+    if journal.plaintexts.len() != ciphertext.polynomial_size().0 {
+        return Err(ProofError::Verification(format!(
+            "journal has {} coefficients, expected {}",
+            journal.plaintexts.len(),
+            ciphertext.polynomial_size().0
+        )));
+    }
+
+    Ok(journal.plaintexts)
+}
@@ -0,0 +1,247 @@
+//! Host side counterparts of the guest hashing helpers, every function produces the digest the
+//! guests commit for the same data, or the canonical encoding that digest is taken of.
+
+use risc0_zkvm::sha::{Impl, Sha256};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::canonical::{
+    self, encode_params, encode_u128s, encode_u64s, glwe_ciphertext_header, lwe_ciphertext_header,
+    lwe_compact_ciphertext_list_header, lwe_keyswitch_key_header, lwe_public_key_header,
+    lwe_secret_key_header,
+};
+use zkfhe_core::journal::DecryptionLeaf;
+use zkfhe_core::params::ProofParams;
+
+use crate::io::{lwe_multi_bit_bootstrap_key_header, ntt_lwe_bootstrap_key_header};
+
+fn to_array(digest: &risc0_zkvm::sha::Digest) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(digest.as_bytes());
+    bytes
+}
+
+/// SHA-256 of `bytes`.
+pub fn sha256_bytes(bytes: &[u8]) -> [u8; 32] {
+    to_array(&Impl::hash_bytes(bytes))
+}
+
+/// SHA-256 of the little endian encoding of `words`.
+pub fn sha256_words(words: &[u32]) -> [u8; 32] {
+    to_array(&Impl::hash_words(words))
+}
+
+/// Canonical encoding of an LWE ciphertext, see [`zkfhe_core::canonical`].
+pub fn canonical_lwe_ciphertext(ciphertext: &LweCiphertextOwned<u64>) -> Vec<u8> {
+    let header = lwe_ciphertext_header(
+        u64::BITS,
+        ciphertext.ciphertext_modulus().get_custom_modulus(),
+        ciphertext.as_ref().len(),
+    );
+    encode_u64s(header, ciphertext.as_ref())
+}
+
+/// Canonical encoding of an LWE ciphertext on 128 bits.
+pub fn canonical_lwe_ciphertext_u128(ciphertext: &LweCiphertextOwned<u128>) -> Vec<u8> {
+    let header = lwe_ciphertext_header(
+        u128::BITS,
+        ciphertext.ciphertext_modulus().get_custom_modulus(),
+        ciphertext.as_ref().len(),
+    );
+    encode_u128s(header, ciphertext.as_ref())
+}
+
+/// Canonical encoding of a GLWE ciphertext.
+pub fn canonical_glwe_ciphertext(ciphertext: &GlweCiphertextOwned<u64>) -> Vec<u8> {
+    let header = glwe_ciphertext_header(
+        ciphertext.polynomial_size().0,
+        ciphertext.ciphertext_modulus().get_custom_modulus(),
+        ciphertext.as_ref().len(),
+    );
+    encode_u64s(header, ciphertext.as_ref())
+}
+
+/// Canonical encoding of an LWE secret key or secret key share.
+pub fn canonical_lwe_secret_key(secret_key: &[u64]) -> Vec<u8> {
+    encode_u64s(lwe_secret_key_header(u64::BITS, secret_key.len()), secret_key)
+}
+
+/// Canonical encoding of an LWE secret key on 128 bits.
+pub fn canonical_lwe_secret_key_u128(secret_key: &[u128]) -> Vec<u8> {
+    encode_u128s(lwe_secret_key_header(u128::BITS, secret_key.len()), secret_key)
+}
+
+/// Canonical encoding of a keyswitching key.
+pub fn canonical_lwe_keyswitch_key(ksk: &LweKeyswitchKeyOwned<u64>) -> Vec<u8> {
+    let header = lwe_keyswitch_key_header(
+        ksk.decomposition_base_log().0,
+        ksk.decomposition_level_count().0,
+        ksk.output_lwe_size().0,
+        ksk.ciphertext_modulus().get_custom_modulus(),
+        ksk.as_ref().len(),
+    );
+    encode_u64s(header, ksk.as_ref())
+}
+
+/// Canonical encoding of an LWE public key.
+pub fn canonical_lwe_public_key(public_key: &LwePublicKeyOwned<u64>) -> Vec<u8> {
+    let header = lwe_public_key_header(
+        public_key.lwe_size().0,
+        public_key.ciphertext_modulus().get_custom_modulus(),
+        public_key.as_ref().len(),
+    );
+    encode_u64s(header, public_key.as_ref())
+}
+
+/// Canonical encoding of an LWE compact ciphertext list, before its expansion.
+pub fn canonical_lwe_compact_ciphertext_list(list: &LweCompactCiphertextListOwned<u64>) -> Vec<u8> {
+    let header = lwe_compact_ciphertext_list_header(
+        list.lwe_size().0,
+        list.lwe_ciphertext_count().0,
+        list.ciphertext_modulus().get_custom_modulus(),
+        list.as_ref().len(),
+    );
+    encode_u64s(header, list.as_ref())
+}
+
+/// Canonical encoding of an NTT bootstrapping key, its header is the one written to the guest.
+pub fn canonical_ntt_lwe_bootstrap_key(bootstrap_key: &NttLweBootstrapKeyOwned<u64>) -> Vec<u8> {
+    let header = canonical::ntt_lwe_bootstrap_key_header(&ntt_lwe_bootstrap_key_header(
+        bootstrap_key,
+    ));
+    encode_u64s(header, bootstrap_key.as_view().into_container())
+}
+
+/// Canonical encoding of a multi-bit bootstrapping key, its header is the one written to the guest.
+pub fn canonical_lwe_multi_bit_bootstrap_key(
+    bootstrap_key: &LweMultiBitBootstrapKeyOwned<u64>,
+) -> Vec<u8> {
+    let header = canonical::lwe_multi_bit_bootstrap_key_header(
+        &lwe_multi_bit_bootstrap_key_header(bootstrap_key),
+    );
+    encode_u64s(header, bootstrap_key.as_ref())
+}
+
+/// SHA-256 of the canonical encoding of the ciphertext.
+pub fn lwe_ciphertext_digest(ciphertext: &LweCiphertextOwned<u64>) -> [u8; 32] {
+    sha256_bytes(&canonical_lwe_ciphertext(ciphertext))
+}
+
+/// SHA-256 of the canonical encoding of a ciphertext on 128 bits.
+pub fn lwe_ciphertext_u128_digest(ciphertext: &LweCiphertextOwned<u128>) -> [u8; 32] {
+    sha256_bytes(&canonical_lwe_ciphertext_u128(ciphertext))
+}
+
+/// SHA-256 of the canonical encoding of the GLWE ciphertext.
+pub fn glwe_ciphertext_digest(ciphertext: &GlweCiphertextOwned<u64>) -> [u8; 32] {
+    sha256_bytes(&canonical_glwe_ciphertext(ciphertext))
+}
+
+/// SHA-256 of the canonical encoding of the NTT bootstrapping key.
+pub fn ntt_lwe_bootstrap_key_digest(bootstrap_key: &NttLweBootstrapKeyOwned<u64>) -> [u8; 32] {
+    sha256_bytes(&canonical_ntt_lwe_bootstrap_key(bootstrap_key))
+}
+
+/// SHA-256 of the canonical encoding of the multi-bit bootstrapping key.
+pub fn lwe_multi_bit_bootstrap_key_digest(
+    bootstrap_key: &LweMultiBitBootstrapKeyOwned<u64>,
+) -> [u8; 32] {
+    sha256_bytes(&canonical_lwe_multi_bit_bootstrap_key(bootstrap_key))
+}
+
+/// SHA-256 of the canonical encoding of the LWE public key.
+pub fn lwe_public_key_digest(public_key: &LwePublicKeyOwned<u64>) -> [u8; 32] {
+    sha256_bytes(&canonical_lwe_public_key(public_key))
+}
+
+/// SHA-256 of the canonical encoding of an LWE compact ciphertext list.
+pub fn lwe_compact_ciphertext_list_digest(list: &LweCompactCiphertextListOwned<u64>) -> [u8; 32] {
+    sha256_bytes(&canonical_lwe_compact_ciphertext_list(list))
+}
+
+/// SHA-256 of the canonical encoding of a secret key or secret key share.
+pub fn secret_key_digest(secret_key: &[u64]) -> [u8; 32] {
+    sha256_bytes(&canonical_lwe_secret_key(secret_key))
+}
+
+/// SHA-256 of the canonical encoding of a secret key on 128 bits.
+pub fn secret_key_u128_digest(secret_key: &[u128]) -> [u8; 32] {
+    sha256_bytes(&canonical_lwe_secret_key_u128(secret_key))
+}
+
+/// SHA-256 of the canonical encoding of a keyswitching key.
+pub fn keyswitch_key_digest(ksk: &LweKeyswitchKeyOwned<u64>) -> [u8; 32] {
+    sha256_bytes(&canonical_lwe_keyswitch_key(ksk))
+}
+
+/// Commitment to a plaintext under the journal policy, `SHA-256(salt || plaintext)`.
+pub fn plaintext_commitment(salt: &[u8; 32], plaintext: u64) -> [u8; 32] {
+    let mut bytes = [0u8; 40];
+    bytes[..32].copy_from_slice(salt);
+    bytes[32..].copy_from_slice(&plaintext.to_le_bytes());
+    sha256_bytes(&bytes)
+}
+
+/// Digest of a homomorphic circuit description, `SHA-256("zkfhe-circuit" || description)`. The
+/// description is opaque to the proofs, any encoding the computation attestation commits to works.
+pub fn circuit_digest(description: &[u8]) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(13 + description.len());
+    bytes.extend_from_slice(b"zkfhe-circuit");
+    bytes.extend_from_slice(description);
+    sha256_bytes(&bytes)
+}
+
+pub use zkfhe_core::digest::parse_digest;
+
+/// Merkle leaf of a batch decryption, see [`DecryptionLeaf`].
+pub fn decryption_leaf_digest(leaf: &DecryptionLeaf) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(1 + 32 + 8 + 32);
+    bytes.push(0x00);
+    bytes.extend_from_slice(&leaf.ct_digest);
+    bytes.extend_from_slice(&leaf.plaintext.to_le_bytes());
+    if let Some(source_ct_digest) = &leaf.source_ct_digest {
+        bytes.extend_from_slice(source_ct_digest);
+    }
+    sha256_bytes(&bytes)
+}
+
+/// Merkle leaf of an aggregated receipt, `SHA-256(0x00 || image_id || journal)`.
+pub fn aggregated_receipt_leaf(image_id: &[u32; 8], journal: &[u8]) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(1 + 32 + journal.len());
+    bytes.push(0x00);
+    bytes.extend(image_id.iter().flat_map(|word| word.to_le_bytes()));
+    bytes.extend_from_slice(journal);
+    sha256_bytes(&bytes)
+}
+
+/// Merkle root of `leaves`, every node is `SHA-256(0x01 || left || right)` and a node without a
+/// sibling is carried up unchanged. The root of no leaf is all zeros.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => merkle_node(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Inner node of the Merkle trees, `SHA-256(0x01 || left || right)`.
+pub fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut bytes = [0u8; 65];
+    bytes[0] = 0x01;
+    bytes[1..33].copy_from_slice(left);
+    bytes[33..].copy_from_slice(right);
+    sha256_bytes(&bytes)
+}
+
+/// SHA-256 of the canonical encoding of the parameters.
+pub fn params_digest(params: &ProofParams) -> [u8; 32] {
+    sha256_bytes(&encode_params(params))
+}
@@ -0,0 +1,75 @@
+//! Pinning of the guest image IDs.
+//!
+//! An image ID is the digest of a guest binary: it changes with the guest code, its dependencies
+//! or the toolchain that built it, and with it the statement every receipt proves. An
+//! `image_id.toml` file records the image ID of every guest, one `guest_name = "hex"` entry per
+//! guest, so a deployment checks the guests it was built with against the released pins and
+//! notices a silent change instead of publishing receipts for another statement.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::ProofError;
+use crate::proof_kind::ProofKind;
+
+/// Default name of the pin file, at the root of the workspace.
+pub const IMAGE_ID_FILE: &str = "image_id.toml";
+
+const PIN_FILE_HEADER: &str = "# Image IDs of the guests, checked with `--check-image-ids` and \
+                               regenerated with `--pin-image-ids` after an intended guest change\n";
+
+/// Hex encoded image ID of every guest of this build, by guest name.
+pub fn current_image_ids() -> BTreeMap<String, String> {
+    ProofKind::ALL
+        .into_iter()
+        .map(|kind| {
+            (
+                kind.guest_name().to_string(),
+                hex::encode(kind.image_id().as_bytes()),
+            )
+        })
+        .collect()
+}
+
+/// Write the image IDs of this build to `path`, replacing the previous pins.
+pub fn write_image_id_pins(path: &Path) -> Result<(), ProofError> {
+    let pins = toml::to_string(&current_image_ids()).map_err(ProofError::serialization)?;
+    std::fs::write(path, format!("{PIN_FILE_HEADER}{pins}"))?;
+    Ok(())
+}
+
+/// Load the pins recorded in `path`.
+pub fn read_image_id_pins(path: &Path) -> Result<BTreeMap<String, String>, ProofError> {
+    toml::from_str(&std::fs::read_to_string(path)?).map_err(ProofError::serialization)
+}
+
+/// Check every guest of this build against the pins of `path`. A guest whose image ID changed, a
+/// guest without a pin and a pin without a guest are all reported in a single error.
+pub fn check_image_id_pins(path: &Path) -> Result<(), ProofError> {
+    let pinned = read_image_id_pins(path)?;
+    let current = current_image_ids();
+
+    let mut mismatches = Vec::new();
+    for (guest, image_id) in &current {
+        match pinned.get(guest) {
+            Some(pinned_id) if pinned_id == image_id => {}
+            Some(pinned_id) => {
+                mismatches.push(format!("{guest} is {image_id}, pinned {pinned_id}"))
+            }
+            None => mismatches.push(format!("{guest} has no pin")),
+        }
+    }
+    for guest in pinned.keys().filter(|guest| !current.contains_key(*guest)) {
+        mismatches.push(format!("{guest} is pinned but no longer built"));
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(ProofError::Verification(format!(
+            "guest image IDs differ from {}: {}",
+            path.display(),
+            mismatches.join("; ")
+        )))
+    }
+}
@@ -0,0 +1,197 @@
+use rayon::prelude::*;
+use risc0_zkvm::ExecutorEnvBuilder;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::{
+    public_input_padded_len, GlweCiphertextData, GlweSecretKeyData, LweCiphertextData,
+    LweCompactCiphertextListData, LweKeyswitchKeyData, LweMultiBitBootstrapKeyHeader,
+    LwePublicKeyData, LweSecretKeyData, NttLweBootstrapKeyHeader, FRAME_BYTES, FRAME_LEN,
+    MAX_PUBLIC_INPUT_BYTES,
+};
+
+use crate::error::ProofError;
+
+// Lower the tfhe entities to the raw containers of the shared I/O types written to the guests.
+
+pub fn lwe_ciphertext_data<Scalar: UnsignedInteger>(
+    ciphertext: &LweCiphertextOwned<Scalar>,
+) -> LweCiphertextData<Scalar> {
+    LweCiphertextData {
+        data: ciphertext.as_ref().to_vec(),
+        ciphertext_modulus: ciphertext.ciphertext_modulus().get_custom_modulus(),
+    }
+}
+
+pub fn lwe_secret_key_data<Scalar: UnsignedInteger>(
+    secret_key: &LweSecretKeyOwned<Scalar>,
+) -> LweSecretKeyData<Scalar> {
+    LweSecretKeyData {
+        data: secret_key.as_ref().to_vec(),
+    }
+}
+
+pub fn glwe_secret_key_data(secret_key: &GlweSecretKeyOwned<u64>) -> GlweSecretKeyData {
+    GlweSecretKeyData {
+        data: secret_key.as_ref().to_vec(),
+        polynomial_size: secret_key.polynomial_size().0,
+    }
+}
+
+pub fn glwe_ciphertext_data(ciphertext: &GlweCiphertextOwned<u64>) -> GlweCiphertextData {
+    GlweCiphertextData {
+        data: ciphertext.as_ref().to_vec(),
+        polynomial_size: ciphertext.polynomial_size().0,
+        ciphertext_modulus: ciphertext.ciphertext_modulus().get_custom_modulus(),
+    }
+}
+
+pub fn lwe_public_key_data(public_key: &LwePublicKeyOwned<u64>) -> LwePublicKeyData {
+    LwePublicKeyData {
+        data: public_key.as_ref().to_vec(),
+        lwe_size: public_key.lwe_size().0,
+        ciphertext_modulus: public_key.ciphertext_modulus().get_custom_modulus(),
+    }
+}
+
+pub fn lwe_compact_ciphertext_list_data(
+    list: &LweCompactCiphertextListOwned<u64>,
+) -> LweCompactCiphertextListData {
+    LweCompactCiphertextListData {
+        data: list.as_ref().to_vec(),
+        lwe_size: list.lwe_size().0,
+        ciphertext_count: list.lwe_ciphertext_count().0,
+        ciphertext_modulus: list.ciphertext_modulus().get_custom_modulus(),
+    }
+}
+
+// The NTT and multi-bit bootstrapping keys are lowered to a header, their container is written raw
+// in frames.
+
+pub fn ntt_lwe_bootstrap_key_header(
+    bsk: &NttLweBootstrapKeyOwned<u64>,
+) -> NttLweBootstrapKeyHeader {
+    NttLweBootstrapKeyHeader {
+        container_len: bsk.as_view().into_container().len() as u64,
+        glwe_size: bsk.glwe_size().0,
+        polynomial_size: bsk.polynomial_size().0,
+        decomposition_base_log: bsk.decomposition_base_log().0,
+        decomposition_level_count: bsk.decomposition_level_count().0,
+        ciphertext_modulus: bsk.ciphertext_modulus().get_custom_modulus(),
+    }
+}
+
+pub fn lwe_multi_bit_bootstrap_key_header(
+    bsk: &LweMultiBitBootstrapKeyOwned<u64>,
+) -> LweMultiBitBootstrapKeyHeader {
+    LweMultiBitBootstrapKeyHeader {
+        container_len: bsk.as_ref().len() as u64,
+        input_lwe_dimension: bsk.input_lwe_dimension().0,
+        glwe_size: bsk.glwe_size().0,
+        polynomial_size: bsk.polynomial_size().0,
+        decomposition_base_log: bsk.decomposition_base_log().0,
+        decomposition_level_count: bsk.decomposition_level_count().0,
+        grouping_factor: bsk.grouping_factor().0,
+        ciphertext_modulus: bsk.ciphertext_modulus().get_custom_modulus(),
+    }
+}
+
+pub fn lwe_keyswitch_key_data(ksk: &LweKeyswitchKeyOwned<u64>) -> LweKeyswitchKeyData {
+    LweKeyswitchKeyData {
+        data: ksk.as_ref().to_vec(),
+        decomposition_base_log: ksk.decomposition_base_log().0,
+        decomposition_level_count: ksk.decomposition_level_count().0,
+        output_lwe_size: ksk.output_lwe_size().0,
+        ciphertext_modulus: ksk.ciphertext_modulus().get_custom_modulus(),
+    }
+}
+
+/// Length prefix of a frame, its number of values as a little endian `u32`.
+const FRAME_PREFIX_BYTES: usize = std::mem::size_of::<u32>();
+
+/// Append a raw container to `bytes` in frames of at most [`FRAME_LEN`] values, each prefixed by
+/// its length as a `u32`, so the guest can read and hash it one frame at a time.
+///
+/// Every frame but the last is full, so each one has a known place in the output and the frames
+/// are encoded in parallel, a multi-hundred-megabyte bootstrapping key takes a fraction of the
+/// single threaded time.
+pub fn append_framed_container(bytes: &mut Vec<u8>, container: &[u64]) {
+    let start = bytes.len();
+    let frame_count = container.len().div_ceil(FRAME_LEN);
+    bytes.resize(
+        start + frame_count * FRAME_PREFIX_BYTES + std::mem::size_of_val(container),
+        0,
+    );
+    bytes[start..]
+        .par_chunks_mut(FRAME_PREFIX_BYTES + FRAME_BYTES)
+        .zip(container.par_chunks(FRAME_LEN))
+        .for_each(|(encoded, frame)| {
+            let (prefix, values) = encoded.split_at_mut(FRAME_PREFIX_BYTES);
+            prefix.copy_from_slice(&(frame.len() as u32).to_le_bytes());
+            for (encoded, value) in values.chunks_exact_mut(8).zip(frame) {
+                encoded.copy_from_slice(&value.to_le_bytes());
+            }
+        });
+}
+
+/// Write a raw container in frames, see [`append_framed_container`].
+pub fn write_framed_container(env_builder: &mut ExecutorEnvBuilder<'_>, container: &[u64]) {
+    let mut bytes = Vec::new();
+    append_framed_container(&mut bytes, container);
+    env_builder.write_slice(&bytes);
+}
+
+/// Append the public input frame holding `encoding`, a canonical encoding: its length in bytes as
+/// a `u32`, then the encoding padded with zeros to whole words.
+pub fn append_public_input(bytes: &mut Vec<u8>, encoding: &[u8]) {
+    bytes.extend((encoding.len() as u32).to_le_bytes());
+    let start = bytes.len();
+    bytes.extend_from_slice(encoding);
+    bytes.resize(start + public_input_padded_len(encoding.len()), 0);
+}
+
+/// Write the public input frame holding `encoding`, see [`append_public_input`]. The encoding must
+/// fit in [`MAX_PUBLIC_INPUT_BYTES`] or the guest rejects it.
+pub fn write_public_input(
+    env_builder: &mut ExecutorEnvBuilder<'_>,
+    encoding: &[u8],
+) -> Result<(), ProofError> {
+    if encoding.len() > MAX_PUBLIC_INPUT_BYTES {
+        return Err(ProofError::InvalidInput(format!(
+            "a public input of {} bytes is over the limit of {MAX_PUBLIC_INPUT_BYTES} bytes",
+            encoding.len()
+        )));
+    }
+    let mut bytes = Vec::new();
+    append_public_input(&mut bytes, encoding);
+    env_builder.write_slice(&bytes);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_frames_match_sequential_layout() {
+        let container: Vec<u64> = (0..2 * FRAME_LEN as u64 + 3).collect();
+        let mut expected = vec![0xaa];
+        for frame in container.chunks(FRAME_LEN) {
+            expected.extend((frame.len() as u32).to_le_bytes());
+            expected.extend(frame.iter().flat_map(|value| value.to_le_bytes()));
+        }
+
+        let mut bytes = vec![0xaa];
+        append_framed_container(&mut bytes, &container);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn public_input_is_padded_to_words() {
+        let mut bytes = Vec::new();
+        append_public_input(&mut bytes, &[1, 2, 3, 4, 5]);
+        assert_eq!(bytes, [5, 0, 0, 0, 1, 2, 3, 4, 5, 0, 0, 0]);
+
+        bytes.clear();
+        append_public_input(&mut bytes, &[1, 2, 3, 4]);
+        assert_eq!(bytes, [4, 0, 0, 0, 1, 2, 3, 4]);
+    }
+}
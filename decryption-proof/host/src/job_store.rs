@@ -0,0 +1,170 @@
+//! Persistent store of the proof jobs submitted to a proving service.
+//!
+//! Every job is recorded with the guest it runs, the digests of its inputs and its status, and
+//! its receipt once proven, in a sled database, so a restarted service can resume the jobs it had
+//! not finished and clients can fetch the receipt of an old job by its ID. The store only holds
+//! digests, never the ciphertexts or keys a job was submitted with.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ProofError;
+use crate::proof_kind::ProofKind;
+
+const JOBS_TREE: &str = "jobs";
+const RECEIPTS_TREE: &str = "receipts";
+
+/// Where a job is in its lifecycle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Proving,
+    /// The receipt is stored and verified against the job's image ID
+    Succeeded,
+    Failed { error: String },
+}
+
+/// A submitted job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: u64,
+    /// Hex encoded image ID of the guest the job proves
+    pub image_id: String,
+    /// Digests of the job inputs, as the journal commits them
+    pub input_digests: Vec<[u8; 32]>,
+    pub status: JobStatus,
+    /// Submission and last status change times, in seconds since the Unix epoch
+    pub submitted_at: u64,
+    pub updated_at: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn store_error(error: sled::Error) -> ProofError {
+    ProofError::Io(error.into())
+}
+
+/// The jobs of a proving service and their receipts.
+pub struct JobStore {
+    db: sled::Db,
+    jobs: sled::Tree,
+    receipts: sled::Tree,
+}
+
+impl JobStore {
+    /// Open the store in `dir`, creating it when it does not exist.
+    pub fn open(dir: &Path) -> Result<Self, ProofError> {
+        let db = sled::open(dir).map_err(store_error)?;
+        let jobs = db.open_tree(JOBS_TREE).map_err(store_error)?;
+        let receipts = db.open_tree(RECEIPTS_TREE).map_err(store_error)?;
+        Ok(Self { db, jobs, receipts })
+    }
+
+    /// Record a new queued job proving `kind` over inputs of these digests.
+    pub fn submit(
+        &self,
+        kind: ProofKind,
+        input_digests: Vec<[u8; 32]>,
+    ) -> Result<JobRecord, ProofError> {
+        let submitted_at = now();
+        let record = JobRecord {
+            id: self.db.generate_id().map_err(store_error)?,
+            image_id: hex::encode(kind.image_id().as_bytes()),
+            input_digests,
+            status: JobStatus::Queued,
+            submitted_at,
+            updated_at: submitted_at,
+        };
+        self.write(&record)?;
+        Ok(record)
+    }
+
+    /// The record of the job with this ID.
+    pub fn get(&self, id: u64) -> Result<Option<JobRecord>, ProofError> {
+        self.jobs
+            .get(id.to_be_bytes())
+            .map_err(store_error)?
+            .map(|bytes| serde_json::from_slice(&bytes).map_err(ProofError::serialization))
+            .transpose()
+    }
+
+    /// Move a job to `status`.
+    pub fn set_status(&self, id: u64, status: JobStatus) -> Result<JobRecord, ProofError> {
+        let mut record = self
+            .get(id)?
+            .ok_or_else(|| ProofError::InvalidInput(format!("job {id} does not exist")))?;
+        record.status = status;
+        record.updated_at = now();
+        self.write(&record)?;
+        Ok(record)
+    }
+
+    /// Store the receipt of a job and mark it succeeded, the receipt must verify against the image
+    /// ID the job was submitted for.
+    pub fn complete(&self, id: u64, receipt: &Receipt) -> Result<JobRecord, ProofError> {
+        let record = self
+            .get(id)?
+            .ok_or_else(|| ProofError::InvalidInput(format!("job {id} does not exist")))?;
+        let image_id: [u8; 32] = hex::decode(&record.image_id)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| ProofError::Serialization(format!("job {id} has no valid image ID")))?;
+        receipt
+            .verify(image_id)
+            .map_err(ProofError::verification)?;
+
+        self.receipts
+            .insert(
+                id.to_be_bytes(),
+                bincode::serialize(receipt).map_err(ProofError::serialization)?,
+            )
+            .map_err(store_error)?;
+        let record = self.set_status(id, JobStatus::Succeeded)?;
+        self.db.flush().map_err(store_error)?;
+        Ok(record)
+    }
+
+    /// The receipt of a succeeded job, `None` until the job succeeded.
+    pub fn receipt(&self, id: u64) -> Result<Option<Receipt>, ProofError> {
+        self.receipts
+            .get(id.to_be_bytes())
+            .map_err(store_error)?
+            .map(|bytes| bincode::deserialize(&bytes).map_err(ProofError::serialization))
+            .transpose()
+    }
+
+    /// The jobs a restarted service must run again, in submission order. A job that was being
+    /// proven when the service stopped has no receipt, it is queued again.
+    pub fn recover(&self) -> Result<Vec<JobRecord>, ProofError> {
+        let mut pending = Vec::new();
+        for entry in self.jobs.iter() {
+            let (_, bytes) = entry.map_err(store_error)?;
+            let record: JobRecord =
+                serde_json::from_slice(&bytes).map_err(ProofError::serialization)?;
+            match record.status {
+                JobStatus::Queued => pending.push(record),
+                JobStatus::Proving => pending.push(self.set_status(record.id, JobStatus::Queued)?),
+                JobStatus::Succeeded | JobStatus::Failed { .. } => {}
+            }
+        }
+        Ok(pending)
+    }
+
+    fn write(&self, record: &JobRecord) -> Result<(), ProofError> {
+        self.jobs
+            .insert(
+                record.id.to_be_bytes(),
+                serde_json::to_vec(record).map_err(ProofError::serialization)?,
+            )
+            .map_err(store_error)?;
+        Ok(())
+    }
+}
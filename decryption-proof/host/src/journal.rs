@@ -0,0 +1,222 @@
+use risc0_zkvm::Receipt;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::journal::{BatchJournal, DecryptionLeaf, JournalKind, NttParams};
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::hashing::{
+    decryption_leaf_digest, keyswitch_key_digest, lwe_ciphertext_digest,
+    lwe_multi_bit_bootstrap_key_digest, merkle_root, ntt_lwe_bootstrap_key_digest,
+    secret_key_digest,
+};
+use crate::merkle::{merkle_proof, verify_merkle_proof, MerkleProof};
+use crate::seed::seed_commitment;
+use crate::verification::{check_journal_kind, decode_envelope};
+
+/// Decode the batch journal of a receipt, committed by either batch guest.
+pub fn decode_journal(receipt: &Receipt) -> Result<BatchJournal, ProofError> {
+    let envelope = decode_envelope(&receipt.journal)?;
+    check_journal_kind(
+        envelope.kind,
+        &[JournalKind::BatchDecrypt, JournalKind::PbsDecrypt],
+    )?;
+    Ok(envelope.payload)
+}
+
+/// The leaves the batch guests commit for `ciphertexts` decrypted to `plaintexts`, in order.
+///
+/// When `source_ciphertexts` is given, every leaf also records the source ciphertext at the same
+/// index, the one the decrypted ciphertext was derived from by a keyswitch or a PBS.
+pub fn decryption_leaves(
+    ciphertexts: &[LweCiphertextOwned<u64>],
+    source_ciphertexts: Option<&[LweCiphertextOwned<u64>]>,
+    plaintexts: &[u64],
+) -> Result<Vec<DecryptionLeaf>, ProofError> {
+    let length_mismatch = plaintexts.len() != ciphertexts.len()
+        || source_ciphertexts.is_some_and(|sources| sources.len() != ciphertexts.len());
+    if length_mismatch {
+        return Err(ProofError::InvalidInput(format!(
+            "{} ciphertexts for {} plaintexts",
+            ciphertexts.len(),
+            plaintexts.len()
+        )));
+    }
+
+    Ok(ciphertexts
+        .iter()
+        .zip(plaintexts)
+        .enumerate()
+        .map(|(index, (ciphertext, &plaintext))| DecryptionLeaf {
+            ct_digest: lwe_ciphertext_digest(ciphertext),
+            source_ct_digest: source_ciphertexts
+                .map(|source_ciphertexts| lwe_ciphertext_digest(&source_ciphertexts[index])),
+            plaintext,
+        })
+        .collect())
+}
+
+/// Check that the journal commits to exactly the given decryptions, in order, under the given
+/// parameters.
+pub fn check_journal(
+    journal: &BatchJournal,
+    leaves: &[DecryptionLeaf],
+    expected_params: &ProofParams,
+) -> Result<(), ProofError> {
+    if &journal.params != expected_params {
+        return Err(ProofError::ParameterMismatch(format!(
+            "journal parameters {} do not match the expected {}",
+            journal.params.name, expected_params.name
+        )));
+    }
+    if journal.decryption_count as usize != leaves.len() {
+        return Err(ProofError::Verification(format!(
+            "journal has {} decryptions, expected {}",
+            journal.decryption_count,
+            leaves.len()
+        )));
+    }
+
+    let leaf_digests: Vec<[u8; 32]> = leaves.iter().map(decryption_leaf_digest).collect();
+    if journal.decryptions_root != merkle_root(&leaf_digests) {
+        return Err(ProofError::Verification("decryptions root mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// Inclusion proof of every decryption of a batch, for verifiers interested in a single one.
+pub fn decryption_proofs(leaves: &[DecryptionLeaf]) -> Vec<MerkleProof> {
+    let leaf_digests: Vec<[u8; 32]> = leaves.iter().map(decryption_leaf_digest).collect();
+    (0..leaves.len())
+        .filter_map(|index| merkle_proof(&leaf_digests, index))
+        .collect()
+}
+
+/// Check that the journal commits to `leaf` with the inclusion proof `proof`, without the other
+/// decryptions of the batch.
+pub fn check_decryption_inclusion(
+    journal: &BatchJournal,
+    leaf: &DecryptionLeaf,
+    proof: &MerkleProof,
+) -> Result<(), ProofError> {
+    if proof.leaf_count != journal.decryption_count {
+        return Err(ProofError::Verification(format!(
+            "inclusion proof for {} decryptions, the journal has {}",
+            proof.leaf_count, journal.decryption_count
+        )));
+    }
+    if !verify_merkle_proof(&journal.decryptions_root, &decryption_leaf_digest(leaf), proof) {
+        return Err(ProofError::Verification(format!(
+            "decryption {} is not included in the journal",
+            proof.index
+        )));
+    }
+    Ok(())
+}
+
+/// Check that the journal binds the NTT bootstrapping key used by the PBS guest and the NTT it
+/// bootstrapped with, and no key at all when the decryption-only guest produced it.
+pub fn check_ntt_bootstrap_key(
+    journal: &BatchJournal,
+    ntt_bootstrap_key: Option<&NttLweBootstrapKeyOwned<u64>>,
+) -> Result<(), ProofError> {
+    if journal.ntt_bsk_digest != ntt_bootstrap_key.map(ntt_lwe_bootstrap_key_digest) {
+        return Err(ProofError::Verification("NTT bootstrapping key digest mismatch".to_string()));
+    }
+    if journal.ntt_params != ntt_bootstrap_key.map(ntt_params) {
+        return Err(ProofError::ParameterMismatch("NTT parameters mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// Check that the journal binds the multi-bit bootstrapping key used by the PBS guest and its
+/// grouping factor, and no multi-bit key when the guest bootstrapped with an NTT key or not at all.
+pub fn check_multi_bit_bootstrap_key(
+    journal: &BatchJournal,
+    multi_bit_bootstrap_key: Option<&LweMultiBitBootstrapKeyOwned<u64>>,
+) -> Result<(), ProofError> {
+    if journal.multi_bit_bsk_digest
+        != multi_bit_bootstrap_key.map(lwe_multi_bit_bootstrap_key_digest)
+    {
+        return Err(ProofError::Verification(
+            "multi-bit bootstrapping key digest mismatch".to_string(),
+        ));
+    }
+    if journal.grouping_factor != multi_bit_bootstrap_key.map(|bsk| bsk.grouping_factor().0 as u32)
+    {
+        return Err(ProofError::ParameterMismatch("grouping factor mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// The NTT parameters a PBS guest commits for `ntt_bootstrap_key`.
+pub fn ntt_params(ntt_bootstrap_key: &NttLweBootstrapKeyOwned<u64>) -> NttParams {
+    NttParams {
+        ciphertext_modulus: ntt_bootstrap_key.ciphertext_modulus().get_custom_modulus(),
+        polynomial_size: ntt_bootstrap_key.polynomial_size().0,
+        decomposition_base_log: ntt_bootstrap_key.decomposition_base_log().0,
+        decomposition_level_count: ntt_bootstrap_key.decomposition_level_count().0,
+    }
+}
+
+/// Check that the journal commits to the IDs of the secret key the batch was decrypted with and of
+/// the keyswitching key, when the guest keyswitched.
+pub fn check_key_ids(
+    journal: &BatchJournal,
+    secret_key: &LweSecretKeyOwned<u64>,
+    ksk: Option<&LweKeyswitchKeyOwned<u64>>,
+) -> Result<(), ProofError> {
+    if journal.secret_key_id != secret_key_digest(secret_key.as_ref()) {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    if journal.ksk_id != ksk.map(keyswitch_key_digest) {
+        return Err(ProofError::Verification("keyswitching key ID mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// Check that the journal commits to the function table the PBS guest bootstrapped, or to none
+/// for the decryption-only guest.
+pub fn check_lut(journal: &BatchJournal, lut: Option<&[u64]>) -> Result<(), ProofError> {
+    if journal.lut.as_deref() != lut {
+        return Err(ProofError::Verification("PBS function table mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// Check that the journal commits to the seed the host was run with, or to no seed at all.
+pub fn check_seed_commitment(
+    journal: &BatchJournal,
+    seed: Option<&[u8; 32]>,
+) -> Result<(), ProofError> {
+    if journal.seed_commitment != seed.map(seed_commitment) {
+        return Err(ProofError::Verification("seed commitment mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// Check that the journal links the batch to the circuit digest the host attached, or to no
+/// circuit at all.
+pub fn check_circuit_digest(
+    journal: &BatchJournal,
+    circuit_digest: Option<&[u8; 32]>,
+) -> Result<(), ProofError> {
+    if journal.circuit_digest.as_ref() != circuit_digest {
+        return Err(ProofError::Verification("circuit digest mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// Check that the journal flags trivial encryptions exactly when the batch was trivially
+/// encrypted, a trivial receipt proves nothing about the secret key and must not pass for a real
+/// proof.
+pub fn check_trivial(journal: &BatchJournal, trivial: bool) -> Result<(), ProofError> {
+    match (journal.trivial, trivial) {
+        (true, false) => Err(ProofError::Verification(
+            "the journal commits trivial encryptions".to_string(),
+        )),
+        (false, true) => Err(ProofError::Verification(
+            "the journal does not flag the trivial encryptions".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
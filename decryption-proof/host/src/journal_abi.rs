@@ -0,0 +1,144 @@
+//! Solidity ABI encoding of the decryption journal and of receipt seals.
+//!
+//! Every value is encoded as `abi.encode` would on the contract side: one 32 bytes word per static
+//! value, unsigned integers left padded with zeros.
+
+use risc0_zkvm::sha::{Digest, Digestible};
+use risc0_zkvm::{InnerReceipt, Receipt};
+use zkfhe_core::journal::DecryptionJournal;
+
+use crate::error::ProofError;
+
+/// Selector of the seals of fake receipts, as accepted by the RISC Zero mock verifier contract.
+const FAKE_RECEIPT_SELECTOR: [u8; 4] = [0xFF; 4];
+
+fn uint_word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// `abi.encode(uint64 plaintext, bytes32 ctDigest, bytes32 secretKeyId, bytes32 paramsDigest,
+/// bytes32 imageId)`
+pub fn encode_decryption(entry: &DecryptionJournal, image_id: Digest) -> Vec<u8> {
+    [
+        uint_word(entry.plaintext),
+        entry.ct_digest,
+        entry.secret_key_id,
+        entry.params_digest,
+        image_id.into(),
+    ]
+    .concat()
+}
+
+/// `abi.encode(Decryption[] decryptions)` where `Decryption` is the tuple of
+/// [`encode_decryption`].
+pub fn encode_decryptions(decryptions: &[DecryptionJournal], image_id: Digest) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(64 + decryptions.len() * 160);
+    // Offset of the dynamic array, then its length and its static elements
+    encoded.extend_from_slice(&uint_word(32));
+    encoded.extend_from_slice(&uint_word(decryptions.len() as u64));
+    for entry in decryptions {
+        encoded.extend(encode_decryption(entry, image_id));
+    }
+    encoded
+}
+
+/// Encode the seal of a receipt for the RISC Zero verifier contracts: the first 4 bytes of the
+/// verifier parameters digest select the verifier, followed by the Groth16 seal. Fake receipts,
+/// produced in dev mode, are encoded for the mock verifier as the fake selector followed by the
+/// claim digest.
+pub fn encode_seal(receipt: &Receipt) -> Result<Vec<u8>, ProofError> {
+    match &receipt.inner {
+        InnerReceipt::Groth16(groth16) => {
+            Ok([&groth16.verifier_parameters.as_bytes()[..4], &groth16.seal[..]].concat())
+        }
+        InnerReceipt::Fake(_) => {
+            let claim_digest = receipt.claim().map_err(ProofError::verification)?.digest();
+            Ok([&FAKE_RECEIPT_SELECTOR[..], claim_digest.as_bytes()].concat())
+        }
+        _ => Err(ProofError::InvalidInput(
+            "only Groth16 and fake receipts can be verified on-chain".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_zkvm::{FakeReceipt, Groth16Receipt, ReceiptClaim};
+    use zkfhe_core::journal::ParamsSummary;
+
+    use crate::params::toy;
+
+    fn entry() -> DecryptionJournal {
+        DecryptionJournal {
+            ct_digest: [1; 32],
+            secret_key_id: [4; 32],
+            source_ct_digest: None,
+            plaintext: 0x0102,
+            overflow: false,
+            params_digest: [2; 32],
+            params: ParamsSummary::from(&toy()),
+            noise_budget_bits: None,
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn decryption_is_five_words() {
+        let image_id = Digest::from([3u8; 32]);
+        let encoded = encode_decryption(&entry(), image_id);
+
+        assert_eq!(encoded.len(), 160);
+        assert!(encoded[..30].iter().all(|&byte| byte == 0));
+        assert_eq!(&encoded[30..32], &[0x01, 0x02]);
+        assert_eq!(&encoded[32..64], &[1; 32]);
+        assert_eq!(&encoded[64..96], &[4; 32]);
+        assert_eq!(&encoded[96..128], &[2; 32]);
+        assert_eq!(&encoded[128..160], &[3; 32]);
+    }
+
+    #[test]
+    fn decryptions_are_a_dynamic_array() {
+        let image_id = Digest::from([3u8; 32]);
+        let encoded = encode_decryptions(&[entry(), entry()], image_id);
+
+        assert_eq!(encoded.len(), 64 + 2 * 160);
+        assert_eq!(&encoded[..32], &uint_word(32));
+        assert_eq!(&encoded[32..64], &uint_word(2));
+        assert_eq!(&encoded[64..224], &encode_decryption(&entry(), image_id)[..]);
+    }
+
+    #[test]
+    fn groth16_seal_is_prefixed_by_the_selector() {
+        let image_id = Digest::from([3u8; 32]);
+        let claim = ReceiptClaim::ok(image_id, vec![4u8; 8]);
+        let verifier_parameters = Digest::from([5u8; 32]);
+        let receipt = Receipt::new(
+            InnerReceipt::Groth16(Groth16Receipt::new(
+                vec![6u8; 256],
+                claim.into(),
+                verifier_parameters,
+            )),
+            vec![4u8; 8],
+        );
+
+        let seal = encode_seal(&receipt).unwrap();
+        assert_eq!(seal.len(), 4 + 256);
+        assert_eq!(&seal[..4], &[5u8; 4]);
+        assert!(seal[4..].iter().all(|&byte| byte == 6));
+    }
+
+    #[test]
+    fn fake_seal_commits_to_the_claim() {
+        let image_id = Digest::from([3u8; 32]);
+        let claim = ReceiptClaim::ok(image_id, vec![4u8; 8]);
+        let claim_digest = claim.digest();
+        let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), vec![4u8; 8]);
+
+        let seal = encode_seal(&receipt).unwrap();
+        assert_eq!(&seal[..4], &FAKE_RECEIPT_SELECTOR);
+        assert_eq!(&seal[4..], claim_digest.as_bytes());
+    }
+}
@@ -0,0 +1,145 @@
+//! Content-addressed on-disk cache of the evaluation keys generated for a run, so repeated runs
+//! with the same secret keys, e.g. seeded with `--seed`, skip minutes of key generation and of NTT
+//! conversion for the large parameter sets.
+//!
+//! An entry is addressed by the SHA-256 of the kind of the key, the digest of the parameters it is
+//! generated with and the digests of the secret keys it encrypts, so a key is only ever reused for
+//! the exact parameters and keys it was generated for. It stores the bincode encoding of the key
+//! after the SHA-256 of that encoding, a corrupted entry is regenerated.
+//!
+//! The entries hold evaluation keys only, never a secret key, but their addresses are derived from
+//! the secret keys: keep the directory private.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::hashing::{params_digest, secret_key_digest, sha256_bytes};
+
+/// The evaluation keys the cache holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachedKeyKind {
+    /// Standard bootstrapping key, converted to the PBS backend after loading
+    BootstrapKey,
+    KeyswitchKey,
+    /// Bootstrapping key on the NTT modulus, already converted to the NTT domain
+    NttBootstrapKey,
+    /// Multi-bit bootstrapping key in the standard domain, keys of other grouping factors are
+    /// other entries
+    MultiBitBootstrapKey { grouping_factor: usize },
+}
+
+impl CachedKeyKind {
+    fn tag(self) -> Vec<u8> {
+        match self {
+            Self::BootstrapKey => b"bootstrap_key".to_vec(),
+            Self::KeyswitchKey => b"keyswitch_key".to_vec(),
+            Self::NttBootstrapKey => b"ntt_bootstrap_key".to_vec(),
+            Self::MultiBitBootstrapKey { grouping_factor } => {
+                format!("multi_bit_bootstrap_key_{grouping_factor}").into_bytes()
+            }
+        }
+    }
+}
+
+impl fmt::Display for CachedKeyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::BootstrapKey => "bootstrapping key",
+            Self::KeyswitchKey => "keyswitching key",
+            Self::NttBootstrapKey => "NTT bootstrapping key",
+            Self::MultiBitBootstrapKey { .. } => "multi-bit bootstrapping key",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A directory of cached keys, one file per entry.
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    /// Open the cache in `dir`, creating it when it does not exist.
+    pub fn open(dir: &Path) -> Result<Self, ProofError> {
+        std::fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// The address of the `kind` key of `params` generated from `secret_keys`, in order.
+    pub fn address(kind: CachedKeyKind, params: &ProofParams, secret_keys: &[&[u64]]) -> [u8; 32] {
+        let mut preimage = kind.tag();
+        preimage.extend(params_digest(params));
+        for secret_key in secret_keys {
+            preimage.extend(secret_key_digest(secret_key));
+        }
+        sha256_bytes(&preimage)
+    }
+
+    /// Load the `kind` key of `params` generated from `secret_keys`, or generate it with
+    /// `generate` and store it for the next runs.
+    pub fn get_or_generate<Key: Serialize + DeserializeOwned>(
+        &self,
+        kind: CachedKeyKind,
+        params: &ProofParams,
+        secret_keys: &[&[u64]],
+        generate: impl FnOnce() -> Key,
+    ) -> Result<Key, ProofError> {
+        let path = self.entry_path(&Self::address(kind, params, secret_keys));
+        if let Some(key) = Self::load(&path)? {
+            tracing::info!("Loaded the {kind} from the key cache");
+            return Ok(key);
+        }
+
+        let key = generate();
+        let encoded = bincode::serialize(&key).map_err(ProofError::serialization)?;
+        let mut entry = sha256_bytes(&encoded).to_vec();
+        entry.extend(encoded);
+        // Written aside then renamed, so a concurrent or interrupted run never reads half an entry
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, entry)?;
+        std::fs::rename(partial, &path)?;
+        tracing::info!("Stored the {kind} in the key cache");
+        Ok(key)
+    }
+
+    /// The key of an entry, `None` when it is missing or corrupted.
+    fn load<Key: DeserializeOwned>(path: &Path) -> Result<Option<Key>, ProofError> {
+        let entry = match std::fs::read(path) {
+            Ok(entry) => entry,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if entry.len() < 32 || entry[..32] != sha256_bytes(&entry[32..]) {
+            tracing::warn!("Regenerating the corrupted key cache entry {}", path.display());
+            return Ok(None);
+        }
+        bincode::deserialize(&entry[32..])
+            .map(Some)
+            .map_err(ProofError::serialization)
+    }
+
+    fn entry_path(&self, address: &[u8; 32]) -> PathBuf {
+        self.dir.join(format!("{}.bin", hex::encode(address)))
+    }
+}
+
+/// Generate a key, through `key_cache` when there is one, see [`KeyCache::get_or_generate`].
+pub fn load_or_generate<Key: Serialize + DeserializeOwned>(
+    key_cache: Option<&KeyCache>,
+    kind: CachedKeyKind,
+    params: &ProofParams,
+    secret_keys: &[&[u64]],
+    generate: impl FnOnce() -> Key,
+) -> Result<Key, ProofError> {
+    match key_cache {
+        Some(key_cache) => key_cache.get_or_generate(kind, params, secret_keys, generate),
+        None => Ok(generate()),
+    }
+}
@@ -0,0 +1,84 @@
+//! Key generation proofs: a published keyswitching key was generated from the claimed secret keys
+//! with the claimed parameters, so relying parties can trust the evaluation key itself and not only
+//! the decryptions proven with it.
+//!
+//! The guest does not re-derive the key from its seed: the CSPRNG of tfhe-rs is not built for the
+//! zkVM (see [`crate::seeded`]). It decrypts instead every encryption of the key under the small
+//! secret key and checks it holds its level of the matching big key element with a noise below
+//! the bound of the parameters, which is what a well formed key is to the keyswitch. The
+//! bootstrapping key is not covered, its GGSW encryptions would have to be decrypted in the
+//! Fourier or NTT domain.
+
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::KeyGenerationInput;
+use zkfhe_core::journal::KeyGenerationJournal;
+use zkfhe_core::params::{NoiseDistribution, ProofParams};
+
+use crate::error::ProofError;
+use crate::hashing::{keyswitch_key_digest, params_digest};
+use crate::io::{lwe_keyswitch_key_data, lwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// Bit length of the largest noise an encryption of `distribution` carries on 64 bits: the bound
+/// itself for a TUniform distribution, 16 standard deviations for a Gaussian one.
+pub fn noise_bound_log2(distribution: NoiseDistribution) -> u32 {
+    match distribution {
+        NoiseDistribution::Gaussian { std_dev } => {
+            (std_dev * 2f64.powi(64)).log2().ceil() as u32 + 4
+        }
+        NoiseDistribution::TUniform { bound_log2 } => bound_log2 + 1,
+    }
+}
+
+/// Prove that `ksk` switches from `input_lwe_sk`, the flattened GLWE key, to `output_lwe_sk` with
+/// the decomposition and the LWE noise of `params`.
+pub fn prove_keyswitch_key_generation(
+    params: &ProofParams,
+    ksk: &LweKeyswitchKeyOwned<u64>,
+    input_lwe_sk: &LweSecretKeyOwned<u64>,
+    output_lwe_sk: &LweSecretKeyOwned<u64>,
+) -> Result<Receipt, ProofError> {
+    let env = ExecutorEnv::builder()
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&KeyGenerationInput {
+            ksk: lwe_keyswitch_key_data(ksk),
+            input_lwe_sk: lwe_secret_key_data(input_lwe_sk),
+            output_lwe_sk: lwe_secret_key_data(output_lwe_sk),
+            noise_bound_log2: noise_bound_log2(params.lwe_noise_distribution),
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::KeyGeneration.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a key generation receipt for `ksk` under `expected_params` and return the journal, whose
+/// secret key IDs a verifier compares with the ones it was given.
+pub fn check_keyswitch_key_generation(
+    receipt: &Receipt,
+    ksk: &LweKeyswitchKeyOwned<u64>,
+    expected_params: &ProofParams,
+) -> Result<KeyGenerationJournal, ProofError> {
+    verify_receipt(receipt, ProofKind::KeyGeneration.image_id())?;
+    let journal: KeyGenerationJournal = decode_payload(receipt, ProofKind::KeyGeneration)?;
+
+    if journal.ksk_id != keyswitch_key_digest(ksk) {
+        return Err(ProofError::Verification("keyswitching key digest mismatch".to_string()));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+    // A looser bound than the parameters' would accept a key with too much noise
+    if journal.noise_bound_log2 != noise_bound_log2(expected_params.lwe_noise_distribution) {
+        return Err(ProofError::ParameterMismatch("noise bound mismatch".to_string()));
+    }
+
+    Ok(journal)
+}
@@ -0,0 +1,224 @@
+//! On-disk registry of the keys proofs are generated with.
+//!
+//! Every public key is stored in its canonical encoding, see [`zkfhe_core::canonical`], under its
+//! content-addressed ID, the SHA-256 of that encoding, which is the ID the guests commit. Secret
+//! keys are only recorded by ID, so the registry can be published as is. Keys of the same kind
+//! are numbered by registration order, so a published registry can be matched against proofs made
+//! over time.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ProofError;
+use crate::hashing::sha256_bytes;
+
+const INDEX_FILE: &str = "index.json";
+
+/// The role of a registered key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyKind {
+    LweSecretKey,
+    KeyswitchKey,
+    NttBootstrapKey,
+    MultiBitBootstrapKey,
+}
+
+impl KeyKind {
+    /// Whether keys of this kind are secret, the registry never stores their encoding.
+    pub fn is_secret(self) -> bool {
+        self == KeyKind::LweSecretKey
+    }
+}
+
+impl fmt::Display for KeyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            KeyKind::LweSecretKey => "LWE secret key",
+            KeyKind::KeyswitchKey => "keyswitching key",
+            KeyKind::NttBootstrapKey => "NTT bootstrapping key",
+            KeyKind::MultiBitBootstrapKey => "multi-bit bootstrapping key",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A registered key, the canonical encoding of a public key is stored in `<id>.bin` next to the
+/// index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRecord {
+    /// Hex encoded key ID
+    pub id: String,
+    pub kind: KeyKind,
+    /// 1 for the first key of its kind, incremented for every new key of the same kind
+    pub version: u32,
+    /// Registration time, in seconds since the Unix epoch
+    pub registered_at: u64,
+    /// Name of the parameter set the key was generated for
+    pub params: String,
+}
+
+/// The ID of a key in its canonical encoding, as committed by the guests.
+pub fn key_id(canonical: &[u8]) -> [u8; 32] {
+    sha256_bytes(canonical)
+}
+
+/// A directory of keys and the JSON index of their records.
+pub struct KeyRegistry {
+    dir: PathBuf,
+    records: Vec<KeyRecord>,
+}
+
+impl KeyRegistry {
+    /// Open the registry in `dir`, creating it when it does not exist.
+    pub fn open(dir: &Path) -> Result<Self, ProofError> {
+        std::fs::create_dir_all(dir)?;
+        let index = dir.join(INDEX_FILE);
+        let records = if index.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&index)?)
+                .map_err(ProofError::serialization)?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            records,
+        })
+    }
+
+    pub fn records(&self) -> &[KeyRecord] {
+        &self.records
+    }
+
+    /// The record of the key with this ID.
+    pub fn get(&self, id: &[u8; 32]) -> Option<&KeyRecord> {
+        let id = hex::encode(id);
+        self.records.iter().find(|record| record.id == id)
+    }
+
+    /// Record a key and store its canonical encoding unless it is secret, and return its record. A
+    /// key registered before keeps its record and version.
+    pub fn register(
+        &mut self,
+        kind: KeyKind,
+        params: &str,
+        canonical: &[u8],
+    ) -> Result<KeyRecord, ProofError> {
+        let id = key_id(canonical);
+        if let Some(record) = self.get(&id) {
+            return Ok(record.clone());
+        }
+
+        if !kind.is_secret() {
+            std::fs::write(self.key_path(&id), canonical)?;
+        }
+        let version = self
+            .records
+            .iter()
+            .filter(|record| record.kind == kind)
+            .count() as u32
+            + 1;
+        let registered_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let record = KeyRecord {
+            id: hex::encode(id),
+            kind,
+            version,
+            registered_at,
+            params: params.to_string(),
+        };
+        self.records.push(record.clone());
+        self.write_index()?;
+        Ok(record)
+    }
+
+    /// Load the canonical encoding of a registered public key, checking it still hashes to its ID.
+    pub fn load(&self, id: &[u8; 32]) -> Result<Vec<u8>, ProofError> {
+        match self.get(id) {
+            Some(record) if record.kind.is_secret() => {
+                return Err(ProofError::InvalidInput(format!(
+                    "{} {} is registered by ID only",
+                    record.kind, record.id
+                )))
+            }
+            Some(_) => {}
+            None => {
+                return Err(ProofError::InvalidInput(format!(
+                    "key {} is not registered",
+                    hex::encode(id)
+                )))
+            }
+        }
+        let canonical = std::fs::read(self.key_path(id))?;
+        if &key_id(&canonical) != id {
+            return Err(ProofError::Verification(format!(
+                "stored key {} does not match its ID",
+                hex::encode(id)
+            )));
+        }
+        Ok(canonical)
+    }
+
+    /// Check that a key ID committed by a proof is registered with the expected kind.
+    pub fn check(&self, id: &[u8; 32], kind: KeyKind) -> Result<&KeyRecord, ProofError> {
+        match self.get(id) {
+            Some(record) if record.kind == kind => Ok(record),
+            Some(record) => Err(ProofError::Verification(format!(
+                "key {} is registered as a {}, not a {kind}",
+                record.id, record.kind
+            ))),
+            None => Err(ProofError::Verification(format!(
+                "{kind} {} is not registered",
+                hex::encode(id)
+            ))),
+        }
+    }
+
+    fn key_path(&self, id: &[u8; 32]) -> PathBuf {
+        self.dir.join(format!("{}.bin", hex::encode(id)))
+    }
+
+    fn write_index(&self) -> Result<(), ProofError> {
+        let index = serde_json::to_string_pretty(&self.records).map_err(ProofError::serialization)?;
+        std::fs::write(self.dir.join(INDEX_FILE), index)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_keys_are_recorded_by_id_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = KeyRegistry::open(dir.path()).unwrap();
+        let secret_key = [7u8; 64];
+        let record = registry.register(KeyKind::LweSecretKey, "toy", &secret_key).unwrap();
+        assert_eq!(record.id, hex::encode(key_id(&secret_key)));
+        assert!(!dir.path().join(format!("{}.bin", record.id)).exists());
+        assert!(registry.load(&key_id(&secret_key)).is_err());
+        for entry in std::fs::read_dir(dir.path()).unwrap() {
+            let contents = std::fs::read(entry.unwrap().path()).unwrap();
+            assert!(!contents.windows(secret_key.len()).any(|window| window == secret_key));
+        }
+
+        let reopened = KeyRegistry::open(dir.path()).unwrap();
+        assert!(reopened.check(&key_id(&secret_key), KeyKind::LweSecretKey).is_ok());
+    }
+
+    #[test]
+    fn public_keys_are_stored() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = KeyRegistry::open(dir.path()).unwrap();
+        let ksk = [3u8; 64];
+        let record = registry.register(KeyKind::KeyswitchKey, "toy", &ksk).unwrap();
+        assert_eq!(record.version, 1);
+        assert_eq!(registry.load(&key_id(&ksk)).unwrap(), ksk);
+        assert!(registry.check(&key_id(&ksk), KeyKind::NttBootstrapKey).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+//! Key rotation proofs, for audits of a rotation from an old secret key to a new one: the guest
+//! keyswitches a ciphertext under the old key with the rotation keyswitching key and decrypts the
+//! result with the new key. The journal commits the digests of both ciphertexts and a salted hash
+//! of the plaintext, never the plaintext or the old key.
+//!
+//! That the plaintext is the one of the old ciphertext follows from the keyswitching key switching
+//! from the old key to the new one, which a key generation receipt for the same key ID attests.
+
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::KeyRotationInput;
+use zkfhe_core::journal::KeyRotationJournal;
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::hashing::{keyswitch_key_digest, lwe_ciphertext_digest, params_digest};
+use crate::io::{lwe_ciphertext_data, lwe_keyswitch_key_data, lwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// Prove that `old_ciphertext`, keyswitched under `ksk`, decrypts under `new_lwe_sk` to a
+/// plaintext, committed under `salt`.
+pub fn prove_key_rotation(
+    params: &ProofParams,
+    ksk: &LweKeyswitchKeyOwned<u64>,
+    old_ciphertext: &LweCiphertextOwned<u64>,
+    new_lwe_sk: &LweSecretKeyOwned<u64>,
+    salt: [u8; 32],
+) -> Result<Receipt, ProofError> {
+    let env = ExecutorEnv::builder()
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&KeyRotationInput {
+            ksk: lwe_keyswitch_key_data(ksk),
+            old_ciphertext: lwe_ciphertext_data(old_ciphertext),
+            new_lwe_sk: lwe_secret_key_data(new_lwe_sk),
+            salt,
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::KeyRotation.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a key rotation receipt for `ksk`, `old_ciphertext` and its rotation `new_ciphertext`
+/// under `expected_params`, and return the committed plaintext hash.
+pub fn check_key_rotation(
+    receipt: &Receipt,
+    ksk: &LweKeyswitchKeyOwned<u64>,
+    old_ciphertext: &LweCiphertextOwned<u64>,
+    new_ciphertext: &LweCiphertextOwned<u64>,
+    expected_params: &ProofParams,
+) -> Result<[u8; 32], ProofError> {
+    verify_receipt(receipt, ProofKind::KeyRotation.image_id())?;
+    let journal: KeyRotationJournal = decode_payload(receipt, ProofKind::KeyRotation)?;
+
+    if journal.ksk_id != keyswitch_key_digest(ksk) {
+        return Err(ProofError::Verification("keyswitching key digest mismatch".to_string()));
+    }
+    if journal.old_ct_digest != lwe_ciphertext_digest(old_ciphertext) {
+        return Err(ProofError::Verification("old ciphertext digest mismatch".to_string()));
+    }
+    if journal.new_ct_digest != lwe_ciphertext_digest(new_ciphertext) {
+        return Err(ProofError::Verification("new ciphertext digest mismatch".to_string()));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+
+    Ok(journal.plaintext_commitment)
+}
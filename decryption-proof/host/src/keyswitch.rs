@@ -0,0 +1,59 @@
+//! Keyswitch proofs: an output ciphertext is the keyswitch of an input ciphertext under a
+//! committed keyswitching key. No secret key is read, so anyone holding the key and the
+//! ciphertexts can prove it, e.g. to attest that ciphertexts were rotated to a new key.
+
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::KeyswitchProofInput;
+use zkfhe_core::journal::KeyswitchJournal;
+
+use crate::error::ProofError;
+use crate::hashing::{keyswitch_key_digest, lwe_ciphertext_digest};
+use crate::io::{lwe_ciphertext_data, lwe_keyswitch_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// Prove that `output` is the keyswitch of `input` under `ksk`, the guest recomputes it.
+pub fn prove_keyswitch(
+    ksk: &LweKeyswitchKeyOwned<u64>,
+    input: &LweCiphertextOwned<u64>,
+    output: &LweCiphertextOwned<u64>,
+) -> Result<Receipt, ProofError> {
+    let env = ExecutorEnv::builder()
+        .write(&KeyswitchProofInput {
+            ksk: lwe_keyswitch_key_data(ksk),
+            input: lwe_ciphertext_data(input),
+            output: lwe_ciphertext_data(output),
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::Keyswitch.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a keyswitch receipt for `ksk` and both ciphertexts.
+pub fn check_keyswitch(
+    receipt: &Receipt,
+    ksk: &LweKeyswitchKeyOwned<u64>,
+    input: &LweCiphertextOwned<u64>,
+    output: &LweCiphertextOwned<u64>,
+) -> Result<(), ProofError> {
+    verify_receipt(receipt, ProofKind::Keyswitch.image_id())?;
+    let journal: KeyswitchJournal = decode_payload(receipt, ProofKind::Keyswitch)?;
+
+    if journal.ksk_id != keyswitch_key_digest(ksk) {
+        return Err(ProofError::Verification("keyswitching key digest mismatch".to_string()));
+    }
+    if journal.input_ct_digest != lwe_ciphertext_digest(input) {
+        return Err(ProofError::Verification("input ciphertext digest mismatch".to_string()));
+    }
+    if journal.output_ct_digest != lwe_ciphertext_digest(output) {
+        return Err(ProofError::Verification("output ciphertext digest mismatch".to_string()));
+    }
+
+    Ok(())
+}
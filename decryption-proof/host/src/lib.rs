@@ -0,0 +1,59 @@
+//! Host side of the zkFHE decryption proofs: lowering tfhe-rs entities to the guest inputs, proving
+//! every kind of decryption statement and checking the receipts and their journals.
+//!
+//! Every fallible function returns a [`ProofError`]. The `host` binary generates the keys of its
+//! run with [`evaluation_keys`] and calls the proving modules of this library, only the demo flow
+//! chaining the proving modes together is left in the binary.
+
+pub mod aggregation;
+pub mod audit;
+pub mod batch;
+pub mod budget;
+pub mod cancel;
+pub mod chained;
+pub mod client_key;
+pub mod compact_list;
+pub mod consistency;
+pub mod decrypt;
+pub mod error;
+pub mod evaluation_keys;
+pub mod evm;
+pub mod execution;
+pub mod format;
+pub mod glwe;
+pub mod hashing;
+pub mod image_pin;
+pub mod io;
+pub mod job_store;
+pub mod journal;
+pub mod key_cache;
+pub mod key_generation;
+pub mod key_registry;
+pub mod key_rotation;
+pub mod keyswitch;
+pub mod journal_abi;
+pub mod merkle;
+pub mod mod_switch;
+pub mod params;
+pub mod pbs;
+pub mod policy;
+pub mod pool;
+pub mod profile;
+pub mod progress;
+pub mod proof_kind;
+pub mod prover;
+pub mod public_key;
+pub mod radix;
+pub mod receipt_policy;
+pub mod record;
+pub mod reencryption;
+pub mod safe_input;
+pub mod sample_extract;
+pub mod seed;
+pub mod seeded;
+pub mod shortint;
+pub mod threshold;
+pub mod verification;
+pub mod wire;
+
+pub use error::ProofError;
@@ -1,43 +1,761 @@
-// These constants represent the RISC-V ELF and the image ID generated by risc0-build.
-// The ELF is used for proving and the ID is used for verification.
-use methods::{
-    HELLO_GUEST_ELF, HELLO_GUEST_ID
-};
-use risc0_zkvm::{default_prover, ExecutorEnv};
-//use serde::{Deserialize, Serialize};
-//use risc0_zkvm::serde::from_slice;
-use tfhe::core_crypto::entities::*;
-use tfhe::core_crypto::commons::parameters::*;
-use tfhe::core_crypto::algorithms::*;
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::{ExecutorEnv, InnerReceipt, Receipt};
 use tfhe::core_crypto::prelude::*;
-//use tfhe::core_crypto::commons::ciphertext_modulus::*;
 use std::error::Error;
-use tfhe::core_crypto::fft_impl::fft64::ABox;
-use tfhe_fft::c64;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zkfhe_core::encoding::Decoding;
+use zkfhe_core::io::{
+    BatchItem, DecryptionInput, KeyswitchInput, PbsBatchItem, PbsBootstrapKeyHeader,
+    PbsDecryptionInput,
+};
+use zkfhe_core::journal::ParamsSummary;
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::policy::JournalPolicy;
+use clap::{ArgGroup, Parser, Subcommand};
+use prost::Message;
+use rand::Rng;
+use rayon::prelude::*;
+use tracing_subscriber::filter::EnvFilter;
+use tfhe::core_crypto::commons::math::random::Seeder;
+use tfhe::shortint::engine::ShortintEngine;
+
+use host::{params, prover};
+use host::aggregation::{check_aggregation, prove_aggregation};
+use host::audit::{replay_audit_bundle, write_audit_bundle, GuestStdin};
+use host::batch::BatchInput;
+use host::budget::{pbs_memory_preset, InputBudget, InputLimits, MemoryPreset};
+use host::cancel::CancellationToken;
+use host::chained::{check_chained_decryption, prove_chained_decryption};
+use host::compact_list::{check_compact_list_decryption, prove_compact_list_decryption};
+use host::consistency::{check_consistency, prove_consistency};
+use host::decrypt::{
+    check_decryption_journal, check_decryption_u128, check_request_id, prove_and_extract,
+    prove_decryption, prove_decryption_u128,
+};
+use host::error::ProofError;
+use host::evaluation_keys::{
+    generate_secret_keys, ntt_ciphertext_modulus, EvaluationKeyGenerator,
+};
+use host::evm::evm_proof;
+use host::execution::check_determinism;
+use host::format::{read_file, verifier_command, write_file, FileFormat};
+use host::glwe::{check_glwe_decryption, prove_glwe_decryption};
+use host::hashing::{
+    canonical_lwe_keyswitch_key, canonical_lwe_multi_bit_bootstrap_key, canonical_lwe_secret_key,
+    canonical_ntt_lwe_bootstrap_key, circuit_digest, lwe_ciphertext_digest, parse_digest,
+    plaintext_commitment, secret_key_digest, secret_key_u128_digest,
+};
+use host::image_pin::{check_image_id_pins, write_image_id_pins};
+use host::io::{
+    glwe_ciphertext_data, lwe_ciphertext_data, lwe_keyswitch_key_data,
+    lwe_multi_bit_bootstrap_key_header, lwe_secret_key_data, ntt_lwe_bootstrap_key_header,
+};
+use host::journal::{
+    check_circuit_digest, check_decryption_inclusion, check_journal, check_key_ids, check_lut,
+    check_multi_bit_bootstrap_key, check_ntt_bootstrap_key, check_seed_commitment, check_trivial,
+    decode_journal, decryption_leaves, decryption_proofs,
+};
+use host::key_cache::KeyCache;
+use host::key_generation::{
+    check_keyswitch_key_generation, noise_bound_log2, prove_keyswitch_key_generation,
+};
+use host::key_registry::{KeyKind, KeyRegistry};
+use host::key_rotation::{check_key_rotation, prove_key_rotation};
+use host::keyswitch::{check_keyswitch, prove_keyswitch};
+use host::mod_switch::{check_mod_switch_decryption, prove_mod_switch_decryption};
+use host::pbs::{multi_bit_bootstrap, PbsBackend};
+use host::params::{ParamsPolicy, PbsOrder, TfheParams, PRESET_NAMES};
+use host::pool::{prove_shards, shard_path, shard_ranges};
+use host::policy::{
+    check_policy_decryption, disclosure, open_plaintext_commitment, parse_journal_policy,
+    plaintext_opening, prove_policy_decryption,
+};
+use host::profile::{print_cycle_profile, GuestOutput};
+use host::progress::Progress;
+use host::proof_kind::ProofKind;
+use host::prover::{HashFn, ProverBackend, ProverSettings, ReceiptKind, RemotePolicy};
+use host::public_key::{encrypt_with_public_key, generate_public_key};
+use host::radix::{check_radix_decryption, prove_radix_decryption, radix_secret_key_id};
+use host::receipt_policy::{check_receipt, ReceiptPolicy};
+use host::record::proof_record;
+use host::reencryption::{check_reencryption, prove_reencryption, random_selection};
+use host::safe_input::{
+    read_integer_client_key, read_radix_ciphertext, read_shortint_ciphertext,
+    read_shortint_client_key, DEFAULT_SIZE_LIMIT,
+};
+use host::sample_extract::{check_sample_extract, prove_sample_extract};
+use host::seed::{
+    parse_seed, reencryption_rng, rotation_rng, seed_commitment, threshold_rng,
+    DeterministicSeeder,
+};
+use host::shortint::{
+    check_shortint_decryption, prove_shortint_decryption, shortint_secret_key_id,
+};
+use host::threshold::{
+    check_dkg, combine_partial_decryptions, generate_dkg_transcript, key_share_commitment,
+    prove_dkg, prove_partial_decryption, split_secret_key, verify_threshold_set,
+};
+use host::verification::verify_receipt;
+use host::wire::{serve_request, ProofRequest};
+
+#[derive(Parser)]
+#[command(about = "Prove the decryption of a batch of TFHE ciphertexts")]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(group(
+    ArgGroup::new("evaluation_proof")
+        .args(["prove_keyswitch", "prove_sample_extract"])
+        .multiple(true)
+))]
+struct Args {
+    /// Name of a parameter preset or path to a .toml/.json parameter file
+    #[arg(long, default_value = "toy")]
+    params: String,
+
+    /// What the single decryption proofs output, `unsigned` messages, `signed` messages in two's
+    /// complement or the rounded `torus` value, instead of the decoding of the parameters
+    #[arg(long, value_parser = params::parse_decoding)]
+    decoding: Option<Decoding>,
+
+    /// Prove with parameters claiming less than 128 bits of security, such as the toy set
+    #[arg(long)]
+    allow_insecure: bool,
+
+    /// Refuse the receipts unless their journal commits parameters bootstrapping in this order,
+    /// `keyswitch-bootstrap` or `bootstrap-keyswitch`
+    #[arg(long, value_parser = params::parse_pbs_order)]
+    require_pbs_order: Option<PbsOrder>,
+
+    /// Refuse the receipts unless their journal commits parameters claiming at least this many
+    /// bits of security
+    #[arg(long, default_value_t = 0)]
+    require_security_bits: u32,
+
+    /// Prove that the decrypted ciphertexts are the keyswitch of the PBS outputs, the keyswitch
+    /// is performed again inside the guest
+    #[arg(long)]
+    keyswitch: bool,
+
+    /// Perform the PBS inside the `pbs_decrypt` guest with an NTT bootstrapping key, so the proof
+    /// covers the bootstrap itself and not only the decryption of its output
+    #[arg(long, conflicts_with = "keyswitch")]
+    pbs_in_guest: bool,
+
+    /// Bootstrap in the `pbs_decrypt` guest with a multi-bit bootstrapping key of this grouping
+    /// factor, as the multi-bit parameter sets do, instead of the NTT key. The PBS then runs in the
+    /// standard domain on the native modulus
+    #[arg(
+        long,
+        requires = "pbs_in_guest",
+        conflicts_with_all = ["seeded", "prove_sample_extract"],
+        value_parser = clap::value_parser!(u32).range(2..=4)
+    )]
+    multi_bit: Option<u32>,
+
+    /// Write the receipt (journal and seal) to this file, it can then be checked with the
+    /// `verifier` binary
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Format of the receipt written with `--out`, the hex and base64 armors hold its bincode
+    /// encoding as text
+    #[arg(long, value_enum, default_value_t = FileFormat::Bincode, requires = "out")]
+    out_format: FileFormat,
+
+    /// Share the big LWE secret key between this many parties and prove one partial decryption per
+    /// party and ciphertext instead of a single full decryption
+    #[arg(long, conflicts_with_all = ["keyswitch", "pbs_in_guest"])]
+    threshold_parties: Option<usize>,
+
+    /// Number of encryptions of zero in the threshold public key the `dkg` guest checks the key
+    /// shares against, a public key encrypting messages needs far more
+    #[arg(long, default_value_t = 16, requires = "threshold_parties")]
+    dkg_zero_encryptions: usize,
+
+    /// Prove the decryption of a GLWE ciphertext, committing every coefficient of its plaintext
+    /// polynomial, instead of a batch of LWE ciphertexts
+    #[arg(long, conflicts_with_all = ["keyswitch", "pbs_in_guest", "threshold_parties"])]
+    glwe: bool,
+
+    /// Encrypt a compact ciphertext list with a compact public key of the flattened GLWE secret
+    /// key and prove the decryption of every ciphertext it expands to, the list is expanded in the
+    /// guest
+    #[arg(long, conflicts_with_all = ["keyswitch", "pbs_in_guest", "threshold_parties", "glwe"])]
+    compact_list: bool,
+
+    /// Encrypt under the flattened GLWE secret key on the native 128 bits modulus and prove the
+    /// decryption of the 128 bits LWE ciphertexts
+    #[arg(
+        long = "u128",
+        conflicts_with_all = ["keyswitch", "pbs_in_guest", "threshold_parties", "glwe"]
+    )]
+    u128_modulus: bool,
+
+    /// Encrypt with the tfhe shortint API and prove the decryption of the shortint ciphertexts,
+    /// requires one of the shortint parameter presets
+    #[arg(long, conflicts_with_all = ["keyswitch", "pbs_in_guest", "threshold_parties", "glwe"])]
+    shortint: bool,
+
+    /// Encrypt radix integers of this many blocks with the tfhe integer API and prove their
+    /// decryption, requires one of the shortint parameter presets
+    #[arg(
+        long,
+        conflicts_with_all = ["keyswitch", "pbs_in_guest", "threshold_parties", "glwe", "shortint"]
+    )]
+    radix_blocks: Option<usize>,
+
+    /// Prove the decryption of the shortint ciphertext in this file, written with the safe
+    /// serialization of tfhe-rs, under the shortint client key of `--client-key`
+    #[arg(long, requires = "client_key", conflicts_with_all = ["shortint", "radix_blocks"])]
+    shortint_ciphertext: Option<PathBuf>,
+
+    /// Prove the decryption of the radix ciphertext in this file, written with the safe
+    /// serialization of tfhe-rs, under the integer client key of `--client-key`
+    #[arg(
+        long,
+        requires = "client_key",
+        conflicts_with_all = ["shortint", "radix_blocks", "shortint_ciphertext"]
+    )]
+    radix_ciphertext: Option<PathBuf>,
+
+    /// Client key of `--shortint-ciphertext` or `--radix-ciphertext`, in the safe serialization
+    /// format
+    #[arg(long)]
+    client_key: Option<PathBuf>,
+
+    /// Largest input in the safe serialization format, in bytes
+    #[arg(long, default_value_t = DEFAULT_SIZE_LIMIT)]
+    safe_size_limit: u64,
+
+    /// Log2 of the maximum number of cycles of a segment, the execution is split in segments of at
+    /// most this size, proven separately and stitched into a single composite receipt
+    #[arg(long, value_parser = clap::value_parser!(u32).range(13..=24))]
+    segment_limit_po2: Option<u32>,
+
+    /// Kind of receipt to produce, Groth16 receipts can be verified by an EVM verifier contract
+    #[arg(long, value_enum, default_value_t = ReceiptKind::Composite)]
+    receipt_kind: ReceiptKind,
+
+    /// Hash function of the STARK seals of the batch proof, succinct and Groth16 receipts need
+    /// Poseidon2
+    #[arg(long, value_enum, default_value_t = HashFn::Poseidon2)]
+    hashfn: HashFn,
+
+    /// `r0vm` server of the local prover, instead of `RISC0_SERVER_PATH` or `r0vm` from the `PATH`
+    #[arg(long)]
+    r0vm_path: Option<PathBuf>,
+
+    /// Write the image ID, journal and Groth16 seal as hex encoded JSON for an EVM verifier
+    /// contract, requires `--receipt-kind groth16`
+    #[arg(long)]
+    evm_out: Option<PathBuf>,
+
+    /// Write the metadata of every receipt of the batch as a JSON proof record for indexing: its
+    /// guest, image ID, journal fields, proving time and cycle counts
+    #[arg(long, value_name = "PATH")]
+    record_out: Option<PathBuf>,
+
+    /// Proving backend of the batch proof, Bonsai is configured by the `BONSAI_API_URL` and
+    /// `BONSAI_API_KEY` environment variables
+    #[arg(long, env = "ZKFHE_PROVER", value_enum, default_value_t = ProverBackend::Local)]
+    prover: ProverBackend,
+
+    /// Seconds to wait for a remote proof before the attempt is abandoned
+    #[arg(long, default_value_t = 3600)]
+    remote_timeout_secs: u64,
+
+    /// Number of remote attempts made after the first one failed or timed out
+    #[arg(long, default_value_t = 2)]
+    remote_retries: u32,
+
+    /// Fail instead of proving locally when every remote attempt failed
+    #[arg(long)]
+    no_local_fallback: bool,
+
+    /// Seconds the batch proof may run in total, its local `r0vm` servers are killed and the proof
+    /// fails once they elapsed
+    #[arg(long)]
+    prove_timeout_secs: Option<u64>,
+
+    /// Log2 of the bound of the flooding noise added to every partial decryption
+    #[arg(long, default_value_t = 40)]
+    flooding_bound_log2: u32,
+
+    /// Prove the decryption of every PBS output in its own receipt with the single ciphertext
+    /// guest, instead of one receipt for the whole batch
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "keyswitch", "pbs_in_guest", "threshold_parties", "glwe", "shortint", "radix_blocks"
+        ]
+    )]
+    single: bool,
+
+    /// Encrypt the input ciphertexts under an LWE public key of the small secret key, as a party
+    /// that does not hold the secret key would, instead of under the secret key itself
+    #[arg(long, conflicts_with_all = ["glwe", "shortint", "radix_blocks"])]
+    public_key: bool,
+
+    /// Derive every secret key, encryption and key share from this 256 bits seed, given as 64 hex
+    /// characters, instead of hardware entropy. A commitment to the seed is recorded in the
+    /// journal
+    #[arg(long, value_parser = parse_seed)]
+    seed: Option<[u8; 32]>,
+
+    /// Attach the digest of the homomorphic circuit that produced the PBS outputs, given as 64 hex
+    /// characters. The batch journal commits it, linking the decryptions to an attestation of the
+    /// computation
+    #[arg(
+        long,
+        value_parser = parse_digest,
+        conflicts_with_all = [
+            "threshold_parties", "glwe", "u128_modulus", "compact_list", "shortint",
+            "radix_blocks", "single", "mod_switch", "journal_policy", "reencrypt"
+        ]
+    )]
+    circuit_digest: Option<[u8; 32]>,
+
+    /// Attach the digest of this circuit description file instead, see `--circuit-digest`
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "circuit_digest", "threshold_parties", "glwe", "u128_modulus", "compact_list",
+            "shortint", "radix_blocks", "single", "mod_switch", "journal_policy", "reencrypt"
+        ]
+    )]
+    circuit: Option<PathBuf>,
+
+    /// Bind the journals of the single ciphertext guests, with `--single`, `--u128-modulus` or
+    /// parameters proven on fresh encryptions, to this external request, say the hash of an
+    /// on-chain request, given as 64 hex characters. The guest echoes it into the journal
+    #[arg(long, value_parser = parse_digest)]
+    request_id: Option<[u8; 32]>,
+
+    /// Split the batch in this many shards proven concurrently, each in its own receipt. Receipt
+    /// files get the shard index appended to their name when there is more than one shard
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    jobs: u32,
+
+    /// Refuse a shard whose serialized guest input is larger than this many MiB, before proving
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    max_input_mib: Option<u64>,
+
+    /// Refuse a shard whose guest would reserve more than this many MiB for its inputs, before
+    /// proving. Defaults to the whole guest memory
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    max_guest_memory_mib: Option<u64>,
+
+    /// Memory layout of the PBS guest: heap and blind rotation stack. Defaults to the smallest
+    /// preset the parameters and the largest shard fit in
+    #[arg(long, value_enum)]
+    memory_preset: Option<MemoryPreset>,
+
+    /// Execute the guest of every shard twice before proving and stop unless both runs commit the
+    /// same journal in the same cycle counts
+    #[arg(long)]
+    check_determinism: bool,
+
+    /// Verify every shard receipt in the aggregation guest and prove a single succinct receipt
+    /// committing a Merkle root of their journals, `--out` then receives the aggregated receipt
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "evm_out", "threshold_parties", "glwe", "shortint", "radix_blocks", "single"
+        ]
+    )]
+    aggregate: bool,
+
+    /// Write every decryption of the batch with its Merkle inclusion proof in the journal root as
+    /// JSON, so a single decryption can be checked without the others
+    #[arg(long)]
+    inclusion_proofs_out: Option<PathBuf>,
+
+    /// Register the secret, keyswitching and NTT bootstrapping keys of the batch proof in this
+    /// directory under their content-addressed IDs, and check the IDs committed by the journals
+    /// against it
+    #[arg(long)]
+    key_registry: Option<PathBuf>,
+
+    /// Load the bootstrapping, keyswitching and NTT bootstrapping keys from this directory when
+    /// they were generated before for the same parameters and secret keys, e.g. with `--seed`,
+    /// and store them there otherwise
+    #[arg(long)]
+    key_cache: Option<PathBuf>,
+
+    /// Prove the decryption of every PBS input after its modulus switch to 2N, the switch the
+    /// blind rotation starts with, instead of the decryption of the PBS outputs
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "pbs_in_guest", "threshold_parties", "glwe", "u128_modulus", "shortint",
+            "radix_blocks", "single", "aggregate"
+        ]
+    )]
+    mod_switch: bool,
+
+    /// Prove the decryption of every PBS output in its own receipt disclosing only what this
+    /// policy allows: `plaintext`, `hash` (salted, `hash:SALT` for a given salt), `digests`, or
+    /// whether a predicate `lt:THRESHOLD`, `eq:VALUE` or `range:MIN..=MAX` holds
+    #[arg(
+        long,
+        value_parser = parse_journal_policy,
+        conflicts_with_all = [
+            "keyswitch", "pbs_in_guest", "threshold_parties", "glwe", "shortint", "radix_blocks",
+            "single", "mod_switch", "aggregate"
+        ]
+    )]
+    journal_policy: Option<JournalPolicy>,
+
+    /// Write the salt and plaintext opening every plaintext hash of `--journal-policy hash` to
+    /// this JSON file, for a later reveal. Keep it private until then
+    #[arg(long, requires = "journal_policy")]
+    openings_out: Option<PathBuf>,
+
+    /// Re-encrypt every PBS output under the public key of a recipient's small LWE key and prove
+    /// the new ciphertext encrypts the same message, without revealing it
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "keyswitch", "pbs_in_guest", "threshold_parties", "glwe", "shortint", "radix_blocks",
+            "single", "mod_switch", "aggregate", "journal_policy"
+        ]
+    )]
+    reencrypt: bool,
+
+    /// Prove every keyswitch of the batch on its own with the `keyswitch` guest, which reads the
+    /// keyswitching key and both ciphertexts but no secret key, instead of the batch decryption
+    #[arg(long, requires = "keyswitch", conflicts_with_all = ["aggregate", "single"])]
+    prove_keyswitch: bool,
+
+    /// Prove with the `consistency` guest that every PBS output and its keyswitch, under the big
+    /// and the small key, decrypt to the same message without revealing it, then exit
+    #[arg(long, requires = "keyswitch", conflicts_with_all = ["aggregate", "single"])]
+    prove_consistency: bool,
+
+    /// Audit the keyswitch as a key rotation from the big to the small key with the
+    /// `key_rotation` guest: every PBS output is rotated and decrypted under the small key, the
+    /// journal commits both ciphertext digests and a salted hash of the plaintext, then exit
+    #[arg(long, requires = "keyswitch", conflicts_with_all = ["aggregate", "single"])]
+    prove_key_rotation: bool,
+
+    /// Prove with the `key_generation` guest that the keyswitching key encrypts the big key under
+    /// the small key with the parameters' decomposition and noise, then exit
+    #[arg(long, requires = "keyswitch", conflicts_with_all = ["aggregate", "single"])]
+    prove_key_generation: bool,
+
+    /// Prove with the `sample_extract` guest that every PBS output is the first coefficient of its
+    /// blind rotated accumulator, then exit
+    #[arg(long, requires = "pbs_in_guest", conflicts_with_all = ["aggregate", "single"])]
+    prove_sample_extract: bool,
+
+    /// Also prove the decryption of every output of `--prove-keyswitch` or
+    /// `--prove-sample-extract` with the `chained_decrypt` guest, which verifies the receipt of the
+    /// step as an assumption
+    #[arg(long, requires = "evaluation_proof")]
+    chain: bool,
+
+    /// Use trivial encryptions, whose mask is all zeros, and skip the bootstrapping key so the
+    /// whole pipeline runs in seconds. The journal flags `trivial`, such receipts prove nothing
+    /// about the secret key
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "keyswitch", "pbs_in_guest", "threshold_parties", "glwe", "u128_modulus", "shortint",
+            "radix_blocks", "single", "public_key", "evm_out"
+        ]
+    )]
+    trivial: bool,
+
+    /// Generate the keyswitching and bootstrapping keys in their seeded form, as a client would
+    /// send them to the prover, and decompress them on the host before building the guest inputs
+    #[arg(long, conflicts_with = "trivial")]
+    seeded: bool,
+
+    /// Print the image ID of every guest and exit
+    #[arg(long)]
+    list_guests: bool,
+
+    /// Refuse to run when the image ID of a guest differs from the one pinned in this file, so a
+    /// deployment notices a guest, and so a statement, that changed silently
+    #[arg(long, value_name = "PATH")]
+    check_image_ids: Option<PathBuf>,
+
+    /// Pin the image IDs of the guests of this build in this file (usually `image_id.toml`) and
+    /// exit
+    #[arg(long, value_name = "PATH", conflicts_with = "check_image_ids")]
+    pin_image_ids: Option<PathBuf>,
+
+    /// Keep in this directory the exact guest stdin of every shard, the parameter set, the
+    /// receipts and a manifest of their digests, so an auditor can reproduce the journals. The
+    /// inputs hold the secret key, keep the directory private
+    #[arg(long, value_name = "DIR")]
+    audit_dir: Option<PathBuf>,
+
+    /// Re-execute the guest on the inputs of an audit bundle, check it reproduces the journals of
+    /// its receipts and exit
+    #[arg(long, value_name = "DIR")]
+    replay_audit: Option<PathBuf>,
+
+    /// Print the metadata of this receipt, read in the `--record-format` format, as a JSON proof
+    /// record and exit. The receipt is verified against the guest its journal names first
+    #[arg(long, value_name = "RECEIPT")]
+    export_record: Option<PathBuf>,
+
+    /// Format of the receipt read with `--export-record`, as written by the host with
+    /// `--out-format`
+    #[arg(long, value_enum, default_value_t = FileFormat::Bincode, requires = "export_record")]
+    record_format: FileFormat,
+
+    /// Prove the job of this protobuf `ProofRequest`, see `host/proto/zkfhe.proto`, write its
+    /// `ProofResponse` to `--proof-response` and exit
+    #[arg(long, requires = "proof_response")]
+    proof_request: Option<PathBuf>,
+
+    /// Protobuf `ProofResponse` written for `--proof-request`, also for a failed job
+    #[arg(long)]
+    proof_response: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check a receipt against a TOML acceptance policy, print the JSON report and exit, with a
+    /// failure when the receipt breaks the policy
+    Verify {
+        /// Receipt file written by the host with `--out`
+        receipt: PathBuf,
+
+        /// TOML policy to enforce: `allowed_image_ids`, `min_security_bits`, `pbs_order`,
+        /// `required_journal_fields` and `max_age_secs`, all optional
+        #[arg(long, value_name = "PATH")]
+        policy: PathBuf,
+
+        /// Format of the receipt file, as written by the host with `--out-format`
+        #[arg(long, value_enum, default_value_t = FileFormat::Bincode)]
+        format: FileFormat,
+    },
+}
+
+/// Refuse parameters below [`params::MIN_SECURITY_BITS`] unless `allow_insecure`, which only
+/// warns.
+fn check_security(params: &ProofParams, allow_insecure: bool) -> Result<(), Box<dyn Error>> {
+    if !allow_insecure {
+        params::check_security(params)
+            .map_err(|e| format!("{e}, pass --allow-insecure to prove anyway"))?;
+    } else if params.security_bits < params::MIN_SECURITY_BITS {
+        tracing::warn!(
+            "proving with {} bits of security, the journal records the annotation",
+            params.security_bits
+        );
+    }
+    Ok(())
+}
 
 fn main() -> Result<(), Box<dyn Error>> { 
-    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    // Initialize tracing, the proving stages of this host are logged unless `RUST_LOG` selects
+    // others. In order to view the logs of the prover too, run `RUST_LOG=info cargo run`
     tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("host=info")),
+        )
         .init();
 
-    // DISCLAIMER: these toy example parameters are not guaranteed to be secure or yield correct
-    // computations
-    // Define the parameters for a 4 bits message able to hold the doubled 2 bits message
-    let small_lwe_dimension = LweDimension(742);
-    let glwe_dimension = GlweDimension(1);
-    let polynomial_size = PolynomialSize(2048);
-    let lwe_noise_distribution =
-        Gaussian::from_dispersion_parameter(StandardDev(0.000007069849454709433), 0.0);
-    let glwe_noise_distribution =
-        Gaussian::from_dispersion_parameter(StandardDev(0.00000000000000029403601535432533), 0.0);
-    let pbs_base_log = DecompositionBaseLog(23);
-    let pbs_level = DecompositionLevelCount(1);
+    let args = Args::parse();
+
+    // Fake receipts are only produced, and accepted, by a host built with the dev-mode feature
+    #[cfg(feature = "dev-mode")]
+    std::env::set_var("RISC0_DEV_MODE", "1");
+    #[cfg(not(feature = "dev-mode"))]
+    if risc0_zkvm::is_dev_mode() {
+        return Err(
+            "RISC0_DEV_MODE is set but the host was built without the dev-mode feature".into(),
+        );
+    }
+
+    if let Some(path) = &args.pin_image_ids {
+        write_image_id_pins(path)?;
+        println!(
+            "Pinned the image IDs of {} guests in {}",
+            ProofKind::ALL.len(),
+            path.display()
+        );
+        return Ok(());
+    }
+    if let Some(dir) = &args.replay_audit {
+        let manifest = replay_audit_bundle(dir)?;
+        println!(
+            "Re-executing {} reproduced the journals of the {} receipts of {}",
+            manifest.guest,
+            manifest.shards.len(),
+            dir.display()
+        );
+        return Ok(());
+    }
+    if let Some(path) = &args.export_record {
+        let receipt: Receipt = read_file(path, args.record_format)?;
+        println!("{}", serde_json::to_string_pretty(&proof_record(&receipt, None)?)?);
+        return Ok(());
+    }
+    if let Some(Command::Verify {
+        receipt: path,
+        policy,
+        format,
+    }) = &args.command
+    {
+        let report = check_receipt(path, *format, &ReceiptPolicy::from_file(policy)?)?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if !report.passed {
+            return Err(format!(
+                "{} breaks {} rules of {}",
+                path.display(),
+                report.violations.len(),
+                policy.display()
+            )
+            .into());
+        }
+        return Ok(());
+    }
+    if let (Some(path), Some(out)) = (&args.proof_request, &args.proof_response) {
+        let request = ProofRequest::decode_from(&std::fs::read(path)?)?;
+        tracing::info!("Proving the job of {}...", path.display());
+        let response = serve_request(request);
+        std::fs::write(out, response.encode_to_vec())?;
+        if !response.error.is_empty() {
+            return Err(response.error.into());
+        }
+        println!("Wrote the response of the job to {}", out.display());
+        return Ok(());
+    }
+    if let Some(path) = &args.check_image_ids {
+        check_image_id_pins(path)?;
+        println!("Guest image IDs match the pins of {}", path.display());
+    }
+
+    if args.list_guests {
+        println!("Guests {}", methods::guest_info().semver);
+        for proof_kind in ProofKind::ALL {
+            println!("{proof_kind:?}: {}", proof_kind.image_id());
+        }
+        return Ok(());
+    }
+
+    // Ciphertexts exported by tfhe-rs applications, read with the bounds and version tags of the
+    // safe serialization. They are proven under the parameters of their client key, not `--params`
+    if let (Some(path), Some(key_path)) = (&args.shortint_ciphertext, &args.client_key) {
+        let client_key = read_shortint_client_key(key_path, args.safe_size_limit)?;
+        let parameters = &client_key.parameters;
+        check_security(&params::from_shortint_parameters(parameters), args.allow_insecure)?;
+        let ciphertext = read_shortint_ciphertext(path, args.safe_size_limit, &client_key)?;
+        tracing::info!("Proving the decryption of {}...", path.display());
+        let receipt = prove_shortint_decryption(&client_key, &ciphertext)?;
+        let secret_key_id = shortint_secret_key_id(&client_key, &ciphertext);
+        let decrypted = check_shortint_decryption(&receipt, &ciphertext, &secret_key_id)?;
+        println!("Shortint decryption result: {decrypted}");
+        if let Some(out) = &args.out {
+            write_file(out, &receipt, args.out_format)?;
+        }
+        return Ok(());
+    }
+    if let (Some(path), Some(key_path)) = (&args.radix_ciphertext, &args.client_key) {
+        let client_key = read_integer_client_key(key_path, args.safe_size_limit)?;
+        let parameters = &client_key.as_ref().parameters;
+        check_security(&params::from_shortint_parameters(parameters), args.allow_insecure)?;
+        let ciphertext = read_radix_ciphertext(path, args.safe_size_limit, &client_key)?;
+        tracing::info!("Proving the decryption of {}...", path.display());
+        let receipt = prove_radix_decryption(&client_key, &ciphertext)?;
+        let secret_key_id = radix_secret_key_id(&client_key, &ciphertext)?;
+        let decrypted = check_radix_decryption(&receipt, &ciphertext, &secret_key_id)?;
+        println!("Radix decryption result: {decrypted}");
+        if let Some(out) = &args.out {
+            write_file(out, &receipt, args.out_format)?;
+        }
+        return Ok(());
+    }
+
+    let mut params = params::from_preset_or_file(&args.params).map_err(|e| {
+        format!(
+            "could not load parameters {}: {e}, available presets are {PRESET_NAMES:?}",
+            args.params
+        )
+    })?;
+    if let Some(decoding) = args.decoding {
+        params.decoding = decoding;
+    }
+    println!("Using parameters {}", params.name);
+    check_security(&params, args.allow_insecure)?;
+
+    // The shortint and integer client keys draw their randomness from the thread local engine
+    if let Some(seed) = &args.seed {
+        println!(
+            "Deriving all randomness from the seed committed to {}",
+            hex::encode(seed_commitment(seed))
+        );
+        ShortintEngine::replace_thread_local(ShortintEngine::new_from_seeder(
+            &mut DeterministicSeeder::new(*seed),
+        ));
+    }
+
+    // Users of the shortint API hand over a ciphertext and its client key, the LWE plumbing is
+    // done by the host
+    if args.shortint {
+        let shortint_params = params::shortint_preset(&params.name)
+            .ok_or_else(|| format!("{} is not a shortint parameter preset", params.name))?;
+        let client_key = tfhe::shortint::ClientKey::new(shortint_params);
+
+        for message in 0..shortint_params.message_modulus.0 as u64 {
+            let ciphertext = client_key.encrypt(message);
+            tracing::info!("Proving the decryption of a shortint ciphertext...");
+            let receipt = prove_shortint_decryption(&client_key, &ciphertext)?;
+            let secret_key_id = shortint_secret_key_id(&client_key, &ciphertext);
+            let decrypted = check_shortint_decryption(&receipt, &ciphertext, &secret_key_id)?;
+            assert_eq!(message, decrypted);
+            println!("Shortint decryption result is correct! Got {decrypted}");
+        }
+
+        return Ok(());
+    }
+
+    // Radix integers are split in their shortint blocks by the host, the guest recombines the
+    // decrypted blocks
+    if let Some(block_count) = args.radix_blocks {
+        if block_count == 0 {
+            return Err("a radix ciphertext needs at least one block".into());
+        }
+        let shortint_params = params::shortint_preset(&params.name)
+            .ok_or_else(|| format!("{} is not a shortint parameter preset", params.name))?;
+        let client_key = tfhe::integer::ClientKey::new(shortint_params);
+
+        let bits = (block_count as u32 * shortint_params.message_modulus.0.ilog2()).min(u64::BITS);
+        let max_value = u64::MAX >> (u64::BITS - bits);
+        for message in [0, max_value / 3, max_value] {
+            let ciphertext = client_key.encrypt_radix(message, block_count);
+            tracing::info!("Proving the decryption of a {block_count} blocks radix ciphertext...");
+            let receipt = prove_radix_decryption(&client_key, &ciphertext)?;
+            let secret_key_id = radix_secret_key_id(&client_key, &ciphertext)?;
+            let decrypted = check_radix_decryption(&receipt, &ciphertext, &secret_key_id)?;
+            assert_eq!(message as u128, decrypted);
+            println!("Radix decryption result is correct! Got {decrypted}");
+        }
+
+        return Ok(());
+    }
+
+    let small_lwe_dimension = params.lwe_dimension();
+    let glwe_dimension = params.glwe_dimension();
+    let polynomial_size = params.polynomial_size();
+    let lwe_noise_distribution = params.lwe_noise();
+    let glwe_noise_distribution = params.glwe_noise();
     let ciphertext_modulus = CiphertextModulus::new_native();
 
     // Request the best seeder possible, starting with hardware entropy sources and falling back to
-    // /dev/random on Unix systems if enabled via cargo features
-    let mut boxed_seeder = new_seeder();
+    // /dev/random on Unix systems if enabled via cargo features, unless a seed was given
+    let mut boxed_seeder: Box<dyn Seeder> = match args.seed {
+        Some(seed) => Box::new(DeterministicSeeder::new(seed)),
+        None => new_seeder(),
+    };
     // Get a mutable reference to the seeder as a trait object from the Box returned by new_seeder
     let seeder = boxed_seeder.as_mut();
 
@@ -50,94 +768,240 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut encryption_generator =
         EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
 
-    println!("Generating keys...");
+    // Generate the small LweSecretKey and the GlweSecretKey, both with binary coefficients
+    let (small_lwe_sk, glwe_sk) = generate_secret_keys(&params, &mut secret_generator);
 
-    // Generate an LweSecretKey with binary coefficients
-    let small_lwe_sk =
-        LweSecretKey::generate_new_binary(small_lwe_dimension, &mut secret_generator);
+    // A GLWE ciphertext is decrypted as a whole polynomial, no bootstrapping material is needed
+    if args.glwe {
+        let encoding = params.encoding;
+        let messages: Vec<u64> = (0..polynomial_size.0 as u64)
+            .map(|index| index % encoding.message_space())
+            .collect();
+        let plaintext_list = PlaintextList::from_container(
+            messages.iter().map(|&message| message * encoding.delta()).collect::<Vec<_>>(),
+        );
 
-    // Generate a GlweSecretKey with binary coefficients
-    let glwe_sk =
-        GlweSecretKey::generate_new_binary(glwe_dimension, polynomial_size, &mut secret_generator);
+        let mut glwe_ciphertext = GlweCiphertext::new(
+            0u64,
+            glwe_dimension.to_glwe_size(),
+            polynomial_size,
+            ciphertext_modulus,
+        );
+        encrypt_glwe_ciphertext(
+            &glwe_sk,
+            &mut glwe_ciphertext,
+            &plaintext_list,
+            glwe_noise_distribution,
+            &mut encryption_generator,
+        );
+
+        tracing::info!("Proving the decryption of a GLWE ciphertext...");
+        let receipt = prove_glwe_decryption(&params, &glwe_sk, &glwe_ciphertext)?;
+        let secret_key_id = secret_key_digest(glwe_sk.as_ref());
+        let decoded =
+            check_glwe_decryption(&receipt, &glwe_ciphertext, &secret_key_id, &params)?;
+        assert_eq!(messages, decoded);
+        println!("GLWE decryption of {} coefficients is correct!", decoded.len());
+
+        if let Some(out) = &args.out {
+            write_file(out, &receipt, args.out_format)?;
+            println!(
+                "Receipt written to {}, verify it with `{}`",
+                out.display(),
+                verifier_command(out, ProofKind::GlweDecrypt.image_id(), args.out_format)
+            );
+        }
+
+        return Ok(());
+    }
 
     // Create a copy of the GlweSecretKey re-interpreted as an LweSecretKey
     let big_lwe_sk = glwe_sk.clone().into_lwe_secret_key();
 
-    // Generate the bootstrapping key, we use the parallel variant for performance reason
-    let std_bootstrapping_key = par_allocate_and_generate_new_lwe_bootstrap_key(
-        &small_lwe_sk,
-        &glwe_sk,
-        pbs_base_log,
-        pbs_level,
-        glwe_noise_distribution,
-        ciphertext_modulus,
-        &mut encryption_generator,
-    );
+    // The binary key is widened to 128 bits, the same key on the native 128 bits modulus
+    if args.u128_modulus {
+        let big_lwe_sk_u128 = LweSecretKey::from_container(
+            big_lwe_sk.as_ref().iter().map(|&bit| bit as u128).collect::<Vec<_>>(),
+        );
+        let encoding = params.encoding;
+        let max_message = encoding.message_space() - 1;
+        for message in [0, max_message / 2, max_message] {
+            let ciphertext = allocate_and_encrypt_new_lwe_ciphertext(
+                &big_lwe_sk_u128,
+                Plaintext(message as u128 * encoding.delta_u128()),
+                params.glwe_noise_u128(),
+                CiphertextModulus::new_native(),
+                &mut encryption_generator,
+            );
+            tracing::info!("Proving the decryption of a 128 bits LWE ciphertext...");
+            let receipt =
+                prove_decryption_u128(&params, &big_lwe_sk_u128, &ciphertext, args.request_id)?;
+            let decrypted = check_decryption_u128(
+                &receipt,
+                &ciphertext,
+                &secret_key_u128_digest(big_lwe_sk_u128.as_ref()),
+                &params,
+                args.request_id,
+            )?;
+            assert_eq!(message as u128, decrypted);
+            println!("128 bits decryption result is correct! Got {decrypted}");
+        }
 
-    // Create the empty bootstrapping key in the Fourier domain
-    let mut fourier_bsk = FourierLweBootstrapKey::new(
-        std_bootstrapping_key.input_lwe_dimension(),
-        std_bootstrapping_key.glwe_size(),
-        std_bootstrapping_key.polynomial_size(),
-        std_bootstrapping_key.decomposition_base_log(),
-        std_bootstrapping_key.decomposition_level_count(),
-    );
+        return Ok(());
+    }
+
+    // One mask is shared by as many ciphertexts as the key dimension, the list spans two of them
+    if args.compact_list {
+        let lwe_dimension = big_lwe_sk.lwe_dimension();
+        if !lwe_dimension.0.is_power_of_two() || params.ciphertext_modulus != 0 {
+            let error = "compact lists need a power of two GLWE key size and the native modulus";
+            return Err(error.into());
+        }
+        let compact_public_key = allocate_and_generate_new_lwe_compact_public_key(
+            &big_lwe_sk,
+            glwe_noise_distribution,
+            ciphertext_modulus,
+            &mut encryption_generator,
+        );
+        let encoding = params.encoding;
+        let messages: Vec<u64> = (0..lwe_dimension.0 as u64 + 1)
+            .map(|index| index % encoding.message_space())
+            .collect();
+        let plaintext_list = PlaintextList::from_container(
+            messages.iter().map(|&message| message * encoding.delta()).collect::<Vec<_>>(),
+        );
+        let mut list = LweCompactCiphertextList::new(
+            0u64,
+            lwe_dimension.to_lwe_size(),
+            LweCiphertextCount(messages.len()),
+            ciphertext_modulus,
+        );
+        encrypt_lwe_compact_ciphertext_list_with_compact_public_key(
+            &compact_public_key,
+            &mut list,
+            &plaintext_list,
+            glwe_noise_distribution,
+            glwe_noise_distribution,
+            &mut secret_generator,
+            &mut encryption_generator,
+        );
 
-    // Use the conversion function (a memory optimized version also exists but is more complicated
-    // to use) to convert the standard bootstrapping key to the Fourier domain
-    convert_standard_lwe_bootstrap_key_to_fourier(&std_bootstrapping_key, &mut fourier_bsk);
-    // We don't need the standard bootstrapping key anymore
-    drop(std_bootstrapping_key.clone());
+        let count = messages.len();
+        tracing::info!("Proving the decryption of a compact list of {count} ciphertexts...");
+        let receipt = prove_compact_list_decryption(&params, &big_lwe_sk, &list)?;
+        let secret_key_id = secret_key_digest(big_lwe_sk.as_ref());
+        let decoded = check_compact_list_decryption(&receipt, &list, &secret_key_id, &params)?;
+        assert_eq!(messages, decoded);
+        println!("Compact list decryption of {} ciphertexts is correct!", decoded.len());
 
-    // Our 4 bits message space
-    let message_modulus = 1u64 << 4;
+        return Ok(());
+    }
 
-    // Our input message
-    let input_message = 3u64;
+    // The PBS runs on the native modulus and its outputs are decoded unsigned, a non-native
+    // modulus or another decoding of the parameters is proven on fresh encryptions under the
+    // flattened GLWE key instead
+    if params.ciphertext_modulus != 0 || params.decoding != Decoding::Unsigned {
+        let encoding = params.encoding;
+        let max_message = encoding.message_space() - 1;
+        for message in [0, max_message / 2, max_message] {
+            let ciphertext = allocate_and_encrypt_new_lwe_ciphertext(
+                &big_lwe_sk,
+                Plaintext(encoding.encode_for_modulus(message, params.ciphertext_modulus)),
+                glwe_noise_distribution,
+                params.ciphertext_modulus(),
+                &mut encryption_generator,
+            );
+            tracing::info!(
+                "Proving the {:?} decryption of an LWE ciphertext modulo {}...",
+                params.decoding,
+                match params.ciphertext_modulus {
+                    0 => "2^64".to_string(),
+                    modulus => modulus.to_string(),
+                }
+            );
+            let receipt = prove_decryption(&params, &big_lwe_sk, &ciphertext, args.request_id)?;
+            let secret_key_id = secret_key_digest(big_lwe_sk.as_ref());
+            let journal =
+                check_decryption_journal(&receipt, &ciphertext, &secret_key_id, &params)?;
+            check_request_id(&journal, args.request_id)?;
+            let decrypted = journal.plaintext;
+            let expected = encoding.output(params.decoding, message, params.ciphertext_modulus);
+            assert_eq!(expected, decrypted);
+            match params.decoding {
+                Decoding::Signed => {
+                    println!("Decryption result is correct! Got {}", decrypted as i64)
+                }
+                _ => println!("Decryption result is correct! Got {decrypted}"),
+            }
+        }
 
-    // Delta used to encode 4 bits of message + a bit of padding on u64
-    let delta = (1_u64 << 63) / message_modulus;
+        return Ok(());
+    }
 
-    // Apply our encoding
-    let plaintext = Plaintext(input_message * delta);
+    // Keys found in the cache are loaded instead of generated. A seeded run then draws the
+    // randomness of its encryptions from another point of the seeded stream than an uncached run
+    let key_cache = args.key_cache.as_deref().map(KeyCache::open).transpose()?;
 
-    // Allocate a new LweCiphertext and encrypt our plaintext
-    let lwe_ciphertext_in: LweCiphertextOwned<u64> = allocate_and_encrypt_new_lwe_ciphertext(
+    // The seeded keys are drawn from the seeder and decompressed, the others from the encryption
+    // generator
+    let mut key_generator = EvaluationKeyGenerator::new(
+        &params,
         &small_lwe_sk,
-        plaintext,
-        lwe_noise_distribution,
-        ciphertext_modulus,
+        &glwe_sk,
+        key_cache.as_ref(),
+        args.seeded.then_some(seeder),
         &mut encryption_generator,
     );
 
-    // Compute a cleartext multiplication by 2
-    let mut cleartext_multiplication_ct = lwe_ciphertext_in.clone();
-    println!("Performing cleartext multiplication...");
-    lwe_ciphertext_cleartext_mul(
-        &mut cleartext_multiplication_ct,
-        &lwe_ciphertext_in,
-        Cleartext(2),
-    );
+    // Trivial encryptions need no bootstrapping key, the PBS outputs are trivially encrypted too.
+    // The key is converted to the Fourier domain on the CPU, or uploaded to the GPU with the cuda
+    // feature
+    let pbs_backend = if args.trivial {
+        None
+    } else {
+        Some(PbsBackend::new(&key_generator.bootstrap_key()?))
+    };
+
+    // The keyswitching key from the big key back to the small key, only needed when proving the
+    // provenance of the decrypted ciphertexts
+    let ksk = if args.keyswitch {
+        Some(key_generator.keyswitch_key(&big_lwe_sk)?)
+    } else {
+        None
+    };
+
+    // A key generation proof only needs the keys, no ciphertext is involved
+    if let (true, Some(ksk)) = (args.prove_key_generation, &ksk) {
+        tracing::info!("Proving the generation of the keyswitching key...");
+        let receipt = prove_keyswitch_key_generation(&params, ksk, &big_lwe_sk, &small_lwe_sk)?;
+        let journal = check_keyswitch_key_generation(&receipt, ksk, &params)?;
+        assert_eq!(journal.input_secret_key_id, secret_key_digest(big_lwe_sk.as_ref()));
+        assert_eq!(journal.output_secret_key_id, secret_key_digest(small_lwe_sk.as_ref()));
+        println!(
+            "Keyswitching key is well formed! Noise below 2^{}",
+            journal.noise_bound_log2
+        );
+
+        return Ok(());
+    }
 
-    // Decrypt the cleartext multiplication result
-    let cleartext_multiplication_plaintext: Plaintext<u64> =
-        decrypt_lwe_ciphertext(&small_lwe_sk, &cleartext_multiplication_ct);
+    // Our message space, message and carry bits included
+    let encoding = params.encoding;
+    let message_space = encoding.message_space();
+
+    // Our batch of input messages, every one of them must still fit in the message space once
+    // doubled
+    let input_messages: Vec<u64> = (0..message_space / 2).collect();
+
+    // Delta used to encode our message and carry bits + the padding bits on u64
+    let delta = encoding.delta();
 
     // Create a SignedDecomposer to perform the rounding of the decrypted plaintext
-    // We pass a DecompositionBaseLog of 5 and a DecompositionLevelCount of 1 indicating we want to
-    // round the 5 MSB, 1 bit of padding plus our 4 bits of message
-    let signed_decomposer =
-        SignedDecomposer::new(DecompositionBaseLog(5), DecompositionLevelCount(1));
-
-    // Round and remove our encoding
-    let cleartext_multiplication_result: u64 =
-        signed_decomposer.closest_representable(cleartext_multiplication_plaintext.0) / delta;
-
-    println!("Checking result...");
-    assert_eq!(6, cleartext_multiplication_result);
-    println!(
-        "Cleartext multiplication result is correct! \
-        Expected 6, got {cleartext_multiplication_result}"
+    // We pass a DecompositionLevelCount of 1 indicating we want to round the MSB, the padding bits
+    // plus our message and carry bits
+    let signed_decomposer = SignedDecomposer::new(
+        DecompositionBaseLog(encoding.decomposer_base_log()),
+        DecompositionLevelCount(1),
     );
 
     // Now we will use a PBS to compute the same multiplication, it is NOT the recommended way of
@@ -145,122 +1009,945 @@ fn main() -> Result<(), Box<dyn Error>> {
     // with a cleartext, however it resets the noise in a ciphertext to a nominal level and allows
     // to evaluate arbitrary functions so depending on your use case it can be a better fit.
 
-    // Generate the accumulator for our multiplication by 2 using a simple closure
+    // Generate the accumulator for our multiplication by 2 using a simple closure, its function
+    // table is committed by the guest when it performs the PBS itself
+    let pbs_function = |x: u64| 2 * x;
+    let lut: Vec<u64> = (0..message_space).map(pbs_function).collect();
     let accumulator: GlweCiphertextOwned<u64> = generate_programmable_bootstrap_glwe_lut(
         polynomial_size,
         glwe_dimension.to_glwe_size(),
-        message_modulus as usize,
+        message_space as usize,
         ciphertext_modulus,
         delta,
-        |x: u64| 2 * x,
+        pbs_function,
     );
 
-    // Allocate the LweCiphertext to store the result of the PBS
-    let mut pbs_multiplication_ct = LweCiphertext::new(
-        0u64,
-        big_lwe_sk.lwe_dimension().to_lwe_size(),
-        ciphertext_modulus,
-    );
-    println!("Computing PBS...");
-    programmable_bootstrap_lwe_ciphertext(
-        &lwe_ciphertext_in,
-        &mut pbs_multiplication_ct,
-        &accumulator,
-        &fourier_bsk,
-    );
-    
-
-    
-    let input_data = bincode::serialize(&std_bootstrapping_key)?;
-    let result_in: LweBootstrapKeyOwned<u64> = bincode::deserialize(&input_data)?;
-    println!("std_bootstrapping_key: {:?}", result_in);
-
-    let input_data_2 = bincode::serialize(&fourier_bsk)?;
-    let result_in_2: FourierLweBootstrapKey<ABox<[c64]>> = bincode::deserialize(&input_data_2)?;
-    println!("fourier_bsk: {:?}", result_in_2);
-
-    let input_data_3 = bincode::serialize(&lwe_ciphertext_in)?;
-    let result_in_3: LweCiphertextOwned<u64> = bincode::deserialize(&input_data_3)?;
-    println!("lwe_ciphertext_in_clear: {:?}", result_in_3);
-
-    let input_data_4 = bincode::serialize(&cleartext_multiplication_result)?;
-    let result_in_4: u64 = bincode::deserialize(&input_data_4)?;
-    println!("cleartext_multiplication_result: {:?}", result_in_4);
-
-    let input_data_5 = bincode::serialize(&accumulator)?;
-    let result_in_5: GlweCiphertextOwned<u64> = bincode::deserialize(&input_data_5)?;
-    println!("accumulator_bf: {:?}", result_in_5);
-
-    let input_data_6 = bincode::serialize(&pbs_multiplication_ct)?;
-    let result_in_6: LweCiphertextOwned<u64> = bincode::deserialize(&input_data_6)?;
-    println!("pbs_multiplication_ct: {:?}", result_in_6);
-
-    let input_data_7 = bincode::serialize(&big_lwe_sk)?;
-    let result_in_7: LweSecretKeyOwned<u64> = bincode::deserialize(&input_data_7)?;
-    println!("big_lwe_sk: {:?}", result_in_7);
-
-    
-
-    
-
-    // par_convert_standard_lwe_bootstrap_key_to_ntt64(&std_bootstrapping_key, &mut ntt_bsk);
-    // println!("ntt_bsk_af: {:?}", ntt_bsk);
-
-    // blind_rotate_ntt64_assign(&lwe_ciphertext_in_clear, &mut accumulator, &ntt_bsk);
-    // println!("accumulator_af: {:?}", accumulator);
-
-    // extract_lwe_sample_from_glwe_ciphertext(
-    //     &accumulator,
-    //     &mut pbs_multiplication_ct,
-    //     MonomialDegree(0),
-    // );
-    // println!("pbs_output: {:?}", pbs_multiplication_ct);
-    
-    let env = ExecutorEnv::builder()
-        .write(&input_data)
-        .unwrap()
-        .write(&input_data_2)
-        .unwrap()
-        .write(&input_data_3)
-        .unwrap()
-        .write(&input_data_4)
-        .unwrap()
-        .write(&input_data_5)
-        .unwrap()
-        .write(&input_data_6)
-        .unwrap()
-        .write(&input_data_7)
-        .unwrap()
-        .build()
-        .unwrap();
-
-    // Obtain the default prover.
-    let prover = default_prover();
+    // The NTT PBS requires a prime modulus supporting a 64 bits NTT, so the guest side PBS works
+    // on its own bootstrapping key, accumulator and input ciphertexts generated for that modulus
+    let ntt_pbs = if args.pbs_in_guest && args.multi_bit.is_none() {
+        let ntt_ciphertext_modulus = ntt_ciphertext_modulus();
+        let ntt_bsk = key_generator.ntt_bootstrap_key()?;
+        let ntt_accumulator: GlweCiphertextOwned<u64> = generate_programmable_bootstrap_glwe_lut(
+            polynomial_size,
+            glwe_dimension.to_glwe_size(),
+            message_space as usize,
+            ntt_ciphertext_modulus,
+            delta,
+            pbs_function,
+        );
+        Some((ntt_ciphertext_modulus, ntt_bsk, ntt_accumulator))
+    } else {
+        None
+    };
+
+    // A multi-bit key bootstraps the inputs on their native modulus, with the native accumulator
+    let multi_bit_bsk = args
+        .multi_bit
+        .map(|grouping_factor| {
+            key_generator.multi_bit_bootstrap_key(LweBskGroupingFactor(grouping_factor as usize))
+        })
+        .transpose()?;
+
+    // In public key mode the inputs are encrypted by a party holding only the public keys of the
+    // small secret key, one per ciphertext modulus in use
+    let public_keys = args.public_key.then(|| {
+        let _keygen_span = tracing::info_span!("keygen").entered();
+        tracing::info!("Generating the LWE public key...");
+        let public_key = generate_public_key(
+            &small_lwe_sk,
+            lwe_noise_distribution,
+            ciphertext_modulus,
+            &mut encryption_generator,
+        );
+        let ntt_public_key = ntt_pbs.as_ref().map(|(ntt_ciphertext_modulus, _, _)| {
+            generate_public_key(
+                &small_lwe_sk,
+                lwe_noise_distribution,
+                *ntt_ciphertext_modulus,
+                &mut encryption_generator,
+            )
+        });
+        (public_key, ntt_public_key)
+    });
+
+    let mut lwe_ciphertexts_in = Vec::with_capacity(input_messages.len());
+    let mut cleartext_multiplication_results = Vec::with_capacity(input_messages.len());
+    let mut pbs_multiplication_cts = Vec::with_capacity(input_messages.len());
+
+    for &input_message in &input_messages {
+        // Apply our encoding
+        let plaintext = Plaintext(input_message * delta);
+
+        // Allocate a new LweCiphertext and encrypt our plaintext
+        let lwe_ciphertext_in: LweCiphertextOwned<u64> = match &public_keys {
+            Some((public_key, _)) => {
+                encrypt_with_public_key(public_key, plaintext, &mut secret_generator)
+            }
+            None if args.trivial => allocate_and_trivially_encrypt_new_lwe_ciphertext(
+                small_lwe_sk.lwe_dimension().to_lwe_size(),
+                plaintext,
+                ciphertext_modulus,
+            ),
+            None => allocate_and_encrypt_new_lwe_ciphertext(
+                &small_lwe_sk,
+                plaintext,
+                lwe_noise_distribution,
+                ciphertext_modulus,
+                &mut encryption_generator,
+            ),
+        };
+
+        // Compute a cleartext multiplication by 2
+        let mut cleartext_multiplication_ct = lwe_ciphertext_in.clone();
+        tracing::info!("Performing cleartext multiplication of {input_message}...");
+        lwe_ciphertext_cleartext_mul(
+            &mut cleartext_multiplication_ct,
+            &lwe_ciphertext_in,
+            Cleartext(2),
+        );
+
+        // Decrypt the cleartext multiplication result
+        let cleartext_multiplication_plaintext: Plaintext<u64> =
+            decrypt_lwe_ciphertext(&small_lwe_sk, &cleartext_multiplication_ct);
+
+        // Round and remove our encoding
+        let cleartext_multiplication_result: u64 =
+            signed_decomposer.closest_representable(cleartext_multiplication_plaintext.0) / delta;
+
+        tracing::info!("Checking result...");
+        assert_eq!(2 * input_message, cleartext_multiplication_result);
+        println!(
+            "Cleartext multiplication result is correct! \
+            Expected {}, got {cleartext_multiplication_result}",
+            2 * input_message
+        );
+
+        // When the guest performs the PBS it receives an input encrypted under the NTT modulus,
+        // the PBS output is still computed here as the claimed output the guest must reproduce
+        let (lwe_ciphertext_in, pbs_multiplication_ct) = match &ntt_pbs {
+            Some((ntt_ciphertext_modulus, ntt_bsk, ntt_accumulator)) => {
+                let ntt_public_key = public_keys
+                    .as_ref()
+                    .and_then(|(_, ntt_public_key)| ntt_public_key.as_ref());
+                let ntt_lwe_ciphertext_in: LweCiphertextOwned<u64> = match ntt_public_key {
+                    Some(ntt_public_key) => {
+                        encrypt_with_public_key(ntt_public_key, plaintext, &mut secret_generator)
+                    }
+                    None => allocate_and_encrypt_new_lwe_ciphertext(
+                        &small_lwe_sk,
+                        plaintext,
+                        lwe_noise_distribution,
+                        *ntt_ciphertext_modulus,
+                        &mut encryption_generator,
+                    ),
+                };
+                let mut ntt_pbs_multiplication_ct = LweCiphertext::new(
+                    0u64,
+                    big_lwe_sk.lwe_dimension().to_lwe_size(),
+                    *ntt_ciphertext_modulus,
+                );
+                tracing::info!("Computing NTT PBS...");
+                programmable_bootstrap_ntt64_lwe_ciphertext(
+                    &ntt_lwe_ciphertext_in,
+                    &mut ntt_pbs_multiplication_ct,
+                    ntt_accumulator,
+                    ntt_bsk,
+                );
+                (ntt_lwe_ciphertext_in, Some(ntt_pbs_multiplication_ct))
+            }
+            // The native and the multi-bit PBS run on the whole batch once it is encrypted
+            None if pbs_backend.is_some() || multi_bit_bsk.is_some() => (lwe_ciphertext_in, None),
+            // The trivial PBS output encrypts the function of the input under the big key
+            None => {
+                let pbs_multiplication_ct = allocate_and_trivially_encrypt_new_lwe_ciphertext(
+                    big_lwe_sk.lwe_dimension().to_lwe_size(),
+                    Plaintext(pbs_function(input_message) * delta),
+                    ciphertext_modulus,
+                );
+                (lwe_ciphertext_in, Some(pbs_multiplication_ct))
+            }
+        };
+
+        lwe_ciphertexts_in.push(lwe_ciphertext_in);
+        cleartext_multiplication_results.push(cleartext_multiplication_result);
+        pbs_multiplication_cts.extend(pbs_multiplication_ct);
+    }
+
+    if let Some(multi_bit_bsk) = &multi_bit_bsk {
+        tracing::info!(
+            "Computing the multi-bit PBS of {} ciphertexts...",
+            lwe_ciphertexts_in.len()
+        );
+        pbs_multiplication_cts = multi_bit_bootstrap(
+            multi_bit_bsk,
+            &lwe_ciphertexts_in,
+            &accumulator,
+            big_lwe_sk.lwe_dimension().to_lwe_size(),
+        );
+    } else if let (None, Some(pbs_backend)) = (&ntt_pbs, &pbs_backend) {
+        tracing::info!(
+            "Computing the PBS of {} ciphertexts on the {}...",
+            lwe_ciphertexts_in.len(),
+            pbs_backend.name()
+        );
+        pbs_multiplication_cts = pbs_backend.bootstrap(
+            &lwe_ciphertexts_in,
+            &accumulator,
+            big_lwe_sk.lwe_dimension().to_lwe_size(),
+        );
+    }
+
+    // A sample extract proof binds the blind rotated accumulator to the PBS output extracted from
+    // it, the accumulator is rotated again here as the PBS does not return it
+    if let (true, Some((_, ntt_bsk, ntt_accumulator))) = (args.prove_sample_extract, &ntt_pbs) {
+        for (lwe_ciphertext_in, pbs_multiplication_ct) in
+            lwe_ciphertexts_in.iter().zip(&pbs_multiplication_cts)
+        {
+            let mut rotated_accumulator = ntt_accumulator.clone();
+            blind_rotate_ntt64_assign(lwe_ciphertext_in, &mut rotated_accumulator, ntt_bsk);
+            tracing::info!("Proving the sample extract of a PBS output...");
+            let receipt = prove_sample_extract(
+                &params,
+                &rotated_accumulator,
+                pbs_multiplication_ct,
+                MonomialDegree(0),
+            )?;
+            check_sample_extract(&receipt, &rotated_accumulator, pbs_multiplication_ct, &params)?;
+            println!("Sample extract proof is correct!");
+
+            if args.chain {
+                tracing::info!(
+                    "Proving the decryption of the PBS output, chained to its receipt..."
+                );
+                let chained = prove_chained_decryption(
+                    &params,
+                    &big_lwe_sk,
+                    pbs_multiplication_ct,
+                    ProofKind::SampleExtract,
+                    &receipt,
+                )?;
+                let plaintext = check_chained_decryption(
+                    &chained,
+                    pbs_multiplication_ct,
+                    &secret_key_digest(big_lwe_sk.as_ref()),
+                    ProofKind::SampleExtract,
+                    &receipt.journal.bytes,
+                    &params,
+                )?;
+                println!(
+                    "Chained decryption proof is correct, the PBS output decrypts to {plaintext}"
+                );
+            }
+        }
+
+        return Ok(());
+    }
+
+    if let Some(party_count) = args.threshold_parties {
+        let mut rng = threshold_rng(args.seed.as_ref());
+        let key_shares = split_secret_key(&big_lwe_sk, party_count, &mut rng);
+
+        // The partial decryptions are checked against the share commitments the DKG receipt
+        // attests, not against commitments the dealer computes
+        tracing::info!("Proving the key generation transcript of {party_count} parties...");
+        let key_noise_bound_log2 = noise_bound_log2(params.glwe_noise_distribution);
+        let transcript = generate_dkg_transcript(
+            &key_shares,
+            args.dkg_zero_encryptions,
+            key_noise_bound_log2,
+            &mut rng,
+        );
+        let dkg_receipt = prove_dkg(&transcript, &key_shares)?;
+        let dkg = check_dkg(&dkg_receipt, &transcript.public_key, key_noise_bound_log2)?;
+        println!("Threshold key commitment: {}", hex::encode(dkg.key_commitment));
+        let key_share_commitments = dkg.share_commitments;
+        assert!(key_shares
+            .iter()
+            .zip(&key_share_commitments)
+            .all(|(key_share, commitment)| key_share_commitment(key_share) == *commitment));
+        let key_commitments: Vec<Digest> =
+            key_share_commitments.iter().copied().map(Digest::from).collect();
+
+        for (pbs_multiplication_ct, cleartext_multiplication_result) in pbs_multiplication_cts
+            .iter()
+            .zip(cleartext_multiplication_results.iter())
+        {
+            tracing::info!("Proving {party_count} partial decryptions...");
+            let receipts = key_shares
+                .iter()
+                .enumerate()
+                .map(|(party_index, key_share)| {
+                    prove_partial_decryption(
+                        party_index as u32,
+                        key_share,
+                        pbs_multiplication_ct,
+                        args.flooding_bound_log2,
+                        rng.gen(),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let combined_plaintext = combine_partial_decryptions(
+                pbs_multiplication_ct,
+                &receipts,
+                &key_share_commitments,
+                args.flooding_bound_log2,
+            )?;
+            // The same receipts checked as a set, every party has to take part with additive shares
+            let threshold_set = verify_threshold_set(
+                &receipts,
+                party_count,
+                lwe_ciphertext_digest(pbs_multiplication_ct),
+                &key_commitments,
+                args.flooding_bound_log2,
+            )?;
+            assert_eq!(threshold_set.combine(pbs_multiplication_ct)?, combined_plaintext);
+
+            let threshold_result =
+                signed_decomposer.closest_representable(combined_plaintext.0) / delta;
+            assert_eq!(*cleartext_multiplication_result, threshold_result);
+            println!("Threshold decryption result is correct! Got {threshold_result}");
+        }
+
+        return Ok(());
+    }
+
+    // Every PBS output is proven in its own receipt, the batch journal is not involved
+    if args.single {
+        for (pbs_multiplication_ct, cleartext_multiplication_result) in pbs_multiplication_cts
+            .iter()
+            .zip(cleartext_multiplication_results.iter())
+        {
+            tracing::info!("Proving the decryption of a single ciphertext...");
+            let output =
+                prove_and_extract(&params, &big_lwe_sk, pbs_multiplication_ct, args.request_id)?;
+            println!(
+                "Proved {} segments, {} user cycles out of {} total cycles",
+                output.stats.segments, output.stats.user_cycles, output.stats.total_cycles
+            );
+            assert_eq!(*cleartext_multiplication_result, output.plaintext);
+            println!(
+                "Single decryption result is correct! Got {} with a noise budget of {} bits",
+                output.plaintext, output.noise_budget_bits
+            );
+        }
+
+        return Ok(());
+    }
+
+    // Only what the policy allows of each plaintext is disclosed, the host knows the expected
+    // disclosure from the cleartext results
+    if let Some(policy) = args.journal_policy {
+        let mut openings = Vec::new();
+        for (pbs_multiplication_ct, &cleartext_multiplication_result) in pbs_multiplication_cts
+            .iter()
+            .zip(cleartext_multiplication_results.iter())
+        {
+            tracing::info!("Proving a decryption under the journal policy...");
+            let receipt =
+                prove_policy_decryption(&params, &big_lwe_sk, pbs_multiplication_ct, policy)?;
+            let disclosed = check_policy_decryption(
+                &receipt,
+                pbs_multiplication_ct,
+                &secret_key_digest(big_lwe_sk.as_ref()),
+                &policy,
+                &params,
+            )?;
+            assert_eq!(disclosure(&policy, cleartext_multiplication_result), disclosed);
+            println!("Policy decryption is correct! Disclosed {disclosed:?}");
+
+            if args.openings_out.is_some() {
+                let opening = plaintext_opening(&policy, cleartext_multiplication_result)?;
+                open_plaintext_commitment(&disclosed, &opening)?;
+                openings.push(opening);
+            }
+        }
+        if let Some(path) = &args.openings_out {
+            std::fs::write(path, serde_json::to_vec_pretty(&openings)?)?;
+            println!("Saved {} plaintext openings to {}", openings.len(), path.display());
+        }
+
+        return Ok(());
+    }
+
+    // The recipient only holds its own secret key, it checks the re-encrypted ciphertexts decrypt
+    // to the messages of the batch
+    if args.reencrypt {
+        let keygen_span = tracing::info_span!("keygen").entered();
+        tracing::info!("Generating the recipient's keys...");
+        let recipient_sk =
+            LweSecretKey::generate_new_binary(small_lwe_dimension, &mut secret_generator);
+        let recipient_public_key = generate_public_key(
+            &recipient_sk,
+            lwe_noise_distribution,
+            ciphertext_modulus,
+            &mut encryption_generator,
+        );
+        drop(keygen_span);
+        let mut rng = reencryption_rng(args.seed.as_ref());
+
+        for (pbs_multiplication_ct, &cleartext_multiplication_result) in pbs_multiplication_cts
+            .iter()
+            .zip(cleartext_multiplication_results.iter())
+        {
+            tracing::info!("Proving the re-encryption of a ciphertext to the recipient...");
+            let selection = random_selection(&recipient_public_key, &mut rng);
+            let (receipt, reencrypted) = prove_reencryption(
+                &params,
+                &big_lwe_sk,
+                pbs_multiplication_ct,
+                cleartext_multiplication_result,
+                &recipient_public_key,
+                &selection,
+            )?;
+            check_reencryption(
+                &receipt,
+                pbs_multiplication_ct,
+                &secret_key_digest(big_lwe_sk.as_ref()),
+                &reencrypted,
+                &recipient_public_key,
+                &params,
+            )?;
+            let recipient_plaintext = decrypt_lwe_ciphertext(&recipient_sk, &reencrypted);
+            let recipient_result =
+                signed_decomposer.closest_representable(recipient_plaintext.0) / delta;
+            assert_eq!(cleartext_multiplication_result, recipient_result);
+            println!("Re-encryption is correct! The recipient decrypts {recipient_result}");
+        }
+
+        return Ok(());
+    }
+
+    // The PBS inputs are under the small key on the native modulus, the guest switches them to 2N
+    // and decrypts the switched ciphertexts
+    if args.mod_switch {
+        for (lwe_ciphertext_in, &input_message) in lwe_ciphertexts_in.iter().zip(&input_messages) {
+            tracing::info!("Proving the decryption of a PBS input after its modulus switch...");
+            let receipt = prove_mod_switch_decryption(&params, &small_lwe_sk, lwe_ciphertext_in)?;
+            let decrypted = check_mod_switch_decryption(
+                &receipt,
+                lwe_ciphertext_in,
+                &secret_key_digest(small_lwe_sk.as_ref()),
+                &params,
+            )?;
+            assert_eq!(input_message, decrypted);
+            println!("Modulus switched decryption result is correct! Got {decrypted}");
+        }
+
+        return Ok(());
+    }
+
+    // In keyswitch mode the guest receives the PBS outputs along with the keyswitching key and the
+    // small secret key, and decrypts the keyswitched ciphertexts it computes itself. Keyswitching
+    // excludes trivial inputs, so the PBS backend is there
+    let keyswitched_cts: Option<Vec<LweCiphertextOwned<u64>>> = ksk
+        .as_ref()
+        .zip(pbs_backend.as_ref())
+        .map(|(ksk, pbs_backend)| pbs_backend.keyswitch(ksk, &pbs_multiplication_cts));
+
+    // A keyswitch attestation needs no decryption, only the key and the ciphertexts on both sides
+    if let (true, Some(ksk), Some(keyswitched_cts)) =
+        (args.prove_keyswitch, &ksk, &keyswitched_cts)
+    {
+        for (pbs_multiplication_ct, keyswitched_ct) in
+            pbs_multiplication_cts.iter().zip(keyswitched_cts)
+        {
+            tracing::info!("Proving the keyswitch of a PBS output...");
+            let receipt = prove_keyswitch(ksk, pbs_multiplication_ct, keyswitched_ct)?;
+            check_keyswitch(&receipt, ksk, pbs_multiplication_ct, keyswitched_ct)?;
+            println!("Keyswitch proof is correct!");
+
+            if args.chain {
+                tracing::info!(
+                    "Proving the decryption of the keyswitch output, chained to its receipt..."
+                );
+                let chained = prove_chained_decryption(
+                    &params,
+                    &small_lwe_sk,
+                    keyswitched_ct,
+                    ProofKind::Keyswitch,
+                    &receipt,
+                )?;
+                let plaintext = check_chained_decryption(
+                    &chained,
+                    keyswitched_ct,
+                    &secret_key_digest(small_lwe_sk.as_ref()),
+                    ProofKind::Keyswitch,
+                    &receipt.journal.bytes,
+                    &params,
+                )?;
+                println!(
+                    "Chained decryption proof is correct, the keyswitched output decrypts to \
+                     {plaintext}"
+                );
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Only the equality of the two messages is committed, both keys stay private
+    if let (true, Some(keyswitched_cts)) = (args.prove_consistency, &keyswitched_cts) {
+        for (pbs_multiplication_ct, keyswitched_ct) in
+            pbs_multiplication_cts.iter().zip(keyswitched_cts)
+        {
+            tracing::info!("Proving a PBS output and its keyswitch decrypt to the same message...");
+            let receipt = prove_consistency(
+                &params,
+                &big_lwe_sk,
+                pbs_multiplication_ct,
+                &small_lwe_sk,
+                keyswitched_ct,
+            )?;
+            let equal = check_consistency(
+                &receipt,
+                pbs_multiplication_ct,
+                keyswitched_ct,
+                Some((&big_lwe_sk, &small_lwe_sk)),
+                &params,
+            )?;
+            if !equal {
+                return Err("the keyswitch changed the message of a PBS output".into());
+            }
+            println!("Consistency proof is correct!");
+        }
+
+        return Ok(());
+    }
+
+    // The rotation audit reveals neither the plaintext nor the old key, the salt opens the
+    // committed hash to whoever the prover hands it
+    if let (true, Some(ksk), Some(keyswitched_cts)) =
+        (args.prove_key_rotation, &ksk, &keyswitched_cts)
+    {
+        let mut rng = rotation_rng(args.seed.as_ref());
+        for (pbs_multiplication_ct, keyswitched_ct) in
+            pbs_multiplication_cts.iter().zip(keyswitched_cts)
+        {
+            tracing::info!("Proving the rotation of a PBS output to the small key...");
+            let salt: [u8; 32] = rng.gen();
+            let receipt =
+                prove_key_rotation(&params, ksk, pbs_multiplication_ct, &small_lwe_sk, salt)?;
+            let commitment =
+                check_key_rotation(&receipt, ksk, pbs_multiplication_ct, keyswitched_ct, &params)?;
+            let plaintext = decrypt_lwe_ciphertext(&small_lwe_sk, keyswitched_ct);
+            let message = signed_decomposer.closest_representable(plaintext.0) / delta;
+            if commitment != plaintext_commitment(&salt, message) {
+                return Err("the rotation proof commits another plaintext".into());
+            }
+            println!("Key rotation proof is correct, the rotated ciphertext decrypts to {message}");
+        }
+
+        return Ok(());
+    }
+
+    // The circuit description is opaque to the guests, only its digest is committed
+    let attached_circuit = match &args.circuit {
+        Some(path) => Some(circuit_digest(&std::fs::read(path)?)),
+        None => args.circuit_digest,
+    };
+    if let Some(digest) = &attached_circuit {
+        tracing::info!("Linking the decryptions to the circuit {}", hex::encode(digest));
+    }
+
+    // Every input is lowered to its raw container or header and serialized once, by the risc0 serde
+    // format. The decryption-only guest needs no evaluation key, only the PBS guest reads the NTT
+    // bootstrapping key, whose container follows its input raw
+    // The PBS guest bootstraps with the NTT key and accumulator, or with the multi-bit key and the
+    // native accumulator
+    let pbs_key = match (&ntt_pbs, &multi_bit_bsk) {
+        (Some((_, ntt_bsk, ntt_accumulator)), _) => Some((
+            PbsBootstrapKeyHeader::Ntt(ntt_lwe_bootstrap_key_header(ntt_bsk)),
+            ntt_bsk.as_view().into_container().to_vec(),
+            ntt_accumulator,
+        )),
+        (None, Some(multi_bit_bsk)) => Some((
+            PbsBootstrapKeyHeader::MultiBit(lwe_multi_bit_bootstrap_key_header(multi_bit_bsk)),
+            multi_bit_bsk.as_view().into_container().to_vec(),
+            &accumulator,
+        )),
+        (None, None) => None,
+    };
+    let guest_input = match pbs_key {
+        Some((bsk, bsk_container, pbs_accumulator)) => BatchInput::Pbs {
+            input: PbsDecryptionInput {
+                memory: pbs_memory_preset(
+                    &params,
+                    &bsk,
+                    input_messages.len().div_ceil(args.jobs as usize) as u32,
+                    args.memory_preset,
+                )?
+                .layout(),
+                accumulator: glwe_ciphertext_data(pbs_accumulator),
+                lut: lut.clone(),
+                big_lwe_sk: lwe_secret_key_data(&big_lwe_sk),
+                bsk,
+                seed_commitment: args.seed.as_ref().map(seed_commitment),
+                circuit_digest: attached_circuit,
+                batch_size: input_messages.len() as u32,
+            },
+            bsk_container: Arc::new(bsk_container),
+            batch_items: lwe_ciphertexts_in
+                .iter()
+                .zip(cleartext_multiplication_results.iter())
+                .zip(pbs_multiplication_cts.iter())
+                .map(
+                    |((lwe_ciphertext_in, cleartext_multiplication_result), pbs_multiplication_ct)| {
+                        PbsBatchItem {
+                            lwe_ciphertext_in: lwe_ciphertext_data(lwe_ciphertext_in),
+                            cleartext_multiplication_result: *cleartext_multiplication_result,
+                            pbs_multiplication_ct: lwe_ciphertext_data(pbs_multiplication_ct),
+                        }
+                    },
+                )
+                .collect(),
+        },
+        None => BatchInput::Decryption {
+            input: DecryptionInput {
+                big_lwe_sk: lwe_secret_key_data(&big_lwe_sk),
+                keyswitch: ksk.as_ref().map(|ksk| KeyswitchInput {
+                    ksk: lwe_keyswitch_key_data(ksk),
+                    small_lwe_sk: lwe_secret_key_data(&small_lwe_sk),
+                }),
+                seed_commitment: args.seed.as_ref().map(seed_commitment),
+                circuit_digest: attached_circuit,
+                batch_size: input_messages.len() as u32,
+            },
+            batch_items: cleartext_multiplication_results
+                .iter()
+                .zip(pbs_multiplication_cts.iter())
+                .map(|(cleartext_multiplication_result, pbs_multiplication_ct)| BatchItem {
+                    cleartext_multiplication_result: *cleartext_multiplication_result,
+                    pbs_multiplication_ct: lwe_ciphertext_data(pbs_multiplication_ct),
+                })
+                .collect(),
+        },
+    };
+    let proof_kind = guest_input.proof_kind();
+
+    // The journals commit the IDs of the keys used, publishing the keys in the registry lets
+    // proofs be matched against them later. In keyswitch mode the batch is decrypted with the small
+    // secret key
+    let decryption_sk = if ksk.is_some() { &small_lwe_sk } else { &big_lwe_sk };
+    let key_registry = match &args.key_registry {
+        Some(dir) => {
+            let mut key_registry = KeyRegistry::open(dir)?;
+            let mut keys: Vec<(KeyKind, Vec<u8>)> = vec![(
+                KeyKind::LweSecretKey,
+                canonical_lwe_secret_key(decryption_sk.as_ref()),
+            )];
+            keys.extend(
+                ksk.as_ref()
+                    .map(|ksk| (KeyKind::KeyswitchKey, canonical_lwe_keyswitch_key(ksk))),
+            );
+            keys.extend(ntt_pbs.as_ref().map(|(_, ntt_bsk, _)| {
+                (KeyKind::NttBootstrapKey, canonical_ntt_lwe_bootstrap_key(ntt_bsk))
+            }));
+            keys.extend(multi_bit_bsk.as_ref().map(|multi_bit_bsk| {
+                (
+                    KeyKind::MultiBitBootstrapKey,
+                    canonical_lwe_multi_bit_bootstrap_key(multi_bit_bsk),
+                )
+            }));
+            for (kind, canonical) in keys {
+                let record = key_registry.register(kind, &params.name, &canonical)?;
+                println!("Registered {kind} {} version {}", record.id, record.version);
+            }
+            Some(key_registry)
+        }
+        None => None,
+    };
+
+    // The batch is streamed into the guest one ciphertext at a time, after its size, so the guest
+    // never has to hold the whole batch. With `--jobs N` it is split in N contiguous shards, each
+    // proven in its own receipt. The stdin of every shard is serialized once, the bytes an audit
+    // bundle records, and environments are rebuilt from it for every proving attempt. Shards are
+    // serialized in parallel, each with its own copy of the keys.
+    let shard_ranges = shard_ranges(input_messages.len(), args.jobs as usize);
+    let default_limits = InputLimits::default();
+    let input_limits = InputLimits {
+        max_input_bytes: args
+            .max_input_mib
+            .map_or(default_limits.max_input_bytes, |mib| (mib as usize) << 20),
+        max_guest_memory_bytes: args
+            .max_guest_memory_mib
+            .map_or(default_limits.max_guest_memory_bytes, |mib| (mib as usize) << 20),
+    };
+    let shard_count = shard_ranges.len();
+    let guest_outputs: Vec<GuestOutput> =
+        shard_ranges.iter().map(|_| GuestOutput::default()).collect();
+    let shard_stdins: Vec<Arc<GuestStdin>> =
+        tracing::info_span!("serialization").in_scope(|| {
+            shard_ranges
+                .par_iter()
+                .map(|range| {
+                    let shard = guest_input.shard(range.clone());
+                    let mut stdin = GuestStdin::new();
+                    stdin.write(&params)?;
+                    shard.write(&mut stdin)?;
+                    // An input the guest memory cannot hold is refused before any proving
+                    InputBudget {
+                        input_bytes: stdin.bytes().len(),
+                        guest_memory_bytes: shard.guest_memory(&params),
+                    }
+                    .check(&input_limits)?;
+                    Ok(Arc::new(stdin))
+                })
+                .collect::<Result<_, ProofError>>()
+        })?;
+    if args.check_determinism {
+        for (shard, stdin) in shard_stdins.iter().enumerate() {
+            tracing::info!("Executing shard {shard} twice...");
+            let stats = check_determinism(proof_kind, || {
+                ExecutorEnv::builder()
+                    .stdout(std::io::sink())
+                    .write_slice(stdin.bytes())
+                    .build()
+                    .map_err(ProofError::proving)
+            })?;
+            println!(
+                "Shard {shard} is deterministic: {} user cycles, {} segments",
+                stats.user_cycles, stats.segments
+            );
+        }
+    }
+
+    let prover_settings = ProverSettings {
+        receipt_kind: args.receipt_kind,
+        hashfn: args.hashfn,
+        segment_limit_po2: args.segment_limit_po2,
+        r0vm_path: args.r0vm_path.clone(),
+    };
+    let env_builders: Vec<prover::EnvBuilder> = shard_stdins
+        .iter()
+        .zip(guest_outputs.iter())
+        .map(|(stdin, guest_output)| {
+            let env_settings = prover_settings.clone();
+            let env_stdin = stdin.clone();
+            let env_guest_output = guest_output.clone();
+            let build_env: prover::EnvBuilder = Arc::new(move || {
+                env_guest_output.clear();
+                let mut env_builder = ExecutorEnv::builder();
+                env_builder.stdout(env_guest_output.clone());
+                env_settings.configure_env(&mut env_builder);
+                env_builder.write_slice(env_stdin.bytes());
+                env_builder.build().map_err(|e| e.to_string())
+            });
+            build_env
+        })
+        .collect();
 
     // Proof information by proving the specified ELF binary.
     // This struct contains the receipt along with statistics about execution of the guest
-    let prove_info = prover
-        .prove(env, HELLO_GUEST_ELF)
-        .unwrap();
+    // With continuations the PBS and the decryption checks of a long batch span many segments, a
+    // composite receipt keeps one receipt per segment and verifies them as a whole, the succinct
+    // and Groth16 kinds compress them further down to a single receipt
+    if shard_count > 1 {
+        tracing::info!("Proving {shard_count} shards of the batch concurrently...");
+    }
+    let token = match args.prove_timeout_secs {
+        Some(timeout) => CancellationToken::with_timeout(Duration::from_secs(timeout)),
+        None => CancellationToken::new(),
+    };
+    let proving_start = Instant::now();
+    let prove_infos = prove_shards(
+        args.prover,
+        RemotePolicy {
+            timeout: Duration::from_secs(args.remote_timeout_secs),
+            retries: args.remote_retries,
+            fallback_to_local: !args.no_local_fallback,
+        },
+        env_builders,
+        proof_kind.elf(),
+        &prover_settings,
+        &Progress::default(),
+        &token,
+    )?;
+    let proving_time = proving_start.elapsed();
 
-    // extract the receipt.
-    let receipt = prove_info.receipt;
-    //println!("receipt: {:?}", receipt);
+    // The decryptions the shards must commit to, the journals only hold their Merkle root
+    let leaves = match &keyswitched_cts {
+        Some(keyswitched_cts) => decryption_leaves(
+            keyswitched_cts,
+            Some(&pbs_multiplication_cts),
+            &cleartext_multiplication_results,
+        )?,
+        None if args.pbs_in_guest => decryption_leaves(
+            &pbs_multiplication_cts,
+            Some(&lwe_ciphertexts_in),
+            &cleartext_multiplication_results,
+        )?,
+        None => {
+            decryption_leaves(&pbs_multiplication_cts, None, &cleartext_multiplication_results)?
+        }
+    };
 
-    // TODO: Implement code for retrieving receipt journal here.
+    let verification_span = tracing::info_span!("verification").entered();
+    let params_policy = ParamsPolicy {
+        pbs_order: args.require_pbs_order,
+        min_security_bits: args.require_security_bits,
+    };
+    let mut receipts = Vec::with_capacity(shard_count);
+    let mut shard_stats = Vec::with_capacity(shard_count);
+    for ((prove_info, guest_output), range) in prove_infos
+        .into_iter()
+        .zip(guest_outputs.iter())
+        .zip(shard_ranges.iter().cloned())
+    {
+        if shard_count > 1 {
+            println!("Shard of ciphertexts {}..{}:", range.start, range.end);
+        }
+        println!(
+            "Proved {} segments, {} user cycles out of {} total cycles",
+            prove_info.stats.segments, prove_info.stats.user_cycles, prove_info.stats.total_cycles
+        );
+        match guest_output.cycle_profile() {
+            Some(profile) => print_cycle_profile(&profile, prove_info.stats.user_cycles),
+            None => println!("No guest cycle profile, the guest output was not captured"),
+        }
 
-    // For example:
-    //let output_data: NttLweBootstrapKey = bincode::deserialize(&receipt.journal.decode().unwrap());
+        // extract the receipt.
+        let receipt = prove_info.receipt;
+        if let InnerReceipt::Composite(composite) = &receipt.inner {
+            println!(
+                "Composite receipt stitching {} segment receipts",
+                composite.segments.len()
+            );
+        }
 
-    let output: LweCiphertextOwned<u64> = receipt.journal.decode().unwrap();
+        // Every shard commits the decryptions of its range, in order. A verifier interested in a
+        // single decryption only needs its leaf and inclusion proof
+        let journal = decode_journal(&receipt)?;
+        let shard_leaves = &leaves[range];
+        check_journal(&journal, shard_leaves, &params)?;
+        params_policy.check(&ParamsSummary::from(&journal.params))?;
+        let inclusion_proofs = decryption_proofs(shard_leaves);
+        for (leaf, inclusion_proof) in shard_leaves.iter().zip(&inclusion_proofs) {
+            check_decryption_inclusion(&journal, leaf, inclusion_proof)?;
+        }
+        println!(
+            "Journal commits {} decryptions under the root {}",
+            journal.decryption_count,
+            hex::encode(journal.decryptions_root)
+        );
+        check_ntt_bootstrap_key(&journal, ntt_pbs.as_ref().map(|(_, ntt_bsk, _)| ntt_bsk))?;
+        check_multi_bit_bootstrap_key(&journal, multi_bit_bsk.as_ref())?;
+        check_key_ids(&journal, decryption_sk, ksk.as_ref())?;
+        if let Some(key_registry) = &key_registry {
+            key_registry.check(&journal.secret_key_id, KeyKind::LweSecretKey)?;
+            if let Some(ksk_id) = &journal.ksk_id {
+                key_registry.check(ksk_id, KeyKind::KeyswitchKey)?;
+            }
+            if let Some(ntt_bsk_digest) = &journal.ntt_bsk_digest {
+                key_registry.check(ntt_bsk_digest, KeyKind::NttBootstrapKey)?;
+            }
+            if let Some(multi_bit_bsk_digest) = &journal.multi_bit_bsk_digest {
+                key_registry.check(multi_bit_bsk_digest, KeyKind::MultiBitBootstrapKey)?;
+            }
+        }
+        check_seed_commitment(&journal, args.seed.as_ref())?;
+        check_circuit_digest(&journal, attached_circuit.as_ref())?;
+        check_trivial(&journal, args.trivial)?;
+        if journal.trivial {
+            println!("The journal flags trivial encryptions, the receipt is a pipeline check only");
+        }
+        check_lut(&journal, args.pbs_in_guest.then_some(lut.as_slice()))?;
+        receipts.push((receipt, inclusion_proofs));
+        shard_stats.push(prove_info.stats);
+    }
+    drop(verification_span);
 
     // The receipt was verified at the end of proving, but the below code is an
     // example of how someone else could verify this receipt.
-    println!("Hello, world! I generated a proof of guest execution! {:?} is a public output from journal ", output);
+    for (index, (((receipt, inclusion_proofs), stats), range)) in receipts
+        .iter()
+        .zip(&shard_stats)
+        .zip(shard_ranges)
+        .enumerate()
+    {
+        verify_receipt(receipt, proof_kind.image_id())?;
+
+        if let Some(inclusion_proofs_out) = &args.inclusion_proofs_out {
+            let inclusion_proofs_out = shard_path(inclusion_proofs_out, index, shard_count);
+            let decryptions: Vec<_> = leaves[range.clone()]
+                .iter()
+                .zip(inclusion_proofs)
+                .map(|(leaf, proof)| serde_json::json!({ "leaf": leaf, "proof": proof }))
+                .collect();
+            std::fs::write(&inclusion_proofs_out, serde_json::to_string_pretty(&decryptions)?)?;
+            println!("Inclusion proofs written to {}", inclusion_proofs_out.display());
+        }
+
+        if let Some(out) = args.out.as_ref().filter(|_| !args.aggregate) {
+            let out = shard_path(out, index, shard_count);
+            write_file(&out, receipt, args.out_format)?;
+            println!(
+                "Receipt written to {}, verify it with `{}`",
+                out.display(),
+                verifier_command(&out, proof_kind.image_id(), args.out_format)
+            );
+        }
+
+        if let Some(evm_out) = &args.evm_out {
+            let evm_out = shard_path(evm_out, index, shard_count);
+            let proof = evm_proof(receipt, proof_kind.image_id(), &leaves[range])
+                .map_err(|e| format!("{e}, prove with --receipt-kind groth16"))?;
+            std::fs::write(&evm_out, serde_json::to_string_pretty(&proof)?)?;
+            println!("EVM proof written to {}", evm_out.display());
+        }
+
+        if let Some(record_out) = &args.record_out {
+            let record_out = shard_path(record_out, index, shard_count);
+            let record = proof_record(receipt, Some((stats, proving_time)))?;
+            std::fs::write(&record_out, serde_json::to_string_pretty(&record)?)?;
+            println!("Proof record written to {}", record_out.display());
+        }
+    }
+
+    if let Some(audit_dir) = &args.audit_dir {
+        let shards: Vec<_> = shard_stdins
+            .iter()
+            .map(Arc::as_ref)
+            .zip(receipts.iter().map(|(receipt, _)| receipt))
+            .collect();
+        let manifest = write_audit_bundle(audit_dir, proof_kind, &params, &shards)?;
+        println!(
+            "Audit bundle of {} receipts written to {}, replay it with `--replay-audit {}`",
+            manifest.shards.len(),
+            audit_dir.display(),
+            audit_dir.display()
+        );
+    }
+
+    // A single succinct receipt attests to every shard, verifiers only need the shard journals to
+    // recompute its root
+    if args.aggregate {
+        tracing::info!("Aggregating {shard_count} receipts...");
+        let aggregated_receipts: Vec<_> = receipts
+            .into_iter()
+            .map(|(receipt, _)| (proof_kind.image_id(), receipt))
+            .collect();
+        let receipt = prove_aggregation(&aggregated_receipts)?;
+        let journals: Vec<_> = aggregated_receipts
+            .iter()
+            .map(|(image_id, receipt)| (*image_id, receipt.journal.bytes.as_slice()))
+            .collect();
+        let journal = check_aggregation(&receipt, &journals)?;
+        println!(
+            "Aggregated {} receipts under the journals root {}",
+            journal.receipt_count,
+            hex::encode(journal.journals_root)
+        );
 
-    receipt
-        .verify(HELLO_GUEST_ID)
-        .unwrap();
+        if let Some(out) = &args.out {
+            write_file(out, &receipt, args.out_format)?;
+            println!(
+                "Aggregated receipt written to {}, verify it with `{}`",
+                out.display(),
+                verifier_command(out, ProofKind::Aggregate.image_id(), args.out_format)
+            );
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,71 @@
+//! Inclusion proofs in the Merkle trees committed by the guests, so a verifier can check a single
+//! decryption of a batch against the journal root without the other decryptions.
+
+use serde::{Deserialize, Serialize};
+
+use crate::hashing::merkle_node;
+
+/// Path from a leaf to the root: the sibling of the node at every level that has one, from the
+/// leaves up. The leaf count fixes the shape of the tree, so which levels have no sibling.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub index: u32,
+    pub leaf_count: u32,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// Inclusion proof of the leaf at `index` among `leaves`, `None` when out of range.
+pub fn merkle_proof(leaves: &[[u8; 32]], index: usize) -> Option<MerkleProof> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut siblings = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut position = index;
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(position ^ 1) {
+            siblings.push(*sibling);
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => merkle_node(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+        position /= 2;
+    }
+    Some(MerkleProof {
+        index: index as u32,
+        leaf_count: leaves.len() as u32,
+        siblings,
+    })
+}
+
+/// Whether `leaf` is at `proof.index` in the tree of root `root`.
+pub fn verify_merkle_proof(root: &[u8; 32], leaf: &[u8; 32], proof: &MerkleProof) -> bool {
+    if proof.index >= proof.leaf_count {
+        return false;
+    }
+    let mut siblings = proof.siblings.iter();
+    let mut node = *leaf;
+    let mut position = proof.index as usize;
+    let mut level_len = proof.leaf_count as usize;
+    while level_len > 1 {
+        let has_sibling = position ^ 1 < level_len;
+        if has_sibling {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            node = if position.is_multiple_of(2) {
+                merkle_node(&node, sibling)
+            } else {
+                merkle_node(sibling, &node)
+            };
+        }
+        position /= 2;
+        level_len = level_len.div_ceil(2);
+    }
+    siblings.next().is_none() && &node == root
+}
@@ -0,0 +1,90 @@
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::canonical::{encode_u64s, lwe_ciphertext_header};
+use zkfhe_core::io::LweDecryptionInput;
+use zkfhe_core::journal::ModulusSwitchJournal;
+use zkfhe_core::modulus_switch::{blind_rotation_modulus_log, modulus_switch};
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::hashing::{lwe_ciphertext_digest, params_digest, sha256_bytes};
+use crate::io::{lwe_ciphertext_data, lwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// The coefficients of a blind rotation input switched to `2N`, as the guest switches them.
+pub fn switched_ciphertext(
+    ciphertext: &LweCiphertextOwned<u64>,
+    polynomial_size: usize,
+) -> Vec<u64> {
+    let log_modulus = blind_rotation_modulus_log(polynomial_size);
+    ciphertext
+        .as_ref()
+        .iter()
+        .map(|&coefficient| modulus_switch(coefficient, log_modulus))
+        .collect()
+}
+
+/// Prove the decryption of a blind rotation input under the small LWE key after its modulus
+/// switch to `2N`, the switch is performed by the guest.
+pub fn prove_mod_switch_decryption(
+    params: &ProofParams,
+    small_lwe_sk: &LweSecretKeyOwned<u64>,
+    ciphertext: &LweCiphertextOwned<u64>,
+) -> Result<Receipt, ProofError> {
+    let env = ExecutorEnv::builder()
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&LweDecryptionInput {
+            lwe_sk: lwe_secret_key_data(small_lwe_sk),
+            ciphertext: lwe_ciphertext_data(ciphertext),
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::ModSwitchDecrypt.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a modulus switch decryption receipt for `ciphertext` under the small secret key of ID
+/// `secret_key_id` and `expected_params`, checking both the native and the switched ciphertext
+/// digests, and return the decoded value.
+pub fn check_mod_switch_decryption(
+    receipt: &Receipt,
+    ciphertext: &LweCiphertextOwned<u64>,
+    secret_key_id: &[u8; 32],
+    expected_params: &ProofParams,
+) -> Result<u64, ProofError> {
+    verify_receipt(receipt, ProofKind::ModSwitchDecrypt.image_id())?;
+    let journal: ModulusSwitchJournal = decode_payload(receipt, ProofKind::ModSwitchDecrypt)?;
+
+    if journal.ct_digest != lwe_ciphertext_digest(ciphertext) {
+        return Err(ProofError::Verification("ciphertext digest mismatch".to_string()));
+    }
+    if &journal.secret_key_id != secret_key_id {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    // The guest hashes the switched coefficients as a ciphertext on `2N`
+    let switched = switched_ciphertext(ciphertext, expected_params.polynomial_size);
+    let log_modulus = blind_rotation_modulus_log(expected_params.polynomial_size);
+    let switched_header = lwe_ciphertext_header(u64::BITS, 1 << log_modulus, switched.len());
+    if journal.switched_ct_digest != sha256_bytes(&encode_u64s(switched_header, &switched)) {
+        return Err(ProofError::Verification(
+            "switched ciphertext digest mismatch".to_string(),
+        ));
+    }
+    if journal.log_modulus != log_modulus {
+        return Err(ProofError::ParameterMismatch(format!(
+            "ciphertext switched to 2^{}, expected 2N",
+            journal.log_modulus
+        )));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+
+    Ok(journal.plaintext)
+}
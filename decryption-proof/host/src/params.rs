@@ -0,0 +1,429 @@
+use std::path::Path;
+use zkfhe_core::checks::validate_ciphertext_modulus;
+use zkfhe_core::encoding::{Decoding, EncodingParams};
+use zkfhe_core::journal::ParamsSummary;
+use tfhe::core_crypto::prelude::*;
+use tfhe::shortint::parameters::{
+    ClassicPBSParameters, EncryptionKeyChoice, ShortintParameterSet,
+    PARAM_MESSAGE_1_CARRY_1_KS_PBS, PARAM_MESSAGE_2_CARRY_2_KS_PBS,
+    PARAM_MESSAGE_3_CARRY_3_KS_PBS,
+};
+
+pub use zkfhe_core::params::{NoiseDistribution, PbsOrder, ProofParams};
+
+use crate::error::ProofError;
+
+/// Names of the presets accepted by [`preset`].
+pub const PRESET_NAMES: [&str; 4] = [
+    "toy",
+    "PARAM_MESSAGE_1_CARRY_1_KS_PBS",
+    "PARAM_MESSAGE_2_CARRY_2_KS_PBS",
+    "PARAM_MESSAGE_3_CARRY_3_KS_PBS",
+];
+
+/// Security level below which the host refuses to prove unless told the parameters are insecure.
+pub const MIN_SECURITY_BITS: u32 = 128;
+
+/// Estimated security of the presets in bits, from the lattice estimator runs tfhe-rs publishes
+/// for its shortint parameters. The toy set was never estimated and claims none.
+const SECURITY_ESTIMATES: [(&str, u32); 4] = [
+    ("toy", 0),
+    ("PARAM_MESSAGE_1_CARRY_1_KS_PBS", 128),
+    ("PARAM_MESSAGE_2_CARRY_2_KS_PBS", 128),
+    ("PARAM_MESSAGE_3_CARRY_3_KS_PBS", 128),
+];
+
+fn security_estimate(name: &str) -> u32 {
+    SECURITY_ESTIMATES
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map_or(0, |(_, bits)| *bits)
+}
+
+/// The historical demo parameters for a 2 bits message with 2 bits of carry, able to hold the doubled
+/// message.
+///
+/// DISCLAIMER: these toy example parameters are not guaranteed to be secure or yield correct
+/// computations
+pub fn toy() -> ProofParams {
+    ProofParams {
+        name: "toy".to_string(),
+        lwe_dimension: 742,
+        glwe_dimension: 1,
+        polynomial_size: 2048,
+        lwe_noise_distribution: NoiseDistribution::Gaussian {
+            std_dev: 0.000007069849454709433,
+        },
+        glwe_noise_distribution: NoiseDistribution::Gaussian {
+            std_dev: 0.00000000000000029403601535432533,
+        },
+        pbs_base_log: 23,
+        pbs_level: 1,
+        ks_base_log: 3,
+        ks_level: 5,
+        pbs_order: PbsOrder::KeyswitchBootstrap,
+        encoding: EncodingParams {
+            message_bits: 2,
+            carry_bits: 2,
+            padding_bits: 1,
+        },
+        decoding: Decoding::Unsigned,
+        ciphertext_modulus: 0,
+        security_bits: security_estimate("toy"),
+    }
+}
+
+fn from_shortint(name: &str, params: ClassicPBSParameters) -> ProofParams {
+    from_parameter_set(name, &ShortintParameterSet::new_pbs_param_set(params.into()))
+}
+
+fn from_parameter_set(name: &str, params: &ShortintParameterSet) -> ProofParams {
+    let ciphertext_modulus = params.ciphertext_modulus();
+    ProofParams {
+        name: name.to_string(),
+        lwe_dimension: params.lwe_dimension().0,
+        glwe_dimension: params.glwe_dimension().0,
+        polynomial_size: params.polynomial_size().0,
+        lwe_noise_distribution: noise_distribution(params.lwe_noise_distribution()),
+        glwe_noise_distribution: noise_distribution(params.glwe_noise_distribution()),
+        pbs_base_log: params.pbs_base_log().0,
+        pbs_level: params.pbs_level().0,
+        ks_base_log: params.ks_base_log().0,
+        ks_level: params.ks_level().0,
+        // Ciphertexts encrypted under the big key are keyswitched before they are bootstrapped
+        pbs_order: match params.encryption_key_choice() {
+            EncryptionKeyChoice::Big => PbsOrder::KeyswitchBootstrap,
+            EncryptionKeyChoice::Small => PbsOrder::BootstrapKeyswitch,
+        },
+        encoding: EncodingParams {
+            message_bits: params.message_modulus().0.ilog2(),
+            carry_bits: params.carry_modulus().0.ilog2(),
+            padding_bits: 1,
+        },
+        decoding: Decoding::Unsigned,
+        ciphertext_modulus: if ciphertext_modulus.is_native_modulus() {
+            0
+        } else {
+            ciphertext_modulus.get_custom_modulus()
+        },
+        security_bits: security_estimate(name),
+    }
+}
+
+/// The parameters of a shortint parameter set, as held by a client key, named and estimated as
+/// the preset they repeat or named `custom` and claiming no security level.
+pub fn from_shortint_parameters(params: &ShortintParameterSet) -> ProofParams {
+    let params = from_parameter_set("custom", params);
+    PRESET_NAMES
+        .iter()
+        .filter_map(|name| preset(name))
+        .find(|preset| same_parameters(preset, &params))
+        .unwrap_or(params)
+}
+
+fn noise_distribution(distribution: DynamicDistribution<u64>) -> NoiseDistribution {
+    match distribution {
+        DynamicDistribution::Gaussian(gaussian) => NoiseDistribution::Gaussian {
+            std_dev: gaussian.std,
+        },
+        DynamicDistribution::TUniform(t_uniform) => NoiseDistribution::TUniform {
+            bound_log2: t_uniform.bound_log2(),
+        },
+    }
+}
+
+/// The shortint parameters behind a preset, `None` for the core_crypto only `toy` preset.
+pub fn shortint_preset(name: &str) -> Option<ClassicPBSParameters> {
+    match name {
+        "PARAM_MESSAGE_1_CARRY_1_KS_PBS" => Some(PARAM_MESSAGE_1_CARRY_1_KS_PBS),
+        "PARAM_MESSAGE_2_CARRY_2_KS_PBS" => Some(PARAM_MESSAGE_2_CARRY_2_KS_PBS),
+        "PARAM_MESSAGE_3_CARRY_3_KS_PBS" => Some(PARAM_MESSAGE_3_CARRY_3_KS_PBS),
+        _ => None,
+    }
+}
+
+/// Get one of the parameter sets listed in [`PRESET_NAMES`].
+pub fn preset(name: &str) -> Option<ProofParams> {
+    match name {
+        "toy" => Some(toy()),
+        _ => shortint_preset(name).map(|shortint_params| from_shortint(name, shortint_params)),
+    }
+}
+
+/// Load a parameter set from a `.toml` or `.json` file.
+pub fn from_file(path: &Path) -> Result<ProofParams, ProofError> {
+    let contents = std::fs::read_to_string(path)?;
+    let params: ProofParams = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => toml::from_str(&contents).map_err(ProofError::invalid_parameters)?,
+        Some("json") => serde_json::from_str(&contents).map_err(ProofError::invalid_parameters)?,
+        _ => {
+            return Err(ProofError::InvalidParameters(format!(
+                "unsupported parameter file {}, expected a .toml or .json file",
+                path.display()
+            )))
+        }
+    };
+    validate(params)
+}
+
+/// Check a parameter set that does not come from [`preset`], from a file or over the wire, and
+/// replace its security claim with the estimate of the preset it repeats.
+pub fn validate(mut params: ProofParams) -> Result<ProofParams, ProofError> {
+    if !params.encoding.is_valid() {
+        return Err(ProofError::InvalidParameters(format!(
+            "invalid encoding {:?}",
+            params.encoding
+        )));
+    }
+    if validate_ciphertext_modulus(&params).is_err() {
+        return Err(ProofError::InvalidParameters(format!(
+            "ciphertext modulus {} must be odd, above 2^{} and below 2^64",
+            params.ciphertext_modulus,
+            params.encoding.total_bits()
+        )));
+    }
+    // Only the presets were run through the lattice estimator, a set repeating one gets its
+    // estimate and any other set is unestimated, which it must admit by claiming 0 bits
+    let estimate = PRESET_NAMES
+        .iter()
+        .filter_map(|name| preset(name))
+        .find(|preset| same_parameters(preset, &params));
+    let estimated_bits = estimate.as_ref().map_or(0, |estimate| estimate.security_bits);
+    if params.security_bits != 0 && params.security_bits != estimated_bits {
+        return Err(ProofError::InvalidParameters(match estimate {
+            Some(estimate) => format!(
+                "{} claims {} bits of security, the {} parameters are estimated at {} bits",
+                params.name, params.security_bits, estimate.name, estimate.security_bits
+            ),
+            None => format!(
+                "{} claims {} bits of security, only the presets are estimated, claim 0 bits and \
+                 prove with --allow-insecure",
+                params.name, params.security_bits
+            ),
+        }));
+    }
+    params.security_bits = estimated_bits;
+    Ok(params)
+}
+
+/// Whether two parameter sets only differ by their name, security annotation and decoding, which
+/// leaves the security of the set unchanged.
+fn same_parameters(lhs: &ProofParams, rhs: &ProofParams) -> bool {
+    let unannotated = |params: &ProofParams| ProofParams {
+        name: String::new(),
+        decoding: Decoding::Unsigned,
+        security_bits: 0,
+        ..params.clone()
+    };
+    unannotated(lhs) == unannotated(rhs)
+}
+
+/// Refuse parameters claiming less than [`MIN_SECURITY_BITS`] bits of security.
+pub fn check_security(params: &ProofParams) -> Result<(), ProofError> {
+    if params.security_bits < MIN_SECURITY_BITS {
+        return Err(ProofError::InvalidParameters(format!(
+            "{} claims {} bits of security, below the {MIN_SECURITY_BITS} bits required to prove",
+            params.name, params.security_bits
+        )));
+    }
+    Ok(())
+}
+
+/// What a verifier requires of the parameters of a proof, checked against the [`ParamsSummary`]
+/// a journal commits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParamsPolicy {
+    /// The only PBS order accepted, any when `None`
+    pub pbs_order: Option<PbsOrder>,
+    pub min_security_bits: u32,
+}
+
+impl ParamsPolicy {
+    /// Refuse a proof whose parameters break the policy.
+    pub fn check(&self, summary: &ParamsSummary) -> Result<(), ProofError> {
+        if let Some(pbs_order) = self.pbs_order {
+            if summary.pbs_order != pbs_order {
+                return Err(ProofError::ParameterMismatch(format!(
+                    "the proof bootstraps in {:?} order, the policy requires {pbs_order:?}",
+                    summary.pbs_order
+                )));
+            }
+        }
+        if summary.security_bits < self.min_security_bits {
+            return Err(ProofError::ParameterMismatch(format!(
+                "the proof was generated with {} bits of security, the policy requires {}",
+                summary.security_bits, self.min_security_bits
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Parse a PBS order as named on the command line, `keyswitch-bootstrap` or
+/// `bootstrap-keyswitch`.
+pub fn parse_pbs_order(name: &str) -> Result<PbsOrder, String> {
+    match name {
+        "keyswitch-bootstrap" => Ok(PbsOrder::KeyswitchBootstrap),
+        "bootstrap-keyswitch" => Ok(PbsOrder::BootstrapKeyswitch),
+        _ => Err(format!(
+            "unknown PBS order {name}, expected keyswitch-bootstrap or bootstrap-keyswitch"
+        )),
+    }
+}
+
+/// Parse a decoding as named on the command line, `unsigned`, `signed` or `torus`.
+pub fn parse_decoding(name: &str) -> Result<Decoding, String> {
+    match name {
+        "unsigned" => Ok(Decoding::Unsigned),
+        "signed" => Ok(Decoding::Signed),
+        "torus" => Ok(Decoding::Torus),
+        _ => Err(format!("unknown decoding {name}, expected unsigned, signed or torus")),
+    }
+}
+
+/// Resolve a preset name, falling back to loading the argument as a parameter file.
+pub fn from_preset_or_file(preset_or_path: &str) -> Result<ProofParams, ProofError> {
+    match preset(preset_or_path) {
+        Some(params) => Ok(params),
+        None => from_file(Path::new(preset_or_path)),
+    }
+}
+
+/// tfhe-rs typed views of the shared parameters, only the host depends on tfhe-rs types.
+pub trait TfheParams {
+    fn lwe_dimension(&self) -> LweDimension;
+    fn glwe_dimension(&self) -> GlweDimension;
+    fn polynomial_size(&self) -> PolynomialSize;
+    fn lwe_noise(&self) -> DynamicDistribution<u64>;
+    fn glwe_noise(&self) -> DynamicDistribution<u64>;
+    /// The GLWE noise on the native 128 bits modulus, with the same variance relative to the
+    /// modulus
+    fn glwe_noise_u128(&self) -> DynamicDistribution<u128>;
+    fn ciphertext_modulus(&self) -> CiphertextModulus<u64>;
+    fn pbs_base_log(&self) -> DecompositionBaseLog;
+    fn pbs_level(&self) -> DecompositionLevelCount;
+    fn ks_base_log(&self) -> DecompositionBaseLog;
+    fn ks_level(&self) -> DecompositionLevelCount;
+}
+
+fn dynamic_distribution(distribution: NoiseDistribution) -> DynamicDistribution<u64> {
+    match distribution {
+        NoiseDistribution::Gaussian { std_dev } => {
+            DynamicDistribution::new_gaussian_from_std_dev(StandardDev(std_dev))
+        }
+        NoiseDistribution::TUniform { bound_log2 } => DynamicDistribution::new_t_uniform(bound_log2),
+    }
+}
+
+fn dynamic_distribution_u128(distribution: NoiseDistribution) -> DynamicDistribution<u128> {
+    match distribution {
+        NoiseDistribution::Gaussian { std_dev } => {
+            DynamicDistribution::new_gaussian_from_std_dev(StandardDev(std_dev))
+        }
+        // The bound is absolute, scale it from the 64 bits modulus to the 128 bits one
+        NoiseDistribution::TUniform { bound_log2 } => {
+            DynamicDistribution::new_t_uniform(bound_log2 + u64::BITS)
+        }
+    }
+}
+
+impl TfheParams for ProofParams {
+    fn lwe_dimension(&self) -> LweDimension {
+        LweDimension(self.lwe_dimension)
+    }
+
+    fn glwe_dimension(&self) -> GlweDimension {
+        GlweDimension(self.glwe_dimension)
+    }
+
+    fn polynomial_size(&self) -> PolynomialSize {
+        PolynomialSize(self.polynomial_size)
+    }
+
+    fn lwe_noise(&self) -> DynamicDistribution<u64> {
+        dynamic_distribution(self.lwe_noise_distribution)
+    }
+
+    fn glwe_noise(&self) -> DynamicDistribution<u64> {
+        dynamic_distribution(self.glwe_noise_distribution)
+    }
+
+    fn glwe_noise_u128(&self) -> DynamicDistribution<u128> {
+        dynamic_distribution_u128(self.glwe_noise_distribution)
+    }
+
+    fn ciphertext_modulus(&self) -> CiphertextModulus<u64> {
+        match self.ciphertext_modulus {
+            0 => CiphertextModulus::new_native(),
+            modulus => CiphertextModulus::try_new(modulus)
+                .expect("the ciphertext modulus is checked when the parameters are loaded"),
+        }
+    }
+
+    fn pbs_base_log(&self) -> DecompositionBaseLog {
+        DecompositionBaseLog(self.pbs_base_log)
+    }
+
+    fn pbs_level(&self) -> DecompositionLevelCount {
+        DecompositionLevelCount(self.pbs_level)
+    }
+
+    fn ks_base_log(&self) -> DecompositionBaseLog {
+        DecompositionBaseLog(self.ks_base_log)
+    }
+
+    fn ks_level(&self) -> DecompositionLevelCount {
+        DecompositionLevelCount(self.ks_level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom() -> ProofParams {
+        let mut params = preset("PARAM_MESSAGE_2_CARRY_2_KS_PBS").unwrap();
+        params.name = "custom".to_string();
+        params.lwe_dimension = 512;
+        params
+    }
+
+    #[test]
+    fn repeated_presets_get_their_estimate() {
+        let mut params = custom();
+        params.lwe_dimension = preset("PARAM_MESSAGE_2_CARRY_2_KS_PBS").unwrap().lwe_dimension;
+        params.security_bits = 0;
+        assert_eq!(validate(params).unwrap().security_bits, 128);
+    }
+
+    #[test]
+    fn repeated_presets_cannot_claim_more() {
+        let mut params = toy();
+        params.security_bits = 128;
+        assert!(validate(params).is_err());
+    }
+
+    #[test]
+    fn custom_sets_cannot_claim_security() {
+        let params = custom();
+        assert_eq!(params.security_bits, 128);
+        assert!(validate(params).is_err());
+    }
+
+    #[test]
+    fn unclaimed_custom_sets_stay_insecure() {
+        let mut params = custom();
+        params.security_bits = 0;
+        let params = validate(params).unwrap();
+        assert_eq!(params.security_bits, 0);
+        assert!(check_security(&params).is_err());
+    }
+
+    #[test]
+    fn parameter_files_are_validated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.json");
+        std::fs::write(&path, serde_json::to_string(&custom()).unwrap()).unwrap();
+        assert!(matches!(from_file(&path), Err(ProofError::InvalidParameters(_))));
+    }
+}
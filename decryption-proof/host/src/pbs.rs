@@ -0,0 +1,246 @@
+//! The PBS and keyswitch of the native PBS inputs, computed outside the zkVM to produce the
+//! ciphertexts the decryption guests are given.
+//!
+//! They run on the CPU with a Fourier bootstrapping key, or with the `cuda` feature on the first
+//! GPU through the CUDA backend of tfhe-rs, where a whole batch is bootstrapped or keyswitched in
+//! a single launch.
+
+use rayon::prelude::*;
+use tfhe::core_crypto::prelude::*;
+
+#[cfg(feature = "cuda")]
+use tfhe::core_crypto::gpu::glwe_ciphertext_list::CudaGlweCiphertextList;
+#[cfg(feature = "cuda")]
+use tfhe::core_crypto::gpu::lwe_bootstrap_key::CudaLweBootstrapKey;
+#[cfg(feature = "cuda")]
+use tfhe::core_crypto::gpu::lwe_ciphertext_list::CudaLweCiphertextList;
+#[cfg(feature = "cuda")]
+use tfhe::core_crypto::gpu::lwe_keyswitch_key::CudaLweKeyswitchKey;
+#[cfg(feature = "cuda")]
+use tfhe::core_crypto::gpu::vec::CudaVec;
+#[cfg(feature = "cuda")]
+use tfhe::core_crypto::gpu::{
+    cuda_keyswitch_lwe_ciphertext, cuda_programmable_bootstrap_lwe_ciphertext, CudaStreams,
+};
+
+/// Bootstrap every input with the multi-bit key `bsk` and `accumulator`, in input order.
+///
+/// The PBS guest recomputes every output with the deterministic standard domain blind rotation,
+/// so the multi-bit PBS runs with it here too instead of the Fourier one, whose outputs would not
+/// match to the bit. The inputs are bootstrapped in parallel, each on a single thread.
+pub fn multi_bit_bootstrap(
+    bsk: &LweMultiBitBootstrapKeyOwned<u64>,
+    inputs: &[LweCiphertextOwned<u64>],
+    accumulator: &GlweCiphertextOwned<u64>,
+    output_lwe_size: LweSize,
+) -> Vec<LweCiphertextOwned<u64>> {
+    inputs
+        .par_iter()
+        .map(|input| {
+            let mut output = LweCiphertext::new(0u64, output_lwe_size, input.ciphertext_modulus());
+            std_multi_bit_deterministic_programmable_bootstrap_lwe_ciphertext(
+                input,
+                &mut output,
+                accumulator,
+                bsk,
+                ThreadCount(1),
+            );
+            output
+        })
+        .collect()
+}
+
+/// Where the host side PBS and keyswitch run.
+pub enum PbsBackend {
+    Cpu {
+        fourier_bsk: FourierLweBootstrapKeyOwned,
+    },
+    #[cfg(feature = "cuda")]
+    Cuda {
+        streams: CudaStreams,
+        bsk: CudaLweBootstrapKey,
+    },
+}
+
+impl PbsBackend {
+    /// The backend of this build for `bsk`: the first GPU with the `cuda` feature, the CPU
+    /// otherwise.
+    pub fn new(bsk: &LweBootstrapKeyOwned<u64>) -> Self {
+        #[cfg(feature = "cuda")]
+        {
+            let streams = CudaStreams::new_single_gpu(0);
+            let bsk = CudaLweBootstrapKey::from_lwe_bootstrap_key(bsk, &streams);
+            Self::Cuda { streams, bsk }
+        }
+        #[cfg(not(feature = "cuda"))]
+        {
+            Self::cpu(bsk)
+        }
+    }
+
+    /// A CPU backend, the bootstrapping key is converted to the Fourier domain.
+    pub fn cpu(bsk: &LweBootstrapKeyOwned<u64>) -> Self {
+        let mut fourier_bsk = FourierLweBootstrapKey::new(
+            bsk.input_lwe_dimension(),
+            bsk.glwe_size(),
+            bsk.polynomial_size(),
+            bsk.decomposition_base_log(),
+            bsk.decomposition_level_count(),
+        );
+        convert_standard_lwe_bootstrap_key_to_fourier(bsk, &mut fourier_bsk);
+        Self::Cpu { fourier_bsk }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cpu { .. } => "CPU",
+            #[cfg(feature = "cuda")]
+            Self::Cuda { .. } => "GPU",
+        }
+    }
+
+    /// Bootstrap every input with `accumulator`, the outputs are under the key of
+    /// `output_lwe_size` the accumulator was built for, in input order.
+    pub fn bootstrap(
+        &self,
+        inputs: &[LweCiphertextOwned<u64>],
+        accumulator: &GlweCiphertextOwned<u64>,
+        output_lwe_size: LweSize,
+    ) -> Vec<LweCiphertextOwned<u64>> {
+        match self {
+            Self::Cpu { fourier_bsk } => inputs
+                .iter()
+                .map(|input| {
+                    let mut output =
+                        LweCiphertext::new(0u64, output_lwe_size, input.ciphertext_modulus());
+                    programmable_bootstrap_lwe_ciphertext(
+                        input,
+                        &mut output,
+                        accumulator,
+                        fourier_bsk,
+                    );
+                    output
+                })
+                .collect(),
+            #[cfg(feature = "cuda")]
+            Self::Cuda { streams, bsk } => {
+                let Some(first) = inputs.first() else {
+                    return Vec::new();
+                };
+                let count = LweCiphertextCount(inputs.len());
+                let d_inputs = CudaLweCiphertextList::from_lwe_ciphertext_list(
+                    &lwe_ciphertext_list(inputs),
+                    streams,
+                );
+                let mut d_outputs = CudaLweCiphertextList::new(
+                    output_lwe_size.to_lwe_dimension(),
+                    count,
+                    first.ciphertext_modulus(),
+                    streams,
+                );
+                let d_accumulator =
+                    CudaGlweCiphertextList::from_glwe_ciphertext(accumulator, streams);
+                // Every input is bootstrapped with the single accumulator
+                let d_lut_indexes = cuda_indexes(&vec![0; inputs.len()], streams);
+                let d_indexes = cuda_indexes(&identity_indexes(inputs.len()), streams);
+                cuda_programmable_bootstrap_lwe_ciphertext(
+                    &d_inputs,
+                    &mut d_outputs,
+                    &d_accumulator,
+                    &d_lut_indexes,
+                    &d_indexes,
+                    &d_indexes,
+                    count,
+                    bsk,
+                    streams,
+                );
+                lwe_ciphertexts(d_outputs.to_lwe_ciphertext_list(streams))
+            }
+        }
+    }
+
+    /// Keyswitch every input with `ksk`, in input order.
+    pub fn keyswitch(
+        &self,
+        ksk: &LweKeyswitchKeyOwned<u64>,
+        inputs: &[LweCiphertextOwned<u64>],
+    ) -> Vec<LweCiphertextOwned<u64>> {
+        match self {
+            Self::Cpu { .. } => inputs
+                .iter()
+                .map(|input| {
+                    let mut output =
+                        LweCiphertext::new(0u64, ksk.output_lwe_size(), input.ciphertext_modulus());
+                    keyswitch_lwe_ciphertext(ksk, input, &mut output);
+                    output
+                })
+                .collect(),
+            #[cfg(feature = "cuda")]
+            Self::Cuda { streams, .. } => {
+                if inputs.is_empty() {
+                    return Vec::new();
+                }
+                let d_ksk = CudaLweKeyswitchKey::from_lwe_keyswitch_key(ksk, streams);
+                let d_inputs = CudaLweCiphertextList::from_lwe_ciphertext_list(
+                    &lwe_ciphertext_list(inputs),
+                    streams,
+                );
+                let mut d_outputs = CudaLweCiphertextList::new(
+                    ksk.output_lwe_size().to_lwe_dimension(),
+                    LweCiphertextCount(inputs.len()),
+                    ksk.ciphertext_modulus(),
+                    streams,
+                );
+                let d_indexes = cuda_indexes(&identity_indexes(inputs.len()), streams);
+                cuda_keyswitch_lwe_ciphertext(
+                    &d_ksk,
+                    &d_inputs,
+                    &mut d_outputs,
+                    &d_indexes,
+                    &d_indexes,
+                    streams,
+                );
+                lwe_ciphertexts(d_outputs.to_lwe_ciphertext_list(streams))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "cuda")]
+fn lwe_ciphertext_list(ciphertexts: &[LweCiphertextOwned<u64>]) -> LweCiphertextListOwned<u64> {
+    let container: Vec<u64> = ciphertexts
+        .iter()
+        .flat_map(|ciphertext| ciphertext.as_ref().iter().copied())
+        .collect();
+    LweCiphertextList::from_container(
+        container,
+        ciphertexts[0].lwe_size(),
+        ciphertexts[0].ciphertext_modulus(),
+    )
+}
+
+#[cfg(feature = "cuda")]
+fn lwe_ciphertexts(list: LweCiphertextListOwned<u64>) -> Vec<LweCiphertextOwned<u64>> {
+    list.iter()
+        .map(|ciphertext| {
+            LweCiphertext::from_container(
+                ciphertext.as_ref().to_vec(),
+                ciphertext.ciphertext_modulus(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(feature = "cuda")]
+fn identity_indexes(len: usize) -> Vec<u64> {
+    (0..len as u64).collect()
+}
+
+#[cfg(feature = "cuda")]
+fn cuda_indexes(indexes: &[u64], streams: &CudaStreams) -> CudaVec<u64> {
+    let mut d_indexes = CudaVec::new(indexes.len(), streams, 0);
+    // SAFETY: the copy is synchronized before the indexes are used
+    unsafe { d_indexes.copy_from_cpu_async(indexes, streams, 0) };
+    streams.synchronize();
+    d_indexes
+}
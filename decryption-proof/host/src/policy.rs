@@ -0,0 +1,172 @@
+//! Journal privacy policies: the policy guest proves a single decryption but its journal only
+//! discloses what the [`JournalPolicy`] allows of the plaintext, the plaintext itself, a salted
+//! hash of it, whether it satisfies a [`Predicate`] (e.g. that a sealed bid is below a threshold),
+//! or nothing beyond the digests.
+//!
+//! A salted hash is opened later with the [`PlaintextOpening`] the requester who chose the salt
+//! reveals, [`open_plaintext_commitment`] checks it against the disclosure of a checked receipt.
+
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::PolicyDecryptionInput;
+use zkfhe_core::journal::PolicyDecryptionJournal;
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::policy::{Disclosure, JournalPolicy, PlaintextOpening, Predicate};
+
+use crate::error::ProofError;
+use crate::hashing::{lwe_ciphertext_digest, params_digest, plaintext_commitment};
+use crate::io::{lwe_ciphertext_data, lwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::seed::parse_seed;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// Parse a predicate given as `lt:THRESHOLD`, `eq:VALUE` or `range:MIN..=MAX`.
+pub fn parse_predicate(predicate: &str) -> Result<Predicate, String> {
+    let parse = |value: &str| value.trim().parse::<u64>().map_err(|e| e.to_string());
+    match predicate.split_once(':') {
+        Some(("lt", threshold)) => Ok(Predicate::LessThan(parse(threshold)?)),
+        Some(("eq", expected)) => Ok(Predicate::Equals(parse(expected)?)),
+        Some(("range", bounds)) => {
+            let (min, max) = bounds
+                .split_once("..=")
+                .ok_or_else(|| format!("range {bounds} is not of the form MIN..=MAX"))?;
+            let (min, max) = (parse(min)?, parse(max)?);
+            if min > max {
+                return Err(format!("empty range {min}..={max}"));
+            }
+            Ok(Predicate::InRange { min, max })
+        }
+        _ => Err(format!(
+            "unknown predicate {predicate}, expected lt:THRESHOLD, eq:VALUE or range:MIN..=MAX"
+        )),
+    }
+}
+
+/// Parse a journal policy: `plaintext`, `digests`, `hash` with a fresh random salt, `hash:SALT`
+/// with a salt of 64 hex characters, or a predicate as accepted by [`parse_predicate`].
+pub fn parse_journal_policy(policy: &str) -> Result<JournalPolicy, String> {
+    match policy {
+        "plaintext" => Ok(JournalPolicy::Plaintext),
+        "digests" => Ok(JournalPolicy::DigestsOnly),
+        "hash" => Ok(JournalPolicy::PlaintextHash {
+            salt: rand::random(),
+        }),
+        _ => match policy.strip_prefix("hash:") {
+            Some(salt) => Ok(JournalPolicy::PlaintextHash {
+                salt: parse_seed(salt)?,
+            }),
+            None => parse_predicate(policy).map(JournalPolicy::Predicate),
+        },
+    }
+}
+
+/// What the policy guest discloses of `plaintext` under `policy`.
+pub fn disclosure(policy: &JournalPolicy, plaintext: u64) -> Disclosure {
+    match *policy {
+        JournalPolicy::Plaintext => Disclosure::Plaintext(plaintext),
+        JournalPolicy::PlaintextHash { salt } => {
+            Disclosure::PlaintextHash(plaintext_commitment(&salt, plaintext))
+        }
+        JournalPolicy::Predicate(predicate) => Disclosure::Predicate {
+            predicate,
+            holds: predicate.evaluate(plaintext),
+        },
+        JournalPolicy::DigestsOnly => Disclosure::DigestsOnly,
+    }
+}
+
+/// Prove the decryption of `ciphertext` committing only what `policy` discloses of its decoded
+/// plaintext.
+pub fn prove_policy_decryption(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    ciphertext: &LweCiphertextOwned<u64>,
+    policy: JournalPolicy,
+) -> Result<Receipt, ProofError> {
+    let env = ExecutorEnv::builder()
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&PolicyDecryptionInput {
+            lwe_sk: lwe_secret_key_data(lwe_sk),
+            ciphertext: lwe_ciphertext_data(ciphertext),
+            policy,
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::PolicyDecrypt.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a policy decryption receipt for `ciphertext` under the secret key of ID `secret_key_id`
+/// and `expected_params` and return what it discloses, which must be of the kind `policy` allows,
+/// and for a predicate the same predicate.
+pub fn check_policy_decryption(
+    receipt: &Receipt,
+    ciphertext: &LweCiphertextOwned<u64>,
+    secret_key_id: &[u8; 32],
+    policy: &JournalPolicy,
+    expected_params: &ProofParams,
+) -> Result<Disclosure, ProofError> {
+    verify_receipt(receipt, ProofKind::PolicyDecrypt.image_id())?;
+    let journal: PolicyDecryptionJournal = decode_payload(receipt, ProofKind::PolicyDecrypt)?;
+
+    if journal.ct_digest != lwe_ciphertext_digest(ciphertext) {
+        return Err(ProofError::Verification("ciphertext digest mismatch".to_string()));
+    }
+    if &journal.secret_key_id != secret_key_id {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    let allowed = match (policy, &journal.disclosure) {
+        (JournalPolicy::Plaintext, Disclosure::Plaintext(_))
+        | (JournalPolicy::PlaintextHash { .. }, Disclosure::PlaintextHash(_))
+        | (JournalPolicy::DigestsOnly, Disclosure::DigestsOnly) => true,
+        (JournalPolicy::Predicate(expected), Disclosure::Predicate { predicate, .. }) => {
+            predicate == expected
+        }
+        _ => false,
+    };
+    if !allowed {
+        return Err(ProofError::Verification(format!(
+            "the receipt discloses {:?}, expected a disclosure under {policy:?}",
+            journal.disclosure
+        )));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+
+    Ok(journal.disclosure)
+}
+
+/// The opening the requester reveals for the plaintext hash `policy` commits of `plaintext`.
+pub fn plaintext_opening(
+    policy: &JournalPolicy,
+    plaintext: u64,
+) -> Result<PlaintextOpening, ProofError> {
+    match *policy {
+        JournalPolicy::PlaintextHash { salt } => Ok(PlaintextOpening { salt, plaintext }),
+        _ => Err(ProofError::InvalidInput(format!("{policy:?} commits no plaintext hash"))),
+    }
+}
+
+/// Check that `opening` opens the plaintext hash of `disclosure` and return the revealed plaintext.
+pub fn open_plaintext_commitment(
+    disclosure: &Disclosure,
+    opening: &PlaintextOpening,
+) -> Result<u64, ProofError> {
+    let Disclosure::PlaintextHash(commitment) = disclosure else {
+        return Err(ProofError::Verification(format!(
+            "{disclosure:?} is not a plaintext hash"
+        )));
+    };
+    if *commitment != plaintext_commitment(&opening.salt, opening.plaintext) {
+        return Err(ProofError::Verification(
+            "the opening does not match the committed plaintext hash".to_string(),
+        ));
+    }
+    Ok(opening.plaintext)
+}
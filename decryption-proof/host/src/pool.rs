@@ -0,0 +1,83 @@
+//! Proving of independent shards of a batch on concurrent host threads.
+//!
+//! Every shard is proven by its own prover, the local prover runs every proof in its own `r0vm`
+//! process, so the shards are proven in parallel and their receipts returned in shard order.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use risc0_zkvm::ProveInfo;
+
+use crate::cancel::CancellationToken;
+use crate::error::ProofError;
+use crate::progress::Progress;
+use crate::prover::{self, EnvBuilder, ProverBackend, ProverSettings, RemotePolicy};
+
+/// Split `len` items in at most `jobs` contiguous, non empty ranges whose sizes differ by at most
+/// one, in order.
+pub fn shard_ranges(len: usize, jobs: usize) -> Vec<Range<usize>> {
+    let shard_count = jobs.clamp(1, len.max(1));
+    let (shard_len, remainder) = (len / shard_count, len % shard_count);
+    let mut start = 0;
+    (0..shard_count)
+        .map(|index| {
+            let end = start + shard_len + usize::from(index < remainder);
+            let range = start..end;
+            start = end;
+            range
+        })
+        .collect()
+}
+
+/// The file a shard receipt is written to, `path` itself when the batch was not sharded and
+/// `path.<index>` otherwise.
+pub fn shard_path(path: &Path, index: usize, shard_count: usize) -> PathBuf {
+    if shard_count == 1 {
+        return path.to_path_buf();
+    }
+    let mut file_name = path.as_os_str().to_owned();
+    file_name.push(format!(".{index}"));
+    PathBuf::from(file_name)
+}
+
+/// Prove every shard concurrently, one thread per shard, and return the proofs in shard order.
+///
+/// Every shard is proven with [`prover::prove_with_cancel`] and its remote policy, cancelling
+/// `token` cancels all of them. The first failed shard is reported once all of them completed. The
+/// events of shard `i` are reported to `progress.for_shard(i)`.
+pub fn prove_shards(
+    backend: ProverBackend,
+    policy: RemotePolicy,
+    env_builders: Vec<EnvBuilder>,
+    elf: &'static [u8],
+    settings: &ProverSettings,
+    progress: &Progress,
+    token: &CancellationToken,
+) -> Result<Vec<ProveInfo>, ProofError> {
+    let span = tracing::Span::current();
+    thread::scope(|scope| {
+        let handles: Vec<_> = env_builders
+            .into_iter()
+            .enumerate()
+            .map(|(index, build_env)| {
+                let progress = progress.for_shard(index);
+                let span = span.clone();
+                scope.spawn(move || {
+                    let _entered = span.enter();
+                    prover::prove_with_cancel(
+                        backend, policy, build_env, elf, settings, &progress, token,
+                    )
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle.join().unwrap_or_else(|_| {
+                    Err(ProofError::Proving("a proving thread panicked".to_string()))
+                })
+            })
+            .collect()
+    })
+}
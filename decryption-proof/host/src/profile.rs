@@ -0,0 +1,62 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use zkfhe_core::profile::CycleProfile;
+
+/// Collects what a guest writes to its stdout, shared between the executor environment and the
+/// host.
+#[derive(Clone, Default)]
+pub struct GuestOutput(Arc<Mutex<Vec<u8>>>);
+
+impl GuestOutput {
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    /// Decode the cycle profile written by the batch guest, `None` when the guest output was not
+    /// captured, as with remote proving.
+    pub fn cycle_profile(&self) -> Option<CycleProfile> {
+        let bytes = self.0.lock().unwrap();
+        let words: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+        risc0_zkvm::serde::from_slice(&words).ok()
+    }
+}
+
+impl Write for GuestOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Print the cycles of every guest phase and their share of the user cycles.
+pub fn print_cycle_profile(profile: &CycleProfile, user_cycles: u64) {
+    let phases = [
+        ("deserialization", profile.deserialization),
+        ("hashing", profile.hashing),
+        ("keyswitch", profile.keyswitch),
+        ("LUT check", profile.lut_check),
+        ("NTT plan", profile.ntt_plan),
+        ("blind rotation", profile.blind_rotation),
+        ("sample extract", profile.sample_extract),
+        ("decryption", profile.decryption),
+    ];
+    let measured: u64 = phases.iter().map(|(_, cycles)| cycles).sum();
+
+    println!("Guest cycle profile:");
+    for (phase, cycles) in phases
+        .into_iter()
+        .chain([("other", user_cycles.saturating_sub(measured))])
+    {
+        println!(
+            "  {phase:<16} {cycles:>14} cycles {:>6.2}%",
+            100.0 * cycles as f64 / user_cycles.max(1) as f64
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Progress of long running proofs, reported to the applications embedding the host library.
+//!
+//! A proof of a large batch takes minutes to hours. The prover reports every stage of every shard
+//! it proves as a [`ProgressEvent`], to a callback or to a channel, next to the `tracing` spans and
+//! events it records for logs. The events are reported from the proving threads, a callback must
+//! return quickly.
+
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A stage of a proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofStage {
+    KeyGeneration,
+    /// Serialization of the guest inputs
+    Serialization,
+    /// Building the executor environment of a proving attempt
+    EnvBuild,
+    /// Executing and proving the segments of the guest
+    Proving,
+    Verification,
+}
+
+impl fmt::Display for ProofStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ProofStage::KeyGeneration => "key generation",
+            ProofStage::Serialization => "serialization",
+            ProofStage::EnvBuild => "environment build",
+            ProofStage::Proving => "proving",
+            ProofStage::Verification => "verification",
+        })
+    }
+}
+
+/// What happened to the proof of `shard`, shard 0 for a proof that is not sharded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    StageStarted {
+        shard: usize,
+        stage: ProofStage,
+    },
+    StageFinished {
+        shard: usize,
+        stage: ProofStage,
+        elapsed: Duration,
+    },
+    /// A remote proving attempt failed or timed out, it is retried or falls back to local proving
+    AttemptFailed {
+        shard: usize,
+        attempt: u32,
+        error: String,
+    },
+    FallbackToLocal {
+        shard: usize,
+    },
+    /// The receipt of the shard is proven
+    Proved {
+        shard: usize,
+        segments: usize,
+        user_cycles: u64,
+        total_cycles: u64,
+    },
+}
+
+/// Receives the progress events of a proof.
+pub type ProgressCallback = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+/// Where the progress of a proof is reported, nowhere by default.
+#[derive(Clone, Default)]
+pub struct Progress {
+    callback: Option<ProgressCallback>,
+    shard: usize,
+}
+
+impl Progress {
+    /// Report every event to `callback`.
+    pub fn callback(callback: ProgressCallback) -> Self {
+        Self {
+            callback: Some(callback),
+            shard: 0,
+        }
+    }
+
+    /// Report every event to the returned channel. Events sent after the receiver is dropped are
+    /// discarded.
+    pub fn channel() -> (Self, Receiver<ProgressEvent>) {
+        let (sender, receiver) = mpsc::channel();
+        let sender: Mutex<Sender<ProgressEvent>> = Mutex::new(sender);
+        let progress = Self::callback(Arc::new(move |event: &ProgressEvent| {
+            if let Ok(sender) = sender.lock() {
+                let _ = sender.send(event.clone());
+            }
+        }));
+        (progress, receiver)
+    }
+
+    /// The progress of shard `shard` of the same proof.
+    pub fn for_shard(&self, shard: usize) -> Self {
+        Self {
+            callback: self.callback.clone(),
+            shard,
+        }
+    }
+
+    pub fn shard(&self) -> usize {
+        self.shard
+    }
+
+    pub fn report(&self, event: ProgressEvent) {
+        if let Some(callback) = &self.callback {
+            callback(&event);
+        }
+    }
+
+    /// Run `f` as `stage` of the shard, in a `tracing` span of the stage, between its started and
+    /// finished events.
+    pub fn stage<T>(&self, stage: ProofStage, f: impl FnOnce() -> T) -> T {
+        let span = tracing::info_span!("stage", %stage, shard = self.shard);
+        let _entered = span.enter();
+        self.report(ProgressEvent::StageStarted {
+            shard: self.shard,
+            stage,
+        });
+        let start = Instant::now();
+        let output = f();
+        let elapsed = start.elapsed();
+        tracing::debug!(elapsed_ms = elapsed.as_millis() as u64, "{stage} finished");
+        self.report(ProgressEvent::StageFinished {
+            shard: self.shard,
+            stage,
+            elapsed,
+        });
+        output
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("callback", &self.callback.is_some())
+            .field("shard", &self.shard)
+            .finish()
+    }
+}
@@ -0,0 +1,214 @@
+use methods::{
+    AGGREGATE_ELF, AGGREGATE_ID, BATCH_DECRYPT_ELF, BATCH_DECRYPT_ID, CHAINED_DECRYPT_ELF,
+    CHAINED_DECRYPT_ID, COMPACT_LIST_DECRYPT_ELF, COMPACT_LIST_DECRYPT_ID, CONSISTENCY_ELF,
+    CONSISTENCY_ID, DECRYPT_ELF, DECRYPT_ID, DECRYPT_U128_ELF, DECRYPT_U128_ID, DKG_ELF, DKG_ID,
+    GLWE_DECRYPT_ELF, GLWE_DECRYPT_ID, KEYSWITCH_ELF, KEYSWITCH_ID, KEY_GENERATION_ELF,
+    KEY_GENERATION_ID, KEY_ROTATION_ELF, KEY_ROTATION_ID, MOD_SWITCH_DECRYPT_ELF,
+    MOD_SWITCH_DECRYPT_ID, PARTIAL_DECRYPT_ELF, PARTIAL_DECRYPT_ID, PBS_DECRYPT_ELF, PBS_DECRYPT_ID,
+    POLICY_DECRYPT_ELF, POLICY_DECRYPT_ID, RADIX_DECRYPT_ELF, RADIX_DECRYPT_ID, REENCRYPT_ELF,
+    REENCRYPT_ID, SAMPLE_EXTRACT_ELF, SAMPLE_EXTRACT_ID, SHORTINT_DECRYPT_ELF, SHORTINT_DECRYPT_ID,
+};
+use methods::GuestInfo;
+use risc0_zkvm::sha::Digest;
+use zkfhe_core::journal::JournalKind;
+
+/// The statement proven, each kind has its own guest binary in the methods crate and so its own
+/// image ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofKind {
+    /// Decryption of a single LWE ciphertext, no evaluation key involved
+    Decrypt,
+    /// Decryption of a single LWE ciphertext on the native 128 bits modulus
+    DecryptU128,
+    /// PBS of a batch of LWE ciphertexts with an NTT bootstrapping key, then decryption
+    PbsAndDecrypt,
+    /// Partial decryption of an LWE ciphertext with a secret key share
+    ThresholdPartialDecrypt,
+    /// Decryption of a batch of LWE ciphertexts, optionally keyswitched first
+    BatchDecrypt,
+    /// Decryption of every coefficient of a GLWE ciphertext
+    GlweDecrypt,
+    /// Decryption of a shortint ciphertext
+    ShortintDecrypt,
+    /// Decryption of a radix integer ciphertext
+    RadixDecrypt,
+    /// Decryption of a blind rotation input after its modulus switch to `2N`
+    ModSwitchDecrypt,
+    /// Decryption of a single LWE ciphertext committing only what a journal policy discloses of
+    /// its plaintext
+    PolicyDecrypt,
+    /// Decryption of a single LWE ciphertext and encryption of its message under a recipient's
+    /// public key
+    Reencrypt,
+    /// Keyswitch of an LWE ciphertext under a keyswitching key, without decryption
+    Keyswitch,
+    /// Verification of many receipts of the other kinds, committing a Merkle root of their
+    /// journals
+    Aggregate,
+    /// Well-formedness of a keyswitching key: it encrypts the decomposed big key under the small
+    /// key with the noise of the parameters
+    KeyGeneration,
+    /// Sample extract of an LWE ciphertext from a GLWE accumulator, without decryption
+    SampleExtract,
+    /// Expansion of an LWE compact ciphertext list and decryption of every ciphertext of it
+    CompactListDecrypt,
+    /// Decryption of the output of an evaluation step, conditioned on the receipt of the step
+    ChainedDecrypt,
+    /// Equality of the messages of two ciphertexts under different keys, the messages stay private
+    Consistency,
+    /// Keyswitch of a ciphertext from an old key to a new one and decryption under the new key,
+    /// committing a salted hash of the plaintext
+    KeyRotation,
+    /// Verification of the transcript of a distributed generation of the threshold key, committing
+    /// the key share commitments the partial decryptions are checked against
+    Dkg,
+}
+
+impl ProofKind {
+    pub const ALL: [ProofKind; 20] = [
+        Self::Decrypt,
+        Self::DecryptU128,
+        Self::PbsAndDecrypt,
+        Self::ThresholdPartialDecrypt,
+        Self::BatchDecrypt,
+        Self::GlweDecrypt,
+        Self::ShortintDecrypt,
+        Self::RadixDecrypt,
+        Self::ModSwitchDecrypt,
+        Self::PolicyDecrypt,
+        Self::Reencrypt,
+        Self::Keyswitch,
+        Self::Aggregate,
+        Self::KeyGeneration,
+        Self::SampleExtract,
+        Self::CompactListDecrypt,
+        Self::ChainedDecrypt,
+        Self::Consistency,
+        Self::KeyRotation,
+        Self::Dkg,
+    ];
+
+    /// The guest ELF to prove.
+    pub fn elf(self) -> &'static [u8] {
+        match self {
+            Self::Decrypt => DECRYPT_ELF,
+            Self::DecryptU128 => DECRYPT_U128_ELF,
+            Self::PbsAndDecrypt => PBS_DECRYPT_ELF,
+            Self::ThresholdPartialDecrypt => PARTIAL_DECRYPT_ELF,
+            Self::BatchDecrypt => BATCH_DECRYPT_ELF,
+            Self::GlweDecrypt => GLWE_DECRYPT_ELF,
+            Self::ShortintDecrypt => SHORTINT_DECRYPT_ELF,
+            Self::RadixDecrypt => RADIX_DECRYPT_ELF,
+            Self::ModSwitchDecrypt => MOD_SWITCH_DECRYPT_ELF,
+            Self::PolicyDecrypt => POLICY_DECRYPT_ELF,
+            Self::Reencrypt => REENCRYPT_ELF,
+            Self::Keyswitch => KEYSWITCH_ELF,
+            Self::Aggregate => AGGREGATE_ELF,
+            Self::KeyGeneration => KEY_GENERATION_ELF,
+            Self::SampleExtract => SAMPLE_EXTRACT_ELF,
+            Self::CompactListDecrypt => COMPACT_LIST_DECRYPT_ELF,
+            Self::ChainedDecrypt => CHAINED_DECRYPT_ELF,
+            Self::Consistency => CONSISTENCY_ELF,
+            Self::KeyRotation => KEY_ROTATION_ELF,
+            Self::Dkg => DKG_ELF,
+        }
+    }
+
+    /// Name of the guest binary in the methods crate.
+    pub fn guest_name(self) -> &'static str {
+        match self {
+            Self::Decrypt => "decrypt",
+            Self::DecryptU128 => "decrypt_u128",
+            Self::PbsAndDecrypt => "pbs_decrypt",
+            Self::ThresholdPartialDecrypt => "partial_decrypt",
+            Self::BatchDecrypt => "batch_decrypt",
+            Self::GlweDecrypt => "glwe_decrypt",
+            Self::ShortintDecrypt => "shortint_decrypt",
+            Self::RadixDecrypt => "radix_decrypt",
+            Self::ModSwitchDecrypt => "mod_switch_decrypt",
+            Self::PolicyDecrypt => "policy_decrypt",
+            Self::Reencrypt => "reencrypt",
+            Self::Keyswitch => "keyswitch",
+            Self::Aggregate => "aggregate",
+            Self::KeyGeneration => "key_generation",
+            Self::SampleExtract => "sample_extract",
+            Self::CompactListDecrypt => "compact_list_decrypt",
+            Self::ChainedDecrypt => "chained_decrypt",
+            Self::Consistency => "consistency",
+            Self::KeyRotation => "key_rotation",
+            Self::Dkg => "dkg",
+        }
+    }
+
+    /// The kind whose guest binary is named `guest_name`.
+    pub fn from_guest_name(guest_name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.guest_name() == guest_name)
+    }
+
+    /// The kinds a peer advertising `peer` proves with the very guests of this build, in the
+    /// order of [`ProofKind::ALL`]. A guest of the same name but another image ID proves another
+    /// statement and is left out.
+    pub fn negotiate(peer: &GuestInfo) -> Vec<Self> {
+        Self::ALL
+            .into_iter()
+            .filter(|kind| {
+                peer.image_id(kind.guest_name())
+                    .is_some_and(|image_id| Digest::from(image_id) == kind.image_id())
+            })
+            .collect()
+    }
+
+    /// The kind of journal the guest commits, recorded in its envelope.
+    pub fn journal_kind(self) -> JournalKind {
+        match self {
+            Self::Decrypt => JournalKind::Decrypt,
+            Self::DecryptU128 => JournalKind::DecryptU128,
+            Self::PbsAndDecrypt => JournalKind::PbsDecrypt,
+            Self::ThresholdPartialDecrypt => JournalKind::PartialDecrypt,
+            Self::BatchDecrypt => JournalKind::BatchDecrypt,
+            Self::GlweDecrypt => JournalKind::GlweDecrypt,
+            Self::ShortintDecrypt => JournalKind::ShortintDecrypt,
+            Self::RadixDecrypt => JournalKind::RadixDecrypt,
+            Self::ModSwitchDecrypt => JournalKind::ModSwitchDecrypt,
+            Self::PolicyDecrypt => JournalKind::PolicyDecrypt,
+            Self::Reencrypt => JournalKind::Reencrypt,
+            Self::Keyswitch => JournalKind::Keyswitch,
+            Self::Aggregate => JournalKind::Aggregate,
+            Self::KeyGeneration => JournalKind::KeyGeneration,
+            Self::SampleExtract => JournalKind::SampleExtract,
+            Self::CompactListDecrypt => JournalKind::CompactListDecrypt,
+            Self::ChainedDecrypt => JournalKind::ChainedDecrypt,
+            Self::Consistency => JournalKind::Consistency,
+            Self::KeyRotation => JournalKind::KeyRotation,
+            Self::Dkg => JournalKind::Dkg,
+        }
+    }
+
+    /// The image ID receipts of this kind are verified against.
+    pub fn image_id(self) -> Digest {
+        Digest::from(match self {
+            Self::Decrypt => DECRYPT_ID,
+            Self::DecryptU128 => DECRYPT_U128_ID,
+            Self::PbsAndDecrypt => PBS_DECRYPT_ID,
+            Self::ThresholdPartialDecrypt => PARTIAL_DECRYPT_ID,
+            Self::BatchDecrypt => BATCH_DECRYPT_ID,
+            Self::GlweDecrypt => GLWE_DECRYPT_ID,
+            Self::ShortintDecrypt => SHORTINT_DECRYPT_ID,
+            Self::RadixDecrypt => RADIX_DECRYPT_ID,
+            Self::ModSwitchDecrypt => MOD_SWITCH_DECRYPT_ID,
+            Self::PolicyDecrypt => POLICY_DECRYPT_ID,
+            Self::Reencrypt => REENCRYPT_ID,
+            Self::Keyswitch => KEYSWITCH_ID,
+            Self::Aggregate => AGGREGATE_ID,
+            Self::KeyGeneration => KEY_GENERATION_ID,
+            Self::SampleExtract => SAMPLE_EXTRACT_ID,
+            Self::CompactListDecrypt => COMPACT_LIST_DECRYPT_ID,
+            Self::ChainedDecrypt => CHAINED_DECRYPT_ID,
+            Self::Consistency => CONSISTENCY_ID,
+            Self::KeyRotation => KEY_ROTATION_ID,
+            Self::Dkg => DKG_ID,
+        })
+    }
+}
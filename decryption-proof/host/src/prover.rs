@@ -0,0 +1,476 @@
+use clap::ValueEnum;
+use risc0_zkvm::{
+    default_prover, is_dev_mode, BonsaiProver, ExecutorEnv, ExecutorEnvBuilder, ExternalProver,
+    ProveInfo, Prover, ProverOpts,
+};
+#[cfg(unix)]
+use std::fs;
+#[cfg(unix)]
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::process::Command;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cancel::CancellationToken;
+use crate::error::ProofError;
+use crate::progress::{Progress, ProgressEvent, ProofStage};
+use crate::verification::check_exit_code;
+
+/// Where proofs are generated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ProverBackend {
+    /// Prove on this machine with the local `r0vm` server
+    Local,
+    /// Prove on Bonsai, or any remote prover implementing the Bonsai API, configured by the
+    /// `BONSAI_API_URL` and `BONSAI_API_KEY` environment variables
+    Bonsai,
+}
+
+/// How long to wait for a remote proof and how many times to try before giving up.
+#[derive(Clone, Copy, Debug)]
+pub struct RemotePolicy {
+    pub timeout: Duration,
+    pub retries: u32,
+    pub fallback_to_local: bool,
+}
+
+/// Kind of receipt produced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReceiptKind {
+    /// One STARK receipt per segment
+    #[default]
+    Composite,
+    /// A single STARK receipt, the segments are recursively compressed
+    Succinct,
+    /// A Groth16 receipt over BN254, small enough to be verified on-chain
+    Groth16,
+}
+
+/// Hash function of the STARK seals.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum HashFn {
+    /// The default, the only one the recursion circuit compresses segments with
+    #[default]
+    Poseidon2,
+    Sha256,
+    Blake2b,
+}
+
+impl HashFn {
+    /// The name risc0 selects the hash suite by.
+    pub fn name(self) -> &'static str {
+        match self {
+            HashFn::Poseidon2 => "poseidon2",
+            HashFn::Sha256 => "sha-256",
+            HashFn::Blake2b => "blake2b",
+        }
+    }
+}
+
+/// How proofs are generated: smaller segments need less memory per segment but more segments,
+/// compressed receipts are smaller but take longer to prove.
+#[derive(Clone, Debug, Default)]
+pub struct ProverSettings {
+    pub receipt_kind: ReceiptKind,
+    pub hashfn: HashFn,
+    /// Log2 of the maximum number of cycles of a segment, the risc0 default when `None`
+    pub segment_limit_po2: Option<u32>,
+    /// The `r0vm` server of the local prover, `RISC0_SERVER_PATH` or `r0vm` from the `PATH` when
+    /// `None`
+    pub r0vm_path: Option<PathBuf>,
+}
+
+impl ProverSettings {
+    /// The options of every proving attempt. Succinct and Groth16 receipts are compressed by the
+    /// recursion circuit, which only reads Poseidon2 seals.
+    pub fn prover_opts(&self) -> Result<ProverOpts, ProofError> {
+        let opts = match self.receipt_kind {
+            ReceiptKind::Composite => ProverOpts::composite(),
+            ReceiptKind::Succinct => ProverOpts::succinct(),
+            ReceiptKind::Groth16 => ProverOpts::groth16(),
+        };
+        if self.receipt_kind != ReceiptKind::Composite && self.hashfn != HashFn::Poseidon2 {
+            return Err(ProofError::InvalidInput(format!(
+                "{:?} receipts need Poseidon2 seals, not {}",
+                self.receipt_kind,
+                self.hashfn.name()
+            )));
+        }
+        Ok(opts.with_hashfn(self.hashfn.name().to_string()))
+    }
+
+    /// Apply the segment limit to the environment of a proof.
+    pub fn configure_env(&self, builder: &mut ExecutorEnvBuilder<'_>) {
+        if let Some(segment_limit_po2) = self.segment_limit_po2 {
+            builder.segment_limit_po2(segment_limit_po2);
+        }
+    }
+}
+
+/// Builds the executor environment of a proof, environments are consumed by the prover so a fresh
+/// one is built for every attempt.
+pub type EnvBuilder = Arc<dyn Fn() -> Result<ExecutorEnv<'static>, String> + Send + Sync>;
+
+/// How often a waiting prover checks its cancellation token.
+const CANCELLATION_POLL: Duration = Duration::from_millis(100);
+
+/// Launches the local `r0vm` server through a shell script recording the PID of the server, so a
+/// cancelled attempt can kill it. The script `exec`s the server, which keeps the PID of the
+/// script. The script and the PID file live in a private temporary directory, removed with the
+/// launcher.
+#[cfg(unix)]
+struct R0vmLauncher {
+    dir: tempfile::TempDir,
+}
+
+#[cfg(unix)]
+impl R0vmLauncher {
+    fn new(r0vm_path: &Path) -> Result<Self, ProofError> {
+        let dir = tempfile::Builder::new().prefix("zkfhe-r0vm-").tempdir()?;
+        let launcher = Self { dir };
+        let quote =
+            |path: &Path| format!("'{}'", path.display().to_string().replace('\'', "'\\''"));
+        // The PID is written before the cancellation is looked for, so a cancellation racing the
+        // launch either finds the PID or is found by the script
+        let mut script = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o700)
+            .open(launcher.script())?;
+        write!(
+            script,
+            "#!/bin/sh\necho $$ > {}\n[ -e {} ] && exit 1\nexec {} \"$@\"\n",
+            quote(&launcher.pid_file()),
+            quote(&launcher.cancelled_file()),
+            quote(r0vm_path)
+        )?;
+        Ok(launcher)
+    }
+
+    fn script(&self) -> PathBuf {
+        self.dir.path().join("r0vm.sh")
+    }
+
+    fn pid_file(&self) -> PathBuf {
+        self.dir.path().join("r0vm.pid")
+    }
+
+    fn cancelled_file(&self) -> PathBuf {
+        self.dir.path().join("cancelled")
+    }
+
+    /// Kill the server the launcher started, or keep the script from starting it.
+    fn cancel(&self) {
+        let _ = fs::write(self.cancelled_file(), b"");
+        if let Ok(pid) = fs::read_to_string(self.pid_file()) {
+            if !pid.trim().is_empty() {
+                tracing::info!("killing the r0vm server {}", pid.trim());
+                let _ = Command::new("kill").arg("-KILL").arg(pid.trim()).status();
+            }
+        }
+    }
+}
+
+/// Without a Unix shell the server is started directly, a cancelled attempt is abandoned like a
+/// remote one.
+#[cfg(not(unix))]
+struct R0vmLauncher {
+    r0vm_path: PathBuf,
+}
+
+#[cfg(not(unix))]
+impl R0vmLauncher {
+    fn new(r0vm_path: &Path) -> Result<Self, ProofError> {
+        Ok(Self {
+            r0vm_path: r0vm_path.to_path_buf(),
+        })
+    }
+
+    fn script(&self) -> PathBuf {
+        self.r0vm_path.clone()
+    }
+
+    fn cancel(&self) {}
+}
+
+fn r0vm_path(settings: &ProverSettings) -> PathBuf {
+    settings.r0vm_path.clone().unwrap_or_else(|| {
+        PathBuf::from(std::env::var("RISC0_SERVER_PATH").unwrap_or_else(|_| "r0vm".to_string()))
+    })
+}
+
+/// Wait for the attempt sending its result on `receiver`, `None` once `timeout` elapsed. A
+/// cancelled `token` is returned as the error, the attempt is left to its caller to stop.
+fn wait_for_attempt(
+    receiver: &Receiver<Result<ProveInfo, String>>,
+    timeout: Option<Duration>,
+    token: &CancellationToken,
+) -> Result<Option<Result<ProveInfo, String>>, ProofError> {
+    let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+    loop {
+        token.check()?;
+        let poll = match deadline {
+            Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                Some(remaining) => remaining.min(CANCELLATION_POLL),
+                None => return Ok(None),
+            },
+            None => CANCELLATION_POLL,
+        };
+        match receiver.recv_timeout(poll) {
+            Ok(result) => return Ok(Some(result)),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                return Ok(Some(Err("the proving thread panicked".to_string())))
+            }
+        }
+    }
+}
+
+/// Prove one local attempt with the local `r0vm` prover, or the fake receipt prover in dev mode,
+/// on its own thread. A cancelled attempt has its server killed. The Bonsai environment variables
+/// are ignored so a fallback never goes remote again.
+fn prove_local_attempt(
+    build_env: &EnvBuilder,
+    elf: &'static [u8],
+    opts: &ProverOpts,
+    settings: &ProverSettings,
+    progress: &Progress,
+    token: &CancellationToken,
+) -> Result<ProveInfo, ProofError> {
+    let launcher = if is_dev_mode() {
+        None
+    } else {
+        Some(Arc::new(R0vmLauncher::new(&r0vm_path(settings))?))
+    };
+    let (sender, receiver) = mpsc::channel();
+    let build_env = build_env.clone();
+    let opts = opts.clone();
+    let progress = progress.clone();
+    let span = tracing::Span::current();
+    // The attempt holds the launcher too, its script outlives a cancellation until the attempt
+    // is over
+    let attempt_launcher = launcher.clone();
+    thread::spawn(move || {
+        let _entered = span.enter();
+        let prover: Rc<dyn Prover> = match &attempt_launcher {
+            Some(launcher) => Rc::new(ExternalProver::new("local", launcher.script())),
+            None => default_prover(),
+        };
+        let result = prove_with(prover.as_ref(), &build_env, elf, &opts, &progress);
+        // The receiver is gone when the attempt was cancelled
+        let _ = sender.send(result);
+    });
+
+    match wait_for_attempt(&receiver, None, token) {
+        Ok(result) => result
+            .expect("a local attempt has no timeout")
+            .map_err(ProofError::proving),
+        Err(cancelled) => {
+            if let Some(launcher) = &launcher {
+                launcher.cancel();
+            }
+            Err(cancelled)
+        }
+    }
+}
+
+fn prove_with(
+    prover: &dyn Prover,
+    build_env: &EnvBuilder,
+    elf: &[u8],
+    opts: &ProverOpts,
+    progress: &Progress,
+) -> Result<ProveInfo, String> {
+    let env = progress.stage(ProofStage::EnvBuild, || build_env())?;
+    progress.stage(ProofStage::Proving, || {
+        prover
+            .prove_with_opts(env, elf, opts)
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Prove one remote attempt, giving up after `timeout`.
+///
+/// The remote prover polls for the receipt on its own thread, a timed out or cancelled attempt is
+/// abandoned and its result ignored.
+fn prove_remote_attempt(
+    build_env: &EnvBuilder,
+    elf: &'static [u8],
+    opts: &ProverOpts,
+    timeout: Duration,
+    progress: &Progress,
+    token: &CancellationToken,
+) -> Result<Result<ProveInfo, String>, ProofError> {
+    let (sender, receiver) = mpsc::channel();
+    let build_env = build_env.clone();
+    let opts = opts.clone();
+    let progress = progress.clone();
+    let span = tracing::Span::current();
+    thread::spawn(move || {
+        let _entered = span.enter();
+        let result = prove_with(&BonsaiProver::new("bonsai"), &build_env, elf, &opts, &progress);
+        // The receiver is gone when the attempt timed out or was cancelled
+        let _ = sender.send(result);
+    });
+
+    Ok(wait_for_attempt(&receiver, Some(timeout), token)?
+        .unwrap_or_else(|| Err(format!("remote proving timed out after {}s", timeout.as_secs()))))
+}
+
+/// Prove `elf` with the selected backend.
+///
+/// Remote proving is retried up to `policy.retries` times after a failure or a timeout, then falls
+/// back to local proving unless disabled. A guest that rejected its inputs is reported as
+/// [`ProofError::GuestRejected`] and not retried. Every attempt and stage is reported to
+/// `progress`.
+pub fn prove(
+    backend: ProverBackend,
+    policy: RemotePolicy,
+    build_env: EnvBuilder,
+    elf: &'static [u8],
+    settings: &ProverSettings,
+    progress: &Progress,
+) -> Result<ProveInfo, ProofError> {
+    let token = CancellationToken::new();
+    prove_with_cancel(backend, policy, build_env, elf, settings, progress, &token)
+}
+
+/// Prove `elf` like [`prove`], until `token` is cancelled or times out.
+///
+/// The token is checked before every attempt and polled while one runs, a cancelled proof returns
+/// [`ProofError::Cancelled`] at once: the `r0vm` server of a local attempt is killed, a remote
+/// attempt is abandoned.
+pub fn prove_with_cancel(
+    backend: ProverBackend,
+    policy: RemotePolicy,
+    build_env: EnvBuilder,
+    elf: &'static [u8],
+    settings: &ProverSettings,
+    progress: &Progress,
+    token: &CancellationToken,
+) -> Result<ProveInfo, ProofError> {
+    let opts = &settings.prover_opts()?;
+    let span = tracing::info_span!("prove", ?backend, shard = progress.shard());
+    let _entered = span.enter();
+    let shard = progress.shard();
+    let prove_info = if backend == ProverBackend::Bonsai {
+        prove_remote(policy, &build_env, elf, opts, progress, token)?
+    } else {
+        None
+    };
+    let prove_info = match prove_info {
+        Some(prove_info) => prove_info,
+        None => {
+            token.check()?;
+            prove_local_attempt(&build_env, elf, opts, settings, progress, token)?
+        }
+    };
+    check_exit_code(&prove_info.receipt)?;
+
+    let stats = &prove_info.stats;
+    tracing::info!(
+        segments = stats.segments,
+        user_cycles = stats.user_cycles,
+        total_cycles = stats.total_cycles,
+        "proved shard {shard}"
+    );
+    progress.report(ProgressEvent::Proved {
+        shard,
+        segments: stats.segments,
+        user_cycles: stats.user_cycles,
+        total_cycles: stats.total_cycles,
+    });
+    Ok(prove_info)
+}
+
+/// Try the remote attempts of `policy`, `None` when they all failed and the proof falls back to
+/// local proving.
+fn prove_remote(
+    policy: RemotePolicy,
+    build_env: &EnvBuilder,
+    elf: &'static [u8],
+    opts: &ProverOpts,
+    progress: &Progress,
+    token: &CancellationToken,
+) -> Result<Option<ProveInfo>, ProofError> {
+    let shard = progress.shard();
+    for attempt in 1..=policy.retries + 1 {
+        token.check()?;
+        match prove_remote_attempt(build_env, elf, opts, policy.timeout, progress, token)? {
+            Ok(prove_info) => return Ok(Some(prove_info)),
+            Err(error) => {
+                tracing::warn!("remote proving attempt {attempt} failed: {error}");
+                progress.report(ProgressEvent::AttemptFailed {
+                    shard,
+                    attempt,
+                    error,
+                });
+            }
+        }
+    }
+    if !policy.fallback_to_local {
+        return Err(ProofError::Proving(
+            "remote proving failed and local fallback is disabled".to_string(),
+        ));
+    }
+    tracing::info!("falling back to local proving");
+    progress.report(ProgressEvent::FallbackToLocal { shard });
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waiting_stops_at_cancellation() {
+        let (_sender, receiver) = mpsc::channel();
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            canceller.cancel();
+        });
+        let waited = wait_for_attempt(&receiver, None, &token);
+        assert!(matches!(waited, Err(ProofError::Cancelled(_))));
+    }
+
+    #[test]
+    fn waiting_times_out() {
+        let (_sender, receiver) = mpsc::channel();
+        let waited = wait_for_attempt(
+            &receiver,
+            Some(Duration::from_millis(10)),
+            &CancellationToken::new(),
+        );
+        assert!(matches!(waited, Ok(None)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cancelled_servers_are_killed() {
+        let launcher = R0vmLauncher::new(Path::new("sleep")).unwrap();
+        let mut server = Command::new(launcher.script()).arg("30").spawn().unwrap();
+        while !fs::read_to_string(launcher.pid_file()).is_ok_and(|pid| pid.ends_with('\n')) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        launcher.cancel();
+        assert!(!server.wait().unwrap().success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn servers_cancelled_before_their_launch_never_start() {
+        let launcher = R0vmLauncher::new(Path::new("true")).unwrap();
+        launcher.cancel();
+        let status = Command::new(launcher.script()).status().unwrap();
+        assert_eq!(status.code(), Some(1));
+    }
+}
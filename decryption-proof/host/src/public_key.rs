@@ -0,0 +1,41 @@
+//! Encryption under an LWE public key, so the party producing the ciphertexts only holds the public
+//! key while the secret key stays with the party decrypting and proving.
+
+use tfhe::core_crypto::prelude::*;
+
+/// Number of encryptions of zero in the public key, `(n + 1) * log2(q) + 128` so the leftover hash
+/// lemma applies to the random subset sums, with `log2(q) <= 64` for `u64` ciphertexts.
+pub fn zero_encryption_count(lwe_dimension: LweDimension) -> LwePublicKeyZeroEncryptionCount {
+    LwePublicKeyZeroEncryptionCount((lwe_dimension.0 + 1) * u64::BITS as usize + 128)
+}
+
+/// Generate the LWE public key of `lwe_sk` for the given ciphertext modulus.
+pub fn generate_public_key<Gen: ByteRandomGenerator>(
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    noise_distribution: DynamicDistribution<u64>,
+    ciphertext_modulus: CiphertextModulus<u64>,
+    generator: &mut EncryptionRandomGenerator<Gen>,
+) -> LwePublicKeyOwned<u64> {
+    allocate_and_generate_new_lwe_public_key(
+        lwe_sk,
+        zero_encryption_count(lwe_sk.lwe_dimension()),
+        noise_distribution,
+        ciphertext_modulus,
+        generator,
+    )
+}
+
+/// Encrypt `plaintext` as a random subset sum of the encryptions of zero of `public_key`.
+pub fn encrypt_with_public_key<Gen: ByteRandomGenerator>(
+    public_key: &LwePublicKeyOwned<u64>,
+    plaintext: Plaintext<u64>,
+    generator: &mut SecretRandomGenerator<Gen>,
+) -> LweCiphertextOwned<u64> {
+    let mut ciphertext = LweCiphertext::new(
+        0u64,
+        public_key.lwe_size(),
+        public_key.ciphertext_modulus(),
+    );
+    encrypt_lwe_ciphertext_with_public_key(public_key, &mut ciphertext, plaintext, generator);
+    ciphertext
+}
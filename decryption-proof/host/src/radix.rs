@@ -0,0 +1,117 @@
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use tfhe::integer::{ClientKey, RadixCiphertext};
+use zkfhe_core::encoding::EncodingParams;
+use zkfhe_core::io::RadixDecryptionInput;
+use zkfhe_core::journal::RadixDecryptionJournal;
+
+use crate::error::ProofError;
+use crate::hashing::{lwe_ciphertext_digest, secret_key_digest};
+use crate::io::{lwe_ciphertext_data, lwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::shortint::{lower_shortint_ciphertext, shortint_encoding};
+use crate::verification::{decode_payload, verify_receipt};
+
+/// Split a radix ciphertext in its little endian LWE blocks, along with the LWE secret key and the
+/// encoding shared by all the blocks.
+pub fn split_radix_ciphertext(
+    client_key: &ClientKey,
+    ciphertext: &RadixCiphertext,
+) -> Result<(LweSecretKeyOwned<u64>, Vec<LweCiphertextOwned<u64>>, EncodingParams), ProofError>
+{
+    let first_block = ciphertext
+        .blocks
+        .first()
+        .ok_or_else(|| ProofError::InvalidInput("radix ciphertext has no blocks".to_string()))?;
+    let encoding = shortint_encoding(first_block);
+    let (lwe_sk, _) = lower_shortint_ciphertext(client_key.as_ref(), first_block);
+
+    let blocks = ciphertext
+        .blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| {
+            if shortint_encoding(block) != encoding || block.pbs_order != first_block.pbs_order {
+                return Err(ProofError::InvalidInput(format!(
+                    "block {index} does not share the encoding of block 0"
+                )));
+            }
+            Ok(block.ct.clone())
+        })
+        .collect::<Result<Vec<_>, ProofError>>()?;
+
+    Ok((lwe_sk, blocks, encoding))
+}
+
+/// Key ID of the LWE secret key of `client_key` every block of `ciphertext` is encrypted under,
+/// the ID the radix guest commits.
+pub fn radix_secret_key_id(
+    client_key: &ClientKey,
+    ciphertext: &RadixCiphertext,
+) -> Result<[u8; 32], ProofError> {
+    let (lwe_sk, _, _) = split_radix_ciphertext(client_key, ciphertext)?;
+    Ok(secret_key_digest(lwe_sk.as_ref()))
+}
+
+/// Prove the decryption of a radix ciphertext, the guest commits the recombined integer.
+pub fn prove_radix_decryption(
+    client_key: &ClientKey,
+    ciphertext: &RadixCiphertext,
+) -> Result<Receipt, ProofError> {
+    let (lwe_sk, blocks, encoding) = split_radix_ciphertext(client_key, ciphertext)?;
+    let env = ExecutorEnv::builder()
+        .write(&RadixDecryptionInput {
+            lwe_sk: lwe_secret_key_data(&lwe_sk),
+            blocks: blocks.iter().map(lwe_ciphertext_data).collect(),
+            encoding,
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::RadixDecrypt.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a radix decryption receipt for `ciphertext` under the secret key of ID `secret_key_id`,
+/// see [`radix_secret_key_id`], and return the committed integer.
+pub fn check_radix_decryption(
+    receipt: &Receipt,
+    ciphertext: &RadixCiphertext,
+    secret_key_id: &[u8; 32],
+) -> Result<u128, ProofError> {
+    verify_receipt(receipt, ProofKind::RadixDecrypt.image_id())?;
+    let journal: RadixDecryptionJournal = decode_payload(receipt, ProofKind::RadixDecrypt)?;
+
+    if journal.block_digests.len() != ciphertext.blocks.len() {
+        return Err(ProofError::Verification(format!(
+            "journal has {} blocks, expected {}",
+            journal.block_digests.len(),
+            ciphertext.blocks.len()
+        )));
+    }
+    if &journal.secret_key_id != secret_key_id {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    for (index, (digest, block)) in journal
+        .block_digests
+        .iter()
+        .zip(ciphertext.blocks.iter())
+        .enumerate()
+    {
+        if digest != &lwe_ciphertext_digest(&block.ct) {
+            return Err(ProofError::Verification(format!(
+                "ciphertext digest mismatch for block {index}"
+            )));
+        }
+        if journal.encoding != shortint_encoding(block) {
+            return Err(ProofError::ParameterMismatch(format!(
+                "encoding mismatch for block {index}"
+            )));
+        }
+    }
+
+    Ok(journal.value)
+}
@@ -0,0 +1,185 @@
+//! Receipt acceptance policies, for CI jobs and gateway services that admit receipts proven
+//! elsewhere.
+//!
+//! A [`ReceiptPolicy`] is a TOML file listing what a receipt must satisfy: the image IDs it may
+//! verify against, the parameters its journal commits (through a [`ParamsPolicy`]), the journal
+//! fields it must set and how old it may be. [`check_receipt`] never stops at the first broken
+//! rule, its [`PolicyReport`] lists every violation next to the [`ProofRecordJson`] of the
+//! receipt, so a caller can log the report as is and act on its `passed` field.
+//!
+//! A receipt commits no proving time, its age is the age of the file it was read from.
+
+use std::path::Path;
+use std::time::SystemTime;
+
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use zkfhe_core::digest::parse_digest;
+use zkfhe_core::params::PbsOrder;
+
+use crate::error::ProofError;
+use crate::format::{read_file, FileFormat};
+use crate::params::ParamsPolicy;
+use crate::record::{journal_guest, proof_record_for, ProofRecordJson};
+
+/// What a receipt must satisfy, every rule is optional.
+///
+/// ```toml
+/// allowed_image_ids = ["<64 hex characters>"]
+/// min_security_bits = 128
+/// pbs_order = "keyswitch_bootstrap"
+/// required_journal_fields = ["noise_budget_bits", "params.security_bits"]
+/// max_age_secs = 86400
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReceiptPolicy {
+    /// Hex encoded image IDs the receipt may verify against, the image ID of the guest of this
+    /// build its journal names when empty
+    #[serde(default)]
+    pub allowed_image_ids: Vec<String>,
+    #[serde(default)]
+    pub min_security_bits: u32,
+    pub pbs_order: Option<PbsOrder>,
+    /// Fields of the journal that must be set, nested ones as a dotted path
+    #[serde(default)]
+    pub required_journal_fields: Vec<String>,
+    pub max_age_secs: Option<u64>,
+}
+
+impl ReceiptPolicy {
+    pub fn from_file(path: &Path) -> Result<Self, ProofError> {
+        toml::from_str(&std::fs::read_to_string(path)?).map_err(|error| {
+            ProofError::InvalidInput(format!("{} is not a valid policy: {error}", path.display()))
+        })
+    }
+
+    fn allowed_image_ids(&self) -> Result<Vec<Digest>, ProofError> {
+        self.allowed_image_ids
+            .iter()
+            .map(|image_id| {
+                parse_digest(image_id).map(Digest::from).map_err(|error| {
+                    ProofError::InvalidInput(format!("invalid image ID {image_id}: {error}"))
+                })
+            })
+            .collect()
+    }
+}
+
+/// The rule of a [`ReceiptPolicy`] a receipt broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyRule {
+    /// The receipt does not verify against any allowed image ID
+    ImageId,
+    /// The parameters of the journal break the `min_security_bits` or `pbs_order` rule, or the
+    /// journal commits no parameters
+    Params,
+    RequiredJournalField,
+    MaxAge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PolicyViolation {
+    pub rule: PolicyRule,
+    pub message: String,
+}
+
+/// The outcome of [`check_receipt`], written as JSON.
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyReport {
+    pub receipt: String,
+    pub passed: bool,
+    pub violations: Vec<PolicyViolation>,
+    /// The record of the receipt, `None` when it verifies against no allowed image ID
+    pub record: Option<ProofRecordJson>,
+    pub age_secs: u64,
+}
+
+/// Check the receipt of `path`, in `format`, against `policy`. An unreadable receipt or policy is
+/// an error, a receipt breaking the policy is reported with `passed` false.
+pub fn check_receipt(
+    path: &Path,
+    format: FileFormat,
+    policy: &ReceiptPolicy,
+) -> Result<PolicyReport, ProofError> {
+    let receipt: Receipt = read_file(path, format)?;
+    let age_secs = SystemTime::now()
+        .duration_since(std::fs::metadata(path)?.modified()?)
+        .unwrap_or_default()
+        .as_secs();
+    let mut violations = Vec::new();
+    if let Some(max_age_secs) = policy.max_age_secs.filter(|&max| age_secs > max) {
+        violations.push(PolicyViolation {
+            rule: PolicyRule::MaxAge,
+            message: format!("the receipt is {age_secs} s old, the policy allows {max_age_secs} s"),
+        });
+    }
+
+    let (_, kind) = journal_guest(&receipt)?;
+    let image_ids = match policy.allowed_image_ids()? {
+        image_ids if image_ids.is_empty() => vec![kind.image_id()],
+        image_ids => image_ids,
+    };
+    let record = image_ids
+        .into_iter()
+        .find_map(|image_id| proof_record_for(&receipt, image_id, None).ok());
+    match &record {
+        Some(record) => check_journal(record, policy, &mut violations)?,
+        None => violations.push(PolicyViolation {
+            rule: PolicyRule::ImageId,
+            message: format!(
+                "the {} receipt verifies against no allowed image ID",
+                kind.guest_name()
+            ),
+        }),
+    }
+
+    Ok(PolicyReport {
+        receipt: path.display().to_string(),
+        passed: violations.is_empty(),
+        violations,
+        record,
+        age_secs,
+    })
+}
+
+fn check_journal(
+    record: &ProofRecordJson,
+    policy: &ReceiptPolicy,
+    violations: &mut Vec<PolicyViolation>,
+) -> Result<(), ProofError> {
+    let params_policy = ParamsPolicy {
+        pbs_order: policy.pbs_order,
+        min_security_bits: policy.min_security_bits,
+    };
+    if params_policy != ParamsPolicy::default() {
+        let checked = match record.journal.params_summary() {
+            Some(summary) => params_policy.check(&summary).map_err(|error| error.to_string()),
+            None => Err(format!("a {} journal commits no parameters", record.guest)),
+        };
+        if let Err(message) = checked {
+            violations.push(PolicyViolation {
+                rule: PolicyRule::Params,
+                message,
+            });
+        }
+    }
+
+    // Through the text encoding, which holds the 128 bits values `to_value` refuses
+    let journal: serde_json::Value = serde_json::to_string(&record.journal)
+        .and_then(|journal| serde_json::from_str(&journal))
+        .map_err(ProofError::serialization)?;
+    for field in &policy.required_journal_fields {
+        let pointer = format!("/{}", field.replace('.', "/"));
+        match journal.pointer(&pointer) {
+            Some(value) if !value.is_null() => {}
+            _ => violations.push(PolicyViolation {
+                rule: PolicyRule::RequiredJournalField,
+                message: format!("the {} journal does not set {field}", record.guest),
+            }),
+        }
+    }
+    Ok(())
+}
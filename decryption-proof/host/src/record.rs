@@ -0,0 +1,191 @@
+//! Proof records: the metadata of a receipt as JSON, so operators can index proofs in databases
+//! and dashboards without decoding binary receipts.
+//!
+//! A record names the guest a receipt was proven with and the image ID it verifies against, holds
+//! the fields of its journal and, for a receipt this host just proved, the proving time and cycle
+//! counts of its session. The digests of the record itself are hex encoded, those inside the
+//! journal fields are byte arrays, as serde writes them.
+
+use std::time::Duration;
+
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::{InnerReceipt, Receipt, SessionStats};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use zkfhe_core::journal::{
+    AggregationJournal, BatchJournal, ChainedDecryptionJournal, CompactListDecryptionJournal,
+    ConsistencyJournal, DecryptionJournal, DkgJournal, GlweDecryptionJournal, JournalHeader,
+    JournalKind, KeyGenerationJournal, KeyRotationJournal, KeyswitchJournal, ModulusSwitchJournal,
+    ParamsSummary, PartialDecryptionJournal, PolicyDecryptionJournal, RadixDecryptionJournal,
+    ReencryptionJournal, SampleExtractJournal, ShortintDecryptionJournal,
+};
+
+use crate::error::ProofError;
+use crate::hashing::sha256_bytes;
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_envelope, verify_receipt};
+
+/// The payload of a journal, serialized without a tag: the record names its kind next to it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum JournalFields {
+    Decryption(DecryptionJournal),
+    DecryptionU128(DecryptionJournal<u128>),
+    Batch(BatchJournal),
+    PartialDecryption(PartialDecryptionJournal),
+    GlweDecryption(GlweDecryptionJournal),
+    ShortintDecryption(ShortintDecryptionJournal),
+    RadixDecryption(RadixDecryptionJournal),
+    ModulusSwitch(ModulusSwitchJournal),
+    PolicyDecryption(PolicyDecryptionJournal),
+    Reencryption(ReencryptionJournal),
+    Keyswitch(KeyswitchJournal),
+    Aggregation(AggregationJournal),
+    KeyGeneration(KeyGenerationJournal),
+    SampleExtract(SampleExtractJournal),
+    CompactListDecryption(CompactListDecryptionJournal),
+    ChainedDecryption(ChainedDecryptionJournal),
+    Consistency(ConsistencyJournal),
+    KeyRotation(KeyRotationJournal),
+    Dkg(DkgJournal),
+}
+
+/// The proving session of a receipt, only known to the host that proved it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ProvingRecord {
+    /// Wall time of the proving, for a sharded batch the time of all its shards proven
+    /// concurrently
+    pub proving_time_ms: u64,
+    /// Cycles spent in the guest itself
+    pub user_cycles: u64,
+    /// Cycles of the padded segments, what proving costs
+    pub total_cycles: u64,
+}
+
+/// The metadata of a verified receipt, ready to be written as JSON.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofRecordJson {
+    pub guest: String,
+    pub image_id: String,
+    pub journal_version: u16,
+    pub journal_kind: JournalKind,
+    pub journal: JournalFields,
+    pub journal_sha256: String,
+    /// `composite`, `succinct`, `groth16` or `fake`
+    pub receipt_kind: String,
+    /// Segments the guest execution spanned, unknown for a compressed receipt read from a file
+    pub segment_count: Option<usize>,
+    pub proving: Option<ProvingRecord>,
+    /// Version of the risc0-zkvm crate of this host
+    pub prover_version: String,
+}
+
+fn journal_fields<Payload: DeserializeOwned>(
+    receipt: &Receipt,
+    fields: fn(Payload) -> JournalFields,
+) -> Result<JournalFields, ProofError> {
+    Ok(fields(decode_envelope(&receipt.journal)?.payload))
+}
+
+impl JournalFields {
+    /// The parameters a verifier policy is enforced on, for the journals that commit them.
+    pub fn params_summary(&self) -> Option<ParamsSummary> {
+        match self {
+            Self::Decryption(journal) => Some(journal.params),
+            Self::DecryptionU128(journal) => Some(journal.params),
+            Self::Batch(journal) => Some(ParamsSummary::from(&journal.params)),
+            _ => None,
+        }
+    }
+}
+
+/// The header of the journal of `receipt` and the guest it names, nothing is verified yet.
+pub fn journal_guest(receipt: &Receipt) -> Result<(JournalHeader, ProofKind), ProofError> {
+    let header: JournalHeader = receipt.journal.decode().map_err(ProofError::serialization)?;
+    let kind = ProofKind::ALL
+        .into_iter()
+        .find(|kind| kind.journal_kind() == header.kind)
+        .ok_or_else(|| {
+            ProofError::Verification(format!("a {:?} journal names no guest", header.kind))
+        })?;
+    Ok((header, kind))
+}
+
+/// The record of `receipt`, which is first verified against the image ID of the guest its journal
+/// names. `proving` is the session and wall time of the proving, when this host proved it.
+pub fn proof_record(
+    receipt: &Receipt,
+    proving: Option<(&SessionStats, Duration)>,
+) -> Result<ProofRecordJson, ProofError> {
+    let (_, kind) = journal_guest(receipt)?;
+    proof_record_for(receipt, kind.image_id(), proving)
+}
+
+/// The record of `receipt` verified against `image_id`, e.g. the image ID of the guest in an older
+/// release, instead of the image ID of the guest in this build.
+pub fn proof_record_for(
+    receipt: &Receipt,
+    image_id: Digest,
+    proving: Option<(&SessionStats, Duration)>,
+) -> Result<ProofRecordJson, ProofError> {
+    let (header, kind) = journal_guest(receipt)?;
+    verify_receipt(receipt, image_id)?;
+
+    let journal = match header.kind {
+        JournalKind::Decrypt => journal_fields(receipt, JournalFields::Decryption)?,
+        JournalKind::DecryptU128 => journal_fields(receipt, JournalFields::DecryptionU128)?,
+        JournalKind::PbsDecrypt | JournalKind::BatchDecrypt => {
+            journal_fields(receipt, JournalFields::Batch)?
+        }
+        JournalKind::PartialDecrypt => journal_fields(receipt, JournalFields::PartialDecryption)?,
+        JournalKind::GlweDecrypt => journal_fields(receipt, JournalFields::GlweDecryption)?,
+        JournalKind::ShortintDecrypt => journal_fields(receipt, JournalFields::ShortintDecryption)?,
+        JournalKind::RadixDecrypt => journal_fields(receipt, JournalFields::RadixDecryption)?,
+        JournalKind::ModSwitchDecrypt => journal_fields(receipt, JournalFields::ModulusSwitch)?,
+        JournalKind::PolicyDecrypt => journal_fields(receipt, JournalFields::PolicyDecryption)?,
+        JournalKind::Reencrypt => journal_fields(receipt, JournalFields::Reencryption)?,
+        JournalKind::Keyswitch => journal_fields(receipt, JournalFields::Keyswitch)?,
+        JournalKind::Aggregate => journal_fields(receipt, JournalFields::Aggregation)?,
+        JournalKind::KeyGeneration => journal_fields(receipt, JournalFields::KeyGeneration)?,
+        JournalKind::SampleExtract => journal_fields(receipt, JournalFields::SampleExtract)?,
+        JournalKind::CompactListDecrypt => {
+            journal_fields(receipt, JournalFields::CompactListDecryption)?
+        }
+        JournalKind::ChainedDecrypt => journal_fields(receipt, JournalFields::ChainedDecryption)?,
+        JournalKind::Consistency => journal_fields(receipt, JournalFields::Consistency)?,
+        JournalKind::KeyRotation => journal_fields(receipt, JournalFields::KeyRotation)?,
+        JournalKind::Dkg => journal_fields(receipt, JournalFields::Dkg)?,
+        // A rejection halts the guest with a non-zero exit code, its receipt does not verify
+        JournalKind::Rejected => unreachable!("rejected journals name no guest"),
+    };
+
+    let segment_count = match (&proving, &receipt.inner) {
+        (Some((stats, _)), _) => Some(stats.segments),
+        (None, InnerReceipt::Composite(composite)) => Some(composite.segments.len()),
+        (None, _) => None,
+    };
+    let receipt_kind = match &receipt.inner {
+        InnerReceipt::Composite(_) => "composite",
+        InnerReceipt::Succinct(_) => "succinct",
+        InnerReceipt::Groth16(_) => "groth16",
+        InnerReceipt::Fake(_) => "fake",
+        _ => "unknown",
+    };
+
+    Ok(ProofRecordJson {
+        guest: kind.guest_name().to_string(),
+        image_id: hex::encode(image_id.as_bytes()),
+        journal_version: header.version,
+        journal_kind: header.kind,
+        journal,
+        journal_sha256: hex::encode(sha256_bytes(&receipt.journal.bytes)),
+        receipt_kind: receipt_kind.to_string(),
+        segment_count,
+        proving: proving.map(|(stats, proving_time)| ProvingRecord {
+            proving_time_ms: proving_time.as_millis() as u64,
+            user_cycles: stats.user_cycles,
+            total_cycles: stats.total_cycles,
+        }),
+        prover_version: risc0_zkvm::VERSION.to_string(),
+    })
+}
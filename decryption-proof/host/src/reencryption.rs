@@ -0,0 +1,123 @@
+//! Re-encryption proofs: a proxy holding the secret key decrypts a ciphertext and encrypts its
+//! message again under a recipient's LWE public key, and proves the new ciphertext encrypts the
+//! same message without revealing it.
+//!
+//! The encryption under the public key is a subset sum of its encryptions of zero. The host draws
+//! the subset, computes the new ciphertext and hands the subset to the guest, which recomputes the
+//! ciphertext and commits its digest. The host checks that digest against its own ciphertext,
+//! which is then sent to the recipient.
+
+use rand::Rng;
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::ReencryptionInput;
+use zkfhe_core::journal::ReencryptionJournal;
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::hashing::{lwe_ciphertext_digest, lwe_public_key_digest, params_digest};
+use crate::io::{lwe_ciphertext_data, lwe_public_key_data, lwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// A uniformly random subset of the encryptions of zero of `public_key`, one bit per encryption
+/// in `u64` words as the guest reads it.
+pub fn random_selection<R: Rng>(public_key: &LwePublicKeyOwned<u64>, rng: &mut R) -> Vec<u64> {
+    let count = public_key.zero_encryption_count().0;
+    let mut selection: Vec<u64> = (0..count.div_ceil(64)).map(|_| rng.gen()).collect();
+    // Bits past the last encryption of zero are cleared, they select nothing
+    if !count.is_multiple_of(64) {
+        if let Some(last) = selection.last_mut() {
+            *last &= (1 << (count % 64)) - 1;
+        }
+    }
+    selection
+}
+
+/// Encrypt `plaintext` under `public_key` with the subset of encryptions of zero `selection`,
+/// the computation the guest repeats.
+pub fn encrypt_with_selection(
+    public_key: &LwePublicKeyOwned<u64>,
+    plaintext: Plaintext<u64>,
+    selection: &[u64],
+) -> LweCiphertextOwned<u64> {
+    let mut ciphertext =
+        LweCiphertext::new(0u64, public_key.lwe_size(), public_key.ciphertext_modulus());
+    for (index, zero_encryption) in public_key.iter().enumerate() {
+        if (selection[index / 64] >> (index % 64)) & 1 == 1 {
+            lwe_ciphertext_add_assign(&mut ciphertext, &zero_encryption);
+        }
+    }
+    lwe_ciphertext_plaintext_add_assign(&mut ciphertext, plaintext);
+    ciphertext
+}
+
+/// Prove the re-encryption of `ciphertext`, whose decoded message is `message`, under
+/// `recipient_public_key` and return the receipt with the new ciphertext.
+pub fn prove_reencryption(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    ciphertext: &LweCiphertextOwned<u64>,
+    message: u64,
+    recipient_public_key: &LwePublicKeyOwned<u64>,
+    selection: &[u64],
+) -> Result<(Receipt, LweCiphertextOwned<u64>), ProofError> {
+    let encoded = params
+        .encoding
+        .encode_for_modulus(message, params.ciphertext_modulus);
+    let reencrypted = encrypt_with_selection(recipient_public_key, Plaintext(encoded), selection);
+
+    let env = ExecutorEnv::builder()
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&ReencryptionInput {
+            lwe_sk: lwe_secret_key_data(lwe_sk),
+            ciphertext: lwe_ciphertext_data(ciphertext),
+            recipient_public_key: lwe_public_key_data(recipient_public_key),
+            selection: selection.to_vec(),
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::Reencrypt.elf())
+        .map_err(ProofError::proving)?;
+    Ok((prove_info.receipt, reencrypted))
+}
+
+/// Verify a re-encryption receipt: `reencrypted` encrypts under `recipient_public_key` the message
+/// of `ciphertext` under the secret key of ID `secret_key_id`, under `expected_params`.
+pub fn check_reencryption(
+    receipt: &Receipt,
+    ciphertext: &LweCiphertextOwned<u64>,
+    secret_key_id: &[u8; 32],
+    reencrypted: &LweCiphertextOwned<u64>,
+    recipient_public_key: &LwePublicKeyOwned<u64>,
+    expected_params: &ProofParams,
+) -> Result<(), ProofError> {
+    verify_receipt(receipt, ProofKind::Reencrypt.image_id())?;
+    let journal: ReencryptionJournal = decode_payload(receipt, ProofKind::Reencrypt)?;
+
+    if journal.ct_digest != lwe_ciphertext_digest(ciphertext) {
+        return Err(ProofError::Verification("ciphertext digest mismatch".to_string()));
+    }
+    if &journal.secret_key_id != secret_key_id {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    if journal.reencrypted_ct_digest != lwe_ciphertext_digest(reencrypted) {
+        return Err(ProofError::Verification(
+            "re-encrypted ciphertext digest mismatch".to_string(),
+        ));
+    }
+    if journal.recipient_pk_digest != lwe_public_key_digest(recipient_public_key) {
+        return Err(ProofError::Verification(
+            "recipient public key digest mismatch".to_string(),
+        ));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+//! Ciphertexts and client keys in the safe serialization format of tfhe-rs, as standard tfhe-rs
+//! applications export them with `tfhe::safe_serialization`.
+//!
+//! The format tags an entity with its type name and the version of its layout, so an entity of
+//! another type is rejected and one written by an older tfhe-rs is upgraded. Inputs are bounded:
+//! a file over the size limit is rejected before it is read, and the deserializer refuses to
+//! allocate past the limit, so a truncated or forged length prefix fails early instead of
+//! exhausting memory. The ciphertexts must conform to the parameters of the key they are proven
+//! under: their dimension, modulus, PBS order, degree and noise level.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use tfhe::conformance::ParameterSetConformant;
+use tfhe::integer::parameters::RadixCiphertextConformanceParams;
+use tfhe::integer::RadixCiphertext;
+use tfhe::safe_serialization::DeserializationConfig;
+use tfhe::shortint::parameters::CiphertextConformanceParams;
+use tfhe::shortint::Ciphertext;
+
+use crate::error::ProofError;
+
+/// Default bound on the size of an input, in bytes, enough for the client keys of every preset.
+pub const DEFAULT_SIZE_LIMIT: u64 = 1 << 30;
+
+fn read_bounded(path: &Path, limit: u64) -> Result<Vec<u8>, ProofError> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len > limit {
+        return Err(ProofError::InvalidInput(format!(
+            "{} holds {len} bytes, over the limit of {limit} bytes",
+            path.display()
+        )));
+    }
+    let mut bytes = Vec::with_capacity(len as usize);
+    file.take(limit).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Read the file at `path` and deserialize it with `deserialize`, a safe deserialization bounded
+/// by `limit`.
+fn read_safe<T>(
+    path: &Path,
+    limit: u64,
+    deserialize: impl FnOnce(DeserializationConfig, &[u8]) -> Result<T, String>,
+) -> Result<T, ProofError> {
+    let bytes = read_bounded(path, limit)?;
+    deserialize(DeserializationConfig::new(limit), &bytes).map_err(|error| {
+        ProofError::Serialization(format!("{} is not a valid input: {error}", path.display()))
+    })
+}
+
+/// Read a shortint client key. Client keys define no conformance parameters in tfhe-rs, the key
+/// is trusted as the prover's own and the ciphertexts are held to its parameters.
+pub fn read_shortint_client_key(
+    path: &Path,
+    limit: u64,
+) -> Result<tfhe::shortint::ClientKey, ProofError> {
+    read_safe(path, limit, |config, bytes| {
+        config.disable_conformance().deserialize_from(bytes)
+    })
+}
+
+/// Read an integer client key, see [`read_shortint_client_key`].
+pub fn read_integer_client_key(
+    path: &Path,
+    limit: u64,
+) -> Result<tfhe::integer::ClientKey, ProofError> {
+    read_safe(path, limit, |config, bytes| {
+        config.disable_conformance().deserialize_from(bytes)
+    })
+}
+
+/// The conformance parameters of the ciphertexts encrypted under `client_key`.
+fn conformance_params(
+    client_key: &tfhe::shortint::ClientKey,
+) -> Result<CiphertextConformanceParams, ProofError> {
+    client_key
+        .parameters
+        .pbs_parameters()
+        .map(|parameters| parameters.to_shortint_conformance_param())
+        .ok_or_else(|| {
+            ProofError::InvalidInput("the client key has no bootstrapping parameters".to_string())
+        })
+}
+
+/// Read a shortint ciphertext conformant with the parameters of `client_key`.
+pub fn read_shortint_ciphertext(
+    path: &Path,
+    limit: u64,
+    client_key: &tfhe::shortint::ClientKey,
+) -> Result<Ciphertext, ProofError> {
+    let conformance = conformance_params(client_key)?;
+    read_safe(path, limit, |config, bytes| {
+        config.deserialize_from(bytes, &conformance)
+    })
+}
+
+/// Read a radix ciphertext whose every block is conformant with the parameters of `client_key`.
+pub fn read_radix_ciphertext(
+    path: &Path,
+    limit: u64,
+    client_key: &tfhe::integer::ClientKey,
+) -> Result<RadixCiphertext, ProofError> {
+    let shortint_params = conformance_params(client_key.as_ref())?;
+    // The conformance parameters of a radix ciphertext include its block count, which is only
+    // known once the ciphertext is read
+    let ciphertext: RadixCiphertext = read_safe(path, limit, |config, bytes| {
+        config.disable_conformance().deserialize_from(bytes)
+    })?;
+    let conformance = RadixCiphertextConformanceParams {
+        shortint_params,
+        num_blocks_per_integer: ciphertext.blocks.len(),
+    };
+    if !ciphertext.is_conformant(&conformance) {
+        return Err(ProofError::Serialization(format!(
+            "{} is not a valid input: a block does not conform to the parameters of the client \
+             key",
+            path.display()
+        )));
+    }
+    Ok(ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tfhe::safe_serialization::safe_serialize;
+    use tfhe::shortint::parameters::{
+        PARAM_MESSAGE_1_CARRY_1_KS_PBS, PARAM_MESSAGE_2_CARRY_2_KS_PBS,
+    };
+
+    fn serialized<T: serde::Serialize + tfhe::Versionize + tfhe::named::Named>(
+        object: &T,
+    ) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        safe_serialize(object, &mut bytes, DEFAULT_SIZE_LIMIT).unwrap();
+        bytes
+    }
+
+    fn written(dir: &tempfile::TempDir, name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn conformant_ciphertexts_are_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let client_key = tfhe::shortint::ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let key_path = written(&dir, "key", &serialized(&client_key));
+        let ciphertext = client_key.encrypt(3);
+        let path = written(&dir, "ct", &serialized(&ciphertext));
+
+        let client_key = read_shortint_client_key(&key_path, DEFAULT_SIZE_LIMIT).unwrap();
+        let read = read_shortint_ciphertext(&path, DEFAULT_SIZE_LIMIT, &client_key).unwrap();
+        assert_eq!(client_key.decrypt(&read), 3);
+    }
+
+    #[test]
+    fn inputs_over_the_limit_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let client_key = tfhe::shortint::ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let bytes = serialized(&client_key.encrypt(1));
+        let path = written(&dir, "ct", &bytes);
+        let limit = bytes.len() as u64 - 1;
+        assert!(matches!(
+            read_shortint_ciphertext(&path, limit, &client_key),
+            Err(ProofError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn truncated_inputs_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let client_key = tfhe::shortint::ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let bytes = serialized(&client_key.encrypt(1));
+        let path = written(&dir, "ct", &bytes[..bytes.len() / 2]);
+        assert!(matches!(
+            read_shortint_ciphertext(&path, DEFAULT_SIZE_LIMIT, &client_key),
+            Err(ProofError::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn entities_of_another_type_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let client_key = tfhe::shortint::ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let path = written(&dir, "key", &serialized(&client_key));
+        assert!(matches!(
+            read_shortint_ciphertext(&path, DEFAULT_SIZE_LIMIT, &client_key),
+            Err(ProofError::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn ciphertexts_of_other_parameters_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let client_key = tfhe::shortint::ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let other_key = tfhe::shortint::ClientKey::new(PARAM_MESSAGE_1_CARRY_1_KS_PBS);
+        let path = written(&dir, "ct", &serialized(&other_key.encrypt(1)));
+        assert!(matches!(
+            read_shortint_ciphertext(&path, DEFAULT_SIZE_LIMIT, &client_key),
+            Err(ProofError::Serialization(_))
+        ));
+
+        let integer_key = tfhe::integer::ClientKey::new(PARAM_MESSAGE_2_CARRY_2_KS_PBS);
+        let other_integer_key = tfhe::integer::ClientKey::new(PARAM_MESSAGE_1_CARRY_1_KS_PBS);
+        let path = written(&dir, "radix", &serialized(&other_integer_key.encrypt_radix(1u64, 4)));
+        assert!(matches!(
+            read_radix_ciphertext(&path, DEFAULT_SIZE_LIMIT, &integer_key),
+            Err(ProofError::Serialization(_))
+        ));
+    }
+}
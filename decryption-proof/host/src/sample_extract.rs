@@ -0,0 +1,70 @@
+//! Sample extract proofs: an LWE ciphertext is a coefficient of a GLWE accumulator, e.g. the PBS
+//! output extracted from the blind rotated accumulator. No secret key is read, the proof binds the
+//! last step of the PBS on its own, the blind rotation being proven by the PBS guest.
+
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::SampleExtractInput;
+use zkfhe_core::journal::SampleExtractJournal;
+use zkfhe_core::params::ProofParams;
+
+use crate::error::ProofError;
+use crate::hashing::{glwe_ciphertext_digest, lwe_ciphertext_digest, params_digest};
+use crate::io::{glwe_ciphertext_data, lwe_ciphertext_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// Prove that `extracted` is coefficient `monomial_degree` of `accumulator`, the guest recomputes
+/// it.
+pub fn prove_sample_extract(
+    params: &ProofParams,
+    accumulator: &GlweCiphertextOwned<u64>,
+    extracted: &LweCiphertextOwned<u64>,
+    monomial_degree: MonomialDegree,
+) -> Result<Receipt, ProofError> {
+    let monomial_degree = u32::try_from(monomial_degree.0).map_err(|_| {
+        ProofError::InvalidInput(format!("monomial degree {} too large", monomial_degree.0))
+    })?;
+    let env = ExecutorEnv::builder()
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&SampleExtractInput {
+            accumulator: glwe_ciphertext_data(accumulator),
+            extracted: lwe_ciphertext_data(extracted),
+            monomial_degree,
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::SampleExtract.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a sample extract receipt for `accumulator` and `extracted` under `expected_params` and
+/// return the extracted coefficient.
+pub fn check_sample_extract(
+    receipt: &Receipt,
+    accumulator: &GlweCiphertextOwned<u64>,
+    extracted: &LweCiphertextOwned<u64>,
+    expected_params: &ProofParams,
+) -> Result<MonomialDegree, ProofError> {
+    verify_receipt(receipt, ProofKind::SampleExtract.image_id())?;
+    let journal: SampleExtractJournal = decode_payload(receipt, ProofKind::SampleExtract)?;
+
+    if journal.accumulator_digest != glwe_ciphertext_digest(accumulator) {
+        return Err(ProofError::Verification("accumulator digest mismatch".to_string()));
+    }
+    if journal.extracted_ct_digest != lwe_ciphertext_digest(extracted) {
+        return Err(ProofError::Verification(
+            "extracted ciphertext digest mismatch".to_string(),
+        ));
+    }
+    if journal.params_digest != params_digest(expected_params) {
+        return Err(ProofError::ParameterMismatch("parameters digest mismatch".to_string()));
+    }
+
+    Ok(MonomialDegree(journal.monomial_degree as usize))
+}
@@ -0,0 +1,83 @@
+//! Deterministic randomness derived from a user supplied 256 bits seed, so a run can be reproduced
+//! exactly for tests and audits.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tfhe::core_crypto::commons::math::random::{Seed, Seeder};
+
+use crate::hashing::sha256_bytes;
+
+/// Parse a 256 bits seed given as 64 hex characters.
+pub fn parse_seed(hex_seed: &str) -> Result<[u8; 32], String> {
+    let bytes = hex::decode(hex_seed.trim_start_matches("0x")).map_err(|e| e.to_string())?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| format!("the seed is {} bytes long, expected 32", bytes.len()))
+}
+
+/// SHA-256 of `seed` under a domain separation label, the label keeps the randomness of every
+/// consumer and the published commitment independent.
+fn derive(seed: &[u8; 32], label: &[u8], counter: u64) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(label.len() + 40);
+    bytes.extend_from_slice(label);
+    bytes.extend_from_slice(seed);
+    bytes.extend_from_slice(&counter.to_le_bytes());
+    sha256_bytes(&bytes)
+}
+
+/// Commitment to the seed recorded in the journal, it does not reveal the seed but lets an auditor
+/// holding it check which seed a proof was generated from.
+pub fn seed_commitment(seed: &[u8; 32]) -> [u8; 32] {
+    derive(seed, b"zkfhe-seed-commitment", 0)
+}
+
+/// A tfhe seeder handing out a fresh 128 bits seed derived from the user seed on every call, in
+/// place of the hardware entropy seeder.
+pub struct DeterministicSeeder {
+    seed: [u8; 32],
+    counter: u64,
+}
+
+impl DeterministicSeeder {
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self { seed, counter: 0 }
+    }
+}
+
+impl Seeder for DeterministicSeeder {
+    fn seed(&mut self) -> Seed {
+        let digest = derive(&self.seed, b"zkfhe-seed-tfhe", self.counter);
+        self.counter += 1;
+        let mut seed = [0u8; 16];
+        seed.copy_from_slice(&digest[..16]);
+        Seed(u128::from_le_bytes(seed))
+    }
+
+    fn is_available() -> bool {
+        true
+    }
+}
+
+/// The RNG drawing the secret key shares and flooding seeds of the threshold mode.
+pub fn threshold_rng(seed: Option<&[u8; 32]>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::from_seed(derive(seed, b"zkfhe-seed-threshold", 0)),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// The RNG drawing the subset sum selections of the re-encryption mode.
+pub fn reencryption_rng(seed: Option<&[u8; 32]>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::from_seed(derive(seed, b"zkfhe-seed-reencryption", 0)),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// The RNG drawing the salts of the plaintext commitments of the key rotation mode.
+pub fn rotation_rng(seed: Option<&[u8; 32]>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::from_seed(derive(seed, b"zkfhe-seed-rotation", 0)),
+        None => StdRng::from_entropy(),
+    }
+}
@@ -0,0 +1,66 @@
+//! Seeded (compressed) ciphertexts and keys as proof inputs.
+//!
+//! A seeded entity only holds the bodies and the seed of the CSPRNG its masks were drawn from, a
+//! fraction of the size of the full entity: a keyswitching or bootstrapping key shrinks by its
+//! output LWE or GLWE dimension. Clients hand the seeded form to the prover, which decompresses it
+//! on the host before building the guest environment.
+//!
+//! The guests cannot decompress themselves: the CSPRNG of tfhe-rs is not built for the zkVM, and
+//! expanding a key's masks with a software AES would cost far more cycles than the decryption.
+//! Decompression is deterministic, so the digests the journals commit are those of the
+//! decompressed entities, which anyone holding the seeded form recomputes with the same
+//! functions.
+
+use risc0_zkvm::Receipt;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::params::ProofParams;
+
+use crate::decrypt::{check_decryption, prove_decryption};
+use crate::error::ProofError;
+
+/// Size in bytes of a container of `len` 64 bits values.
+pub fn container_size(len: usize) -> usize {
+    len * std::mem::size_of::<u64>()
+}
+
+pub fn decompress_lwe_ciphertext(seeded: &SeededLweCiphertext<u64>) -> LweCiphertextOwned<u64> {
+    seeded.clone().decompress_into_lwe_ciphertext()
+}
+
+pub fn decompress_lwe_keyswitch_key(
+    seeded: SeededLweKeyswitchKeyOwned<u64>,
+) -> LweKeyswitchKeyOwned<u64> {
+    seeded.par_decompress_into_lwe_keyswitch_key()
+}
+
+pub fn decompress_lwe_bootstrap_key(
+    seeded: SeededLweBootstrapKeyOwned<u64>,
+) -> LweBootstrapKeyOwned<u64> {
+    seeded.par_decompress_into_lwe_bootstrap_key()
+}
+
+/// Prove the decryption of a seeded LWE ciphertext with the single decryption guest, the journal
+/// commits the digest of the decompressed ciphertext.
+pub fn prove_seeded_decryption(
+    params: &ProofParams,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    seeded: &SeededLweCiphertext<u64>,
+) -> Result<Receipt, ProofError> {
+    prove_decryption(params, lwe_sk, &decompress_lwe_ciphertext(seeded), None)
+}
+
+/// Verify a single decryption receipt for a seeded ciphertext under the secret key of ID
+/// `secret_key_id` and `expected_params` and return the decoded value.
+pub fn check_seeded_decryption(
+    receipt: &Receipt,
+    seeded: &SeededLweCiphertext<u64>,
+    secret_key_id: &[u8; 32],
+    expected_params: &ProofParams,
+) -> Result<u64, ProofError> {
+    check_decryption(
+        receipt,
+        &decompress_lwe_ciphertext(seeded),
+        secret_key_id,
+        expected_params,
+    )
+}
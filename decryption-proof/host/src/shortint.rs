@@ -0,0 +1,92 @@
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use tfhe::core_crypto::prelude::*;
+use tfhe::shortint::{Ciphertext, ClientKey, PBSOrder};
+use zkfhe_core::encoding::EncodingParams;
+use zkfhe_core::io::ShortintDecryptionInput;
+use zkfhe_core::journal::ShortintDecryptionJournal;
+
+use crate::error::ProofError;
+use crate::hashing::{lwe_ciphertext_digest, secret_key_digest};
+use crate::io::{lwe_ciphertext_data, lwe_secret_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// Message/carry encoding of a shortint ciphertext, shortint always keeps one bit of padding.
+pub fn shortint_encoding(ciphertext: &Ciphertext) -> EncodingParams {
+    EncodingParams {
+        message_bits: ciphertext.message_modulus.0.ilog2(),
+        carry_bits: ciphertext.carry_modulus.0.ilog2(),
+        padding_bits: 1,
+    }
+}
+
+/// Lower a shortint ciphertext to the core_crypto LWE ciphertext and the LWE secret key it is
+/// encrypted under, which depends on the PBS order of the ciphertext.
+pub fn lower_shortint_ciphertext(
+    client_key: &ClientKey,
+    ciphertext: &Ciphertext,
+) -> (LweSecretKeyOwned<u64>, LweCiphertextOwned<u64>) {
+    let (glwe_sk, small_lwe_sk, _parameters) = client_key.clone().into_raw_parts();
+    let lwe_sk = match ciphertext.pbs_order {
+        PBSOrder::KeyswitchBootstrap => glwe_sk.into_lwe_secret_key(),
+        PBSOrder::BootstrapKeyswitch => small_lwe_sk,
+    };
+
+    (lwe_sk, ciphertext.ct.clone())
+}
+
+/// Key ID of the LWE secret key of `client_key` that `ciphertext` is encrypted under, the ID the
+/// shortint guest commits.
+pub fn shortint_secret_key_id(client_key: &ClientKey, ciphertext: &Ciphertext) -> [u8; 32] {
+    let (lwe_sk, _) = lower_shortint_ciphertext(client_key, ciphertext);
+    secret_key_digest(lwe_sk.as_ref())
+}
+
+/// Prove the decryption of a shortint ciphertext, the guest commits its message.
+pub fn prove_shortint_decryption(
+    client_key: &ClientKey,
+    ciphertext: &Ciphertext,
+) -> Result<Receipt, ProofError> {
+    let (lwe_sk, lwe_ciphertext) = lower_shortint_ciphertext(client_key, ciphertext);
+    let env = ExecutorEnv::builder()
+        .write(&ShortintDecryptionInput {
+            lwe_sk: lwe_secret_key_data(&lwe_sk),
+            ciphertext: lwe_ciphertext_data(&lwe_ciphertext),
+            encoding: shortint_encoding(ciphertext),
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::ShortintDecrypt.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a shortint decryption receipt for `ciphertext` under the secret key of ID
+/// `secret_key_id`, see [`shortint_secret_key_id`], and return the committed message.
+pub fn check_shortint_decryption(
+    receipt: &Receipt,
+    ciphertext: &Ciphertext,
+    secret_key_id: &[u8; 32],
+) -> Result<u64, ProofError> {
+    verify_receipt(receipt, ProofKind::ShortintDecrypt.image_id())?;
+    let journal: ShortintDecryptionJournal =
+        decode_payload(receipt, ProofKind::ShortintDecrypt)?;
+
+    if journal.ct_digest != lwe_ciphertext_digest(&ciphertext.ct) {
+        return Err(ProofError::Verification("shortint ciphertext digest mismatch".to_string()));
+    }
+    if &journal.secret_key_id != secret_key_id {
+        return Err(ProofError::Verification("secret key ID mismatch".to_string()));
+    }
+    if journal.encoding != shortint_encoding(ciphertext) {
+        return Err(ProofError::ParameterMismatch(format!(
+            "journal encoding {:?} does not match the ciphertext encoding",
+            journal.encoding
+        )));
+    }
+
+    Ok(journal.message)
+}
@@ -0,0 +1,470 @@
+use rand::Rng;
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use std::collections::HashSet;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::{DkgInput, DkgParty, PartialDecryptionInput};
+use zkfhe_core::journal::{DkgJournal, PartialDecryptionJournal};
+
+use crate::error::ProofError;
+use crate::hashing::{lwe_ciphertext_digest, lwe_public_key_digest, secret_key_digest, sha256_bytes};
+use crate::io::{lwe_ciphertext_data, lwe_public_key_data};
+use crate::proof_kind::ProofKind;
+use crate::verification::{decode_payload, verify_receipt};
+
+/// Split an LWE secret key in `party_count` additive shares, the shares sum to the key modulo
+/// 2^64.
+pub fn split_secret_key<R: Rng>(
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    party_count: usize,
+    rng: &mut R,
+) -> Vec<Vec<u64>> {
+    assert!(party_count > 0, "at least one party is required");
+
+    let mut shares: Vec<Vec<u64>> = (0..party_count - 1)
+        .map(|_| (0..lwe_sk.lwe_dimension().0).map(|_| rng.gen()).collect())
+        .collect();
+
+    let last_share = lwe_sk
+        .as_ref()
+        .iter()
+        .enumerate()
+        .map(|(index, &key_element)| {
+            shares
+                .iter()
+                .fold(key_element, |acc, share| acc.wrapping_sub(share[index]))
+        })
+        .collect();
+    shares.push(last_share);
+
+    shares
+}
+
+/// Digest committing to a key share, matching the `key_share_digest` of the partial decryption
+/// journal.
+pub fn key_share_commitment(key_share: &[u64]) -> [u8; 32] {
+    secret_key_digest(key_share)
+}
+
+/// The transcript of a distributed generation of the threshold key: the aggregated public key and
+/// the public key share of every party, the body of its encryptions of zero under the common masks.
+#[derive(Debug, Clone)]
+pub struct DkgTranscript {
+    pub public_key: LwePublicKeyOwned<u64>,
+    pub public_key_shares: Vec<Vec<u64>>,
+    pub noise_bound_log2: u32,
+}
+
+/// Run the key generation of the parties holding `key_shares`: every party encrypts
+/// `zero_encryption_count` zeros under its share with common random masks and a noise drawn
+/// uniformly below `2^noise_bound_log2`, the aggregated public key sums their bodies.
+pub fn generate_dkg_transcript<R: Rng>(
+    key_shares: &[Vec<u64>],
+    zero_encryption_count: usize,
+    noise_bound_log2: u32,
+    rng: &mut R,
+) -> DkgTranscript {
+    assert!(!key_shares.is_empty(), "at least one party is required");
+    assert!(noise_bound_log2 < 63, "the noise bound must fit an i64");
+
+    let lwe_dimension = key_shares[0].len();
+    let noise_bound = 1i64 << noise_bound_log2;
+    let mut public_key_shares = vec![Vec::with_capacity(zero_encryption_count); key_shares.len()];
+    let mut data = Vec::with_capacity(zero_encryption_count * (lwe_dimension + 1));
+    for _ in 0..zero_encryption_count {
+        let mask: Vec<u64> = (0..lwe_dimension).map(|_| rng.gen()).collect();
+        let mut body = 0u64;
+        for (key_share, bodies) in key_shares.iter().zip(public_key_shares.iter_mut()) {
+            let noise = rng.gen_range(1 - noise_bound..noise_bound);
+            let party_body = mask
+                .iter()
+                .zip(key_share)
+                .fold(noise as u64, |acc, (&mask_element, &key_element)| {
+                    acc.wrapping_add(mask_element.wrapping_mul(key_element))
+                });
+            bodies.push(party_body);
+            body = body.wrapping_add(party_body);
+        }
+        data.extend_from_slice(&mask);
+        data.push(body);
+    }
+
+    DkgTranscript {
+        public_key: LwePublicKey::from_container(
+            data,
+            LweSize(lwe_dimension + 1),
+            CiphertextModulus::new_native(),
+        ),
+        public_key_shares,
+        noise_bound_log2,
+    }
+}
+
+/// SHA-256 of the key share commitments one after the other, the `key_commitment` of the DKG
+/// journal.
+pub fn threshold_key_commitment(share_commitments: &[[u8; 32]]) -> [u8; 32] {
+    sha256_bytes(&share_commitments.concat())
+}
+
+/// Prove that `transcript` generates the threshold key split in `key_shares`, in party order.
+pub fn prove_dkg(
+    transcript: &DkgTranscript,
+    key_shares: &[Vec<u64>],
+) -> Result<Receipt, ProofError> {
+    if key_shares.len() != transcript.public_key_shares.len() {
+        return Err(ProofError::InvalidInput(format!(
+            "{} key shares for a transcript of {} parties",
+            key_shares.len(),
+            transcript.public_key_shares.len()
+        )));
+    }
+
+    let parties = key_shares
+        .iter()
+        .zip(&transcript.public_key_shares)
+        .map(|(key_share, public_key_share)| DkgParty {
+            key_share: key_share.clone(),
+            public_key_share: public_key_share.clone(),
+        })
+        .collect();
+    let env = ExecutorEnv::builder()
+        .write(&DkgInput {
+            public_key: lwe_public_key_data(&transcript.public_key),
+            parties,
+            noise_bound_log2: transcript.noise_bound_log2,
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::Dkg.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify a DKG receipt for `public_key` whose parties' noise is bounded by at most
+/// `2^max_noise_bound_log2`, and return its journal. The `share_commitments` of the journal are
+/// the key commitments [`verify_threshold_set`] checks the partial decryptions against.
+pub fn check_dkg(
+    receipt: &Receipt,
+    public_key: &LwePublicKeyOwned<u64>,
+    max_noise_bound_log2: u32,
+) -> Result<DkgJournal, ProofError> {
+    verify_receipt(receipt, ProofKind::Dkg.image_id())?;
+    let journal: DkgJournal = decode_payload(receipt, ProofKind::Dkg)?;
+
+    if journal.public_key_digest != lwe_public_key_digest(public_key) {
+        return Err(ProofError::Verification("public key digest mismatch".to_string()));
+    }
+    if journal.noise_bound_log2 > max_noise_bound_log2 {
+        return Err(ProofError::Verification(format!(
+            "key share noise bound 2^{} exceeds 2^{max_noise_bound_log2}",
+            journal.noise_bound_log2
+        )));
+    }
+    if journal.key_commitment != threshold_key_commitment(&journal.share_commitments) {
+        return Err(ProofError::Verification("threshold key commitment mismatch".to_string()));
+    }
+
+    Ok(journal)
+}
+
+/// Prove the partial decryption of `ciphertext` by the party holding `key_share`.
+///
+/// The guest draws the flooding noise uniformly in `[-2^flooding_bound_log2,
+/// 2^flooding_bound_log2]` from `flooding_seed` and commits the seed digest.
+pub fn prove_partial_decryption(
+    party_index: u32,
+    key_share: &[u64],
+    ciphertext: &LweCiphertextOwned<u64>,
+    flooding_bound_log2: u32,
+    flooding_seed: [u8; 32],
+) -> Result<Receipt, ProofError> {
+    let env = ExecutorEnv::builder()
+        .write(&PartialDecryptionInput {
+            party_index,
+            key_share: key_share.to_vec(),
+            ciphertext: lwe_ciphertext_data(ciphertext),
+            flooding_bound_log2,
+            flooding_seed,
+        })
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+
+    let prove_info = default_prover()
+        .prove(env, ProofKind::ThresholdPartialDecrypt.elf())
+        .map_err(ProofError::proving)?;
+    Ok(prove_info.receipt)
+}
+
+/// Verify the partial decryption receipts of all the parties and combine them into the encoded
+/// plaintext of `ciphertext`.
+///
+/// Every receipt must be valid, be for `ciphertext`, come from a distinct party, use the key
+/// share committed to in `key_share_commitments` for that party and flood with noise bounded by
+/// at most `2^max_flooding_bound_log2`, so the combined noise keeps the plaintext decodable.
+pub fn combine_partial_decryptions(
+    ciphertext: &LweCiphertextOwned<u64>,
+    receipts: &[Receipt],
+    key_share_commitments: &[[u8; 32]],
+    max_flooding_bound_log2: u32,
+) -> Result<Plaintext<u64>, ProofError> {
+    if receipts.len() != key_share_commitments.len() {
+        return Err(ProofError::Verification(format!(
+            "got {} partial decryptions for {} parties",
+            receipts.len(),
+            key_share_commitments.len()
+        )));
+    }
+
+    let ct_digest = lwe_ciphertext_digest(ciphertext);
+    let mut seen_parties = HashSet::new();
+    let mut combined = *ciphertext.get_body().data;
+
+    for receipt in receipts {
+        verify_receipt(receipt, ProofKind::ThresholdPartialDecrypt.image_id())?;
+        let journal: PartialDecryptionJournal =
+            decode_payload(receipt, ProofKind::ThresholdPartialDecrypt)?;
+
+        let party_index = journal.party_index as usize;
+        let expected_commitment = key_share_commitments
+            .get(party_index)
+            .ok_or_else(|| ProofError::Verification(format!("unknown party {party_index}")))?;
+        if !seen_parties.insert(party_index) {
+            return Err(ProofError::Verification(format!(
+                "duplicate partial decryption for party {party_index}"
+            )));
+        }
+        if &journal.key_share_digest != expected_commitment {
+            return Err(ProofError::Verification(format!(
+                "key share commitment mismatch for party {party_index}"
+            )));
+        }
+        if journal.flooding_bound_log2 > max_flooding_bound_log2 {
+            return Err(ProofError::Verification(format!(
+                "flooding bound 2^{} of party {party_index} exceeds 2^{max_flooding_bound_log2}",
+                journal.flooding_bound_log2
+            )));
+        }
+        if journal.ct_digest != ct_digest {
+            return Err(ProofError::Verification(format!(
+                "ciphertext digest mismatch for party {party_index}"
+            )));
+        }
+
+        combined = combined.wrapping_sub(journal.partial_decryption);
+    }
+
+    Ok(Plaintext(combined))
+}
+
+/// The valid partial decryptions found by [`verify_threshold_set`], one per key share.
+#[derive(Debug)]
+pub struct ThresholdSet {
+    pub ct_digest: [u8; 32],
+    /// Index in the key commitments of the share behind every counted receipt, in receipt order
+    pub shares: Vec<usize>,
+    /// Wrapping sum of the counted partial decryptions
+    pub partial_sum: u64,
+    /// Largest flooding bound of the counted partial decryptions
+    pub max_flooding_bound_log2: u32,
+    /// Receipts that were not counted, by index, with the reason
+    pub rejected: Vec<(usize, ProofError)>,
+}
+
+impl ThresholdSet {
+    /// The encoded plaintext of `ciphertext`, which must be the one the set decrypts.
+    pub fn combine(
+        &self,
+        ciphertext: &LweCiphertextOwned<u64>,
+    ) -> Result<Plaintext<u64>, ProofError> {
+        if lwe_ciphertext_digest(ciphertext) != self.ct_digest {
+            return Err(ProofError::Verification(
+                "the partial decryptions are of another ciphertext".to_string(),
+            ));
+        }
+        Ok(Plaintext(ciphertext.get_body().data.wrapping_sub(self.partial_sum)))
+    }
+}
+
+fn check_partial_decryption(receipt: &Receipt) -> Result<PartialDecryptionJournal, ProofError> {
+    verify_receipt(receipt, ProofKind::ThresholdPartialDecrypt.image_id())?;
+    decode_payload(receipt, ProofKind::ThresholdPartialDecrypt)
+}
+
+/// Check that `receipts` hold at least `t` valid partial decryptions of the ciphertext digested
+/// in `ct_digest`, over distinct key shares committed to in `key_commitments` and flooded with
+/// noise bounded by at most `2^max_flooding_bound_log2`, the rules of
+/// [`combine_partial_decryptions`].
+///
+/// The shares are additive, the secret key is their sum and the partial decryptions only combine
+/// into the plaintext once all of them are summed: `t` must be the number of shares, any other
+/// threshold is refused. Invalid receipts are not fatal, they are reported in
+/// [`ThresholdSet::rejected`] with the receipts of other ciphertexts, and a second valid receipt
+/// of a share is not counted again, so a party cannot block the set by sending more receipts.
+pub fn verify_threshold_set(
+    receipts: &[Receipt],
+    t: usize,
+    ct_digest: [u8; 32],
+    key_commitments: &[Digest],
+    max_flooding_bound_log2: u32,
+) -> Result<ThresholdSet, ProofError> {
+    count_partial_decryptions(
+        receipts.iter().map(check_partial_decryption),
+        t,
+        ct_digest,
+        key_commitments,
+        max_flooding_bound_log2,
+    )
+}
+
+fn count_partial_decryptions(
+    journals: impl IntoIterator<Item = Result<PartialDecryptionJournal, ProofError>>,
+    t: usize,
+    ct_digest: [u8; 32],
+    key_commitments: &[Digest],
+    max_flooding_bound_log2: u32,
+) -> Result<ThresholdSet, ProofError> {
+    if t != key_commitments.len() {
+        return Err(ProofError::InvalidInput(format!(
+            "a threshold of {t} out of {} additive key shares, they all have to take part",
+            key_commitments.len()
+        )));
+    }
+    let mut set = ThresholdSet {
+        ct_digest,
+        shares: Vec::new(),
+        partial_sum: 0,
+        max_flooding_bound_log2: 0,
+        rejected: Vec::new(),
+    };
+
+    for (index, journal) in journals.into_iter().enumerate() {
+        let journal = match journal {
+            Ok(journal) => journal,
+            Err(error) => {
+                set.rejected.push((index, error));
+                continue;
+            }
+        };
+        let share = journal.party_index as usize;
+        let rejection = match key_commitments.get(share) {
+            None => Some(format!("unknown key share {share}")),
+            Some(commitment) if commitment.as_bytes() != journal.key_share_digest => {
+                Some(format!("key share commitment mismatch for share {share}"))
+            }
+            Some(_) if journal.ct_digest != ct_digest => {
+                Some("the partial decryption is of another ciphertext".to_string())
+            }
+            Some(_) if journal.flooding_bound_log2 > max_flooding_bound_log2 => Some(format!(
+                "flooding bound 2^{} of share {share} exceeds 2^{max_flooding_bound_log2}",
+                journal.flooding_bound_log2
+            )),
+            Some(_) if set.shares.contains(&share) => {
+                Some(format!("share {share} is already counted"))
+            }
+            Some(_) => None,
+        };
+        if let Some(rejection) = rejection {
+            set.rejected.push((index, ProofError::Verification(rejection)));
+            continue;
+        }
+        set.shares.push(share);
+        set.partial_sum = set.partial_sum.wrapping_add(journal.partial_decryption);
+        set.max_flooding_bound_log2 = set.max_flooding_bound_log2.max(journal.flooding_bound_log2);
+    }
+
+    if set.shares.len() < t {
+        return Err(ProofError::Verification(format!(
+            "{} of {} key shares have a valid partial decryption, the threshold is {t}",
+            set.shares.len(),
+            key_commitments.len()
+        )));
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commitments(count: u8) -> Vec<Digest> {
+        (0..count).map(|share| Digest::from([share; 32])).collect()
+    }
+
+    fn partial_decryption(share: u8, ct_digest: [u8; 32]) -> PartialDecryptionJournal {
+        PartialDecryptionJournal {
+            party_index: share.into(),
+            key_share_digest: [share; 32],
+            ct_digest,
+            partial_decryption: u64::from(share) + 1,
+            flooding_bound_log2: 20,
+            flooding_seed_digest: [0; 32],
+        }
+    }
+
+    #[test]
+    fn every_share_is_counted() {
+        let journals = (0..3).map(|share| Ok(partial_decryption(share, [1; 32])));
+        let set = count_partial_decryptions(journals, 3, [1; 32], &commitments(3), 20).unwrap();
+        assert_eq!(set.shares, [0, 1, 2]);
+        assert_eq!(set.partial_sum, 6);
+        assert!(set.rejected.is_empty());
+    }
+
+    #[test]
+    fn another_ciphertext_cannot_block_the_set() {
+        // A first receipt of another ciphertext is rejected instead of setting the ciphertext
+        let journals = [partial_decryption(0, [9; 32])]
+            .into_iter()
+            .chain((0..3).map(|share| partial_decryption(share, [1; 32])))
+            .map(Ok);
+        let set = count_partial_decryptions(journals, 3, [1; 32], &commitments(3), 20).unwrap();
+        assert_eq!(set.shares, [0, 1, 2]);
+        assert_eq!(set.rejected.len(), 1);
+        assert_eq!(set.rejected[0].0, 0);
+    }
+
+    #[test]
+    fn duplicates_and_unknown_shares_are_rejected() {
+        let mut wrong_commitment = partial_decryption(1, [1; 32]);
+        wrong_commitment.key_share_digest = [7; 32];
+        let journals = [
+            Ok(partial_decryption(0, [1; 32])),
+            Ok(partial_decryption(0, [1; 32])),
+            Ok(partial_decryption(5, [1; 32])),
+            Ok(wrong_commitment),
+            Err(ProofError::Verification("invalid receipt".to_string())),
+            Ok(partial_decryption(1, [1; 32])),
+        ];
+        let set = count_partial_decryptions(journals, 2, [1; 32], &commitments(2), 20).unwrap();
+        assert_eq!(set.shares, [0, 1]);
+        let rejected: Vec<usize> = set.rejected.iter().map(|(index, _)| *index).collect();
+        assert_eq!(rejected, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn missing_shares_are_refused() {
+        let journals = (0..2).map(|share| Ok(partial_decryption(share, [1; 32])));
+        assert!(count_partial_decryptions(journals, 3, [1; 32], &commitments(3), 20).is_err());
+    }
+
+    #[test]
+    fn loose_flooding_bounds_are_not_counted() {
+        // The bound combine_partial_decryptions enforces, a set it would refuse is not accepted
+        let mut loose = partial_decryption(1, [1; 32]);
+        loose.flooding_bound_log2 = 21;
+        let journals = [partial_decryption(0, [1; 32]), loose].into_iter().map(Ok);
+        let error = count_partial_decryptions(journals, 2, [1; 32], &commitments(2), 20);
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn threshold_below_the_share_count_is_refused() {
+        let journals = (0..3).map(|share| Ok(partial_decryption(share, [1; 32])));
+        let error = count_partial_decryptions(journals, 2, [1; 32], &commitments(3), 20);
+        assert!(matches!(error, Err(ProofError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,156 @@
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::{ExitCode, InnerReceipt, Journal, Receipt};
+use serde::de::DeserializeOwned;
+use zkfhe_core::journal::{JournalEnvelope, JournalHeader, JournalKind, JOURNAL_VERSION};
+use zkfhe_core::validation::ErrorCode;
+
+use crate::error::ProofError;
+use crate::proof_kind::ProofKind;
+
+/// Whether `receipt` is a fake receipt produced in dev mode, it proves nothing.
+pub fn is_dev_receipt(receipt: &Receipt) -> bool {
+    matches!(receipt.inner, InnerReceipt::Fake(_))
+}
+
+/// Map a guest halted by one of its input sanity checks to [`ProofError::GuestRejected`], so
+/// the failed check is reported instead of a bare exit code mismatch.
+pub fn check_exit_code(receipt: &Receipt) -> Result<(), ProofError> {
+    let claim = receipt.claim().map_err(ProofError::verification)?;
+    let claim = claim.as_value().map_err(ProofError::verification)?;
+    match claim.exit_code {
+        ExitCode::Halted(0) => Ok(()),
+        ExitCode::Halted(exit_code) => Err(decode_rejection(exit_code, &receipt.journal)),
+        _ => Ok(()),
+    }
+}
+
+/// The error of a guest halted with `exit_code`: [`ProofError::GuestRejected`] when its journal is
+/// the rejection of its inputs with the same code, a prover or guest bug otherwise.
+pub fn decode_rejection(exit_code: u32, journal: &Journal) -> ProofError {
+    let rejection = journal
+        .decode::<JournalEnvelope<ErrorCode>>()
+        .ok()
+        .filter(|envelope| envelope.kind == JournalKind::Rejected)
+        .map(|envelope| envelope.payload)
+        .filter(|code| u32::from(code.0) == exit_code)
+        .and_then(ErrorCode::validation_failure);
+    match rejection {
+        Some(failure) => ProofError::GuestRejected(failure),
+        None => ProofError::Proving(format!(
+            "guest halted with exit code {exit_code} without committing the rejection of its inputs"
+        )),
+    }
+}
+
+/// Verify `receipt` against `image_id`, refusing fake receipts unless the host was built with the
+/// `dev-mode` feature.
+pub fn verify_receipt(receipt: &Receipt, image_id: impl Into<Digest>) -> Result<(), ProofError> {
+    if is_dev_receipt(receipt) && !cfg!(feature = "dev-mode") {
+        return Err(ProofError::Verification(
+            "refusing a fake dev mode receipt, build the host with the dev-mode feature to \
+             accept it"
+                .to_string(),
+        ));
+    }
+    check_exit_code(receipt)?;
+    receipt.verify(image_id).map_err(ProofError::verification)
+}
+
+/// Decode the envelope of a journal. A journal of the current [`JOURNAL_VERSION`] is decoded as
+/// is, an older one is migrated to the current layout by the [`MIGRATIONS`] of every version in
+/// between, and a newer one is refused: its layout is unknown to this build.
+pub fn decode_envelope<Payload: DeserializeOwned>(
+    journal: &Journal,
+) -> Result<JournalEnvelope<Payload>, ProofError> {
+    let header: JournalHeader = journal.decode().map_err(ProofError::serialization)?;
+    match header.version {
+        JOURNAL_VERSION => journal.decode().map_err(ProofError::serialization),
+        version if version > JOURNAL_VERSION => Err(ProofError::Verification(format!(
+            "journal version {version} is newer than the supported version {JOURNAL_VERSION}, \
+             upgrade the verifier"
+        ))),
+        version => migrate(journal, version)
+            .and_then(|journal| journal.decode().map_err(|error| error.to_string()))
+            .map_err(|error| {
+                ProofError::Verification(format!(
+                    "journal version {version} cannot be read: {error}"
+                ))
+            }),
+    }
+}
+
+/// Rewrite of a journal of one version into the layout of the next one.
+type Migration = fn(&Journal) -> Result<Journal, String>;
+
+/// The migrations of the host, keyed by the version they read. A layout change whose older
+/// receipts stay acceptable adds the migration from the previous version here along with the bump
+/// of [`JOURNAL_VERSION`]. None has so far: every change came with new guests, and the receipts of
+/// the previous ones are proven again.
+const MIGRATIONS: &[(u16, Migration)] = &[];
+
+/// Read a journal of `version` into the current layout, one version at a time. A version without
+/// a migration to the next one cannot be read.
+fn migrate(journal: &Journal, version: u16) -> Result<Journal, String> {
+    let mut journal = journal.clone();
+    for from in version..JOURNAL_VERSION {
+        let (_, migration) = MIGRATIONS
+            .iter()
+            .find(|(migrated, _)| *migrated == from)
+            .ok_or_else(|| format!("no migration from version {from} to {}", from + 1))?;
+        journal = migration(&journal)?;
+    }
+    Ok(journal)
+}
+
+/// Decode the payload of a journal committed by the guest of `kind`.
+pub fn decode_payload<Payload: DeserializeOwned>(
+    receipt: &Receipt,
+    kind: ProofKind,
+) -> Result<Payload, ProofError> {
+    let envelope = decode_envelope(&receipt.journal)?;
+    check_journal_kind(envelope.kind, &[kind.journal_kind()])?;
+    Ok(envelope.payload)
+}
+
+/// Refuse a journal committed by another guest than one of `expected`.
+pub fn check_journal_kind(kind: JournalKind, expected: &[JournalKind]) -> Result<(), ProofError> {
+    if expected.contains(&kind) {
+        Ok(())
+    } else {
+        Err(ProofError::Verification(format!(
+            "journal of a {kind:?} receipt, expected one of {expected:?}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkfhe_core::journal::DecryptionJournal;
+
+    fn journal(version: u16) -> Journal {
+        let header = JournalHeader {
+            version,
+            kind: JournalKind::Decrypt,
+        };
+        let words = risc0_zkvm::serde::to_vec(&header).unwrap();
+        Journal::new(words.iter().flat_map(|word| word.to_le_bytes()).collect())
+    }
+
+    #[test]
+    fn older_version_without_migration_is_refused() {
+        let error =
+            decode_envelope::<DecryptionJournal>(&journal(JOURNAL_VERSION - 1)).unwrap_err();
+        assert!(error.to_string().contains(&format!(
+            "no migration from version {} to {JOURNAL_VERSION}",
+            JOURNAL_VERSION - 1
+        )));
+    }
+
+    #[test]
+    fn newer_version_is_refused() {
+        let error =
+            decode_envelope::<DecryptionJournal>(&journal(JOURNAL_VERSION + 1)).unwrap_err();
+        assert!(error.to_string().contains("upgrade the verifier"));
+    }
+}
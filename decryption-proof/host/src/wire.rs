@@ -0,0 +1,580 @@
+//! Protobuf messages of the proof jobs, mirroring `host/proto/zkfhe.proto`, so services written
+//! in Go or TypeScript can submit jobs and consume receipts with code generated from the schema
+//! instead of the Rust types.
+//!
+//! The messages are derived with prost by hand, the host needs no `protoc` to build. A
+//! [`ProofRequest`] is lowered to the parameters and the tfhe-rs entities the decryption guest
+//! takes, a [`ProofResponse`] carries the verified receipt and its journal, or the classified
+//! error of a failed job.
+
+use prost::Message;
+use risc0_zkvm::Receipt;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::encoding::{self, EncodingParams};
+use zkfhe_core::journal::{DecryptionJournal, ParamsSummary, JOURNAL_VERSION};
+use zkfhe_core::params::{self, ProofParams};
+use zkfhe_core::validation::ErrorCode;
+
+use crate::decrypt::{check_decryption_journal, check_request_id, prove_decryption};
+use crate::error::{FailureKind as ProofFailureKind, ProofError};
+use crate::hashing::secret_key_digest;
+use crate::params::{self as host_params, check_security, preset};
+use crate::proof_kind::ProofKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum PbsOrder {
+    KeyswitchBootstrap = 0,
+    BootstrapKeyswitch = 1,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Decoding {
+    Unsigned = 0,
+    Signed = 1,
+    Torus = 2,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct NoiseDistribution {
+    #[prost(oneof = "noise_distribution::Kind", tags = "1, 2")]
+    pub kind: Option<noise_distribution::Kind>,
+}
+
+pub mod noise_distribution {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        #[prost(double, tag = "1")]
+        GaussianStdDev(f64),
+        #[prost(uint32, tag = "2")]
+        TuniformBoundLog2(u32),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Encoding {
+    #[prost(uint32, tag = "1")]
+    pub message_bits: u32,
+    #[prost(uint32, tag = "2")]
+    pub carry_bits: u32,
+    #[prost(uint32, tag = "3")]
+    pub padding_bits: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Params {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(uint64, tag = "2")]
+    pub lwe_dimension: u64,
+    #[prost(uint64, tag = "3")]
+    pub glwe_dimension: u64,
+    #[prost(uint64, tag = "4")]
+    pub polynomial_size: u64,
+    #[prost(message, optional, tag = "5")]
+    pub lwe_noise_distribution: Option<NoiseDistribution>,
+    #[prost(message, optional, tag = "6")]
+    pub glwe_noise_distribution: Option<NoiseDistribution>,
+    #[prost(uint64, tag = "7")]
+    pub pbs_base_log: u64,
+    #[prost(uint64, tag = "8")]
+    pub pbs_level: u64,
+    #[prost(uint64, tag = "9")]
+    pub ks_base_log: u64,
+    #[prost(uint64, tag = "10")]
+    pub ks_level: u64,
+    #[prost(enumeration = "PbsOrder", tag = "11")]
+    pub pbs_order: i32,
+    #[prost(message, optional, tag = "12")]
+    pub encoding: Option<Encoding>,
+    #[prost(string, tag = "13")]
+    pub ciphertext_modulus: String,
+    #[prost(uint32, tag = "14")]
+    pub security_bits: u32,
+    #[prost(enumeration = "Decoding", tag = "15")]
+    pub decoding: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LweCiphertext {
+    /// The mask followed by the body
+    #[prost(uint64, repeated, tag = "1")]
+    pub data: Vec<u64>,
+    #[prost(string, tag = "2")]
+    pub ciphertext_modulus: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ProofRequest {
+    /// Guest proving the job, only `decrypt` for now
+    #[prost(string, tag = "1")]
+    pub guest: String,
+    #[prost(oneof = "proof_request::Parameters", tags = "2, 3")]
+    pub parameters: Option<proof_request::Parameters>,
+    #[prost(uint64, repeated, tag = "4")]
+    pub lwe_secret_key: Vec<u64>,
+    #[prost(message, optional, tag = "5")]
+    pub ciphertext: Option<LweCiphertext>,
+    /// Opaque 32 bytes identifier of the external request the job answers, echoed in the journal
+    #[prost(bytes = "vec", optional, tag = "6")]
+    pub request_id: Option<Vec<u8>>,
+}
+
+pub mod proof_request {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Parameters {
+        /// Name of a parameter preset of the host
+        #[prost(string, tag = "2")]
+        Preset(String),
+        #[prost(message, tag = "3")]
+        Params(super::Params),
+    }
+}
+
+/// The wire form of the [`ParamsSummary`] a journal commits.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct JournalParams {
+    #[prost(enumeration = "PbsOrder", tag = "1")]
+    pub pbs_order: i32,
+    #[prost(uint64, tag = "2")]
+    pub pbs_base_log: u64,
+    #[prost(uint64, tag = "3")]
+    pub pbs_level: u64,
+    #[prost(uint64, tag = "4")]
+    pub ks_base_log: u64,
+    #[prost(uint64, tag = "5")]
+    pub ks_level: u64,
+    #[prost(string, tag = "6")]
+    pub ciphertext_modulus: String,
+    #[prost(uint32, tag = "7")]
+    pub message_bits: u32,
+    #[prost(uint32, tag = "8")]
+    pub carry_bits: u32,
+    #[prost(uint32, tag = "9")]
+    pub security_bits: u32,
+    #[prost(enumeration = "Decoding", tag = "10")]
+    pub decoding: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct JournalV1 {
+    #[prost(uint32, tag = "1")]
+    pub journal_version: u32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub ct_digest: Vec<u8>,
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub source_ct_digest: Option<Vec<u8>>,
+    #[prost(uint64, tag = "4")]
+    pub plaintext: u64,
+    #[prost(bytes = "vec", tag = "5")]
+    pub params_digest: Vec<u8>,
+    #[prost(message, optional, tag = "6")]
+    pub params: Option<JournalParams>,
+    #[prost(uint32, optional, tag = "7")]
+    pub noise_budget_bits: Option<u32>,
+    #[prost(bool, tag = "8")]
+    pub overflow: bool,
+    #[prost(bytes = "vec", optional, tag = "9")]
+    pub request_id: Option<Vec<u8>>,
+    #[prost(bytes = "vec", tag = "10")]
+    pub secret_key_id: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum FailureKind {
+    None = 0,
+    InvalidInput = 1,
+    MalformedInput = 2,
+    FailedCheck = 3,
+    Rejected = 4,
+    Internal = 5,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ProofResponse {
+    /// Hex encoded image ID of the guest
+    #[prost(string, tag = "1")]
+    pub image_id: String,
+    /// The bincode encoded receipt, as the `verifier` binary reads it
+    #[prost(bytes = "vec", tag = "2")]
+    pub receipt: Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub journal: Option<JournalV1>,
+    #[prost(string, tag = "4")]
+    pub error: String,
+    #[prost(enumeration = "FailureKind", tag = "5")]
+    pub failure_kind: i32,
+    /// Validation exit code or decryption failure code of a guest that refused the job
+    #[prost(uint32, tag = "6")]
+    pub error_code: u32,
+}
+
+fn invalid(message: &str) -> ProofError {
+    ProofError::InvalidInput(message.to_string())
+}
+
+fn modulus_to_wire(modulus: u128) -> String {
+    modulus.to_string()
+}
+
+fn modulus_from_wire(modulus: &str) -> Result<u128, ProofError> {
+    match modulus {
+        "" => Ok(0),
+        modulus => modulus
+            .parse()
+            .map_err(|_| ProofError::InvalidInput(format!("invalid ciphertext modulus {modulus}"))),
+    }
+}
+
+fn usize_from_wire(value: u64) -> Result<usize, ProofError> {
+    usize::try_from(value).map_err(|_| invalid("a parameter does not fit the host word"))
+}
+
+impl From<params::PbsOrder> for PbsOrder {
+    fn from(order: params::PbsOrder) -> Self {
+        match order {
+            params::PbsOrder::KeyswitchBootstrap => PbsOrder::KeyswitchBootstrap,
+            params::PbsOrder::BootstrapKeyswitch => PbsOrder::BootstrapKeyswitch,
+        }
+    }
+}
+
+impl From<PbsOrder> for params::PbsOrder {
+    fn from(order: PbsOrder) -> Self {
+        match order {
+            PbsOrder::KeyswitchBootstrap => params::PbsOrder::KeyswitchBootstrap,
+            PbsOrder::BootstrapKeyswitch => params::PbsOrder::BootstrapKeyswitch,
+        }
+    }
+}
+
+impl From<encoding::Decoding> for Decoding {
+    fn from(decoding: encoding::Decoding) -> Self {
+        match decoding {
+            encoding::Decoding::Unsigned => Decoding::Unsigned,
+            encoding::Decoding::Signed => Decoding::Signed,
+            encoding::Decoding::Torus => Decoding::Torus,
+        }
+    }
+}
+
+impl From<Decoding> for encoding::Decoding {
+    fn from(decoding: Decoding) -> Self {
+        match decoding {
+            Decoding::Unsigned => encoding::Decoding::Unsigned,
+            Decoding::Signed => encoding::Decoding::Signed,
+            Decoding::Torus => encoding::Decoding::Torus,
+        }
+    }
+}
+
+impl From<&params::NoiseDistribution> for NoiseDistribution {
+    fn from(noise: &params::NoiseDistribution) -> Self {
+        let kind = match *noise {
+            params::NoiseDistribution::Gaussian { std_dev } => {
+                noise_distribution::Kind::GaussianStdDev(std_dev)
+            }
+            params::NoiseDistribution::TUniform { bound_log2 } => {
+                noise_distribution::Kind::TuniformBoundLog2(bound_log2)
+            }
+        };
+        Self { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<Option<NoiseDistribution>> for params::NoiseDistribution {
+    type Error = ProofError;
+
+    fn try_from(noise: Option<NoiseDistribution>) -> Result<Self, ProofError> {
+        match noise.and_then(|noise| noise.kind) {
+            Some(noise_distribution::Kind::GaussianStdDev(std_dev)) => {
+                Ok(Self::Gaussian { std_dev })
+            }
+            Some(noise_distribution::Kind::TuniformBoundLog2(bound_log2)) => {
+                Ok(Self::TUniform { bound_log2 })
+            }
+            None => Err(invalid("missing noise distribution")),
+        }
+    }
+}
+
+impl From<&ProofParams> for Params {
+    fn from(params: &ProofParams) -> Self {
+        Self {
+            name: params.name.clone(),
+            lwe_dimension: params.lwe_dimension as u64,
+            glwe_dimension: params.glwe_dimension as u64,
+            polynomial_size: params.polynomial_size as u64,
+            lwe_noise_distribution: Some((&params.lwe_noise_distribution).into()),
+            glwe_noise_distribution: Some((&params.glwe_noise_distribution).into()),
+            pbs_base_log: params.pbs_base_log as u64,
+            pbs_level: params.pbs_level as u64,
+            ks_base_log: params.ks_base_log as u64,
+            ks_level: params.ks_level as u64,
+            pbs_order: PbsOrder::from(params.pbs_order) as i32,
+            encoding: Some(Encoding {
+                message_bits: params.encoding.message_bits,
+                carry_bits: params.encoding.carry_bits,
+                padding_bits: params.encoding.padding_bits,
+            }),
+            ciphertext_modulus: modulus_to_wire(params.ciphertext_modulus),
+            security_bits: params.security_bits,
+            decoding: Decoding::from(params.decoding) as i32,
+        }
+    }
+}
+
+impl TryFrom<Params> for ProofParams {
+    type Error = ProofError;
+
+    fn try_from(params: Params) -> Result<Self, ProofError> {
+        let pbs_order = PbsOrder::try_from(params.pbs_order)
+            .map_err(|_| invalid("unknown PBS order"))?
+            .into();
+        let decoding = Decoding::try_from(params.decoding)
+            .map_err(|_| invalid("unknown decoding"))?
+            .into();
+        let encoding = params.encoding.ok_or_else(|| invalid("missing encoding"))?;
+        Ok(Self {
+            name: params.name,
+            lwe_dimension: usize_from_wire(params.lwe_dimension)?,
+            glwe_dimension: usize_from_wire(params.glwe_dimension)?,
+            polynomial_size: usize_from_wire(params.polynomial_size)?,
+            lwe_noise_distribution: params.lwe_noise_distribution.try_into()?,
+            glwe_noise_distribution: params.glwe_noise_distribution.try_into()?,
+            pbs_base_log: usize_from_wire(params.pbs_base_log)?,
+            pbs_level: usize_from_wire(params.pbs_level)?,
+            ks_base_log: usize_from_wire(params.ks_base_log)?,
+            ks_level: usize_from_wire(params.ks_level)?,
+            pbs_order,
+            encoding: EncodingParams {
+                message_bits: encoding.message_bits,
+                carry_bits: encoding.carry_bits,
+                padding_bits: encoding.padding_bits,
+            },
+            decoding,
+            ciphertext_modulus: modulus_from_wire(&params.ciphertext_modulus)?,
+            security_bits: params.security_bits,
+        })
+    }
+}
+
+impl From<&ParamsSummary> for JournalParams {
+    fn from(summary: &ParamsSummary) -> Self {
+        Self {
+            pbs_order: PbsOrder::from(summary.pbs_order) as i32,
+            pbs_base_log: summary.pbs_base_log as u64,
+            pbs_level: summary.pbs_level as u64,
+            ks_base_log: summary.ks_base_log as u64,
+            ks_level: summary.ks_level as u64,
+            ciphertext_modulus: modulus_to_wire(summary.ciphertext_modulus),
+            message_bits: summary.message_bits,
+            carry_bits: summary.carry_bits,
+            security_bits: summary.security_bits,
+            decoding: Decoding::from(summary.decoding) as i32,
+        }
+    }
+}
+
+impl TryFrom<JournalParams> for ParamsSummary {
+    type Error = ProofError;
+
+    fn try_from(summary: JournalParams) -> Result<Self, ProofError> {
+        Ok(Self {
+            pbs_order: PbsOrder::try_from(summary.pbs_order)
+                .map_err(|_| invalid("unknown PBS order"))?
+                .into(),
+            pbs_base_log: usize_from_wire(summary.pbs_base_log)?,
+            pbs_level: usize_from_wire(summary.pbs_level)?,
+            ks_base_log: usize_from_wire(summary.ks_base_log)?,
+            ks_level: usize_from_wire(summary.ks_level)?,
+            ciphertext_modulus: modulus_from_wire(&summary.ciphertext_modulus)?,
+            message_bits: summary.message_bits,
+            carry_bits: summary.carry_bits,
+            decoding: Decoding::try_from(summary.decoding)
+                .map_err(|_| invalid("unknown decoding"))?
+                .into(),
+            security_bits: summary.security_bits,
+        })
+    }
+}
+
+impl From<&DecryptionJournal> for JournalV1 {
+    fn from(journal: &DecryptionJournal) -> Self {
+        Self {
+            journal_version: JOURNAL_VERSION.into(),
+            ct_digest: journal.ct_digest.to_vec(),
+            source_ct_digest: journal.source_ct_digest.map(|digest| digest.to_vec()),
+            plaintext: journal.plaintext,
+            params_digest: journal.params_digest.to_vec(),
+            params: Some((&journal.params).into()),
+            noise_budget_bits: journal.noise_budget_bits,
+            overflow: journal.overflow,
+            request_id: journal.request_id.map(|request_id| request_id.to_vec()),
+            secret_key_id: journal.secret_key_id.to_vec(),
+        }
+    }
+}
+
+/// The inputs of a decryption job.
+pub struct DecryptionJob {
+    pub params: ProofParams,
+    pub lwe_sk: LweSecretKeyOwned<u64>,
+    pub ciphertext: LweCiphertextOwned<u64>,
+    pub request_id: Option<[u8; 32]>,
+}
+
+impl ProofRequest {
+    pub fn decode_from(bytes: &[u8]) -> Result<Self, ProofError> {
+        Self::decode(bytes).map_err(ProofError::serialization)
+    }
+
+    /// The parameters and entities of the job. Inline parameters are checked as a parameter file
+    /// is, the shapes of the entities by the guest, a mismatch is reported as a malformed input.
+    pub fn into_job(self) -> Result<DecryptionJob, ProofError> {
+        if self.guest != ProofKind::Decrypt.guest_name() {
+            return Err(ProofError::InvalidInput(format!(
+                "the {} guest cannot be requested over the wire",
+                self.guest
+            )));
+        }
+        let params = match self.parameters {
+            Some(proof_request::Parameters::Preset(name)) => preset(&name)
+                .ok_or_else(|| ProofError::InvalidInput(format!("unknown preset {name}")))?,
+            Some(proof_request::Parameters::Params(params)) => {
+                host_params::validate(params.try_into()?)?
+            }
+            None => return Err(invalid("missing parameters")),
+        };
+        let ciphertext = self.ciphertext.ok_or_else(|| invalid("missing ciphertext"))?;
+        if ciphertext.data.is_empty() {
+            return Err(invalid("empty ciphertext"));
+        }
+        let ciphertext_modulus = match modulus_from_wire(&ciphertext.ciphertext_modulus)? {
+            0 => CiphertextModulus::new_native(),
+            modulus => CiphertextModulus::try_new(modulus).map_err(|error| {
+                ProofError::InvalidInput(format!("invalid ciphertext modulus: {error}"))
+            })?,
+        };
+        let request_id = self
+            .request_id
+            .map(<[u8; 32]>::try_from)
+            .transpose()
+            .map_err(|_| invalid("the request ID is not 32 bytes"))?;
+        Ok(DecryptionJob {
+            params,
+            lwe_sk: LweSecretKey::from_container(self.lwe_secret_key),
+            ciphertext: LweCiphertext::from_container(ciphertext.data, ciphertext_modulus),
+            request_id,
+        })
+    }
+}
+
+impl ProofResponse {
+    /// The response of a proven job: `receipt` is verified and its journal checked against the
+    /// job first.
+    pub fn proven(receipt: &Receipt, job: &DecryptionJob) -> Result<Self, ProofError> {
+        let journal = check_decryption_journal(
+            receipt,
+            &job.ciphertext,
+            &secret_key_digest(job.lwe_sk.as_ref()),
+            &job.params,
+        )?;
+        check_request_id(&journal, job.request_id)?;
+        Ok(Self {
+            image_id: hex::encode(ProofKind::Decrypt.image_id().as_bytes()),
+            receipt: bincode::serialize(receipt).map_err(ProofError::serialization)?,
+            journal: Some((&journal).into()),
+            ..Self::default()
+        })
+    }
+
+    pub fn failed(error: &ProofError) -> Self {
+        let (failure_kind, error_code) = match error.failure_kind() {
+            ProofFailureKind::InvalidInput => (FailureKind::InvalidInput, 0),
+            ProofFailureKind::MalformedInput(failure) => {
+                (FailureKind::MalformedInput, ErrorCode::from(failure).0)
+            }
+            ProofFailureKind::FailedCheck(failure) => (FailureKind::FailedCheck, failure.code()),
+            ProofFailureKind::Rejected => (FailureKind::Rejected, 0),
+            ProofFailureKind::Internal => (FailureKind::Internal, 0),
+        };
+        Self {
+            image_id: hex::encode(ProofKind::Decrypt.image_id().as_bytes()),
+            error: error.to_string(),
+            failure_kind: failure_kind as i32,
+            error_code: error_code.into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Prove the decryption job of `request` and answer it, a failed job is answered with its error.
+///
+/// Jobs are held to [`crate::params::MIN_SECURITY_BITS`], the wire has no `--allow-insecure`.
+pub fn serve_request(request: ProofRequest) -> ProofResponse {
+    let response = request.into_job().and_then(|job| {
+        check_security(&job.params)?;
+        let receipt = prove_decryption(&job.params, &job.lwe_sk, &job.ciphertext, job.request_id)?;
+        ProofResponse::proven(&receipt, &job)
+    });
+    response.unwrap_or_else(|error| ProofResponse::failed(&error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::toy;
+
+    #[test]
+    fn params_survive_the_wire() {
+        let mut params = toy();
+        params.ciphertext_modulus = (1 << 64) - (1 << 32) + 1;
+        params.decoding = encoding::Decoding::Signed;
+        let bytes = Params::from(&params).encode_to_vec();
+        let decoded = Params::decode(bytes.as_slice()).unwrap();
+        assert_eq!(ProofParams::try_from(decoded).unwrap(), params);
+    }
+
+    #[test]
+    fn summary_survives_the_wire() {
+        let mut params = preset("PARAM_MESSAGE_2_CARRY_2_KS_PBS").unwrap();
+        params.decoding = encoding::Decoding::Torus;
+        let summary = ParamsSummary::from(&params);
+        let bytes = JournalParams::from(&summary).encode_to_vec();
+        let decoded = JournalParams::decode(bytes.as_slice()).unwrap();
+        assert_eq!(ParamsSummary::try_from(decoded).unwrap(), summary);
+    }
+
+    fn request(params: ProofParams) -> ProofRequest {
+        ProofRequest {
+            guest: ProofKind::Decrypt.guest_name().to_string(),
+            parameters: Some(proof_request::Parameters::Params((&params).into())),
+            lwe_secret_key: vec![0; params.lwe_dimension],
+            ciphertext: Some(LweCiphertext {
+                data: vec![0; params.lwe_dimension + 1],
+                ciphertext_modulus: String::new(),
+            }),
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn inline_params_are_validated() {
+        let mut params = toy();
+        params.ciphertext_modulus = 3;
+        assert!(matches!(
+            request(params).into_job(),
+            Err(ProofError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn insecure_jobs_are_refused() {
+        let response = serve_request(request(toy()));
+        assert_eq!(response.failure_kind, FailureKind::InvalidInput as i32);
+        assert!(response.error.contains("bits of security"), "{}", response.error);
+    }
+}
@@ -0,0 +1,132 @@
+// The decryption arithmetic of the guests must return the plaintext `decrypt_lwe_ciphertext`
+// returns, on the native modulus and on the non-native ones the guests accept, for binary keys
+// through their ones and for any key through the lazily reduced dot product:
+//
+//     cargo test -p host --test decryption
+use host::params::{self, TfheParams};
+use host::seed::DeterministicSeeder;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tfhe::core_crypto::commons::math::random::Seeder;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::checks::NTT64_CIPHERTEXT_MODULUS;
+use zkfhe_core::math::{BinaryLweSecretKey, Modulus};
+
+const LWE_DIMENSION: usize = 742;
+
+/// The native modulus, the NTT prime, an odd modulus and the largest one below `2^64`.
+fn ciphertext_moduli() -> Vec<u128> {
+    vec![0, NTT64_CIPHERTEXT_MODULUS, (1 << 61) - 1, u64::MAX as u128]
+}
+
+fn tfhe_modulus(modulus: u128) -> CiphertextModulus<u64> {
+    match modulus {
+        0 => CiphertextModulus::new_native(),
+        modulus => CiphertextModulus::try_new(modulus).unwrap(),
+    }
+}
+
+fn random_below(rng: &mut StdRng, modulus: u128) -> u64 {
+    match modulus {
+        0 => rng.gen(),
+        modulus => rng.gen_range(0..modulus) as u64,
+    }
+}
+
+#[test]
+fn binary_key_decryption_matches_tfhe() {
+    let mut rng = StdRng::seed_from_u64(845);
+    let mut seeder = DeterministicSeeder::new([8; 32]);
+    let mut secret_generator =
+        SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+    let mut encryption_generator =
+        EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), &mut seeder);
+    let lwe_sk =
+        LweSecretKey::generate_new_binary(LweDimension(LWE_DIMENSION), &mut secret_generator);
+    let binary_key = BinaryLweSecretKey::new(lwe_sk.as_ref()).expect("the key is binary");
+    assert_eq!(binary_key.dimension(), LWE_DIMENSION);
+
+    for ciphertext_modulus in ciphertext_moduli() {
+        let modulus = Modulus::new(ciphertext_modulus);
+        for _ in 0..50 {
+            let plaintext = Plaintext(random_below(&mut rng, ciphertext_modulus));
+            let ciphertext = allocate_and_encrypt_new_lwe_ciphertext(
+                &lwe_sk,
+                plaintext,
+                params::toy().glwe_noise(),
+                tfhe_modulus(ciphertext_modulus),
+                &mut encryption_generator,
+            );
+            let expected = decrypt_lwe_ciphertext(&lwe_sk, &ciphertext).0;
+            assert_eq!(
+                binary_key.decrypt_lwe(&modulus, ciphertext.as_ref()),
+                expected,
+                "modulus {ciphertext_modulus:#x}"
+            );
+            assert_eq!(
+                modulus.decrypt_lwe(lwe_sk.as_ref(), ciphertext.as_ref()),
+                expected,
+                "modulus {ciphertext_modulus:#x}"
+            );
+        }
+    }
+}
+
+/// Keys of any element and ciphertexts of reduced random coefficients, whose dot products carry
+/// out of 128 bits on the large moduli.
+#[test]
+fn general_key_decryption_matches_tfhe() {
+    let mut rng = StdRng::seed_from_u64(845);
+    for ciphertext_modulus in ciphertext_moduli() {
+        let modulus = Modulus::new(ciphertext_modulus);
+        for _ in 0..20 {
+            let key: Vec<u64> = (0..LWE_DIMENSION)
+                .map(|_| random_below(&mut rng, ciphertext_modulus))
+                .collect();
+            let data: Vec<u64> = (0..=LWE_DIMENSION)
+                .map(|_| random_below(&mut rng, ciphertext_modulus))
+                .collect();
+            let lwe_sk = LweSecretKey::from_container(key);
+            let ciphertext =
+                LweCiphertext::from_container(data, tfhe_modulus(ciphertext_modulus));
+            assert_eq!(
+                modulus.decrypt_lwe(lwe_sk.as_ref(), ciphertext.as_ref()),
+                decrypt_lwe_ciphertext(&lwe_sk, &ciphertext).0,
+                "modulus {ciphertext_modulus:#x}"
+            );
+        }
+    }
+}
+
+#[test]
+fn reduction_matches_the_remainder() {
+    let mut rng = StdRng::seed_from_u64(845);
+    for ciphertext_modulus in ciphertext_moduli().into_iter().skip(1).chain([2, 3, 1 << 32]) {
+        let modulus = Modulus::new(ciphertext_modulus);
+        let edges = [
+            0,
+            1,
+            ciphertext_modulus - 1,
+            ciphertext_modulus,
+            ciphertext_modulus + 1,
+            u64::MAX as u128,
+            u128::MAX - 1,
+            u128::MAX,
+        ];
+        let randoms = (0..1000).map(|_| rng.gen::<u128>());
+        for x in edges.into_iter().chain(randoms) {
+            assert_eq!(
+                modulus.reduce(x) as u128,
+                x % ciphertext_modulus,
+                "{x:#x} modulo {ciphertext_modulus:#x}"
+            );
+        }
+    }
+}
+
+#[test]
+fn non_binary_keys_are_rejected() {
+    assert!(BinaryLweSecretKey::new(&[0, 1, 1, 0]).is_some());
+    assert!(BinaryLweSecretKey::new(&[0, 1, 2, 0]).is_none());
+    assert!(BinaryLweSecretKey::new(&[u64::MAX]).is_none());
+}
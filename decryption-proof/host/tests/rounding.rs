@@ -0,0 +1,104 @@
+// The fixed shift rounding the guests decode with must agree with the single level
+// `SignedDecomposer` of tfhe-rs on every plaintext, for every encoding a guest accepts:
+//
+//     cargo test -p host --test rounding
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::encoding::{EncodingParams, Rounding};
+
+/// Every encoding of up to 4 message bits, 4 carry bits and one padding bit, plus one leaving a
+/// single noise bit, the decomposer needs at least one.
+fn encodings(plaintext_bits: u32) -> Vec<EncodingParams> {
+    let mut encodings = Vec::new();
+    for message_bits in 1..=4 {
+        for carry_bits in 0..=4 {
+            for padding_bits in 0..=1 {
+                encodings.push(EncodingParams {
+                    message_bits,
+                    carry_bits,
+                    padding_bits,
+                });
+            }
+        }
+    }
+    encodings.push(EncodingParams {
+        message_bits: plaintext_bits - 2,
+        carry_bits: 0,
+        padding_bits: 1,
+    });
+    encodings
+}
+
+fn decomposer<Scalar: UnsignedInteger>(encoding: &EncodingParams) -> SignedDecomposer<Scalar> {
+    SignedDecomposer::new(
+        DecompositionBaseLog(encoding.decomposer_base_log()),
+        DecompositionLevelCount(1),
+    )
+}
+
+/// Plaintexts around the rounding boundaries of the first, a middle and the last encoded message,
+/// then random ones.
+fn plaintexts_u64(delta_log: u32, rng: &mut StdRng) -> Vec<u64> {
+    let mut plaintexts = vec![0, 1, u64::MAX, u64::MAX - 1, 1 << 63, (1 << 63) - 1];
+    let half_delta = 1u64 << (delta_log - 1);
+    for encoded in [0, 1u64 << delta_log, u64::MAX << delta_log] {
+        for boundary in [encoded.wrapping_add(half_delta), encoded.wrapping_sub(half_delta)] {
+            plaintexts.extend([boundary.wrapping_sub(1), boundary, boundary.wrapping_add(1)]);
+        }
+    }
+    plaintexts.extend((0..1000).map(|_| rng.gen::<u64>()));
+    plaintexts
+}
+
+#[test]
+fn rounding_matches_the_decomposer_on_64_bits() {
+    let mut rng = StdRng::seed_from_u64(818);
+    for encoding in encodings(u64::BITS) {
+        assert!(encoding.is_valid());
+        let rounding = Rounding::<u64>::new(&encoding);
+        let decomposer = decomposer::<u64>(&encoding);
+        let delta_log = u64::BITS - encoding.total_bits();
+        for plaintext in plaintexts_u64(delta_log, &mut rng) {
+            let expected = decomposer.closest_representable(plaintext) >> delta_log;
+            assert_eq!(
+                rounding.decode(plaintext),
+                expected,
+                "{encoding:?} plaintext {plaintext:#x}"
+            );
+        }
+    }
+}
+
+#[test]
+fn rounding_matches_the_decomposer_on_128_bits() {
+    let mut rng = StdRng::seed_from_u64(818);
+    for encoding in encodings(u128::BITS) {
+        assert!(encoding.is_valid_for(u128::BITS));
+        let rounding = Rounding::<u128>::new(&encoding);
+        let decomposer = decomposer::<u128>(&encoding);
+        let delta_log = u128::BITS - encoding.total_bits();
+        let half_delta = 1u128 << (delta_log - 1);
+        let mut plaintexts = vec![0, 1, u128::MAX, 1 << 127, half_delta, half_delta - 1];
+        plaintexts.extend((0..1000).map(|_| rng.gen::<u128>()));
+        for plaintext in plaintexts {
+            let expected = decomposer.closest_representable(plaintext) >> delta_log;
+            assert_eq!(
+                rounding.decode(plaintext),
+                expected,
+                "{encoding:?} plaintext {plaintext:#x}"
+            );
+        }
+    }
+}
+
+#[test]
+fn rounding_decodes_encoded_messages() {
+    for encoding in encodings(u64::BITS) {
+        let rounding = Rounding::<u64>::new(&encoding);
+        let last_message = u64::MAX >> (u64::BITS - encoding.total_bits());
+        for message in [0, 1, last_message] {
+            assert_eq!(rounding.decode(message.wrapping_mul(encoding.delta())), message);
+        }
+    }
+}
@@ -0,0 +1,361 @@
+// Host to guest roundtrips of the single decryption guest, run in the executor without proving.
+//
+// Every case encrypts with small or preset parameters from a fixed seed, executes the `decrypt`
+// guest and checks the journal it commits, or the exit code or panic of a corrupted input:
+//
+//     cargo test -p host --test roundtrip
+use host::decrypt::{check_request_id, execute_decryption};
+use host::error::{FailureKind, ProofError};
+use host::execution::check_determinism;
+use host::hashing::{lwe_ciphertext_digest, params_digest};
+use host::io::{lwe_ciphertext_data, lwe_secret_key_data, write_public_input};
+use host::params::{self, ProofParams, TfheParams};
+use host::proof_kind::ProofKind;
+use host::seed::DeterministicSeeder;
+use host::verification::{check_journal_kind, decode_envelope, decode_rejection};
+use risc0_zkvm::{default_executor, ExecutorEnv, ExitCode, SessionInfo};
+use tfhe::core_crypto::commons::math::random::Seeder;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::canonical::{encode_u64s, lwe_ciphertext_header};
+use zkfhe_core::encoding::{Decoding, EncodingParams};
+use zkfhe_core::io::LweDecryptionInput;
+use zkfhe_core::journal::{DecryptionJournal, JournalKind, ParamsSummary, JOURNAL_VERSION};
+use zkfhe_core::validation::{DecryptionFailure, ValidationFailure};
+
+/// Solinas prime of the NTT, a non-native modulus the decryption guest supports.
+const SOLINAS_MODULUS: u128 = (1 << 64) - (1 << 32) + 1;
+
+struct Keys {
+    params: ProofParams,
+    lwe_sk: LweSecretKeyOwned<u64>,
+    encryption_generator: EncryptionRandomGenerator<ActivatedRandomGenerator>,
+}
+
+impl Keys {
+    /// The flattened GLWE key of `params`, the key the decryption guest decrypts under.
+    fn new(params: ProofParams) -> Self {
+        let mut seeder = DeterministicSeeder::new([3; 32]);
+        let mut secret_generator =
+            SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+        let encryption_generator =
+            EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), &mut seeder);
+        let lwe_sk = GlweSecretKey::generate_new_binary(
+            params.glwe_dimension(),
+            params.polynomial_size(),
+            &mut secret_generator,
+        )
+        .into_lwe_secret_key();
+        Self {
+            params,
+            lwe_sk,
+            encryption_generator,
+        }
+    }
+
+    fn encrypt(&mut self, message: u64) -> LweCiphertextOwned<u64> {
+        let plaintext = self
+            .params
+            .encoding
+            .encode_for_modulus(message, self.params.ciphertext_modulus);
+        allocate_and_encrypt_new_lwe_ciphertext(
+            &self.lwe_sk,
+            Plaintext(plaintext),
+            self.params.glwe_noise(),
+            self.params.ciphertext_modulus(),
+            &mut self.encryption_generator,
+        )
+    }
+
+    fn input(&self, ciphertext: &LweCiphertextOwned<u64>) -> LweDecryptionInput {
+        LweDecryptionInput {
+            lwe_sk: lwe_secret_key_data(&self.lwe_sk),
+            ciphertext: lwe_ciphertext_data(ciphertext),
+        }
+    }
+}
+
+/// The inputs of the `decrypt` guest as the host writes them, the ciphertext of `input` encoded in
+/// the public input frame as is, so a corrupted one reaches the guest.
+fn decryption_env(
+    params: &ProofParams,
+    input: &LweDecryptionInput,
+) -> Result<ExecutorEnv<'static>, ProofError> {
+    let ciphertext = &input.ciphertext;
+    let header = lwe_ciphertext_header(
+        u64::BITS,
+        ciphertext.ciphertext_modulus,
+        ciphertext.data.len(),
+    );
+    let mut env_builder = ExecutorEnv::builder();
+    env_builder
+        .write(params)
+        .map_err(ProofError::serialization)?
+        .write(&None::<[u8; 32]>)
+        .map_err(ProofError::serialization)?;
+    write_public_input(&mut env_builder, &encode_u64s(header, &ciphertext.data))?;
+    env_builder
+        .write(&input.lwe_sk)
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)
+}
+
+fn execute(params: &ProofParams, input: &LweDecryptionInput) -> Result<SessionInfo, ProofError> {
+    default_executor()
+        .execute(decryption_env(params, input)?, ProofKind::Decrypt.elf())
+        .map_err(ProofError::proving)
+}
+
+fn decryption_journal(session: &SessionInfo) -> DecryptionJournal {
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    let envelope = decode_envelope::<DecryptionJournal>(&session.journal).unwrap();
+    assert_eq!(envelope.version, JOURNAL_VERSION);
+    check_journal_kind(envelope.kind, &[JournalKind::Decrypt]).unwrap();
+    envelope.payload
+}
+
+/// Messages at both ends of the message space and across the carry boundary.
+fn messages(encoding: &EncodingParams) -> Vec<u64> {
+    let mut messages = vec![
+        0,
+        1,
+        encoding.message_modulus() - 1,
+        encoding.message_modulus(),
+        encoding.message_space() - 1,
+    ];
+    messages.dedup();
+    messages
+}
+
+fn assert_roundtrips(params: ProofParams) {
+    let mut keys = Keys::new(params);
+    for message in messages(&keys.params.encoding) {
+        let ciphertext = keys.encrypt(message);
+        let unproven =
+            execute_decryption(&keys.params, &keys.lwe_sk, &ciphertext, None).unwrap();
+        assert_eq!(unproven.kind(), ProofKind::Decrypt);
+        assert!(unproven.stats.user_cycles > 0);
+        let journal = unproven.into_unproven_payload();
+
+        let expected = keys.params.encoding.output(
+            keys.params.decoding,
+            message,
+            keys.params.ciphertext_modulus,
+        );
+        assert_eq!(journal.plaintext, expected, "{}", keys.params.name);
+        assert_eq!(journal.ct_digest, lwe_ciphertext_digest(&ciphertext));
+        assert_eq!(journal.params_digest, params_digest(&keys.params));
+        assert_eq!(journal.params, ParamsSummary::from(&keys.params));
+        assert_eq!(journal.source_ct_digest, None);
+        assert!(!journal.overflow);
+        assert!(journal.noise_budget_bits.is_some());
+        assert_eq!(journal.request_id, None);
+    }
+}
+
+#[test]
+fn toy_roundtrip() {
+    assert_roundtrips(params::toy());
+}
+
+#[test]
+fn preset_roundtrips() {
+    for name in [
+        "PARAM_MESSAGE_1_CARRY_1_KS_PBS",
+        "PARAM_MESSAGE_2_CARRY_2_KS_PBS",
+        "PARAM_MESSAGE_3_CARRY_3_KS_PBS",
+    ] {
+        assert_roundtrips(params::preset(name).unwrap());
+    }
+}
+
+#[test]
+fn non_native_modulus_roundtrip() {
+    assert_roundtrips(ProofParams {
+        ciphertext_modulus: SOLINAS_MODULUS,
+        ..params::toy()
+    });
+}
+
+#[test]
+fn signed_and_torus_roundtrips() {
+    for decoding in [Decoding::Signed, Decoding::Torus] {
+        for ciphertext_modulus in [0, SOLINAS_MODULUS] {
+            assert_roundtrips(ProofParams {
+                decoding,
+                ciphertext_modulus,
+                ..params::toy()
+            });
+        }
+    }
+}
+
+#[test]
+fn signed_decoding_is_twos_complement() {
+    let params = ProofParams {
+        decoding: Decoding::Signed,
+        ..params::toy()
+    };
+    let mut keys = Keys::new(params);
+    // The carries are dropped, the top message bit is the sign
+    for (message, signed) in [(1, 1), (2, -2), (3, -1), (0b0110, -2)] {
+        let ciphertext = keys.encrypt(message);
+        let journal = decryption_journal(&execute(&keys.params, &keys.input(&ciphertext)).unwrap());
+        assert_eq!(journal.plaintext as i64, signed, "message {message}");
+    }
+}
+
+#[test]
+fn decryption_is_deterministic() {
+    // An accepted and a rejected input both reproduce their journal and cycle counts
+    let mut keys = Keys::new(params::toy());
+    let ciphertext = keys.encrypt(1);
+    let mut short_key = keys.input(&ciphertext);
+    short_key.lwe_sk.data.pop();
+
+    for input in [keys.input(&ciphertext), short_key] {
+        let stats =
+            check_determinism(ProofKind::Decrypt, || decryption_env(&keys.params, &input)).unwrap();
+        assert!(stats.user_cycles > 0);
+    }
+}
+
+#[test]
+fn params_digest_binds_the_parameters() {
+    let mut keys = Keys::new(params::toy());
+    let ciphertext = keys.encrypt(1);
+    let journal = decryption_journal(&execute(&keys.params, &keys.input(&ciphertext)).unwrap());
+
+    let other = params::preset("PARAM_MESSAGE_2_CARRY_2_KS_PBS").unwrap();
+    assert_ne!(journal.params_digest, params_digest(&other));
+}
+
+#[test]
+fn request_id_is_echoed() {
+    let mut keys = Keys::new(params::toy());
+    let ciphertext = keys.encrypt(1);
+    let request_id = [7; 32];
+    let journal = execute_decryption(&keys.params, &keys.lwe_sk, &ciphertext, Some(request_id))
+        .unwrap()
+        .into_unproven_payload();
+    assert_eq!(journal.request_id, Some(request_id));
+
+    // The journal answers its own request only, and a journal bound to none answers no request
+    check_request_id(&journal, None).unwrap();
+    assert!(check_request_id(&journal, Some([8; 32])).is_err());
+    let unbound = DecryptionJournal {
+        request_id: None,
+        ..journal
+    };
+    assert!(check_request_id(&unbound, Some(request_id)).is_err());
+}
+
+#[test]
+fn short_secret_key_halts() {
+    let mut keys = Keys::new(params::toy());
+    let ciphertext = keys.encrypt(1);
+    let mut input = keys.input(&ciphertext);
+    input.lwe_sk.data.pop();
+
+    let session = execute(&keys.params, &input).unwrap();
+    assert_eq!(
+        session.exit_code,
+        ExitCode::Halted(ValidationFailure::SecretKeyDimension.exit_code().into())
+    );
+    // The guest commits the failed check before halting, the host reads it back from the journal
+    let error = decode_rejection(
+        ValidationFailure::SecretKeyDimension.exit_code().into(),
+        &session.journal,
+    );
+    assert_eq!(
+        error.failure_kind(),
+        FailureKind::MalformedInput(ValidationFailure::SecretKeyDimension),
+        "{error}"
+    );
+}
+
+#[test]
+fn halt_without_rejection_journal_is_not_a_rejection() {
+    let mut keys = Keys::new(params::toy());
+    let ciphertext = keys.encrypt(1);
+    let session = execute(&keys.params, &keys.input(&ciphertext)).unwrap();
+
+    // A decryption journal halted with a validation exit code was not rejected by the guest
+    let error = decode_rejection(
+        ValidationFailure::SecretKeyDimension.exit_code().into(),
+        &session.journal,
+    );
+    assert_eq!(error.failure_kind(), FailureKind::Internal, "{error}");
+}
+
+#[test]
+fn short_ciphertext_halts() {
+    let mut keys = Keys::new(params::toy());
+    let ciphertext = keys.encrypt(1);
+    let mut input = keys.input(&ciphertext);
+    input.ciphertext.data.remove(0);
+
+    let session = execute(&keys.params, &input).unwrap();
+    assert_eq!(
+        session.exit_code,
+        ExitCode::Halted(ValidationFailure::CiphertextDimension.exit_code().into())
+    );
+}
+
+#[test]
+fn invalid_encoding_halts() {
+    let mut keys = Keys::new(params::toy());
+    let ciphertext = keys.encrypt(1);
+    let input = keys.input(&ciphertext);
+    let params = ProofParams {
+        encoding: EncodingParams {
+            message_bits: 0,
+            ..keys.params.encoding
+        },
+        ..keys.params.clone()
+    };
+
+    let session = execute(&params, &input).unwrap();
+    assert_eq!(
+        session.exit_code,
+        ExitCode::Halted(ValidationFailure::InvalidEncoding.exit_code().into())
+    );
+}
+
+#[test]
+fn unreduced_non_native_ciphertext_halts() {
+    let params = ProofParams {
+        ciphertext_modulus: SOLINAS_MODULUS,
+        ..params::toy()
+    };
+    let mut keys = Keys::new(params);
+    let ciphertext = keys.encrypt(1);
+    let mut input = keys.input(&ciphertext);
+    input.ciphertext.data[0] = u64::MAX;
+
+    let session = execute(&keys.params, &input).unwrap();
+    assert_eq!(
+        session.exit_code,
+        ExitCode::Halted(ValidationFailure::CiphertextModulus.exit_code().into())
+    );
+}
+
+#[test]
+fn borderline_noise_panics() {
+    // A trivial encryption of a plaintext half way between two encoded messages, its noise is
+    // exactly the decoding bound
+    let keys = Keys::new(params::toy());
+    let encoding = keys.params.encoding;
+    let ciphertext = allocate_and_trivially_encrypt_new_lwe_ciphertext(
+        keys.lwe_sk.lwe_dimension().to_lwe_size(),
+        Plaintext(encoding.delta() / 2),
+        keys.params.ciphertext_modulus(),
+    );
+
+    let error = execute(&keys.params, &keys.input(&ciphertext)).unwrap_err();
+    assert_eq!(
+        error.failure_kind(),
+        FailureKind::FailedCheck(DecryptionFailure::NoiseBound),
+        "{error}"
+    );
+}
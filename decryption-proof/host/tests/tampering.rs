@@ -0,0 +1,251 @@
+// Tampered inputs run in the executor: a wrong claimed result or a ciphertext altered after
+// encryption must make the guest panic with the code of the failed check, or produce a journal the
+// host refuses. Every error is classified with `ProofError::failure_kind`:
+//
+//     cargo test -p host --test tampering
+use host::decrypt::check_decryption_digests;
+use host::error::{FailureKind, ProofError};
+use host::hashing::{canonical_lwe_ciphertext, secret_key_digest};
+use host::io::{lwe_ciphertext_data, lwe_secret_key_data, write_public_input};
+use host::params::{self, ProofParams, TfheParams};
+use host::policy::{disclosure, open_plaintext_commitment, plaintext_opening};
+use host::proof_kind::ProofKind;
+use host::seed::DeterministicSeeder;
+use host::verification::decode_envelope;
+use risc0_zkvm::{default_executor, ExecutorEnv, SessionInfo};
+use tfhe::core_crypto::commons::math::random::Seeder;
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::io::{BatchItem, DecryptionInput};
+use zkfhe_core::journal::{DecryptionJournal, JournalEnvelope};
+use zkfhe_core::policy::{JournalPolicy, PlaintextOpening};
+use zkfhe_core::validation::DecryptionFailure;
+
+struct Keys {
+    params: ProofParams,
+    lwe_sk: LweSecretKeyOwned<u64>,
+    encryption_generator: EncryptionRandomGenerator<ActivatedRandomGenerator>,
+}
+
+impl Keys {
+    fn new() -> Self {
+        let params = params::toy();
+        let mut seeder = DeterministicSeeder::new([5; 32]);
+        let mut secret_generator =
+            SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+        let encryption_generator =
+            EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), &mut seeder);
+        let lwe_sk = GlweSecretKey::generate_new_binary(
+            params.glwe_dimension(),
+            params.polynomial_size(),
+            &mut secret_generator,
+        )
+        .into_lwe_secret_key();
+        Self {
+            params,
+            lwe_sk,
+            encryption_generator,
+        }
+    }
+
+    fn encrypt(&mut self, message: u64) -> LweCiphertextOwned<u64> {
+        allocate_and_encrypt_new_lwe_ciphertext(
+            &self.lwe_sk,
+            Plaintext(message * self.params.encoding.delta()),
+            self.params.glwe_noise(),
+            self.params.ciphertext_modulus(),
+            &mut self.encryption_generator,
+        )
+    }
+}
+
+/// Execute the batch guest on `(claimed result, ciphertext)` pairs decrypted with the big key.
+fn execute_batch(
+    keys: &Keys,
+    items: &[(u64, &LweCiphertextOwned<u64>)],
+) -> Result<SessionInfo, ProofError> {
+    let mut builder = ExecutorEnv::builder();
+    builder
+        .write(&keys.params)
+        .map_err(ProofError::serialization)?
+        .write(&DecryptionInput {
+            big_lwe_sk: lwe_secret_key_data(&keys.lwe_sk),
+            keyswitch: None,
+            seed_commitment: None,
+            circuit_digest: None,
+            batch_size: items.len() as u32,
+        })
+        .map_err(ProofError::serialization)?;
+    for &(claimed, ciphertext) in items {
+        builder
+            .write(&BatchItem {
+                cleartext_multiplication_result: claimed,
+                pbs_multiplication_ct: lwe_ciphertext_data(ciphertext),
+            })
+            .map_err(ProofError::serialization)?;
+    }
+    let env = builder.build().map_err(ProofError::proving)?;
+    default_executor()
+        .execute(env, ProofKind::BatchDecrypt.elf())
+        .map_err(ProofError::proving)
+}
+
+fn execute_single(
+    keys: &Keys,
+    ciphertext: &LweCiphertextOwned<u64>,
+) -> Result<SessionInfo, ProofError> {
+    let mut env_builder = ExecutorEnv::builder();
+    env_builder
+        .write(&keys.params)
+        .map_err(ProofError::serialization)?
+        .write(&None::<[u8; 32]>)
+        .map_err(ProofError::serialization)?;
+    write_public_input(&mut env_builder, &canonical_lwe_ciphertext(ciphertext))?;
+    let env = env_builder
+        .write(&lwe_secret_key_data(&keys.lwe_sk))
+        .map_err(ProofError::serialization)?
+        .build()
+        .map_err(ProofError::proving)?;
+    default_executor()
+        .execute(env, ProofKind::Decrypt.elf())
+        .map_err(ProofError::proving)
+}
+
+fn assert_failed_check(result: Result<SessionInfo, ProofError>, expected: DecryptionFailure) {
+    let error = result.map(|_| ()).unwrap_err();
+    assert_eq!(error.failure_kind(), FailureKind::FailedCheck(expected), "{error}");
+}
+
+#[test]
+fn honest_batch_executes() {
+    let mut keys = Keys::new();
+    let messages = [0, 1, 3];
+    let ciphertexts: Vec<_> = messages.iter().map(|&message| keys.encrypt(message)).collect();
+    let items: Vec<_> = messages.into_iter().zip(&ciphertexts).collect();
+    execute_batch(&keys, &items).unwrap();
+}
+
+#[test]
+fn wrong_claimed_result_fails() {
+    let mut keys = Keys::new();
+    let ciphertext = keys.encrypt(2);
+    assert_failed_check(
+        execute_batch(&keys, &[(3, &ciphertext)]),
+        DecryptionFailure::ResultMismatch,
+    );
+}
+
+#[test]
+fn flipped_body_bit_fails() {
+    // Flipping the lowest bit of delta in the body adds one to the encrypted message
+    let mut keys = Keys::new();
+    let mut ciphertext = keys.encrypt(2);
+    *ciphertext.get_mut_body().data ^= keys.params.encoding.delta();
+    assert_failed_check(
+        execute_batch(&keys, &[(2, &ciphertext)]),
+        DecryptionFailure::ResultMismatch,
+    );
+}
+
+#[test]
+fn padding_overflow_fails() {
+    // The first message past the message space sets the padding bit
+    let mut keys = Keys::new();
+    let overflowed = keys.params.encoding.message_space();
+    let ciphertext = keys.encrypt(overflowed);
+    assert_failed_check(
+        execute_single(&keys, &ciphertext),
+        DecryptionFailure::PaddingOverflow,
+    );
+    assert_failed_check(
+        execute_batch(&keys, &[(overflowed, &ciphertext)]),
+        DecryptionFailure::PaddingOverflow,
+    );
+}
+
+#[test]
+fn tampering_fails_deterministically() {
+    let mut keys = Keys::new();
+    let ciphertext = keys.encrypt(1);
+    let errors: Vec<String> = (0..2)
+        .map(|_| {
+            execute_batch(&keys, &[(0, &ciphertext)])
+                .map(|_| ())
+                .unwrap_err()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(errors[0], errors[1]);
+}
+
+#[test]
+fn flipped_mask_bit_is_refused_at_verification() {
+    // The single decryption guest has no claimed result, it decrypts the tampered ciphertext but
+    // commits its digest, which is not the one of the ciphertext the verifier expects
+    let mut keys = Keys::new();
+    let ciphertext = keys.encrypt(1);
+    let mut tampered = ciphertext.clone();
+    tampered.get_mut_mask().as_mut()[0] ^= 1 << 40;
+
+    let session = execute_single(&keys, &tampered).unwrap();
+    let envelope: JournalEnvelope<DecryptionJournal> = decode_envelope(&session.journal).unwrap();
+    let secret_key_id = secret_key_digest(keys.lwe_sk.as_ref());
+    let error =
+        check_decryption_digests(&envelope.payload, &ciphertext, &secret_key_id, &keys.params)
+            .unwrap_err();
+    assert_eq!(error.failure_kind(), FailureKind::Rejected);
+}
+
+#[test]
+fn decryption_under_another_key_is_refused_at_verification() {
+    // The journal commits the ID of the key the guest decrypted with, a verifier expecting another
+    // key refuses it even though the ciphertext is the expected one
+    let mut keys = Keys::new();
+    let ciphertext = keys.encrypt(1);
+
+    let session = execute_single(&keys, &ciphertext).unwrap();
+    let envelope: JournalEnvelope<DecryptionJournal> = decode_envelope(&session.journal).unwrap();
+    let secret_key_id = secret_key_digest(keys.lwe_sk.as_ref());
+    check_decryption_digests(&envelope.payload, &ciphertext, &secret_key_id, &keys.params).unwrap();
+    let error = check_decryption_digests(&envelope.payload, &ciphertext, &[0; 32], &keys.params)
+        .unwrap_err();
+    assert_eq!(error.failure_kind(), FailureKind::Rejected);
+}
+
+#[test]
+fn panic_messages_carry_their_code() {
+    for failure in DecryptionFailure::ALL {
+        let message = format!("panicked at src/bin/batch_decrypt.rs:135:9:\n{failure}");
+        assert_eq!(DecryptionFailure::from_panic_message(&message), Some(failure));
+    }
+    assert_eq!(DecryptionFailure::from_panic_message("invalid encoding"), None);
+    let error = ProofError::GuestPanic {
+        message: "index out of bounds".to_string(),
+    };
+    assert_eq!(error.failure_kind(), FailureKind::Internal);
+}
+
+#[test]
+fn tampered_plaintext_opening_is_refused() {
+    // A sealed bid is revealed by its salt and plaintext, neither can be changed after the hash
+    // was committed
+    let policy = JournalPolicy::PlaintextHash { salt: [9; 32] };
+    let disclosed = disclosure(&policy, 3);
+    let opening = plaintext_opening(&policy, 3).unwrap();
+    assert_eq!(open_plaintext_commitment(&disclosed, &opening).unwrap(), 3);
+
+    for tampered in [
+        PlaintextOpening {
+            plaintext: 2,
+            ..opening
+        },
+        PlaintextOpening {
+            salt: [8; 32],
+            ..opening
+        },
+    ] {
+        let error = open_plaintext_commitment(&disclosed, &tampered).unwrap_err();
+        assert_eq!(error.failure_kind(), FailureKind::Rejected);
+    }
+    let error = plaintext_opening(&JournalPolicy::Plaintext, 3).unwrap_err();
+    assert_eq!(error.failure_kind(), FailureKind::InvalidInput);
+}
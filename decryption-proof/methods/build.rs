@@ -1,3 +1,36 @@
+use std::fmt::Write;
+use std::path::Path;
+
+/// Version of the guest crate, read from its manifest so it follows the binaries it describes.
+fn guest_version() -> String {
+    let manifest = std::fs::read_to_string("guest/Cargo.toml").expect("missing guest manifest");
+    manifest
+        .lines()
+        .find_map(|line| line.strip_prefix("version = "))
+        .map(|version| version.trim_matches('"').to_string())
+        .expect("guest manifest has no version")
+}
+
 fn main() {
-    risc0_build::embed_methods();
+    let guests = risc0_build::embed_methods();
+
+    // The generated constants only name each guest, services also need the list of them and the
+    // version they were built from to advertise the proofs they support
+    let mut guest_list = format!("const GUEST_VERSION: &str = {:?};\n\n", guest_version());
+    guest_list.push_str("const GUESTS: &[GuestMethod] = &[\n");
+    for guest in &guests {
+        writeln!(
+            guest_list,
+            "    GuestMethod {{ name: {:?}, image_id: {:?} }},",
+            guest.name,
+            guest.image_id.as_words()
+        )
+        .unwrap();
+    }
+    guest_list.push_str("];\n");
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    std::fs::write(Path::new(&out_dir).join("guest_list.rs"), guest_list)
+        .expect("could not write the guest list");
+    println!("cargo:rerun-if-changed=guest/Cargo.toml");
 }
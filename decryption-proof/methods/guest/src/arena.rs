@@ -0,0 +1,61 @@
+//! Global allocator of the guests: a bump arena over the zkVM heap with a budget sized from the
+//! committed parameters.
+//!
+//! Nothing is ever freed, so the heap cannot fragment, and every allocation is counted against the
+//! budget. The buffers of every batch item stay allocated, so the budgets grow with the batch.
+//! Once the guest knows its parameters it reserves the memory its largest inputs need, as budgeted
+//! by [`zkfhe_core::budget`], failing early with [`ValidationFailure::OutOfMemory`] when the guest
+//! memory cannot hold them, and an allocation past the budget halts the guest with the same exit
+//! code instead of trapping deep inside tfhe-rs.
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use risc0_zkvm_platform::memory::GUEST_MAX_MEM;
+use risc0_zkvm_platform::syscall::sys_alloc_aligned;
+use zkfhe_core::validation::ValidationFailure;
+
+use crate::journal::reject;
+use crate::validation::ensure;
+
+pub struct Arena {
+    used: AtomicUsize,
+    budget: AtomicUsize,
+}
+
+#[global_allocator]
+static ARENA: Arena = Arena {
+    used: AtomicUsize::new(0),
+    budget: AtomicUsize::new(usize::MAX),
+};
+
+unsafe impl GlobalAlloc for Arena {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let used = self.used.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        if used > self.budget.load(Ordering::Relaxed) {
+            // Committing the rejection must not come back here
+            self.budget.store(usize::MAX, Ordering::Relaxed);
+            reject(ValidationFailure::OutOfMemory);
+        }
+        sys_alloc_aligned(layout.size(), layout.align())
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
+        // Bump arena, memory is never reused
+    }
+}
+
+/// Bytes allocated so far.
+pub fn used() -> usize {
+    ARENA.used.load(Ordering::Relaxed)
+}
+
+/// Limit the arena to `bytes` more than what is already allocated, halting the guest when the
+/// guest memory left cannot hold them.
+pub fn reserve(bytes: usize) {
+    // A zero sized allocation returns the current end of the heap
+    let heap_end = unsafe { sys_alloc_aligned(0, 1) } as usize;
+    let available = GUEST_MAX_MEM.saturating_sub(heap_end);
+    ensure(bytes <= available, ValidationFailure::OutOfMemory);
+    ARENA.budget.store(used() + bytes, Ordering::Relaxed);
+}
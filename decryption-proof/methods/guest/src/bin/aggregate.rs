@@ -0,0 +1,37 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+
+use hello_guest::hashing::{aggregated_receipt_leaf, merkle_root};
+use hello_guest::journal::{check_journal_version, commit};
+use zkfhe_core::io::AggregationInput;
+use zkfhe_core::journal::{AggregationJournal, JournalKind};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let AggregationInput { receipts } = env::read();
+
+    // Every inner receipt is verified in the guest, the verification is recorded as an assumption
+    // the host resolves with the inner receipt, so the aggregated receipt only holds if all of them
+    // do. Their journals must have the layout of this build, the root commits to them as such
+    let leaves: Vec<[u8; 32]> = receipts
+        .iter()
+        .map(|receipt| {
+            env::verify(receipt.image_id, &receipt.journal).expect("inner receipt does not verify");
+            check_journal_version(&receipt.journal);
+            aggregated_receipt_leaf(&receipt.image_id, &receipt.journal)
+        })
+        .collect();
+
+    commit(
+        JournalKind::Aggregate,
+        &AggregationJournal {
+            receipt_count: receipts.len() as u32,
+            journals_root: merkle_root(&leaves),
+        },
+    );
+}
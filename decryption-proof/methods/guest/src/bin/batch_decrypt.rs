@@ -0,0 +1,176 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::arena::reserve;
+use hello_guest::entities::{is_trivial, lwe_ciphertext, lwe_keyswitch_key, lwe_secret_key};
+use hello_guest::hashing::{
+    decryption_leaf_digest, keyswitch_key_digest, lwe_ciphertext_digest, merkle_root,
+    secret_key_digest,
+};
+use hello_guest::journal::commit;
+use hello_guest::math::decrypt_lwe;
+use zkfhe_core::encoding::Rounding;
+use zkfhe_core::journal::{BatchJournal, DecryptionLeaf, JournalKind};
+use zkfhe_core::budget::batch_budget;
+use zkfhe_core::io::{BatchItem, DecryptionInput};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::profile::CycleProfile;
+use zkfhe_core::validation::DecryptionFailure;
+use hello_guest::profile::measure;
+use hello_guest::validation::{
+    big_lwe_dimension, check, validate_binary_secret_key, validate_encoding, validate_keyswitch_key,
+    validate_lwe_ciphertext, validate_lwe_secret_key, validate_native_modulus,
+    validate_unsigned_decoding,
+};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // Cycles of every phase, reported to the host to locate the expensive parts of the proof
+    let mut profile = CycleProfile::default();
+
+    // Read the typed inputs, each of them is deserialized exactly once. Decrypting needs no
+    // evaluation key, the PBS-inclusive proof is the job of the `pbs_decrypt` guest
+    let (params, input): (ProofParams, DecryptionInput) =
+        measure(&mut profile.deserialization, || (env::read(), env::read()));
+
+    // Check the inputs against the committed parameters before any crypto, a mismatch halts the
+    // guest with the exit code of the failed check
+    let big_lwe_dimension = big_lwe_dimension(&params);
+    reserve(batch_budget(&params, input.keyswitch.is_some(), input.batch_size));
+    validate_encoding(&params.encoding);
+    validate_unsigned_decoding(&params);
+    validate_native_modulus(&params);
+    validate_lwe_secret_key(&input.big_lwe_sk, big_lwe_dimension);
+    validate_binary_secret_key(&input.big_lwe_sk);
+    if let Some(keyswitch) = &input.keyswitch {
+        validate_keyswitch_key(&keyswitch.ksk, &params);
+        validate_lwe_secret_key(&keyswitch.small_lwe_sk, params.lwe_dimension);
+        validate_binary_secret_key(&keyswitch.small_lwe_sk);
+    }
+
+    // The keys are bound to the journal by their registry IDs, the digests of their canonical
+    // encodings. In keyswitch mode the batch is decrypted with the small secret key
+    let (secret_key_id, ksk_id) = measure(&mut profile.hashing, || match &input.keyswitch {
+        Some(keyswitch) => (
+            secret_key_digest(&keyswitch.small_lwe_sk.data),
+            Some(keyswitch_key_digest(&keyswitch.ksk)),
+        ),
+        None => (secret_key_digest(&input.big_lwe_sk.data), None),
+    });
+
+    let batch_size = input.batch_size;
+    let seed_commitment = input.seed_commitment;
+    let circuit_digest = input.circuit_digest;
+    let (big_lwe_sk, keyswitch) = measure(&mut profile.deserialization, || {
+        let big_lwe_sk: LweSecretKeyOwned<u64> = lwe_secret_key(input.big_lwe_sk);
+        let keyswitch: Option<(LweKeyswitchKeyOwned<u64>, LweSecretKeyOwned<u64>)> =
+            input.keyswitch.map(|keyswitch| {
+                (
+                    lwe_keyswitch_key(keyswitch.ksk),
+                    lwe_secret_key(keyswitch.small_lwe_sk),
+                )
+            });
+        (big_lwe_sk, keyswitch)
+    });
+
+    // The decoder is built from the committed encoding, so ciphertexts of any message, carry and
+    // padding layout can be proven
+    let rounding = Rounding::<u64>::new(&params.encoding);
+
+    // Ciphertexts are streamed one at a time so only the current one is held in memory. Nothing is
+    // committed before the whole batch is checked, so continuations can split the decryption
+    // checks over as many segments as needed and the journal stays that of one proof. Only the
+    // leaf digests are kept, the journal commits their Merkle root
+    let mut leaves = Vec::with_capacity(batch_size as usize);
+    let mut trivial = false;
+    for _ in 0..batch_size {
+        let (cleartext_multiplication_result, pbs_multiplication_ct) =
+            measure(&mut profile.deserialization, || {
+                let item: BatchItem = env::read();
+                validate_lwe_ciphertext(&item.pbs_multiplication_ct, big_lwe_dimension, 0);
+                let pbs_multiplication_ct: LweCiphertextOwned<u64> =
+                    lwe_ciphertext(item.pbs_multiplication_ct);
+                (item.cleartext_multiplication_result, pbs_multiplication_ct)
+            });
+
+        trivial |= is_trivial(&pbs_multiplication_ct);
+
+        // In keyswitch mode re-perform the keyswitch of the PBS output so the decrypted ciphertext
+        // is bound to the ciphertext it was derived from
+        let (decrypted_ct, source_ct_digest) = match &keyswitch {
+            Some((ksk, _)) => {
+                let mut keyswitched_ct = LweCiphertext::new(
+                    0u64,
+                    ksk.output_lwe_size(),
+                    pbs_multiplication_ct.ciphertext_modulus(),
+                );
+                measure(&mut profile.keyswitch, || {
+                    keyswitch_lwe_ciphertext(ksk, &pbs_multiplication_ct, &mut keyswitched_ct)
+                });
+                (keyswitched_ct, Some(lwe_ciphertext_digest(pbs_multiplication_ct.as_ref(), 0)))
+            }
+            None => (pbs_multiplication_ct.clone(), None),
+        };
+        let decryption_sk = match &keyswitch {
+            Some((_, small_lwe_sk)) => small_lwe_sk,
+            None => &big_lwe_sk,
+        };
+
+        // Decrypt and verify, the ciphertexts are on the native modulus checked above
+        let pbs_multiplication_result = measure(&mut profile.decryption, || {
+            let pbs_multiplication_plaintext = decrypt_lwe(decryption_sk, &decrypted_ct);
+            rounding.decode(pbs_multiplication_plaintext.0)
+        });
+
+        // Verify results match
+        check(
+            cleartext_multiplication_result == pbs_multiplication_result,
+            DecryptionFailure::ResultMismatch,
+        );
+        // A claimed result setting a padding bit is refused too, every leaf is in range
+        check(
+            !params.encoding.overflows(pbs_multiplication_result.into()),
+            DecryptionFailure::PaddingOverflow,
+        );
+
+        let leaf = measure(&mut profile.hashing, || {
+            decryption_leaf_digest(&DecryptionLeaf {
+                ct_digest: lwe_ciphertext_digest(decrypted_ct.as_ref(), 0),
+                source_ct_digest,
+                plaintext: pbs_multiplication_result,
+            })
+        });
+        leaves.push(leaf);
+    }
+    let decryptions_root = measure(&mut profile.hashing, || merkle_root(&leaves));
+
+    // The profile goes to the host stdout, it is not committed
+    env::write(&profile);
+
+    // Commit the parameters along with the Merkle root of the digest and decrypted value of every
+    // ciphertext of the batch
+    commit(
+        JournalKind::BatchDecrypt,
+        &BatchJournal {
+            params,
+            secret_key_id,
+            ksk_id,
+            ntt_bsk_digest: None,
+            ntt_params: None,
+            multi_bit_bsk_digest: None,
+            grouping_factor: None,
+            lut: None,
+            seed_commitment,
+            circuit_digest,
+            trivial,
+            decryption_count: batch_size,
+            decryptions_root,
+        },
+    );
+}
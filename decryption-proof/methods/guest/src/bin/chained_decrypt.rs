@@ -0,0 +1,107 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::decoding::decode_message_and_carry_modulo;
+use hello_guest::entities::{lwe_ciphertext, lwe_secret_key};
+use hello_guest::hashing::{lwe_ciphertext_digest, params_digest, secret_key_digest, sha256_bytes};
+use hello_guest::journal::{check_journal_version, commit};
+use hello_guest::math::decrypt_lwe;
+use hello_guest::validation::{
+    big_lwe_dimension, ensure, validate_binary_secret_key, validate_encoding,
+    validate_lwe_ciphertext, validate_lwe_secret_key, validate_unsigned_decoding,
+    NTT64_CIPHERTEXT_MODULUS,
+};
+use zkfhe_core::io::ChainedDecryptionInput;
+use zkfhe_core::journal::{
+    ChainedDecryptionJournal, JournalEnvelope, JournalHeader, JournalKind, KeyswitchJournal,
+    SampleExtractJournal,
+};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::validation::ValidationFailure;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    let ChainedDecryptionInput {
+        lwe_sk,
+        ciphertext,
+        evaluation,
+    } = env::read();
+    let params_digest = params_digest(&params);
+
+    // The evaluation receipt is verified as an assumption the host resolves with the receipt
+    // itself, so the decryption receipt only holds if it does
+    env::verify(evaluation.image_id, &evaluation.journal)
+        .expect("evaluation receipt does not verify");
+    check_journal_version(&evaluation.journal);
+    let header: JournalHeader =
+        risc0_zkvm::serde::from_slice(&evaluation.journal).expect("journal has no envelope");
+
+    // The evaluation journal names the ciphertext its step output: a sample extract outputs a
+    // ciphertext under the flattened GLWE key, on the native or the NTT modulus, a keyswitch one
+    // under the small key, on the native modulus
+    let (output_ct_digest, lwe_dimension, ciphertext_modulus) = match header.kind {
+        JournalKind::SampleExtract => {
+            let envelope: JournalEnvelope<SampleExtractJournal> =
+                risc0_zkvm::serde::from_slice(&evaluation.journal)
+                    .expect("malformed sample extract journal");
+            assert!(
+                envelope.payload.params_digest == params_digest,
+                "the sample extract was proven under other parameters"
+            );
+            (
+                envelope.payload.extracted_ct_digest,
+                big_lwe_dimension(&params),
+                ciphertext.ciphertext_modulus,
+            )
+        }
+        JournalKind::Keyswitch => {
+            let envelope: JournalEnvelope<KeyswitchJournal> =
+                risc0_zkvm::serde::from_slice(&evaluation.journal)
+                    .expect("malformed keyswitch journal");
+            (envelope.payload.output_ct_digest, params.lwe_dimension, 0)
+        }
+        kind => panic!("a {kind:?} journal attests no evaluation output"),
+    };
+
+    validate_encoding(&params.encoding);
+    validate_unsigned_decoding(&params);
+    ensure(
+        ciphertext_modulus == 0 || ciphertext_modulus == NTT64_CIPHERTEXT_MODULUS,
+        ValidationFailure::CiphertextModulus,
+    );
+    validate_lwe_secret_key(&lwe_sk, lwe_dimension);
+    validate_binary_secret_key(&lwe_sk);
+    validate_lwe_ciphertext(&ciphertext, lwe_dimension, ciphertext_modulus);
+
+    let ct_digest = lwe_ciphertext_digest(&ciphertext.data, ciphertext_modulus);
+    assert!(
+        ct_digest == output_ct_digest,
+        "the evaluation journal does not attest the decrypted ciphertext"
+    );
+    let secret_key_id = secret_key_digest(&lwe_sk.data);
+    let lwe_sk: LweSecretKeyOwned<u64> = lwe_secret_key(lwe_sk);
+    let ciphertext: LweCiphertextOwned<u64> = lwe_ciphertext(ciphertext);
+
+    let plaintext = decrypt_lwe(&lwe_sk, &ciphertext);
+    commit(
+        JournalKind::ChainedDecrypt,
+        &ChainedDecryptionJournal {
+            evaluation_image_id: evaluation.image_id,
+            evaluation_kind: header.kind,
+            evaluation_journal_digest: sha256_bytes(&evaluation.journal),
+            ct_digest,
+            secret_key_id,
+            plaintext: decode_message_and_carry_modulo(
+                &params.encoding,
+                plaintext.0,
+                ciphertext_modulus,
+            ),
+            params_digest,
+        },
+    );
+}
@@ -0,0 +1,68 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::entities::{lwe_compact_ciphertext_list, lwe_secret_key};
+use hello_guest::hashing::{lwe_compact_ciphertext_list_digest, params_digest, secret_key_digest};
+use hello_guest::journal::commit;
+use hello_guest::math::LweDecryptionKey;
+use hello_guest::validation::{
+    big_lwe_dimension, validate_binary_secret_key, validate_encoding,
+    validate_lwe_compact_ciphertext_list, validate_lwe_secret_key, validate_native_modulus,
+    validate_unsigned_decoding,
+};
+use zkfhe_core::encoding::Rounding;
+use zkfhe_core::io::CompactListDecryptionInput;
+use zkfhe_core::journal::{CompactListDecryptionJournal, JournalKind};
+use zkfhe_core::params::ProofParams;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    let CompactListDecryptionInput { lwe_sk, list } = env::read();
+
+    // The list is encrypted with a compact public key of the flattened GLWE key, the expansion
+    // only rotates masks on the native modulus
+    validate_encoding(&params.encoding);
+    validate_unsigned_decoding(&params);
+    validate_native_modulus(&params);
+    validate_lwe_secret_key(&lwe_sk, big_lwe_dimension(&params));
+    validate_binary_secret_key(&lwe_sk);
+    validate_lwe_compact_ciphertext_list(&list, big_lwe_dimension(&params), 0);
+
+    let list_digest = lwe_compact_ciphertext_list_digest(&list);
+    let secret_key_id = secret_key_digest(&lwe_sk.data);
+    let lwe_sk = LweDecryptionKey::new(lwe_secret_key(lwe_sk));
+    let list = lwe_compact_ciphertext_list(list);
+
+    // Expand the list in the guest, the journal commits the list the client produced and the
+    // decryption of every ciphertext it expands to
+    let mut ciphertexts = LweCiphertextList::new(
+        0u64,
+        list.lwe_size(),
+        list.lwe_ciphertext_count(),
+        list.ciphertext_modulus(),
+    );
+    expand_lwe_compact_ciphertext_list(&mut ciphertexts, &list);
+
+    let rounding = Rounding::<u64>::new(&params.encoding);
+    let plaintexts: Vec<u64> = ciphertexts
+        .iter()
+        .map(|ciphertext| rounding.decode(lwe_sk.decrypt(&ciphertext).0))
+        .collect();
+
+    commit(
+        JournalKind::CompactListDecrypt,
+        &CompactListDecryptionJournal {
+            list_digest,
+            secret_key_id,
+            plaintexts,
+            params_digest: params_digest(&params),
+        },
+    );
+}
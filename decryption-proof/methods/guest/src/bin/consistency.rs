@@ -0,0 +1,76 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::decoding::{decode_message_and_carry_modulo, noise_budget_bits};
+use hello_guest::entities::{lwe_ciphertext, lwe_secret_key};
+use hello_guest::hashing::{lwe_ciphertext_digest, params_digest, secret_key_digest};
+use hello_guest::journal::commit;
+use hello_guest::math::decrypt_lwe;
+use hello_guest::validation::{
+    big_lwe_dimension, validate_binary_secret_key, validate_ciphertext_modulus, validate_encoding,
+    validate_lwe_ciphertext, validate_lwe_secret_key,
+};
+use zkfhe_core::io::{ConsistencyInput, LweDecryptionInput};
+use zkfhe_core::journal::{ConsistencyJournal, JournalKind};
+use zkfhe_core::params::ProofParams;
+
+risc0_zkvm::guest::entry!(main);
+
+/// Digest, key ID and decoded message of one side. A decryption whose noise reaches the decoding
+/// bound is not proven, its message could not be compared.
+fn decrypt(params: &ProofParams, input: LweDecryptionInput) -> ([u8; 32], [u8; 32], u64) {
+    let LweDecryptionInput { lwe_sk, ciphertext } = input;
+    // A key of the small dimension is a small key, any other must be a big one
+    let lwe_dimension = if lwe_sk.data.len() == params.lwe_dimension {
+        params.lwe_dimension
+    } else {
+        big_lwe_dimension(params)
+    };
+    validate_lwe_secret_key(&lwe_sk, lwe_dimension);
+    validate_binary_secret_key(&lwe_sk);
+    validate_lwe_ciphertext(&ciphertext, lwe_dimension, params.ciphertext_modulus);
+
+    let ct_digest = lwe_ciphertext_digest(&ciphertext.data, ciphertext.ciphertext_modulus);
+    let secret_key_id = secret_key_digest(&lwe_sk.data);
+    let lwe_sk: LweSecretKeyOwned<u64> = lwe_secret_key(lwe_sk);
+    let ciphertext: LweCiphertextOwned<u64> = lwe_ciphertext(ciphertext);
+
+    let plaintext = decrypt_lwe(&lwe_sk, &ciphertext);
+    let message =
+        decode_message_and_carry_modulo(&params.encoding, plaintext.0, params.ciphertext_modulus);
+    noise_budget_bits(
+        &params.encoding,
+        plaintext.0,
+        message,
+        params.ciphertext_modulus,
+    );
+    (ct_digest, secret_key_id, message)
+}
+
+fn main() {
+    let params: ProofParams = env::read();
+    let ConsistencyInput { left, right } = env::read();
+
+    // Each ciphertext is under a big or a small key of the parameters, both on the modulus of the
+    // parameters and decoded with the same encoding
+    validate_encoding(&params.encoding);
+    validate_ciphertext_modulus(&params);
+    let (left_ct_digest, left_secret_key_id, left_message) = decrypt(&params, left);
+    let (right_ct_digest, right_secret_key_id, right_message) = decrypt(&params, right);
+
+    // Only the equality bit of the messages is committed
+    commit(
+        JournalKind::Consistency,
+        &ConsistencyJournal {
+            left_ct_digest,
+            left_secret_key_id,
+            right_ct_digest,
+            right_secret_key_id,
+            equal: left_message == right_message,
+            params_digest: params_digest(&params),
+        },
+    );
+}
@@ -0,0 +1,89 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::decoding::{decode_message_and_carry_modulo, noise_budget_bits};
+use hello_guest::entities::{lwe_ciphertext, lwe_secret_key};
+use hello_guest::frames::read_public_input;
+use hello_guest::hashing::{params_digest, secret_key_digest};
+use hello_guest::journal::{commit, reject};
+use hello_guest::math::LweDecryptionKey;
+use hello_guest::validation::{
+    big_lwe_dimension, check, validate_binary_secret_key, validate_ciphertext_modulus,
+    validate_encoding, validate_lwe_ciphertext, validate_lwe_secret_key,
+};
+use zkfhe_core::canonical::decode_lwe_ciphertext;
+use zkfhe_core::io::LweSecretKeyData;
+use zkfhe_core::journal::{DecryptionJournal, JournalKind, ParamsSummary};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::validation::{DecryptionFailure, ValidationFailure};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    // The request ID takes no part in the statement, it is committed as read
+    let request_id: Option<[u8; 32]> = env::read();
+    // The ciphertext is the public statement: its digest is the SHA-256 of the public input frame
+    // as read, and only the secret key read after it is private
+    let (public_input, ct_digest) = read_public_input();
+    let Some(ciphertext) = decode_lwe_ciphertext(&public_input) else {
+        reject(ValidationFailure::PublicInput);
+    };
+    let lwe_sk: LweSecretKeyData = env::read();
+
+    // The ciphertext is under the flattened GLWE key, on the modulus of the parameters: a PBS
+    // output on the native modulus or a fresh encryption on a non-native one
+    validate_encoding(&params.encoding);
+    validate_ciphertext_modulus(&params);
+    validate_lwe_secret_key(&lwe_sk, big_lwe_dimension(&params));
+    validate_binary_secret_key(&lwe_sk);
+    validate_lwe_ciphertext(
+        &ciphertext,
+        big_lwe_dimension(&params),
+        params.ciphertext_modulus,
+    );
+
+    let secret_key_id = secret_key_digest(&lwe_sk.data);
+    // tfhe-rs keys are binary, their ones are gathered once and the decryption only adds
+    let lwe_sk = LweDecryptionKey::new(lwe_secret_key(lwe_sk));
+    let ciphertext: LweCiphertextOwned<u64> = lwe_ciphertext(ciphertext);
+
+    let plaintext = lwe_sk.decrypt(&ciphertext);
+    let message =
+        decode_message_and_carry_modulo(&params.encoding, plaintext.0, params.ciphertext_modulus);
+    // An overflow of the computation that produced the ciphertext sets the padding, its result is
+    // out of range and is not proven
+    check(
+        !params.encoding.overflows(message.into()),
+        DecryptionFailure::PaddingOverflow,
+    );
+    // A decryption whose noise reaches the decoding bound is not proven
+    let noise_budget_bits = noise_budget_bits(
+        &params.encoding,
+        plaintext.0,
+        message,
+        params.ciphertext_modulus,
+    );
+    // A signed message is committed sign extended, a torus value as the rounded plaintext
+    let plaintext = params
+        .encoding
+        .output(params.decoding, message, params.ciphertext_modulus);
+
+    commit(
+        JournalKind::Decrypt,
+        &DecryptionJournal {
+            ct_digest,
+            secret_key_id,
+            source_ct_digest: None,
+            plaintext,
+            overflow: false,
+            params_digest: params_digest(&params),
+            params: ParamsSummary::from(&params),
+            noise_budget_bits: Some(noise_budget_bits),
+            request_id,
+        },
+    );
+}
@@ -0,0 +1,62 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::decoding::{decode_message_and_carry_u128, noise_budget_bits_u128};
+use hello_guest::entities::{lwe_ciphertext, lwe_secret_key};
+use hello_guest::hashing::{lwe_ciphertext_u128_digest, params_digest, secret_key_u128_digest};
+use hello_guest::journal::commit;
+use hello_guest::validation::{
+    big_lwe_dimension, check, validate_binary_secret_key, validate_encoding_for,
+    validate_lwe_ciphertext, validate_lwe_secret_key, validate_native_modulus,
+    validate_unsigned_decoding,
+};
+use zkfhe_core::io::LweDecryptionInput;
+use zkfhe_core::journal::{DecryptionJournal, JournalKind, ParamsSummary};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::validation::DecryptionFailure;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    let request_id: Option<[u8; 32]> = env::read();
+    let LweDecryptionInput { lwe_sk, ciphertext }: LweDecryptionInput<u128> = env::read();
+
+    // Same statement as the `decrypt` guest, on the native 128 bits modulus
+    validate_encoding_for(&params.encoding, u128::BITS);
+    validate_unsigned_decoding(&params);
+    validate_native_modulus(&params);
+    validate_lwe_secret_key(&lwe_sk, big_lwe_dimension(&params));
+    validate_binary_secret_key(&lwe_sk);
+    validate_lwe_ciphertext(&ciphertext, big_lwe_dimension(&params), 0);
+
+    let ct_digest = lwe_ciphertext_u128_digest(&ciphertext.data, ciphertext.ciphertext_modulus);
+    let secret_key_id = secret_key_u128_digest(&lwe_sk.data);
+    let lwe_sk: LweSecretKeyOwned<u128> = lwe_secret_key(lwe_sk);
+    let ciphertext: LweCiphertextOwned<u128> = lwe_ciphertext(ciphertext);
+
+    let plaintext = decrypt_lwe_ciphertext(&lwe_sk, &ciphertext);
+    let message = decode_message_and_carry_u128(&params.encoding, plaintext.0);
+    check(
+        !params.encoding.overflows(message),
+        DecryptionFailure::PaddingOverflow,
+    );
+
+    commit(
+        JournalKind::DecryptU128,
+        &DecryptionJournal {
+            ct_digest,
+            secret_key_id,
+            source_ct_digest: None,
+            plaintext: message,
+            overflow: false,
+            params_digest: params_digest(&params),
+            params: ParamsSummary::from(&params),
+            noise_budget_bits: Some(noise_budget_bits_u128(&params.encoding, plaintext.0, message)),
+            request_id,
+        },
+    );
+}
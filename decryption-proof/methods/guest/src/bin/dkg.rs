@@ -0,0 +1,82 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+
+use hello_guest::hashing::{public_key_digest, secret_key_digest, sha256_bytes};
+use hello_guest::journal::commit;
+use hello_guest::validation::{check, ensure, validate_lwe_public_key};
+use zkfhe_core::io::DkgInput;
+use zkfhe_core::journal::{DkgJournal, JournalKind};
+use zkfhe_core::validation::{DecryptionFailure, ValidationFailure};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let DkgInput {
+        public_key,
+        parties,
+        noise_bound_log2,
+    } = env::read();
+
+    // The key shares are additive shares modulo 2^64, as the partial decryptions combine them
+    validate_lwe_public_key(&public_key, 0);
+    let lwe_dimension = public_key.lwe_size - 1;
+    let encryption_count = public_key.data.len() / public_key.lwe_size;
+    ensure(!parties.is_empty(), ValidationFailure::SecretKeyDimension);
+    for party in &parties {
+        ensure(
+            party.key_share.len() == lwe_dimension,
+            ValidationFailure::SecretKeyDimension,
+        );
+        ensure(
+            party.public_key_share.len() == encryption_count,
+            ValidationFailure::CiphertextDimension,
+        );
+    }
+    // The noise of the aggregated key, the sum of the parties' noises, must stay far from the
+    // message of an encryption under it
+    ensure(
+        noise_bound_log2.saturating_add(parties.len().ilog2() + 1) < u64::BITS - 1,
+        ValidationFailure::NoiseBound,
+    );
+
+    // Every party's body encrypts zero under its share with the common mask, and the bodies sum to
+    // the one of the aggregated key, which then encrypts zero under the sum of the shares
+    for (index, encryption) in public_key.data.chunks_exact(public_key.lwe_size).enumerate() {
+        let (mask, body) = encryption.split_at(lwe_dimension);
+        let mut body_sum = 0u64;
+        for party in &parties {
+            let party_body = party.public_key_share[index];
+            let dot_product = mask
+                .iter()
+                .zip(party.key_share.iter())
+                .fold(0u64, |acc, (&mask_element, &key_element)| {
+                    acc.wrapping_add(mask_element.wrapping_mul(key_element))
+                });
+            let noise = party_body.wrapping_sub(dot_product) as i64;
+            check(
+                noise.unsigned_abs() < 1 << noise_bound_log2,
+                DecryptionFailure::KeyNoise,
+            );
+            body_sum = body_sum.wrapping_add(party_body);
+        }
+        check(body_sum == body[0], DecryptionFailure::RecomputationMismatch);
+    }
+
+    let share_commitments: Vec<[u8; 32]> = parties
+        .iter()
+        .map(|party| secret_key_digest(&party.key_share))
+        .collect();
+    commit(
+        JournalKind::Dkg,
+        &DkgJournal {
+            key_commitment: sha256_bytes(share_commitments.concat().as_slice()),
+            share_commitments,
+            public_key_digest: public_key_digest(&public_key),
+            noise_bound_log2,
+        },
+    );
+}
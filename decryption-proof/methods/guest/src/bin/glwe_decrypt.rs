@@ -0,0 +1,66 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::entities::{glwe_ciphertext, glwe_secret_key};
+use hello_guest::hashing::{glwe_ciphertext_digest, params_digest, secret_key_digest};
+use hello_guest::journal::commit;
+use hello_guest::validation::{
+    validate_binary_glwe_secret_key, validate_encoding, validate_glwe_ciphertext,
+    validate_glwe_secret_key, validate_native_modulus, validate_unsigned_decoding,
+};
+use zkfhe_core::encoding::Rounding;
+use zkfhe_core::io::GlweDecryptionInput;
+use zkfhe_core::journal::{GlweDecryptionJournal, JournalKind};
+use zkfhe_core::params::ProofParams;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    let input: GlweDecryptionInput = env::read();
+
+    // The decoder rounds on the native 64 bits torus
+    validate_encoding(&params.encoding);
+    validate_unsigned_decoding(&params);
+    validate_native_modulus(&params);
+    validate_glwe_secret_key(&input.glwe_sk, &params);
+    validate_binary_glwe_secret_key(&input.glwe_sk);
+    validate_glwe_ciphertext(&input.ciphertext, &params, 0);
+
+    let ct_digest = glwe_ciphertext_digest(
+        &input.ciphertext.data,
+        input.ciphertext.polynomial_size,
+        input.ciphertext.ciphertext_modulus,
+    );
+    // The GLWE key is named by the ID of its flattened LWE key, the same coefficients
+    let secret_key_id = secret_key_digest(&input.glwe_sk.data);
+    let glwe_sk: GlweSecretKeyOwned<u64> = glwe_secret_key(input.glwe_sk);
+    let ciphertext: GlweCiphertextOwned<u64> = glwe_ciphertext(input.ciphertext);
+
+    let rounding = Rounding::<u64>::new(&params.encoding);
+
+    // Recover the whole plaintext polynomial, then decode every coefficient
+    let mut plaintext_list =
+        PlaintextList::new(0u64, PlaintextCount(ciphertext.polynomial_size().0));
+    decrypt_glwe_ciphertext(&glwe_sk, &ciphertext, &mut plaintext_list);
+
+    let plaintexts: Vec<u64> = plaintext_list
+        .iter()
+        .map(|plaintext| rounding.decode(*plaintext.0))
+        .collect();
+
+    commit(
+        JournalKind::GlweDecrypt,
+        &GlweDecryptionJournal {
+            ct_digest,
+            secret_key_id,
+            plaintexts,
+            params_digest: params_digest(&params),
+        },
+    );
+}
@@ -0,0 +1,77 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::entities::{lwe_keyswitch_key, lwe_secret_key};
+use hello_guest::hashing::{keyswitch_key_digest, params_digest, secret_key_digest};
+use hello_guest::journal::commit;
+use hello_guest::math::decrypt_lwe;
+use hello_guest::validation::{
+    big_lwe_dimension, check, ensure, validate_binary_secret_key, validate_keyswitch_key,
+    validate_lwe_secret_key,
+};
+use zkfhe_core::io::KeyGenerationInput;
+use zkfhe_core::journal::{JournalKind, KeyGenerationJournal};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::validation::{DecryptionFailure, ValidationFailure};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    let KeyGenerationInput {
+        ksk,
+        input_lwe_sk,
+        output_lwe_sk,
+        noise_bound_log2,
+    } = env::read();
+
+    validate_keyswitch_key(&ksk, &params);
+    ensure(
+        params.ks_base_log > 0 && params.ks_base_log * params.ks_level <= u64::BITS as usize,
+        ValidationFailure::Decomposition,
+    );
+    validate_lwe_secret_key(&input_lwe_sk, big_lwe_dimension(&params));
+    validate_lwe_secret_key(&output_lwe_sk, params.lwe_dimension);
+    validate_binary_secret_key(&input_lwe_sk);
+    validate_binary_secret_key(&output_lwe_sk);
+    // The top level encrypts a key element shifted by `64 - ks_base_log` bits, a noise reaching it
+    // would let any key element pass
+    ensure(
+        (noise_bound_log2 as usize) + params.ks_base_log < u64::BITS as usize,
+        ValidationFailure::NoiseBound,
+    );
+
+    let journal = KeyGenerationJournal {
+        ksk_id: keyswitch_key_digest(&ksk),
+        input_secret_key_id: secret_key_digest(&input_lwe_sk.data),
+        output_secret_key_id: secret_key_digest(&output_lwe_sk.data),
+        params_digest: params_digest(&params),
+        noise_bound_log2,
+    };
+    let ksk: LweKeyswitchKeyOwned<u64> = lwe_keyswitch_key(ksk);
+    let input_lwe_sk: LweSecretKeyOwned<u64> = lwe_secret_key(input_lwe_sk);
+    let output_lwe_sk: LweSecretKeyOwned<u64> = lwe_secret_key(output_lwe_sk);
+
+    // Block `i` of the key encrypts the decomposition of the `i`-th input key element, its rows
+    // from the last level down to the first as tfhe-rs generates them: row `j` holds the element
+    // shifted to level `level_count - j`
+    let base_log = params.ks_base_log;
+    let level_count = params.ks_level;
+    for (&input_key_element, block) in input_lwe_sk.as_ref().iter().zip(ksk.iter()) {
+        for (row, encryption) in block.iter().enumerate() {
+            let level = level_count - row;
+            let expected = input_key_element << (u64::BITS as usize - base_log * level);
+            let plaintext = decrypt_lwe(&output_lwe_sk, &encryption);
+            let noise = plaintext.0.wrapping_sub(expected) as i64;
+            check(
+                noise.unsigned_abs() < 1 << noise_bound_log2,
+                DecryptionFailure::KeyNoise,
+            );
+        }
+    }
+
+    commit(JournalKind::KeyGeneration, &journal);
+}
@@ -0,0 +1,70 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::decoding::{decode_message_and_carry_modulo, noise_budget_bits};
+use hello_guest::entities::{lwe_ciphertext, lwe_keyswitch_key, lwe_secret_key};
+use hello_guest::hashing::{
+    keyswitch_key_digest, lwe_ciphertext_digest, params_digest, plaintext_commitment,
+    secret_key_digest,
+};
+use hello_guest::journal::commit;
+use hello_guest::math::decrypt_lwe;
+use hello_guest::validation::{
+    validate_binary_secret_key, validate_encoding, validate_keyswitch_input,
+    validate_lwe_secret_key,
+};
+use zkfhe_core::io::KeyRotationInput;
+use zkfhe_core::journal::{JournalKind, KeyRotationJournal};
+use zkfhe_core::params::ProofParams;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    let KeyRotationInput {
+        ksk,
+        old_ciphertext,
+        new_lwe_sk,
+        salt,
+    } = env::read();
+
+    // The old and the new key may have any dimension, the keyswitching key links them and its
+    // well-formedness is proven on its own by the key generation guest
+    validate_encoding(&params.encoding);
+    validate_keyswitch_input(&ksk, &old_ciphertext);
+    validate_lwe_secret_key(&new_lwe_sk, ksk.output_lwe_size - 1);
+    validate_binary_secret_key(&new_lwe_sk);
+
+    let ksk_id = keyswitch_key_digest(&ksk);
+    let old_ct_digest = lwe_ciphertext_digest(&old_ciphertext.data, 0);
+    let new_secret_key_id = secret_key_digest(&new_lwe_sk.data);
+    let ksk: LweKeyswitchKeyOwned<u64> = lwe_keyswitch_key(ksk);
+    let old_ciphertext: LweCiphertextOwned<u64> = lwe_ciphertext(old_ciphertext);
+    let new_lwe_sk: LweSecretKeyOwned<u64> = lwe_secret_key(new_lwe_sk);
+
+    let mut new_ciphertext =
+        LweCiphertext::new(0u64, ksk.output_lwe_size(), ksk.ciphertext_modulus());
+    keyswitch_lwe_ciphertext(&ksk, &old_ciphertext, &mut new_ciphertext);
+    let new_ct_digest = lwe_ciphertext_digest(new_ciphertext.as_ref(), 0);
+
+    // A rotated ciphertext whose noise reaches the decoding bound could decode to another message,
+    // it is not proven
+    let plaintext = decrypt_lwe(&new_lwe_sk, &new_ciphertext);
+    let message = decode_message_and_carry_modulo(&params.encoding, plaintext.0, 0);
+    noise_budget_bits(&params.encoding, plaintext.0, message, 0);
+
+    commit(
+        JournalKind::KeyRotation,
+        &KeyRotationJournal {
+            ksk_id,
+            old_ct_digest,
+            new_ct_digest,
+            new_secret_key_id,
+            plaintext_commitment: plaintext_commitment(&salt, message),
+            params_digest: params_digest(&params),
+        },
+    );
+}
@@ -0,0 +1,36 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::entities::{lwe_ciphertext, lwe_keyswitch_key};
+use hello_guest::hashing::{keyswitch_key_digest, lwe_ciphertext_digest};
+use hello_guest::journal::commit;
+use hello_guest::validation::{check, validate_keyswitch};
+use zkfhe_core::io::KeyswitchProofInput;
+use zkfhe_core::journal::{JournalKind, KeyswitchJournal};
+use zkfhe_core::validation::DecryptionFailure;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let KeyswitchProofInput { ksk, input, output } = env::read();
+    validate_keyswitch(&ksk, &input, &output);
+
+    let journal = KeyswitchJournal {
+        ksk_id: keyswitch_key_digest(&ksk),
+        input_ct_digest: lwe_ciphertext_digest(&input.data, 0),
+        output_ct_digest: lwe_ciphertext_digest(&output.data, 0),
+    };
+    let ksk: LweKeyswitchKeyOwned<u64> = lwe_keyswitch_key(ksk);
+    let input: LweCiphertextOwned<u64> = lwe_ciphertext(input);
+    let output: LweCiphertextOwned<u64> = lwe_ciphertext(output);
+
+    // The keyswitch is deterministic, the claimed output must be exactly the recomputed one
+    let mut keyswitched = LweCiphertext::new(0u64, ksk.output_lwe_size(), ksk.ciphertext_modulus());
+    keyswitch_lwe_ciphertext(&ksk, &input, &mut keyswitched);
+    check(keyswitched == output, DecryptionFailure::RecomputationMismatch);
+
+    commit(JournalKind::Keyswitch, &journal);
+}
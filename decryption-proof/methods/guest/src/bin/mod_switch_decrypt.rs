@@ -0,0 +1,65 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+
+use hello_guest::hashing::{lwe_ciphertext_digest, params_digest, secret_key_digest};
+use hello_guest::journal::commit;
+use hello_guest::validation::{
+    ensure, validate_binary_secret_key, validate_encoding, validate_lwe_ciphertext,
+    validate_lwe_secret_key, validate_native_modulus, validate_unsigned_decoding,
+};
+use zkfhe_core::io::LweDecryptionInput;
+use zkfhe_core::journal::{JournalKind, ModulusSwitchJournal};
+use zkfhe_core::modulus_switch::{blind_rotation_modulus_log, modulus_switch};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::validation::ValidationFailure;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    let LweDecryptionInput { lwe_sk, ciphertext } = env::read();
+
+    // The ciphertext is a blind rotation input, on the native modulus under the small LWE key. The
+    // encoded bits must survive the switch to 2N
+    let log_modulus = blind_rotation_modulus_log(params.polynomial_size);
+    validate_encoding(&params.encoding);
+    validate_unsigned_decoding(&params);
+    ensure(
+        params.encoding.total_bits() < log_modulus,
+        ValidationFailure::InvalidEncoding,
+    );
+    validate_native_modulus(&params);
+    validate_lwe_secret_key(&lwe_sk, params.lwe_dimension);
+    validate_binary_secret_key(&lwe_sk);
+    validate_lwe_ciphertext(&ciphertext, params.lwe_dimension, 0);
+
+    // Switch every coefficient as the blind rotation does, then decrypt on the switched modulus.
+    // The modulus is a power of two, so wrapping arithmetic reduced by a mask is exact
+    let switched: Vec<u64> = ciphertext
+        .data
+        .iter()
+        .map(|&coefficient| modulus_switch(coefficient, log_modulus))
+        .collect();
+    let (body, mask) = switched.split_last().expect("a ciphertext has a body");
+    let mask_key_dot_product = mask
+        .iter()
+        .zip(&lwe_sk.data)
+        .fold(0u64, |acc, (&a, &s)| acc.wrapping_add(a.wrapping_mul(s)));
+    let plaintext = body.wrapping_sub(mask_key_dot_product) & ((1 << log_modulus) - 1);
+
+    commit(
+        JournalKind::ModSwitchDecrypt,
+        &ModulusSwitchJournal {
+            ct_digest: lwe_ciphertext_digest(&ciphertext.data, ciphertext.ciphertext_modulus),
+            switched_ct_digest: lwe_ciphertext_digest(&switched, 1 << log_modulus),
+            secret_key_id: secret_key_digest(&lwe_sk.data),
+            log_modulus,
+            plaintext: params.encoding.decode_modulo(plaintext, 1 << log_modulus),
+            params_digest: params_digest(&params),
+        },
+    );
+}
@@ -0,0 +1,61 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+
+use hello_guest::flooding::{flooding_seed_digest, smudging_noise};
+use hello_guest::hashing::{lwe_ciphertext_digest, secret_key_digest};
+use hello_guest::journal::commit;
+use hello_guest::validation::ensure;
+use zkfhe_core::io::PartialDecryptionInput;
+use zkfhe_core::journal::{JournalKind, PartialDecryptionJournal};
+use zkfhe_core::validation::ValidationFailure;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    // The key share is an additive share of the LWE secret key, the ciphertext is its mask followed
+    // by its body
+    let PartialDecryptionInput {
+        party_index,
+        key_share,
+        ciphertext,
+        flooding_bound_log2,
+        flooding_seed,
+    } = env::read();
+    let ciphertext_modulus = ciphertext.ciphertext_modulus;
+    let ciphertext: Vec<u64> = ciphertext.data;
+
+    ensure(
+        key_share.len() + 1 == ciphertext.len(),
+        ValidationFailure::CiphertextDimension,
+    );
+
+    // The smudging noise is drawn inside the guest so the party cannot pick it, only the seed
+    // is chosen outside and it is committed
+    let ct_digest = lwe_ciphertext_digest(&ciphertext, ciphertext_modulus);
+    let flooding_noise = smudging_noise(&flooding_seed, &ct_digest, flooding_bound_log2);
+
+    let (mask, _body) = ciphertext.split_at(key_share.len());
+    let partial_decryption = mask
+        .iter()
+        .zip(key_share.iter())
+        .fold(0u64, |acc, (&mask_element, &key_element)| {
+            acc.wrapping_add(mask_element.wrapping_mul(key_element))
+        })
+        .wrapping_add(flooding_noise as u64);
+
+    commit(
+        JournalKind::PartialDecrypt,
+        &PartialDecryptionJournal {
+            party_index,
+            key_share_digest: secret_key_digest(&key_share),
+            ct_digest,
+            partial_decryption,
+            flooding_bound_log2,
+            flooding_seed_digest: flooding_seed_digest(&flooding_seed),
+        },
+    );
+}
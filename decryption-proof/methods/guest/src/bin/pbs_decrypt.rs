@@ -0,0 +1,358 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::algorithms::misc::divide_round;
+use tfhe::core_crypto::commons::math::ntt::ntt64::Ntt64;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::arena::reserve;
+use hello_guest::entities::{
+    glwe_ciphertext, is_trivial, lwe_ciphertext, lwe_multi_bit_bootstrap_key, lwe_secret_key,
+    ntt_lwe_bootstrap_key,
+};
+use hello_guest::frames::read_framed_container;
+use hello_guest::hashing::{
+    decryption_leaf_digest, lwe_ciphertext_digest, merkle_root, secret_key_digest,
+};
+use hello_guest::journal::commit;
+use hello_guest::math::decrypt_lwe;
+use hello_guest::profile::measure;
+use hello_guest::validation::{
+    big_lwe_dimension, check, ensure, validate_binary_secret_key, validate_encoding,
+    validate_glwe_ciphertext, validate_lwe_ciphertext, validate_lwe_secret_key,
+    validate_multi_bit_bootstrap_key, validate_native_modulus, validate_ntt_bootstrap_key,
+    validate_unsigned_decoding,
+};
+use zkfhe_core::budget::{multi_bit_scratch_budget, pbs_budget};
+use zkfhe_core::canonical::{lwe_multi_bit_bootstrap_key_header, ntt_lwe_bootstrap_key_header};
+use zkfhe_core::io::{PbsBatchItem, PbsBootstrapKeyHeader, PbsDecryptionInput};
+use zkfhe_core::journal::{BatchJournal, DecryptionLeaf, JournalKind, NttParams};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::profile::CycleProfile;
+use zkfhe_core::validation::{DecryptionFailure, ValidationFailure};
+
+risc0_zkvm::guest::entry!(main);
+
+/// The bootstrapping key rebuilt from its container, with what its blind rotations share.
+enum BootstrapKey {
+    Ntt {
+        bsk: NttLweBootstrapKey<Vec<u64>>,
+        ntt: Ntt64,
+        buffers: ComputationBuffers,
+    },
+    MultiBit(LweMultiBitBootstrapKeyOwned<u64>),
+}
+
+impl BootstrapKey {
+    fn blind_rotate(
+        &mut self,
+        input: &LweCiphertextOwned<u64>,
+        accumulator: &mut GlweCiphertextOwned<u64>,
+    ) {
+        match self {
+            Self::Ntt { bsk, ntt, buffers } => blind_rotate_ntt64_assign_mem_optimized(
+                input,
+                accumulator,
+                bsk,
+                ntt.as_view(),
+                buffers.stack(),
+            ),
+            // The standard domain products are exact, the host bootstraps with the same
+            // deterministic rotation on as many threads as it has, the zkVM runs a single one
+            Self::MultiBit(bsk) => {
+                std_multi_bit_deterministic_blind_rotate_assign(
+                    input,
+                    accumulator,
+                    bsk,
+                    ThreadCount(1),
+                );
+            }
+        }
+    }
+}
+
+/// What the blind rotations of the batch share: the key rebuilt once from its container, the
+/// accumulator of the function table, and the rotated accumulator and extracted sample every item
+/// is bootstrapped into. The guest arena never frees, reusing them keeps an item from allocating
+/// more than its own ciphertexts.
+struct SharedRotation {
+    bsk: BootstrapKey,
+    accumulator: GlweCiphertextOwned<u64>,
+    rotated: GlweCiphertextOwned<u64>,
+    extracted: LweCiphertextOwned<u64>,
+}
+
+impl SharedRotation {
+    fn new(bsk: BootstrapKey, accumulator: GlweCiphertextOwned<u64>, output_size: LweSize) -> Self {
+        let rotated = accumulator.clone();
+        let extracted = LweCiphertext::new(0u64, output_size, accumulator.ciphertext_modulus());
+        Self {
+            bsk,
+            accumulator,
+            rotated,
+            extracted,
+        }
+    }
+
+    /// The PBS output of `input`: the first sample of the accumulator blind rotated by `input`.
+    fn bootstrap(
+        &mut self,
+        input: &LweCiphertextOwned<u64>,
+        profile: &mut CycleProfile,
+    ) -> &LweCiphertextOwned<u64> {
+        self.rotated.as_mut().copy_from_slice(self.accumulator.as_ref());
+        measure(&mut profile.blind_rotation, || {
+            self.bsk.blind_rotate(input, &mut self.rotated)
+        });
+        measure(&mut profile.sample_extract, || {
+            extract_lwe_sample_from_glwe_ciphertext(
+                &self.rotated,
+                &mut self.extracted,
+                MonomialDegree(0),
+            )
+        });
+        &self.extracted
+    }
+}
+
+fn main() {
+    // Cycles of every phase, reported to the host to locate the expensive parts of the proof
+    let mut profile = CycleProfile::default();
+
+    // Read the typed inputs, then the raw bootstrapping key container whose frames are copied
+    // straight into its buffer
+    let (params, input): (ProofParams, PbsDecryptionInput) =
+        measure(&mut profile.deserialization, || (env::read(), env::read()));
+
+    // Check the inputs against the committed parameters before any crypto, a mismatch halts the
+    // guest with the exit code of the failed check. Every ciphertext lives on the modulus of the
+    // bootstrapping key rather than on the one of the parameters: the NTT prime for an NTT key,
+    // the native modulus for a multi-bit key
+    let big_lwe_dimension = big_lwe_dimension(&params);
+    let bsk_modulus = input.bsk.ciphertext_modulus();
+    validate_encoding(&params.encoding);
+    validate_unsigned_decoding(&params);
+    validate_native_modulus(&params);
+    // The key header is checked, reserve the memory its container, the batch items and the blind
+    // rotations need, within the heap of the layout the host chose. The NTT rotations share the
+    // stack of the layout, the multi-bit ones allocate their own scratch
+    let memory = input.memory;
+    let rotation_bytes = match &input.bsk {
+        PbsBootstrapKeyHeader::Ntt(bsk) => {
+            validate_ntt_bootstrap_key(bsk, &params);
+            memory.stack_bytes
+        }
+        PbsBootstrapKeyHeader::MultiBit(bsk) => {
+            validate_multi_bit_bootstrap_key(bsk, &params);
+            multi_bit_scratch_budget(&params, input.batch_size) as u64
+        }
+    };
+    let budget = pbs_budget(&params, input.bsk.container_len(), input.batch_size) as u64;
+    let budget = budget.checked_add(rotation_bytes);
+    ensure(
+        budget.is_some_and(|budget| budget <= memory.heap_bytes),
+        ValidationFailure::OutOfMemory,
+    );
+    reserve(budget.unwrap_or(u64::MAX) as usize);
+    validate_glwe_ciphertext(&input.accumulator, &params, bsk_modulus);
+    validate_lwe_secret_key(&input.big_lwe_sk, big_lwe_dimension);
+    validate_binary_secret_key(&input.big_lwe_sk);
+
+    // The bootstrapping key is bound to the journal by the digest of its canonical encoding, so a
+    // verifier can check the proof was generated with the published evaluation key. Every frame is
+    // hashed as it arrives, its hashing cycles are counted as deserialization
+    let bsk_header = match &input.bsk {
+        PbsBootstrapKeyHeader::Ntt(bsk) => ntt_lwe_bootstrap_key_header(bsk),
+        PbsBootstrapKeyHeader::MultiBit(bsk) => lwe_multi_bit_bootstrap_key_header(bsk),
+    };
+    let (bsk_container, bsk_digest) = measure(&mut profile.deserialization, || {
+        read_framed_container(&bsk_header, input.bsk.container_len())
+    });
+
+    let secret_key_id = measure(&mut profile.hashing, || {
+        secret_key_digest(&input.big_lwe_sk.data)
+    });
+
+    // An NTT blind rotation runs on the integer NTT of the key, never on floating point, so it is
+    // exact and its parameters are committed. A multi-bit one runs in the standard domain, its
+    // grouping factor is committed
+    let (ntt_params, grouping_factor) = match &input.bsk {
+        PbsBootstrapKeyHeader::Ntt(bsk) => (
+            Some(NttParams {
+                ciphertext_modulus: bsk.ciphertext_modulus,
+                polynomial_size: bsk.polynomial_size,
+                decomposition_base_log: bsk.decomposition_base_log,
+                decomposition_level_count: bsk.decomposition_level_count,
+            }),
+            None,
+        ),
+        PbsBootstrapKeyHeader::MultiBit(bsk) => (None, Some(bsk.grouping_factor as u32)),
+    };
+    let batch_size = input.batch_size;
+    let seed_commitment = input.seed_commitment;
+    let circuit_digest = input.circuit_digest;
+    let lut = input.lut;
+    let (accumulator, big_lwe_sk) = measure(&mut profile.deserialization, || {
+        let accumulator: GlweCiphertextOwned<u64> = glwe_ciphertext(input.accumulator);
+        let big_lwe_sk: LweSecretKeyOwned<u64> = lwe_secret_key(input.big_lwe_sk);
+        (accumulator, big_lwe_sk)
+    });
+    let ciphertext_modulus = accumulator.ciphertext_modulus();
+
+    let encoding = params.encoding;
+    let delta = encoding.delta();
+
+    // The accumulator must encode the committed function table, so the proof also covers which
+    // function was bootstrapped
+    // The function table covers the message space
+    ensure(
+        lut.len() as u64 == encoding.message_space(),
+        ValidationFailure::PublicInput,
+    );
+    let expected_accumulator = measure(&mut profile.lut_check, || {
+        generate_programmable_bootstrap_glwe_lut(
+            PolynomialSize(params.polynomial_size),
+            GlweDimension(params.glwe_dimension).to_glwe_size(),
+            lut.len(),
+            ciphertext_modulus,
+            delta,
+            |x: u64| lut[x as usize],
+        )
+    });
+    check(
+        expected_accumulator == accumulator,
+        DecryptionFailure::RecomputationMismatch,
+    );
+
+    let bsk = match input.bsk {
+        PbsBootstrapKeyHeader::Ntt(header) => {
+            let bsk = measure(&mut profile.deserialization, || {
+                ntt_lwe_bootstrap_key(header, bsk_container)
+            });
+
+            // The twiddle factors are computed once for the whole batch, every blind rotation
+            // reuses the cached plan. The guest computes them itself, the NTT plan has no
+            // constructor taking tables from the host, the `ntt_plan` phase of the profile
+            // measures what that costs
+            let ntt = measure(&mut profile.ntt_plan, || {
+                Ntt64::new(bsk.ciphertext_modulus(), bsk.polynomial_size())
+            });
+
+            // The blind rotations share one stack of the size of the layout, the bump arena would
+            // keep the buffers of every rotation otherwise
+            let stack_bytes = blind_rotate_ntt64_assign_mem_optimized_requirement(
+                bsk.glwe_size(),
+                bsk.polynomial_size(),
+                ntt.as_view(),
+            )
+            .map(|requirement| requirement.unaligned_bytes_required() as u64);
+            ensure(
+                stack_bytes.is_ok_and(|stack_bytes| stack_bytes <= memory.stack_bytes),
+                ValidationFailure::OutOfMemory,
+            );
+            let mut buffers = ComputationBuffers::new();
+            buffers.resize(memory.stack_bytes as usize);
+            BootstrapKey::Ntt { bsk, ntt, buffers }
+        }
+        PbsBootstrapKeyHeader::MultiBit(header) => {
+            BootstrapKey::MultiBit(measure(&mut profile.deserialization, || {
+                lwe_multi_bit_bootstrap_key(header, bsk_container)
+            }))
+        }
+    };
+
+    // The key, its NTT plan and the rotation buffers are set up once for the whole batch
+    let output_size = big_lwe_sk.lwe_dimension().to_lwe_size();
+    let mut rotation = SharedRotation::new(bsk, accumulator, output_size);
+
+    // Ciphertexts are streamed one at a time, nothing is committed before the whole batch is
+    // checked so continuations can split the PBS over as many segments as needed. Only the leaf
+    // digests are kept, the journal commits their Merkle root
+    let mut leaves = Vec::with_capacity(batch_size as usize);
+    let mut trivial = false;
+    for _ in 0..batch_size {
+        let (lwe_ciphertext_in, cleartext_multiplication_result, pbs_multiplication_ct) =
+            measure(&mut profile.deserialization, || {
+                let item: PbsBatchItem = env::read();
+                validate_lwe_ciphertext(&item.lwe_ciphertext_in, params.lwe_dimension, bsk_modulus);
+                validate_lwe_ciphertext(
+                    &item.pbs_multiplication_ct,
+                    big_lwe_dimension,
+                    bsk_modulus,
+                );
+                let lwe_ciphertext_in: LweCiphertextOwned<u64> =
+                    lwe_ciphertext(item.lwe_ciphertext_in);
+                let pbs_multiplication_ct: LweCiphertextOwned<u64> =
+                    lwe_ciphertext(item.pbs_multiplication_ct);
+                (
+                    lwe_ciphertext_in,
+                    item.cleartext_multiplication_result,
+                    pbs_multiplication_ct,
+                )
+            });
+
+        trivial |= is_trivial(&lwe_ciphertext_in) || is_trivial(&pbs_multiplication_ct);
+
+        // The claimed PBS output must be the one obtained by blind rotating the accumulator with
+        // the input ciphertext and extracting its first sample
+        let guest_pbs_multiplication_ct = rotation.bootstrap(&lwe_ciphertext_in, &mut profile);
+        check(
+            *guest_pbs_multiplication_ct == pbs_multiplication_ct,
+            DecryptionFailure::RecomputationMismatch,
+        );
+
+        // The native rounding does not apply to the NTT modulus, round to the closest multiple of
+        // delta instead, on either modulus. A plaintext just below the modulus rounds to
+        // `2^total_bits`, it is reduced so it wraps to 0 as the native `closest_representable` does
+        let pbs_multiplication_result = measure(&mut profile.decryption, || {
+            let pbs_multiplication_plaintext = decrypt_lwe(&big_lwe_sk, &pbs_multiplication_ct);
+            divide_round(pbs_multiplication_plaintext.0, delta) % (1 << encoding.total_bits())
+        });
+        check(
+            cleartext_multiplication_result == pbs_multiplication_result,
+            DecryptionFailure::ResultMismatch,
+        );
+        check(
+            !encoding.overflows(pbs_multiplication_result.into()),
+            DecryptionFailure::PaddingOverflow,
+        );
+
+        let leaf = measure(&mut profile.hashing, || {
+            decryption_leaf_digest(&DecryptionLeaf {
+                ct_digest: lwe_ciphertext_digest(pbs_multiplication_ct.as_ref(), bsk_modulus),
+                source_ct_digest: Some(lwe_ciphertext_digest(
+                    lwe_ciphertext_in.as_ref(),
+                    bsk_modulus,
+                )),
+                plaintext: pbs_multiplication_result,
+            })
+        });
+        leaves.push(leaf);
+    }
+    let decryptions_root = measure(&mut profile.hashing, || merkle_root(&leaves));
+
+    // The profile goes to the host stdout, it is not committed
+    env::write(&profile);
+
+    commit(
+        JournalKind::PbsDecrypt,
+        &BatchJournal {
+            params,
+            secret_key_id,
+            ksk_id: None,
+            ntt_bsk_digest: ntt_params.map(|_| bsk_digest),
+            ntt_params,
+            multi_bit_bsk_digest: grouping_factor.map(|_| bsk_digest),
+            grouping_factor,
+            lut: Some(lut),
+            seed_commitment,
+            circuit_digest,
+            trivial,
+            decryption_count: batch_size,
+            decryptions_root,
+        },
+    );
+}
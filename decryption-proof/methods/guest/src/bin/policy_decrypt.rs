@@ -0,0 +1,76 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::decoding::decode_message_and_carry_modulo;
+use hello_guest::entities::{lwe_ciphertext, lwe_secret_key};
+use hello_guest::hashing::{
+    lwe_ciphertext_digest, params_digest, plaintext_commitment, secret_key_digest,
+};
+use hello_guest::journal::commit;
+use hello_guest::math::decrypt_lwe;
+use hello_guest::validation::{
+    big_lwe_dimension, validate_binary_secret_key, validate_ciphertext_modulus, validate_encoding,
+    validate_lwe_ciphertext, validate_lwe_secret_key, validate_unsigned_decoding,
+};
+use zkfhe_core::io::PolicyDecryptionInput;
+use zkfhe_core::journal::{JournalKind, PolicyDecryptionJournal};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::policy::{Disclosure, JournalPolicy};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    let PolicyDecryptionInput {
+        lwe_sk,
+        ciphertext,
+        policy,
+    } = env::read();
+
+    // Same inputs and checks as the single ciphertext guest
+    validate_encoding(&params.encoding);
+    validate_unsigned_decoding(&params);
+    validate_ciphertext_modulus(&params);
+    validate_lwe_secret_key(&lwe_sk, big_lwe_dimension(&params));
+    validate_binary_secret_key(&lwe_sk);
+    validate_lwe_ciphertext(
+        &ciphertext,
+        big_lwe_dimension(&params),
+        params.ciphertext_modulus,
+    );
+
+    let ct_digest = lwe_ciphertext_digest(&ciphertext.data, ciphertext.ciphertext_modulus);
+    let secret_key_id = secret_key_digest(&lwe_sk.data);
+    let lwe_sk: LweSecretKeyOwned<u64> = lwe_secret_key(lwe_sk);
+    let ciphertext: LweCiphertextOwned<u64> = lwe_ciphertext(ciphertext);
+
+    let plaintext = decrypt_lwe(&lwe_sk, &ciphertext);
+    let decoded =
+        decode_message_and_carry_modulo(&params.encoding, plaintext.0, params.ciphertext_modulus);
+
+    // Nothing of the decoded value leaves the guest beyond what the policy allows
+    let disclosure = match policy {
+        JournalPolicy::Plaintext => Disclosure::Plaintext(decoded),
+        JournalPolicy::PlaintextHash { salt } => {
+            Disclosure::PlaintextHash(plaintext_commitment(&salt, decoded))
+        }
+        JournalPolicy::Predicate(predicate) => Disclosure::Predicate {
+            predicate,
+            holds: predicate.evaluate(decoded),
+        },
+        JournalPolicy::DigestsOnly => Disclosure::DigestsOnly,
+    };
+
+    commit(
+        JournalKind::PolicyDecrypt,
+        &PolicyDecryptionJournal {
+            ct_digest,
+            secret_key_id,
+            disclosure,
+            params_digest: params_digest(&params),
+        },
+    );
+}
@@ -0,0 +1,62 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::decoding::recompose_radix;
+use hello_guest::entities::{lwe_ciphertext, lwe_secret_key};
+use hello_guest::hashing::{lwe_ciphertext_digest, secret_key_digest};
+use hello_guest::journal::commit;
+use hello_guest::math::LweDecryptionKey;
+use hello_guest::validation::{
+    validate_binary_secret_key, validate_encoding, validate_lwe_ciphertext,
+};
+use zkfhe_core::encoding::Rounding;
+use zkfhe_core::io::RadixDecryptionInput;
+use zkfhe_core::journal::{JournalKind, RadixDecryptionJournal};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let RadixDecryptionInput {
+        lwe_sk,
+        blocks,
+        encoding,
+    } = env::read();
+
+    // No parameter set is committed, every block is checked against the key and the native
+    // modulus the decoder rounds on
+    validate_encoding(&encoding);
+    validate_binary_secret_key(&lwe_sk);
+    for block in &blocks {
+        validate_lwe_ciphertext(block, lwe_sk.data.len(), 0);
+    }
+
+    // Every block is decrypted under the same key, a binary one is prepared once
+    let secret_key_id = secret_key_digest(&lwe_sk.data);
+    let lwe_sk = LweDecryptionKey::new(lwe_secret_key(lwe_sk));
+    let rounding = Rounding::<u64>::new(&encoding);
+
+    let mut block_digests = Vec::with_capacity(blocks.len());
+    let mut block_messages = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        block_digests.push(lwe_ciphertext_digest(&block.data, block.ciphertext_modulus));
+        let block: LweCiphertextOwned<u64> = lwe_ciphertext(block);
+
+        let plaintext = lwe_sk.decrypt(&block);
+        block_messages.push(rounding.decode(plaintext.0));
+    }
+
+    commit(
+        JournalKind::RadixDecrypt,
+        &RadixDecryptionJournal {
+            block_digests,
+            secret_key_id,
+            encoding,
+            value: recompose_radix(&encoding, &block_messages),
+        },
+    );
+}
@@ -0,0 +1,103 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::decoding::{decode_message_and_carry_modulo, noise_budget_bits};
+use hello_guest::entities::{lwe_ciphertext, lwe_public_key, lwe_secret_key};
+use hello_guest::hashing::{
+    lwe_ciphertext_digest, params_digest, public_key_digest, secret_key_digest,
+};
+use hello_guest::journal::commit;
+use hello_guest::math::decrypt_lwe;
+use hello_guest::validation::{
+    big_lwe_dimension, ensure, validate_binary_secret_key, validate_ciphertext_modulus,
+    validate_encoding, validate_lwe_ciphertext, validate_lwe_public_key, validate_lwe_secret_key,
+};
+use zkfhe_core::io::ReencryptionInput;
+use zkfhe_core::journal::{JournalKind, ReencryptionJournal};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::validation::ValidationFailure;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    let ReencryptionInput {
+        lwe_sk,
+        ciphertext,
+        recipient_public_key,
+        selection,
+    } = env::read();
+
+    // The recipient key may have any dimension, but it must be on the modulus of the parameters
+    // so the message is encoded the same way for the recipient
+    validate_encoding(&params.encoding);
+    validate_ciphertext_modulus(&params);
+    validate_lwe_secret_key(&lwe_sk, big_lwe_dimension(&params));
+    validate_binary_secret_key(&lwe_sk);
+    validate_lwe_ciphertext(
+        &ciphertext,
+        big_lwe_dimension(&params),
+        params.ciphertext_modulus,
+    );
+    validate_lwe_public_key(&recipient_public_key, params.ciphertext_modulus);
+
+    let ct_digest = lwe_ciphertext_digest(&ciphertext.data, ciphertext.ciphertext_modulus);
+    let secret_key_id = secret_key_digest(&lwe_sk.data);
+    let recipient_pk_digest = public_key_digest(&recipient_public_key);
+    let lwe_sk: LweSecretKeyOwned<u64> = lwe_secret_key(lwe_sk);
+    let ciphertext: LweCiphertextOwned<u64> = lwe_ciphertext(ciphertext);
+    let recipient_public_key = lwe_public_key(recipient_public_key);
+
+    let zero_encryption_count = recipient_public_key.zero_encryption_count().0;
+    // The selection holds one bit per encryption of zero
+    ensure(
+        selection.len() == zero_encryption_count.div_ceil(64),
+        ValidationFailure::PublicInput,
+    );
+
+    // Decrypt and decode, the message is encoded again without the noise of the input
+    let plaintext = decrypt_lwe(&lwe_sk, &ciphertext);
+    let message =
+        decode_message_and_carry_modulo(&params.encoding, plaintext.0, params.ciphertext_modulus);
+    // A decryption whose noise reaches the decoding bound is not re-encrypted, the message would
+    // not be the one of the input
+    noise_budget_bits(
+        &params.encoding,
+        plaintext.0,
+        message,
+        params.ciphertext_modulus,
+    );
+    let encoded = params
+        .encoding
+        .encode_for_modulus(message, params.ciphertext_modulus);
+
+    // Public key encryption: the sum of the selected encryptions of zero plus the plaintext
+    let mut reencrypted = LweCiphertext::new(
+        0u64,
+        recipient_public_key.lwe_size(),
+        recipient_public_key.ciphertext_modulus(),
+    );
+    for (index, zero_encryption) in recipient_public_key.iter().enumerate() {
+        if (selection[index / 64] >> (index % 64)) & 1 == 1 {
+            lwe_ciphertext_add_assign(&mut reencrypted, &zero_encryption);
+        }
+    }
+    lwe_ciphertext_plaintext_add_assign(&mut reencrypted, Plaintext(encoded));
+
+    commit(
+        JournalKind::Reencrypt,
+        &ReencryptionJournal {
+            ct_digest,
+            secret_key_id,
+            reencrypted_ct_digest: lwe_ciphertext_digest(
+                reencrypted.as_ref(),
+                params.ciphertext_modulus,
+            ),
+            recipient_pk_digest,
+            params_digest: params_digest(&params),
+        },
+    );
+}
@@ -0,0 +1,70 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::entities::{glwe_ciphertext, lwe_ciphertext};
+use hello_guest::hashing::{glwe_ciphertext_digest, lwe_ciphertext_digest, params_digest};
+use hello_guest::journal::commit;
+use hello_guest::validation::{
+    big_lwe_dimension, check, ensure, validate_glwe_ciphertext, validate_lwe_ciphertext,
+    NTT64_CIPHERTEXT_MODULUS,
+};
+use zkfhe_core::io::SampleExtractInput;
+use zkfhe_core::journal::{JournalKind, SampleExtractJournal};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::validation::{DecryptionFailure, ValidationFailure};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let params: ProofParams = env::read();
+    let SampleExtractInput {
+        accumulator,
+        extracted,
+        monomial_degree,
+    } = env::read();
+
+    // The accumulator is blind rotated by the native PBS or by the NTT one of the PBS guest, the
+    // extracted ciphertext stays on its modulus
+    let ciphertext_modulus = accumulator.ciphertext_modulus;
+    ensure(
+        ciphertext_modulus == 0 || ciphertext_modulus == NTT64_CIPHERTEXT_MODULUS,
+        ValidationFailure::CiphertextModulus,
+    );
+    validate_glwe_ciphertext(&accumulator, &params, ciphertext_modulus);
+    validate_lwe_ciphertext(&extracted, big_lwe_dimension(&params), ciphertext_modulus);
+    ensure(
+        (monomial_degree as usize) < params.polynomial_size,
+        ValidationFailure::PolynomialSize,
+    );
+
+    let journal = SampleExtractJournal {
+        accumulator_digest: glwe_ciphertext_digest(
+            &accumulator.data,
+            accumulator.polynomial_size,
+            ciphertext_modulus,
+        ),
+        extracted_ct_digest: lwe_ciphertext_digest(&extracted.data, ciphertext_modulus),
+        monomial_degree,
+        params_digest: params_digest(&params),
+    };
+    let accumulator: GlweCiphertextOwned<u64> = glwe_ciphertext(accumulator);
+    let extracted: LweCiphertextOwned<u64> = lwe_ciphertext(extracted);
+
+    // The sample extract is deterministic, the claimed ciphertext must be the recomputed one
+    let mut guest_extracted = LweCiphertext::new(
+        0u64,
+        LweSize(big_lwe_dimension(&params) + 1),
+        accumulator.ciphertext_modulus(),
+    );
+    extract_lwe_sample_from_glwe_ciphertext(
+        &accumulator,
+        &mut guest_extracted,
+        MonomialDegree(monomial_degree as usize),
+    );
+    check(guest_extracted == extracted, DecryptionFailure::RecomputationMismatch);
+
+    commit(JournalKind::SampleExtract, &journal);
+}
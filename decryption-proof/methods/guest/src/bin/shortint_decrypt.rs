@@ -0,0 +1,50 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use tfhe::core_crypto::prelude::*;
+
+use hello_guest::decoding::decode_message_and_carry;
+use hello_guest::entities::{lwe_ciphertext, lwe_secret_key};
+use hello_guest::hashing::{lwe_ciphertext_digest, secret_key_digest};
+use hello_guest::journal::commit;
+use hello_guest::math::decrypt_lwe;
+use hello_guest::validation::{
+    validate_binary_secret_key, validate_encoding, validate_lwe_ciphertext,
+};
+use zkfhe_core::io::ShortintDecryptionInput;
+use zkfhe_core::journal::{JournalKind, ShortintDecryptionJournal};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let ShortintDecryptionInput {
+        lwe_sk,
+        ciphertext,
+        encoding,
+    } = env::read();
+
+    // No parameter set is committed, the ciphertext is checked against its key and the native
+    // modulus the decoder rounds on
+    validate_encoding(&encoding);
+    validate_binary_secret_key(&lwe_sk);
+    validate_lwe_ciphertext(&ciphertext, lwe_sk.data.len(), 0);
+
+    let ct_digest = lwe_ciphertext_digest(&ciphertext.data, ciphertext.ciphertext_modulus);
+    let secret_key_id = secret_key_digest(&lwe_sk.data);
+    let lwe_sk: LweSecretKeyOwned<u64> = lwe_secret_key(lwe_sk);
+    let ciphertext: LweCiphertextOwned<u64> = lwe_ciphertext(ciphertext);
+
+    let plaintext = decrypt_lwe(&lwe_sk, &ciphertext);
+    let message_and_carry = decode_message_and_carry(&encoding, plaintext.0);
+
+    commit(
+        JournalKind::ShortintDecrypt,
+        &ShortintDecryptionJournal {
+            ct_digest,
+            secret_key_id,
+            encoding,
+            message: message_and_carry % encoding.message_modulus(),
+        },
+    );
+}
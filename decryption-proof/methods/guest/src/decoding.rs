@@ -0,0 +1,84 @@
+use zkfhe_core::encoding::{EncodingParams, Rounding};
+use zkfhe_core::validation::DecryptionFailure;
+
+use crate::validation::check;
+
+/// Round a native 64 bits plaintext to its padding, carry and message bits and return the message
+/// with its carries, as `shortint::ClientKey::decrypt_message_and_carry` does. Guests decoding many
+/// plaintexts build the [`Rounding`] once instead.
+pub fn decode_message_and_carry(encoding: &EncodingParams, plaintext: u64) -> u64 {
+    assert!(encoding.is_valid(), "invalid encoding");
+    Rounding::<u64>::new(encoding).decode(plaintext)
+}
+
+/// Round a native 128 bits plaintext, see [`decode_message_and_carry`].
+pub fn decode_message_and_carry_u128(encoding: &EncodingParams, plaintext: u128) -> u128 {
+    assert!(encoding.is_valid_for(u128::BITS), "invalid encoding");
+    Rounding::<u128>::new(encoding).decode(plaintext)
+}
+
+/// Decode a plaintext of a ciphertext on `ciphertext_modulus`, 0 standing for the native modulus.
+/// Non-native plaintexts are rounded with exact integer arithmetic, see
+/// [`EncodingParams::decode_modulo`].
+pub fn decode_message_and_carry_modulo(
+    encoding: &EncodingParams,
+    plaintext: u64,
+    ciphertext_modulus: u128,
+) -> u64 {
+    if ciphertext_modulus == 0 {
+        decode_message_and_carry(encoding, plaintext)
+    } else {
+        assert!(encoding.is_valid(), "invalid encoding");
+        encoding.decode_modulo(plaintext, ciphertext_modulus)
+    }
+}
+
+/// Noise budget of a plaintext on `ciphertext_modulus` decoded to `message`, see
+/// [`zkfhe_core::journal::DecryptionJournal`]. Panics when the noise reaches the decoding bound.
+pub fn noise_budget_bits(
+    encoding: &EncodingParams,
+    plaintext: u64,
+    message: u64,
+    ciphertext_modulus: u128,
+) -> u32 {
+    if ciphertext_modulus == 0 {
+        let noise = plaintext.wrapping_sub(message.wrapping_mul(encoding.delta())) as i64;
+        let bound = 1u128 << (u64::BITS - encoding.total_bits() - 1);
+        budget_bits(noise.unsigned_abs() as u128, bound)
+    } else {
+        // Centered distance to the encoding of the message, the bound is rounded down so the
+        // check is never looser than the exact half gap
+        let encoded = encoding.encode_modulo(message, ciphertext_modulus) as u128;
+        let distance = (plaintext as u128 + ciphertext_modulus - encoded) % ciphertext_modulus;
+        let noise = distance.min(ciphertext_modulus - distance);
+        budget_bits(noise, ciphertext_modulus >> (encoding.total_bits() + 1))
+    }
+}
+
+/// Noise budget of a native 128 bits plaintext decoded to `message`.
+pub fn noise_budget_bits_u128(encoding: &EncodingParams, plaintext: u128, message: u128) -> u32 {
+    let noise = plaintext.wrapping_sub(message.wrapping_mul(encoding.delta_u128())) as i128;
+    budget_bits(
+        noise.unsigned_abs(),
+        1 << (u128::BITS - encoding.total_bits() - 1),
+    )
+}
+
+fn budget_bits(noise: u128, bound: u128) -> u32 {
+    check(noise < bound, DecryptionFailure::NoiseBound);
+    noise.leading_zeros() - bound.leading_zeros()
+}
+
+/// Recombine the messages of little endian radix blocks, each block holding
+/// `encoding.message_bits` bits, carries are dropped. Bits past 128 are ignored.
+pub fn recompose_radix(encoding: &EncodingParams, block_messages: &[u64]) -> u128 {
+    let bits_in_block = encoding.message_bits;
+    block_messages
+        .iter()
+        .enumerate()
+        .take_while(|(index, _)| (*index as u32) * bits_in_block < u128::BITS)
+        .fold(0u128, |acc, (index, &message)| {
+            let message = (message % encoding.message_modulus()) as u128;
+            acc | (message << (index as u32 * bits_in_block))
+        })
+}
@@ -0,0 +1,106 @@
+use zkfhe_core::io::{
+    GlweCiphertextData, GlweSecretKeyData, LweCiphertextData, LweCompactCiphertextListData,
+    LweKeyswitchKeyData, LweMultiBitBootstrapKeyHeader, LwePublicKeyData, LweSecretKeyData,
+    NttLweBootstrapKeyHeader,
+};
+use alloc::vec::Vec;
+use tfhe::core_crypto::prelude::*;
+
+// Rebuild the tfhe entities from the raw containers received from the host, the containers are
+// moved, not copied.
+
+pub fn lwe_ciphertext<Scalar: UnsignedInteger>(
+    ciphertext: LweCiphertextData<Scalar>,
+) -> LweCiphertextOwned<Scalar> {
+    LweCiphertext::from_container(
+        ciphertext.data,
+        CiphertextModulus::new(ciphertext.ciphertext_modulus),
+    )
+}
+
+pub fn lwe_secret_key<Scalar: UnsignedInteger>(
+    secret_key: LweSecretKeyData<Scalar>,
+) -> LweSecretKeyOwned<Scalar> {
+    LweSecretKey::from_container(secret_key.data)
+}
+
+pub fn glwe_secret_key(secret_key: GlweSecretKeyData) -> GlweSecretKeyOwned<u64> {
+    GlweSecretKey::from_container(secret_key.data, PolynomialSize(secret_key.polynomial_size))
+}
+
+pub fn glwe_ciphertext(ciphertext: GlweCiphertextData) -> GlweCiphertextOwned<u64> {
+    GlweCiphertext::from_container(
+        ciphertext.data,
+        PolynomialSize(ciphertext.polynomial_size),
+        CiphertextModulus::new(ciphertext.ciphertext_modulus),
+    )
+}
+
+pub fn lwe_keyswitch_key(ksk: LweKeyswitchKeyData) -> LweKeyswitchKeyOwned<u64> {
+    LweKeyswitchKey::from_container(
+        ksk.data,
+        DecompositionBaseLog(ksk.decomposition_base_log),
+        DecompositionLevelCount(ksk.decomposition_level_count),
+        LweSize(ksk.output_lwe_size),
+        CiphertextModulus::new(ksk.ciphertext_modulus),
+    )
+}
+
+pub fn lwe_compact_ciphertext_list(
+    list: LweCompactCiphertextListData,
+) -> LweCompactCiphertextListOwned<u64> {
+    LweCompactCiphertextList::from_container(
+        list.data,
+        LweSize(list.lwe_size),
+        LweCiphertextCount(list.ciphertext_count),
+        CiphertextModulus::new(list.ciphertext_modulus),
+    )
+}
+
+pub fn lwe_public_key(public_key: LwePublicKeyData) -> LwePublicKeyOwned<u64> {
+    LwePublicKey::from_container(
+        public_key.data,
+        LweSize(public_key.lwe_size),
+        CiphertextModulus::new(public_key.ciphertext_modulus),
+    )
+}
+
+/// Rebuild the NTT bootstrapping key from its header and the container reassembled from its frames,
+/// `from_container` checks the container length against the header.
+pub fn ntt_lwe_bootstrap_key(
+    bsk: NttLweBootstrapKeyHeader,
+    container: Vec<u64>,
+) -> NttLweBootstrapKey<Vec<u64>> {
+    NttLweBootstrapKey::from_container(
+        container,
+        GlweSize(bsk.glwe_size),
+        PolynomialSize(bsk.polynomial_size),
+        DecompositionBaseLog(bsk.decomposition_base_log),
+        DecompositionLevelCount(bsk.decomposition_level_count),
+        CiphertextModulus::new(bsk.ciphertext_modulus),
+    )
+}
+
+/// Rebuild the multi-bit bootstrapping key from its header and the container reassembled from its
+/// frames.
+pub fn lwe_multi_bit_bootstrap_key(
+    bsk: LweMultiBitBootstrapKeyHeader,
+    container: Vec<u64>,
+) -> LweMultiBitBootstrapKeyOwned<u64> {
+    LweMultiBitBootstrapKey::from_container(
+        container,
+        GlweSize(bsk.glwe_size),
+        PolynomialSize(bsk.polynomial_size),
+        DecompositionBaseLog(bsk.decomposition_base_log),
+        DecompositionLevelCount(bsk.decomposition_level_count),
+        LweDimension(bsk.input_lwe_dimension),
+        LweBskGroupingFactor(bsk.grouping_factor),
+        CiphertextModulus::new(bsk.ciphertext_modulus),
+    )
+}
+
+/// Whether the mask of `ciphertext` is all zeros, a trivial encryption of its body anyone can
+/// produce without the secret key.
+pub fn is_trivial(ciphertext: &LweCiphertextOwned<u64>) -> bool {
+    ciphertext.get_mask().as_ref().iter().all(|&coefficient| coefficient == 0)
+}
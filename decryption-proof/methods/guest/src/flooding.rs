@@ -0,0 +1,32 @@
+use alloc::vec::Vec;
+
+use crate::hashing::sha256_bytes;
+
+/// Draw the smudging noise of a partial decryption uniformly in
+/// `[-2^flooding_bound_log2, 2^flooding_bound_log2]`.
+///
+/// The noise is derived from SHA-256 of the seed and the ciphertext digest, so a committed seed
+/// fixes the noise of every ciphertext while different ciphertexts never share the same noise.
+pub fn smudging_noise(seed: &[u8; 32], ct_digest: &[u8; 32], flooding_bound_log2: u32) -> i64 {
+    assert!(flooding_bound_log2 < 62, "flooding bound is too large");
+
+    let mut preimage = Vec::with_capacity(64);
+    preimage.extend_from_slice(seed);
+    preimage.extend_from_slice(ct_digest);
+    let digest = sha256_bytes(&preimage);
+
+    let mut random_bytes = [0u8; 8];
+    random_bytes.copy_from_slice(&digest[..8]);
+    let random = u64::from_le_bytes(random_bytes);
+
+    // The modulo bias is at most 2^(flooding_bound_log2 + 1 - 64), negligible for any bound small
+    // enough to still allow decryption
+    let bound = 1i64 << flooding_bound_log2;
+    let range = (2 * bound + 1) as u64;
+    (random % range) as i64 - bound
+}
+
+/// Digest committing to a flooding seed.
+pub fn flooding_seed_digest(seed: &[u8; 32]) -> [u8; 32] {
+    sha256_bytes(seed)
+}
@@ -0,0 +1,111 @@
+//! Incremental reader of the raw containers the host streams in length-prefixed frames.
+//!
+//! Every frame is read straight into its place in the container and hashed as it arrives, so no
+//! serialization buffer nor hashing copy of a multi-megabyte key is ever held next to it.
+//!
+//! The public input frame of the decrypt guest is read here too, see [`read_public_input`].
+
+use alloc::vec;
+use alloc::vec::Vec;
+use risc0_zkvm::guest::env;
+use risc0_zkvm::sha::rust_crypto::{Digest as _, Sha256};
+use zkfhe_core::io::{public_input_padded_len, FRAME_LEN, MAX_PUBLIC_INPUT_BYTES};
+use zkfhe_core::validation::ValidationFailure;
+
+use crate::validation::ensure;
+
+/// Reads the frames of one container of `container_len` values.
+pub struct FrameReader {
+    remaining: usize,
+    hasher: Sha256,
+}
+
+impl FrameReader {
+    /// Reader of a container whose canonical encoding starts with `header`, see
+    /// [`zkfhe_core::canonical`].
+    pub fn new(header: &[u8], container_len: u64) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(header);
+        Self {
+            remaining: container_len as usize,
+            hasher,
+        }
+    }
+
+    /// Read the next frame into the front of `buffer` and return its length, 0 once the whole
+    /// container was read. A frame that is empty, longer than [`FRAME_LEN`], longer than `buffer`
+    /// or past the end of the container halts the guest.
+    pub fn read_frame(&mut self, buffer: &mut [u64]) -> usize {
+        if self.remaining == 0 {
+            return 0;
+        }
+        let mut frame_len = [0u32; 1];
+        env::read_slice(&mut frame_len);
+        let frame_len = frame_len[0] as usize;
+        ensure(
+            frame_len > 0
+                && frame_len <= FRAME_LEN
+                && frame_len <= buffer.len()
+                && frame_len <= self.remaining,
+            ValidationFailure::Framing,
+        );
+
+        let frame = &mut buffer[..frame_len];
+        env::read_slice(frame);
+        self.hasher.update(bytemuck::cast_slice::<u64, u8>(frame));
+        self.remaining -= frame_len;
+        frame_len
+    }
+
+    /// SHA-256 of the canonical encoding, the header followed by the little endian encoding of
+    /// every value read.
+    pub fn finalize(self) -> [u8; 32] {
+        ensure(self.remaining == 0, ValidationFailure::Framing);
+        self.hasher.finalize().into()
+    }
+}
+
+/// Reassemble a framed container and return it with its digest, the values are written in place
+/// so peak memory is the container itself.
+pub fn read_framed_container(header: &[u8], container_len: u64) -> (Vec<u64>, [u8; 32]) {
+    let mut container = vec![0u64; container_len as usize];
+    let mut reader = FrameReader::new(header, container_len);
+    let mut offset = 0;
+    loop {
+        let frame_len = reader.read_frame(&mut container[offset..]);
+        if frame_len == 0 {
+            break;
+        }
+        offset += frame_len;
+    }
+    (container, reader.finalize())
+}
+
+/// Digest of a framed container that is not needed afterwards, only one frame is held in memory,
+/// so the container may be larger than the guest memory.
+pub fn hash_framed_container(header: &[u8], container_len: u64) -> [u8; 32] {
+    let mut frame = vec![0u64; FRAME_LEN];
+    let mut reader = FrameReader::new(header, container_len);
+    while reader.read_frame(&mut frame) > 0 {}
+    reader.finalize()
+}
+
+/// Read the public input frame and return the canonical encoding it holds with its SHA-256. The
+/// digest is taken over the bytes as read, before anything is decoded from them, and a frame over
+/// [`MAX_PUBLIC_INPUT_BYTES`] or whose padding is not zero halts the guest.
+pub fn read_public_input() -> (Vec<u8>, [u8; 32]) {
+    let mut len = [0u32; 1];
+    env::read_slice(&mut len);
+    let len = len[0] as usize;
+    ensure(len <= MAX_PUBLIC_INPUT_BYTES, ValidationFailure::PublicInput);
+
+    let mut bytes = vec![0u8; public_input_padded_len(len)];
+    env::read_slice(&mut bytes);
+    ensure(
+        bytes[len..].iter().all(|&byte| byte == 0),
+        ValidationFailure::PublicInput,
+    );
+    bytes.truncate(len);
+    let digest = Sha256::digest(&bytes).into();
+    (bytes, digest)
+}
@@ -0,0 +1,170 @@
+//! Every digest committed by the guests, computed with the risc0 SHA-256 accelerator.
+//!
+//! Inputs are hashed in their canonical encoding, see [`zkfhe_core::canonical`]: the header is fed
+//! to the accelerator followed by the little endian bytes of the container, so the host helpers
+//! hashing the same encoding produce identical digests without the guest ever copying a key.
+
+use alloc::vec::Vec;
+use risc0_zkvm::sha::rust_crypto::{Digest as _, Sha256 as Sha256Hasher};
+use risc0_zkvm::sha::{Impl, Sha256};
+use zkfhe_core::canonical::{
+    encode_params, glwe_ciphertext_header, lwe_ciphertext_header,
+    lwe_compact_ciphertext_list_header, lwe_keyswitch_key_header, lwe_public_key_header,
+    lwe_secret_key_header,
+};
+use zkfhe_core::io::{LweCompactCiphertextListData, LweKeyswitchKeyData, LwePublicKeyData};
+use zkfhe_core::journal::DecryptionLeaf;
+use zkfhe_core::params::ProofParams;
+
+fn to_array(digest: &risc0_zkvm::sha::Digest) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(digest.as_bytes());
+    bytes
+}
+
+/// SHA-256 of `bytes`.
+pub fn sha256_bytes(bytes: &[u8]) -> [u8; 32] {
+    to_array(&Impl::hash_bytes(bytes))
+}
+
+/// SHA-256 of the little endian encoding of `words`.
+pub fn sha256_words(words: &[u32]) -> [u8; 32] {
+    to_array(&Impl::hash_words(words))
+}
+
+/// SHA-256 of the canonical encoding `header || values`, the guest is little endian so the
+/// values are hashed in place.
+pub fn sha256_canonical<T: bytemuck::Pod>(header: &[u8], values: &[T]) -> [u8; 32] {
+    let mut hasher = Sha256Hasher::new();
+    hasher.update(header);
+    hasher.update(bytemuck::cast_slice::<T, u8>(values));
+    hasher.finalize().into()
+}
+
+/// SHA-256 of the canonical encoding of an LWE ciphertext, mask then body, on
+/// `ciphertext_modulus`.
+pub fn lwe_ciphertext_digest(ciphertext: &[u64], ciphertext_modulus: u128) -> [u8; 32] {
+    let header = lwe_ciphertext_header(u64::BITS, ciphertext_modulus, ciphertext.len());
+    sha256_canonical(&header, ciphertext)
+}
+
+/// SHA-256 of the canonical encoding of an LWE ciphertext on 128 bits.
+pub fn lwe_ciphertext_u128_digest(ciphertext: &[u128], ciphertext_modulus: u128) -> [u8; 32] {
+    let header = lwe_ciphertext_header(u128::BITS, ciphertext_modulus, ciphertext.len());
+    sha256_canonical(&header, ciphertext)
+}
+
+/// SHA-256 of the canonical encoding of a GLWE ciphertext, masks then body polynomials.
+pub fn glwe_ciphertext_digest(
+    ciphertext: &[u64],
+    polynomial_size: usize,
+    ciphertext_modulus: u128,
+) -> [u8; 32] {
+    let header = glwe_ciphertext_header(polynomial_size, ciphertext_modulus, ciphertext.len());
+    sha256_canonical(&header, ciphertext)
+}
+
+/// SHA-256 of the canonical encoding of an LWE compact ciphertext list, before its expansion.
+pub fn lwe_compact_ciphertext_list_digest(list: &LweCompactCiphertextListData) -> [u8; 32] {
+    let header = lwe_compact_ciphertext_list_header(
+        list.lwe_size,
+        list.ciphertext_count,
+        list.ciphertext_modulus,
+        list.data.len(),
+    );
+    sha256_canonical(&header, &list.data)
+}
+
+/// SHA-256 of the canonical encoding of a keyswitching key.
+pub fn keyswitch_key_digest(keyswitch_key: &LweKeyswitchKeyData) -> [u8; 32] {
+    let header = lwe_keyswitch_key_header(
+        keyswitch_key.decomposition_base_log,
+        keyswitch_key.decomposition_level_count,
+        keyswitch_key.output_lwe_size,
+        keyswitch_key.ciphertext_modulus,
+        keyswitch_key.data.len(),
+    );
+    sha256_canonical(&header, &keyswitch_key.data)
+}
+
+/// SHA-256 of the canonical encoding of an LWE public key.
+pub fn public_key_digest(public_key: &LwePublicKeyData) -> [u8; 32] {
+    let header = lwe_public_key_header(
+        public_key.lwe_size,
+        public_key.ciphertext_modulus,
+        public_key.data.len(),
+    );
+    sha256_canonical(&header, &public_key.data)
+}
+
+/// SHA-256 of the canonical encoding of a secret key or secret key share.
+pub fn secret_key_digest(secret_key: &[u64]) -> [u8; 32] {
+    sha256_canonical(&lwe_secret_key_header(u64::BITS, secret_key.len()), secret_key)
+}
+
+/// SHA-256 of the canonical encoding of a secret key on 128 bits.
+pub fn secret_key_u128_digest(secret_key: &[u128]) -> [u8; 32] {
+    sha256_canonical(&lwe_secret_key_header(u128::BITS, secret_key.len()), secret_key)
+}
+
+/// Commitment to a plaintext under the journal policy, `SHA-256(salt || plaintext)`.
+pub fn plaintext_commitment(salt: &[u8; 32], plaintext: u64) -> [u8; 32] {
+    let mut bytes = [0u8; 40];
+    bytes[..32].copy_from_slice(salt);
+    bytes[32..].copy_from_slice(&plaintext.to_le_bytes());
+    sha256_bytes(&bytes)
+}
+
+/// Merkle leaf of a batch decryption, see [`DecryptionLeaf`].
+pub fn decryption_leaf_digest(leaf: &DecryptionLeaf) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(1 + 32 + 8 + 32);
+    bytes.push(0x00);
+    bytes.extend_from_slice(&leaf.ct_digest);
+    bytes.extend_from_slice(&leaf.plaintext.to_le_bytes());
+    if let Some(source_ct_digest) = &leaf.source_ct_digest {
+        bytes.extend_from_slice(source_ct_digest);
+    }
+    sha256_bytes(&bytes)
+}
+
+/// Merkle leaf of an aggregated receipt, `SHA-256(0x00 || image_id || journal)`.
+pub fn aggregated_receipt_leaf(image_id: &[u32; 8], journal: &[u8]) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(1 + 32 + journal.len());
+    bytes.push(0x00);
+    bytes.extend(image_id.iter().flat_map(|word| word.to_le_bytes()));
+    bytes.extend_from_slice(journal);
+    sha256_bytes(&bytes)
+}
+
+/// Merkle root of `leaves`, every node is `SHA-256(0x01 || left || right)` and a node without a
+/// sibling is carried up unchanged. The root of no leaf is all zeros.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => merkle_node(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut bytes = [0u8; 65];
+    bytes[0] = 0x01;
+    bytes[1..33].copy_from_slice(left);
+    bytes[33..].copy_from_slice(right);
+    sha256_bytes(&bytes)
+}
+
+/// SHA-256 of the canonical encoding of the parameters.
+pub fn params_digest(params: &ProofParams) -> [u8; 32] {
+    sha256_bytes(&encode_params(params))
+}
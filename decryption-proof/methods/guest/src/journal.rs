@@ -0,0 +1,37 @@
+use risc0_zkvm::guest::env;
+use serde::Serialize;
+use zkfhe_core::journal::{JournalEnvelope, JournalHeader, JournalKind, JOURNAL_VERSION};
+use zkfhe_core::validation::{ErrorCode, ValidationFailure};
+
+/// Commit `payload` in its envelope at the current journal version, the only way guests commit.
+pub fn commit<Payload: Serialize>(kind: JournalKind, payload: &Payload) {
+    env::commit(&JournalEnvelope::new(kind, payload));
+}
+
+/// Commit the [`ErrorCode`] of `failure` in a rejection envelope and halt with its exit code.
+///
+/// The envelope is committed as the words the risc0 serde format encodes it to, the version, the
+/// kind index and the code, without serializing: the arena rejects through here once out of
+/// memory.
+pub fn reject(failure: ValidationFailure) -> ! {
+    env::log(failure.description());
+    let ErrorCode(code) = ErrorCode::from(failure);
+    env::commit_slice(&[
+        u32::from(JOURNAL_VERSION),
+        JournalKind::Rejected as u32,
+        u32::from(code),
+    ]);
+    env::exit(code)
+}
+
+/// Check that a journal committed by another guest has the layout of this build, a journal of
+/// another version cannot be interpreted here.
+pub fn check_journal_version(journal: &[u8]) {
+    let header: JournalHeader =
+        risc0_zkvm::serde::from_slice(journal).expect("journal has no envelope");
+    assert_eq!(
+        header.version, JOURNAL_VERSION,
+        "journal version {} of a {:?} receipt is not the supported version",
+        header.version, header.kind
+    );
+}
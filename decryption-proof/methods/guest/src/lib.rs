@@ -0,0 +1,14 @@
+#![no_std]
+
+extern crate alloc;
+
+pub mod arena;
+pub mod decoding;
+pub mod entities;
+pub mod flooding;
+pub mod frames;
+pub mod hashing;
+pub mod journal;
+pub mod math;
+pub mod profile;
+pub mod validation;
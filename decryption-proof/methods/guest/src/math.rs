@@ -0,0 +1,57 @@
+//! Decryption of tfhe-rs LWE ciphertexts with the arithmetic of [`zkfhe_core::math`].
+
+use tfhe::core_crypto::prelude::*;
+use zkfhe_core::math::{BinaryLweSecretKey, Modulus};
+
+fn modulus(ciphertext_modulus: CiphertextModulus<u64>) -> Modulus {
+    if ciphertext_modulus.is_native_modulus() {
+        Modulus::new(0)
+    } else {
+        Modulus::new(ciphertext_modulus.get_custom_modulus())
+    }
+}
+
+/// Decrypt `ciphertext` under `lwe_sk`, the plaintext `decrypt_lwe_ciphertext` returns for a
+/// ciphertext whose coefficients are reduced, as [`crate::validation::validate_lwe_ciphertext`]
+/// requires on a non-native modulus.
+pub fn decrypt_lwe<KeyCont, CtCont>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    ciphertext: &LweCiphertext<CtCont>,
+) -> Plaintext<u64>
+where
+    KeyCont: Container<Element = u64>,
+    CtCont: Container<Element = u64>,
+{
+    let modulus = modulus(ciphertext.ciphertext_modulus());
+    Plaintext(modulus.decrypt_lwe(lwe_sk.as_ref(), ciphertext.as_ref()))
+}
+
+/// A secret key decrypting many ciphertexts: a binary key, as tfhe-rs generates them, is turned
+/// into a [`BinaryLweSecretKey`] once, other keys decrypt with [`decrypt_lwe`].
+pub enum LweDecryptionKey {
+    Binary(BinaryLweSecretKey),
+    General(LweSecretKeyOwned<u64>),
+}
+
+impl LweDecryptionKey {
+    pub fn new(lwe_sk: LweSecretKeyOwned<u64>) -> Self {
+        match BinaryLweSecretKey::new(lwe_sk.as_ref()) {
+            Some(binary) => Self::Binary(binary),
+            None => Self::General(lwe_sk),
+        }
+    }
+
+    /// Decrypt `ciphertext`, see [`decrypt_lwe`].
+    pub fn decrypt<CtCont: Container<Element = u64>>(
+        &self,
+        ciphertext: &LweCiphertext<CtCont>,
+    ) -> Plaintext<u64> {
+        match self {
+            Self::Binary(binary) => {
+                let modulus = modulus(ciphertext.ciphertext_modulus());
+                Plaintext(binary.decrypt_lwe(&modulus, ciphertext.as_ref()))
+            }
+            Self::General(lwe_sk) => decrypt_lwe(lwe_sk, ciphertext),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use risc0_zkvm::guest::env;
+
+/// Run `f` and add the cycles it took to `counter`.
+pub fn measure<T>(counter: &mut u64, f: impl FnOnce() -> T) -> T {
+    let start = env::cycle_count();
+    let result = f();
+    *counter += env::cycle_count() - start;
+    result
+}
@@ -0,0 +1,154 @@
+use zkfhe_core::checks::{self, Check};
+use zkfhe_core::encoding::EncodingParams;
+use zkfhe_core::io::{
+    GlweCiphertextData, GlweSecretKeyData, LweCiphertextData, LweCompactCiphertextListData,
+    LweKeyswitchKeyData, LweMultiBitBootstrapKeyHeader, LwePublicKeyData, LweSecretKeyData,
+    NttLweBootstrapKeyHeader,
+};
+use zkfhe_core::params::ProofParams;
+use zkfhe_core::validation::{DecryptionFailure, ValidationFailure};
+
+use crate::journal::reject;
+
+// Checks of the deserialized inputs against the committed parameters. They run on the raw data
+// before the tfhe entities are rebuilt, so a malformed input halts the guest with the code of the
+// failed check instead of an assertion deep inside tfhe-rs or a proof of garbage. The checks
+// themselves live in `zkfhe_core::checks`, where the host fuzzes them.
+
+pub use zkfhe_core::checks::NTT64_CIPHERTEXT_MODULUS;
+
+/// Reject the inputs with `failure` when `condition` does not hold, see [`reject`].
+pub fn ensure(condition: bool, failure: ValidationFailure) {
+    if !condition {
+        reject(failure);
+    }
+}
+
+/// Reject the inputs with the failure of `check` when it does not hold.
+fn enforce(check: Check) {
+    if let Err(failure) = check {
+        reject(failure);
+    }
+}
+
+/// Panic with the message of `failure` when `condition` does not hold. A decryption check fails
+/// after the inputs were validated, the host recovers the failure from the panic message.
+pub fn check(condition: bool, failure: DecryptionFailure) {
+    if !condition {
+        panic!("{failure}");
+    }
+}
+
+/// Dimension of the LWE key obtained by flattening the GLWE secret key, the key the PBS outputs
+/// are encrypted under. Parameters overflowing it are rejected.
+pub fn big_lwe_dimension(params: &ProofParams) -> usize {
+    match checks::checked_big_lwe_dimension(params) {
+        Some(dimension) => dimension,
+        None => reject(ValidationFailure::SecretKeyDimension),
+    }
+}
+
+pub fn validate_encoding(encoding: &EncodingParams) {
+    validate_encoding_for(encoding, u64::BITS);
+}
+
+/// Check the encoding against plaintexts of `plaintext_bits` bits, for the 128 bits guests.
+pub fn validate_encoding_for(encoding: &EncodingParams, plaintext_bits: u32) {
+    enforce(checks::validate_encoding_for(encoding, plaintext_bits));
+}
+
+pub fn validate_unsigned_decoding(params: &ProofParams) {
+    enforce(checks::validate_unsigned_decoding(params));
+}
+
+pub fn validate_lwe_secret_key<Scalar>(
+    secret_key: &LweSecretKeyData<Scalar>,
+    lwe_dimension: usize,
+) {
+    enforce(checks::validate_lwe_secret_key(secret_key, lwe_dimension));
+}
+
+pub fn validate_ciphertext_modulus(params: &ProofParams) {
+    enforce(checks::validate_ciphertext_modulus(params));
+}
+
+pub fn validate_native_modulus(params: &ProofParams) {
+    enforce(checks::validate_native_modulus(params));
+}
+
+pub fn validate_lwe_ciphertext<Scalar: Copy + Into<u128>>(
+    ciphertext: &LweCiphertextData<Scalar>,
+    lwe_dimension: usize,
+    ciphertext_modulus: u128,
+) {
+    enforce(checks::validate_lwe_ciphertext(
+        ciphertext,
+        lwe_dimension,
+        ciphertext_modulus,
+    ));
+}
+
+pub fn validate_lwe_public_key(public_key: &LwePublicKeyData, ciphertext_modulus: u128) {
+    enforce(checks::validate_lwe_public_key(public_key, ciphertext_modulus));
+}
+
+pub fn validate_lwe_compact_ciphertext_list(
+    list: &LweCompactCiphertextListData,
+    lwe_dimension: usize,
+    ciphertext_modulus: u128,
+) {
+    enforce(checks::validate_lwe_compact_ciphertext_list(
+        list,
+        lwe_dimension,
+        ciphertext_modulus,
+    ));
+}
+
+pub fn validate_glwe_secret_key(secret_key: &GlweSecretKeyData, params: &ProofParams) {
+    enforce(checks::validate_glwe_secret_key(secret_key, params));
+}
+
+pub fn validate_glwe_ciphertext(
+    ciphertext: &GlweCiphertextData,
+    params: &ProofParams,
+    ciphertext_modulus: u128,
+) {
+    enforce(checks::validate_glwe_ciphertext(ciphertext, params, ciphertext_modulus));
+}
+
+pub fn validate_keyswitch_key(ksk: &LweKeyswitchKeyData, params: &ProofParams) {
+    enforce(checks::validate_keyswitch_key(ksk, params));
+}
+
+pub fn validate_binary_secret_key<Scalar: Copy + Into<u128>>(
+    secret_key: &LweSecretKeyData<Scalar>,
+) {
+    enforce(checks::validate_binary_secret_key(secret_key));
+}
+
+pub fn validate_binary_glwe_secret_key(secret_key: &GlweSecretKeyData) {
+    enforce(checks::validate_binary_glwe_secret_key(secret_key));
+}
+
+pub fn validate_keyswitch_input(ksk: &LweKeyswitchKeyData, input: &LweCiphertextData) {
+    enforce(checks::validate_keyswitch_input(ksk, input));
+}
+
+pub fn validate_keyswitch(
+    ksk: &LweKeyswitchKeyData,
+    input: &LweCiphertextData,
+    output: &LweCiphertextData,
+) {
+    enforce(checks::validate_keyswitch(ksk, input, output));
+}
+
+pub fn validate_ntt_bootstrap_key(bsk: &NttLweBootstrapKeyHeader, params: &ProofParams) {
+    enforce(checks::validate_ntt_bootstrap_key(bsk, params));
+}
+
+pub fn validate_multi_bit_bootstrap_key(
+    bsk: &LweMultiBitBootstrapKeyHeader,
+    params: &ProofParams,
+) {
+    enforce(checks::validate_multi_bit_bootstrap_key(bsk, params));
+}
@@ -1 +1,38 @@
 include!(concat!(env!("OUT_DIR"), "/methods.rs"));
+include!(concat!(env!("OUT_DIR"), "/guest_list.rs"));
+
+/// A guest binary of this build.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GuestMethod {
+    /// Name of the binary, the prefix of its `_ELF` and `_ID` constants in lower case
+    pub name: &'static str,
+    pub image_id: [u32; 8],
+}
+
+/// What this build of the guests proves, for services to advertise and negotiate the proofs they
+/// accept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GuestInfo {
+    /// Version of the guest crate the binaries were built from
+    pub semver: &'static str,
+    /// Every guest binary, each proves its own kind of statement
+    pub supported_proof_kinds: &'static [GuestMethod],
+}
+
+impl GuestInfo {
+    /// The image ID of the guest named `name`, if this build has one.
+    pub fn image_id(&self, name: &str) -> Option<[u32; 8]> {
+        self.supported_proof_kinds
+            .iter()
+            .find(|guest| guest.name == name)
+            .map(|guest| guest.image_id)
+    }
+}
+
+/// The guests of this build and their version.
+pub fn guest_info() -> GuestInfo {
+    GuestInfo {
+        semver: GUEST_VERSION,
+        supported_proof_kinds: GUESTS,
+    }
+}
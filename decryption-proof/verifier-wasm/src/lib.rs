@@ -0,0 +1,115 @@
+// WebAssembly bindings of the receipt verification, so web frontends and Node services can check
+// a single decryption receipt written by the host with `--out` without native code. Build with
+// `wasm-pack build verifier-wasm --target web` (or `--target nodejs`).
+use risc0_zkvm::sha::{Digest, Impl, Sha256};
+use risc0_zkvm::{InnerReceipt, Receipt};
+use wasm_bindgen::prelude::*;
+use zkfhe_core::canonical::decode_lwe_ciphertext;
+use zkfhe_core::digest::parse_digest;
+use zkfhe_core::journal::{DecryptionJournal, JournalEnvelope, JournalKind, JOURNAL_VERSION};
+
+/// Outcome of [`verify_receipt`]. A receipt that fails any check is reported with `valid` false
+/// and the reason in `error`, the journal fields are only set for a valid receipt.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, Default)]
+pub struct VerifyResult {
+    pub valid: bool,
+    pub error: String,
+    /// The decrypted plaintext committed by the guest
+    pub plaintext: u64,
+    /// ID of the secret key the guest decrypted with, to compare with the published key ID
+    pub secret_key_id: Vec<u8>,
+    /// Digest of the committed parameters, to compare with the published parameter set
+    pub params_digest: Vec<u8>,
+    /// Digest of the ciphertext the decrypted one was derived from, empty when the guest proved
+    /// no provenance
+    pub source_ct_digest: Vec<u8>,
+    /// Bits between the noise of the ciphertext and its decoding bound, 0 for journals that
+    /// commit no noise budget
+    pub noise_budget_bits: u32,
+    /// ID of the external request the receipt answers, empty when it is bound to none. A caller
+    /// answering a request compares it with the request's ID
+    pub request_id: Vec<u8>,
+}
+
+impl VerifyResult {
+    fn invalid(error: impl ToString) -> Self {
+        Self {
+            error: error.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+fn parse_image_id(image_id: &str) -> Result<Digest, String> {
+    let bytes = parse_digest(image_id).map_err(|error| format!("invalid image ID: {error}"))?;
+    Ok(Digest::from(bytes))
+}
+
+fn verify(
+    receipt_bytes: &[u8],
+    image_id: &str,
+    expected_ct_digest: &[u8],
+) -> Result<DecryptionJournal, String> {
+    let image_id = parse_image_id(image_id)?;
+    let receipt: Receipt = bincode::deserialize(receipt_bytes).map_err(|err| err.to_string())?;
+
+    // Fake receipts only verify in dev mode, which a browser or a Node service never runs in
+    if matches!(receipt.inner, InnerReceipt::Fake(_)) {
+        return Err("refusing a fake dev mode receipt".to_string());
+    }
+    receipt.verify(image_id).map_err(|err| err.to_string())?;
+
+    let envelope: JournalEnvelope<DecryptionJournal> =
+        receipt.journal.decode().map_err(|err| err.to_string())?;
+    if envelope.version != JOURNAL_VERSION {
+        return Err(format!("unsupported journal version {}", envelope.version));
+    }
+    if envelope.kind != JournalKind::Decrypt {
+        return Err(format!("expected a decryption journal, got {:?}", envelope.kind));
+    }
+    let journal = envelope.payload;
+    if journal.ct_digest.as_slice() != expected_ct_digest {
+        return Err("ciphertext digest mismatch".to_string());
+    }
+    if journal.overflow {
+        return Err("the plaintext overflows into its padding".to_string());
+    }
+    Ok(journal)
+}
+
+/// Verify a single decryption receipt, serialized with bincode, against the hex encoded image ID
+/// of its guest and the 32 bytes digest of the ciphertext it must decrypt.
+#[wasm_bindgen]
+pub fn verify_receipt(
+    receipt_bytes: &[u8],
+    image_id: &str,
+    expected_ct_digest: &[u8],
+) -> VerifyResult {
+    match verify(receipt_bytes, image_id, expected_ct_digest) {
+        Ok(journal) => VerifyResult {
+            valid: true,
+            error: String::new(),
+            plaintext: journal.plaintext,
+            secret_key_id: journal.secret_key_id.to_vec(),
+            params_digest: journal.params_digest.to_vec(),
+            source_ct_digest: journal
+                .source_ct_digest
+                .map_or_else(Vec::new, |digest| digest.to_vec()),
+            noise_budget_bits: journal.noise_budget_bits.unwrap_or(0),
+            request_id: journal.request_id.map_or_else(Vec::new, |request_id| request_id.to_vec()),
+        },
+        Err(error) => VerifyResult::invalid(error),
+    }
+}
+
+/// Digest of the ciphertext whose canonical encoding is `encoding`, the public input of the
+/// decrypt guest, to pass to [`verify_receipt`]. Empty when `encoding` is not the encoding of an
+/// LWE ciphertext.
+#[wasm_bindgen]
+pub fn ciphertext_digest(encoding: &[u8]) -> Vec<u8> {
+    match decode_lwe_ciphertext(encoding) {
+        Some(_) => Impl::hash_bytes(encoding).as_bytes().to_vec(),
+        None => Vec::new(),
+    }
+}
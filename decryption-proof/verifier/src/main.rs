@@ -0,0 +1,137 @@
+// Standalone verification of a receipt written by the host with `--out`. Only the receipt and the
+// image ID of the guest are needed, no tfhe-rs and no secret material. A decryption receipt can
+// also be bound to the ciphertext it decrypts: the decrypt guest commits the SHA-256 of the
+// canonical encoding it read as public input, which is recomputed here from the same bytes, and
+// to the external request it answers, whose ID the guest echoes.
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use clap::{Parser, ValueEnum};
+use risc0_zkvm::sha::{Digest, Impl, Sha256};
+use risc0_zkvm::{InnerReceipt, Receipt};
+use std::error::Error;
+use std::path::PathBuf;
+use zkfhe_core::canonical::decode_lwe_ciphertext;
+use zkfhe_core::digest::parse_digest;
+use zkfhe_core::journal::{DecryptionJournal, JournalEnvelope, JournalKind, JOURNAL_VERSION};
+
+#[derive(Parser)]
+#[command(about = "Verify a decryption proof receipt")]
+struct Args {
+    /// Receipt file written by the host with `--out`
+    receipt: PathBuf,
+
+    /// Hex encoded image ID of the guest the receipt must have been produced by
+    #[arg(long)]
+    image_id: String,
+
+    /// Format of the receipt file, as written by the host with `--out-format`
+    #[arg(long, value_enum, default_value_t = ReceiptFormat::Bincode)]
+    format: ReceiptFormat,
+
+    /// Accept fake receipts produced in dev mode, they prove nothing
+    #[arg(long)]
+    allow_dev_receipts: bool,
+
+    /// File holding the canonical encoding of the LWE ciphertext a decrypt receipt must decrypt,
+    /// the public input of the guest
+    #[arg(long)]
+    ciphertext: Option<PathBuf>,
+
+    /// Hex encoded ID of the external request a decrypt receipt must answer
+    #[arg(long)]
+    request_id: Option<String>,
+}
+
+/// The formats of `host::format::FileFormat`, the armors hold the bincode encoding as text.
+#[derive(Clone, Copy, ValueEnum)]
+enum ReceiptFormat {
+    Bincode,
+    Cbor,
+    Hex,
+    Base64,
+}
+
+fn read_receipt(bytes: &[u8], format: ReceiptFormat) -> Result<Receipt, Box<dyn Error>> {
+    let text: Vec<u8> = bytes.iter().copied().filter(|byte| !byte.is_ascii_whitespace()).collect();
+    let bincode = match format {
+        ReceiptFormat::Bincode => bytes.to_vec(),
+        ReceiptFormat::Cbor => return Ok(ciborium::from_reader(bytes)?),
+        ReceiptFormat::Hex => hex::decode(text.strip_prefix(b"0x").unwrap_or(&text))?,
+        ReceiptFormat::Base64 => STANDARD.decode(&text)?,
+    };
+    Ok(bincode::deserialize(&bincode)?)
+}
+
+fn parse_image_id(image_id: &str) -> Result<Digest, Box<dyn Error>> {
+    let bytes = parse_digest(image_id).map_err(|error| format!("invalid image ID: {error}"))?;
+    Ok(Digest::from(bytes))
+}
+
+fn decryption_journal(receipt: &Receipt) -> Result<DecryptionJournal, Box<dyn Error>> {
+    let envelope: JournalEnvelope<DecryptionJournal> = receipt.journal.decode()?;
+    if envelope.version != JOURNAL_VERSION || envelope.kind != JournalKind::Decrypt {
+        return Err(format!(
+            "expected a decryption journal of version {JOURNAL_VERSION}, got a {:?} journal of \
+             version {}",
+            envelope.kind, envelope.version
+        )
+        .into());
+    }
+    Ok(envelope.payload)
+}
+
+/// Check that `receipt` decrypts the ciphertext whose canonical encoding is `encoding`.
+fn check_ciphertext(receipt: &Receipt, encoding: &[u8]) -> Result<(), Box<dyn Error>> {
+    if decode_lwe_ciphertext(encoding).is_none() {
+        return Err("the ciphertext file is not the canonical encoding of an LWE ciphertext".into());
+    }
+    if decryption_journal(receipt)?.ct_digest.as_slice() != Impl::hash_bytes(encoding).as_bytes() {
+        return Err("the receipt decrypts another ciphertext".into());
+    }
+    Ok(())
+}
+
+/// Check that `receipt` answers the request `request_id`, a receipt bound to no request answers
+/// none.
+fn check_request_id(receipt: &Receipt, request_id: &str) -> Result<(), Box<dyn Error>> {
+    let request_id =
+        parse_digest(request_id).map_err(|error| format!("invalid request ID: {error}"))?;
+    if decryption_journal(receipt)?.request_id != Some(request_id) {
+        return Err("the receipt answers another request".into());
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    let image_id = parse_image_id(&args.image_id)?;
+    let receipt = read_receipt(&std::fs::read(&args.receipt)?, args.format)?;
+
+    if matches!(receipt.inner, InnerReceipt::Fake(_)) {
+        if !args.allow_dev_receipts {
+            return Err(
+                "refusing a fake dev mode receipt, pass --allow-dev-receipts to accept it".into(),
+            );
+        }
+        // Fake receipts only verify in dev mode
+        std::env::set_var("RISC0_DEV_MODE", "1");
+    }
+    receipt.verify(image_id)?;
+    if let Some(ciphertext) = &args.ciphertext {
+        check_ciphertext(&receipt, &std::fs::read(ciphertext)?)?;
+        println!("Receipt decrypts the ciphertext of {}", ciphertext.display());
+    }
+    if let Some(request_id) = &args.request_id {
+        check_request_id(&receipt, request_id)?;
+        println!("Receipt answers request {request_id}");
+    }
+
+    println!(
+        "Receipt {} is valid for image ID {image_id}, journal is {} bytes",
+        args.receipt.display(),
+        receipt.journal.bytes.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! Guest memory budgets of the batch guests, computed from the committed parameters.
+//!
+//! The guests reserve these budgets in their arena before reading the batch, and the host checks
+//! them against the guest memory before proving, so an input too large for the zkVM is refused
+//! up front instead of halting the guest once proving started.
+
+use core::mem::size_of;
+
+use serde::{Deserialize, Serialize};
+
+use crate::params::ProofParams;
+
+/// Memory the PBS guest is given, chosen by the host from its presets.
+///
+/// The stack is the scratch space of the blind rotation, allocated once and reused for every batch
+/// item. It grows with the accumulators, so large polynomial sizes need a larger one. The heap
+/// caps the arena: the guest rejects its inputs when their budget and the stack do not fit in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryLayout {
+    pub heap_bytes: u64,
+    pub stack_bytes: u64,
+}
+
+/// Scratch space on top of the inputs: deserialization copies, tfhe-rs buffers and the journal.
+pub const HEADROOM_BYTES: usize = 16 << 20;
+
+fn big_lwe_dimension(params: &ProofParams) -> usize {
+    params.glwe_dimension * params.polynomial_size
+}
+
+/// Memory the batch decryption guest needs under `params`: the secret keys rebuilt from their
+/// deserialized copies, the keyswitching key when it keyswitches, and for every batch item its PBS
+/// output, the copy decrypted and the keyswitched ciphertext.
+pub fn batch_budget(params: &ProofParams, keyswitch: bool, batch_size: u32) -> usize {
+    let big_lwe_size = big_lwe_dimension(params) + 1;
+    let small_lwe_size = params.lwe_dimension + 1;
+    let mut words = 2 * big_lwe_size;
+    let mut item_words = 2 * big_lwe_size;
+    if keyswitch {
+        words += big_lwe_dimension(params) * params.ks_level * small_lwe_size + small_lwe_size;
+        item_words += small_lwe_size;
+    }
+    words += batch_size as usize * item_words;
+    words * size_of::<u64>() + HEADROOM_BYTES
+}
+
+/// Scratch of the standard domain blind rotations of a multi-bit PBS under `params`: every rotation
+/// of the batch allocates the GGSW ciphertext of a group and the accumulators it sums into, the
+/// guest arena never frees them.
+pub fn multi_bit_scratch_budget(params: &ProofParams, batch_size: u32) -> usize {
+    let glwe_size = params.glwe_dimension + 1;
+    let glwe_len = glwe_size * params.polynomial_size;
+    let ggsw_len = params.pbs_level * glwe_size * glwe_len;
+    batch_size as usize * (ggsw_len + 3 * glwe_len) * size_of::<u64>()
+}
+
+/// Memory the PBS guest needs under `params`: the NTT bootstrapping key, the accumulator and the
+/// one rebuilt from the function table, the big secret key, the rotated accumulator and the
+/// extracted sample the whole batch is bootstrapped into, and for every batch item its ciphertexts.
+pub fn pbs_budget(params: &ProofParams, ntt_bsk_container_len: u64, batch_size: u32) -> usize {
+    let glwe_len = (params.glwe_dimension + 1) * params.polynomial_size;
+    let big_lwe_size = big_lwe_dimension(params) + 1;
+    let small_lwe_size = params.lwe_dimension + 1;
+    let item_words = small_lwe_size + big_lwe_size;
+    let words = ntt_bsk_container_len as usize
+        + 3 * glwe_len
+        + 2 * big_lwe_size
+        + batch_size as usize * item_words;
+    words * size_of::<u64>() + HEADROOM_BYTES
+}
@@ -0,0 +1,470 @@
+//! Canonical serialization of the proof inputs, the preimage of every digest a journal commits.
+//!
+//! An encoding is the [`CANONICAL_VERSION`] byte, the [`Tag`] of what is encoded, its metadata and
+//! finally its values, all little endian: integers at their fixed width, `usize`s as `u64`s,
+//! `f64`s as their IEEE 754 bits, strings and containers prefixed by their length as a `u64`.
+//! Unlike bincode or the risc0 serde format the layout is owned by this module, so a digest only
+//! changes with the version, and it is the same on the host and in the guests.
+//!
+//! Containers come last so a multi-megabyte key is hashed as its header followed by its values,
+//! without ever building its encoding.
+//!
+//! The encoding of a public statement is also its wire format: the decrypt guest reads the
+//! ciphertext as the bytes of its encoding, hashes them as they are and decodes them with
+//! [`decode_lwe_ciphertext`], so the digest it commits is the SHA-256 of the bytes it was given.
+
+use alloc::vec::Vec;
+
+use crate::io::{LweCiphertextData, LweMultiBitBootstrapKeyHeader, NttLweBootstrapKeyHeader};
+use crate::params::{NoiseDistribution, ProofParams};
+
+/// Version of the layout, the first byte of every encoding.
+pub const CANONICAL_VERSION: u8 = 4;
+
+/// What an encoding holds, its second byte, so two kinds of inputs never share a digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Tag {
+    ProofParams = 1,
+    LweCiphertext = 2,
+    LweSecretKey = 3,
+    GlweCiphertext = 4,
+    GlweSecretKey = 5,
+    LweKeyswitchKey = 6,
+    NttLweBootstrapKey = 7,
+    LwePublicKey = 8,
+    LweCompactCiphertextList = 9,
+    LweMultiBitBootstrapKey = 10,
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn new(tag: Tag) -> Self {
+        Self(Vec::from([CANONICAL_VERSION, tag as u8]))
+    }
+
+    fn u8(mut self, value: u8) -> Self {
+        self.0.push(value);
+        self
+    }
+
+    fn u32(mut self, value: u32) -> Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn u64(mut self, value: u64) -> Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn usize(self, value: usize) -> Self {
+        self.u64(value as u64)
+    }
+
+    fn u128(mut self, value: u128) -> Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn str(mut self, value: &str) -> Self {
+        self = self.usize(value.len());
+        self.0.extend_from_slice(value.as_bytes());
+        self
+    }
+
+    fn noise(self, noise: &NoiseDistribution) -> Self {
+        match *noise {
+            NoiseDistribution::Gaussian { std_dev } => self.u8(0).u64(std_dev.to_bits()),
+            NoiseDistribution::TUniform { bound_log2 } => self.u8(1).u32(bound_log2),
+        }
+    }
+}
+
+/// Header of an LWE ciphertext of `len` coefficients of `scalar_bits` bits, mask then body, on
+/// `ciphertext_modulus`, 0 for the native modulus.
+pub fn lwe_ciphertext_header(scalar_bits: u32, ciphertext_modulus: u128, len: usize) -> Vec<u8> {
+    Writer::new(Tag::LweCiphertext)
+        .u32(scalar_bits)
+        .u128(ciphertext_modulus)
+        .usize(len)
+        .0
+}
+
+/// Header of an LWE secret key, or an additive share of one, of `len` coefficients of
+/// `scalar_bits` bits.
+pub fn lwe_secret_key_header(scalar_bits: u32, len: usize) -> Vec<u8> {
+    Writer::new(Tag::LweSecretKey).u32(scalar_bits).usize(len).0
+}
+
+/// Header of a GLWE ciphertext of `len` coefficients, masks then body, on `ciphertext_modulus`.
+pub fn glwe_ciphertext_header(
+    polynomial_size: usize,
+    ciphertext_modulus: u128,
+    len: usize,
+) -> Vec<u8> {
+    Writer::new(Tag::GlweCiphertext)
+        .usize(polynomial_size)
+        .u128(ciphertext_modulus)
+        .usize(len)
+        .0
+}
+
+/// Header of a GLWE secret key of `len` coefficients.
+pub fn glwe_secret_key_header(polynomial_size: usize, len: usize) -> Vec<u8> {
+    Writer::new(Tag::GlweSecretKey)
+        .usize(polynomial_size)
+        .usize(len)
+        .0
+}
+
+/// Header of a keyswitching key container of `len` values.
+pub fn lwe_keyswitch_key_header(
+    decomposition_base_log: usize,
+    decomposition_level_count: usize,
+    output_lwe_size: usize,
+    ciphertext_modulus: u128,
+    len: usize,
+) -> Vec<u8> {
+    Writer::new(Tag::LweKeyswitchKey)
+        .usize(decomposition_base_log)
+        .usize(decomposition_level_count)
+        .usize(output_lwe_size)
+        .u128(ciphertext_modulus)
+        .usize(len)
+        .0
+}
+
+/// Header of an LWE public key, encryptions of zero of `lwe_size` coefficients each, of `len`
+/// values in total.
+pub fn lwe_public_key_header(lwe_size: usize, ciphertext_modulus: u128, len: usize) -> Vec<u8> {
+    Writer::new(Tag::LwePublicKey)
+        .usize(lwe_size)
+        .u128(ciphertext_modulus)
+        .usize(len)
+        .0
+}
+
+/// Header of an LWE compact ciphertext list of `ciphertext_count` ciphertexts of `lwe_size`
+/// coefficients once expanded, its masks then its bodies, of `len` values in total.
+pub fn lwe_compact_ciphertext_list_header(
+    lwe_size: usize,
+    ciphertext_count: usize,
+    ciphertext_modulus: u128,
+    len: usize,
+) -> Vec<u8> {
+    Writer::new(Tag::LweCompactCiphertextList)
+        .usize(lwe_size)
+        .usize(ciphertext_count)
+        .u128(ciphertext_modulus)
+        .usize(len)
+        .0
+}
+
+/// Header of an NTT bootstrapping key, the container length is the one of `header`.
+pub fn ntt_lwe_bootstrap_key_header(header: &NttLweBootstrapKeyHeader) -> Vec<u8> {
+    Writer::new(Tag::NttLweBootstrapKey)
+        .usize(header.glwe_size)
+        .usize(header.polynomial_size)
+        .usize(header.decomposition_base_log)
+        .usize(header.decomposition_level_count)
+        .u128(header.ciphertext_modulus)
+        .u64(header.container_len)
+        .0
+}
+
+/// Header of a multi-bit bootstrapping key, the container length is the one of `header`.
+pub fn lwe_multi_bit_bootstrap_key_header(header: &LweMultiBitBootstrapKeyHeader) -> Vec<u8> {
+    Writer::new(Tag::LweMultiBitBootstrapKey)
+        .usize(header.input_lwe_dimension)
+        .usize(header.glwe_size)
+        .usize(header.polynomial_size)
+        .usize(header.decomposition_base_log)
+        .usize(header.decomposition_level_count)
+        .usize(header.grouping_factor)
+        .u128(header.ciphertext_modulus)
+        .u64(header.container_len)
+        .0
+}
+
+/// Encoding of a parameter set, every field in declaration order.
+pub fn encode_params(params: &ProofParams) -> Vec<u8> {
+    Writer::new(Tag::ProofParams)
+        .str(&params.name)
+        .usize(params.lwe_dimension)
+        .usize(params.glwe_dimension)
+        .usize(params.polynomial_size)
+        .noise(&params.lwe_noise_distribution)
+        .noise(&params.glwe_noise_distribution)
+        .usize(params.pbs_base_log)
+        .usize(params.pbs_level)
+        .usize(params.ks_base_log)
+        .usize(params.ks_level)
+        .u8(params.pbs_order as u8)
+        .u32(params.encoding.message_bits)
+        .u32(params.encoding.carry_bits)
+        .u32(params.encoding.padding_bits)
+        .u8(params.decoding as u8)
+        .u128(params.ciphertext_modulus)
+        .u32(params.security_bits)
+        .0
+}
+
+/// Encoding of a container of 64 bit values: its header followed by the values.
+pub fn encode_u64s(mut header: Vec<u8>, values: &[u64]) -> Vec<u8> {
+    header.reserve(values.len() * 8);
+    header.extend(values.iter().flat_map(|value| value.to_le_bytes()));
+    header
+}
+
+/// Encoding of a container of 128 bit values: its header followed by the values.
+pub fn encode_u128s(mut header: Vec<u8>, values: &[u128]) -> Vec<u8> {
+    header.reserve(values.len() * 16);
+    header.extend(values.iter().flat_map(|value| value.to_le_bytes()));
+    header
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        if self.0.len() < N {
+            return None;
+        }
+        let (bytes, rest) = self.0.split_at(N);
+        self.0 = rest;
+        bytes.try_into().ok()
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.bytes().map(u64::from_le_bytes)
+    }
+
+    fn u128(&mut self) -> Option<u128> {
+        self.bytes().map(u128::from_le_bytes)
+    }
+}
+
+/// The LWE ciphertext on 64 bits `bytes` is the encoding of, `None` when `bytes` is the encoding
+/// of anything else, or is truncated or followed by other bytes. Encoding the ciphertext again
+/// gives back `bytes`, so their digest is the digest of the ciphertext.
+pub fn decode_lwe_ciphertext(bytes: &[u8]) -> Option<LweCiphertextData> {
+    let mut reader = Reader(bytes);
+    if reader.bytes()? != [CANONICAL_VERSION, Tag::LweCiphertext as u8] {
+        return None;
+    }
+    if reader.u32()? != u64::BITS {
+        return None;
+    }
+    let ciphertext_modulus = reader.u128()?;
+    let len = reader.u64()?;
+    if len.checked_mul(8)? != reader.0.len() as u64 {
+        return None;
+    }
+    let data = reader
+        .0
+        .chunks_exact(8)
+        .map(|value| u64::from_le_bytes(value.try_into().expect("chunks of 8 bytes")))
+        .collect();
+    Some(LweCiphertextData {
+        data,
+        ciphertext_modulus,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    //! Golden vectors, a failure here means every digest committed so far changes: bump
+    //! [`CANONICAL_VERSION`] rather than updating the vectors.
+
+    use alloc::string::String;
+
+    use super::*;
+    use crate::encoding::{Decoding, EncodingParams};
+    use crate::params::PbsOrder;
+
+    fn hex(bytes: &[u8]) -> String {
+        use core::fmt::Write;
+        bytes.iter().fold(String::new(), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+    }
+
+    #[test]
+    fn lwe_ciphertext() {
+        let encoding = encode_u64s(
+            lwe_ciphertext_header(64, 0, 3),
+            &[1, u64::MAX, 0x0102_0304_0506_0708],
+        );
+        assert_eq!(
+            hex(&encoding),
+            "0402\
+             40000000\
+             00000000000000000000000000000000\
+             0300000000000000\
+             0100000000000000\
+             ffffffffffffffff\
+             0807060504030201"
+        );
+    }
+
+    #[test]
+    fn lwe_ciphertext_u128_non_native() {
+        let encoding = encode_u128s(lwe_ciphertext_header(128, 1 << 64, 1), &[1 << 64 | 2]);
+        assert_eq!(
+            hex(&encoding),
+            "0402\
+             80000000\
+             00000000000000000100000000000000\
+             0100000000000000\
+             02000000000000000100000000000000"
+        );
+    }
+
+    #[test]
+    fn lwe_ciphertext_decodes_from_its_encoding() {
+        let ciphertext = LweCiphertextData {
+            data: Vec::from([1, u64::MAX, 0x0102_0304_0506_0708]),
+            ciphertext_modulus: 1 << 62,
+        };
+        let encoding = encode_u64s(
+            lwe_ciphertext_header(64, ciphertext.ciphertext_modulus, ciphertext.data.len()),
+            &ciphertext.data,
+        );
+        assert_eq!(decode_lwe_ciphertext(&encoding), Some(ciphertext));
+
+        assert_eq!(decode_lwe_ciphertext(&encoding[..encoding.len() - 1]), None);
+        let mut extended = encoding.clone();
+        extended.push(0);
+        assert_eq!(decode_lwe_ciphertext(&extended), None);
+        let u128_encoding = encode_u128s(lwe_ciphertext_header(128, 0, 1), &[1]);
+        assert_eq!(decode_lwe_ciphertext(&u128_encoding), None);
+        let key_encoding = encode_u64s(lwe_secret_key_header(64, 3), &[0, 1, 1]);
+        assert_eq!(decode_lwe_ciphertext(&key_encoding), None);
+    }
+
+    #[test]
+    fn lwe_secret_key() {
+        let encoding = encode_u64s(lwe_secret_key_header(64, 2), &[0, 1]);
+        assert_eq!(
+            hex(&encoding),
+            "0403\
+             40000000\
+             0200000000000000\
+             0000000000000000\
+             0100000000000000"
+        );
+    }
+
+    #[test]
+    fn ntt_lwe_bootstrap_key() {
+        let header = NttLweBootstrapKeyHeader {
+            container_len: 4096,
+            glwe_size: 2,
+            polynomial_size: 1024,
+            decomposition_base_log: 23,
+            decomposition_level_count: 1,
+            ciphertext_modulus: 0,
+        };
+        assert_eq!(
+            hex(&ntt_lwe_bootstrap_key_header(&header)),
+            "0407\
+             0200000000000000\
+             0004000000000000\
+             1700000000000000\
+             0100000000000000\
+             00000000000000000000000000000000\
+             0010000000000000"
+        );
+    }
+
+    #[test]
+    fn lwe_multi_bit_bootstrap_key() {
+        let header = LweMultiBitBootstrapKeyHeader {
+            container_len: 8192,
+            input_lwe_dimension: 4,
+            glwe_size: 2,
+            polynomial_size: 256,
+            decomposition_base_log: 22,
+            decomposition_level_count: 1,
+            grouping_factor: 2,
+            ciphertext_modulus: 0,
+        };
+        assert_eq!(
+            hex(&lwe_multi_bit_bootstrap_key_header(&header)),
+            "040a\
+             0400000000000000\
+             0200000000000000\
+             0001000000000000\
+             1600000000000000\
+             0100000000000000\
+             0200000000000000\
+             00000000000000000000000000000000\
+             0020000000000000"
+        );
+    }
+
+    #[test]
+    fn params() {
+        let params = ProofParams {
+            name: "toy".into(),
+            lwe_dimension: 2,
+            glwe_dimension: 1,
+            polynomial_size: 512,
+            lwe_noise_distribution: NoiseDistribution::Gaussian { std_dev: 0.5 },
+            glwe_noise_distribution: NoiseDistribution::TUniform { bound_log2: 17 },
+            pbs_base_log: 23,
+            pbs_level: 1,
+            ks_base_log: 3,
+            ks_level: 5,
+            pbs_order: PbsOrder::KeyswitchBootstrap,
+            encoding: EncodingParams {
+                message_bits: 2,
+                carry_bits: 2,
+                padding_bits: 1,
+            },
+            decoding: Decoding::Signed,
+            ciphertext_modulus: 0,
+            security_bits: 128,
+        };
+        assert_eq!(
+            hex(&encode_params(&params)),
+            "0401\
+             0300000000000000746f79\
+             0200000000000000\
+             0100000000000000\
+             0002000000000000\
+             00000000000000e03f\
+             0111000000\
+             1700000000000000\
+             0100000000000000\
+             0300000000000000\
+             0500000000000000\
+             00\
+             02000000\
+             02000000\
+             01000000\
+             01\
+             00000000000000000000000000000000\
+             80000000"
+        );
+    }
+
+    #[test]
+    fn kinds_never_collide() {
+        let ciphertext = encode_u64s(lwe_ciphertext_header(64, 0, 1), &[0]);
+        let key = encode_u64s(lwe_secret_key_header(64, 1), &[0]);
+        assert_ne!(ciphertext, key);
+        assert_ne!(
+            encode_u64s(glwe_ciphertext_header(1, 0, 1), &[0]),
+            encode_u64s(glwe_secret_key_header(1, 1), &[0]),
+        );
+        assert_eq!(ciphertext[0], CANONICAL_VERSION);
+    }
+}
@@ -0,0 +1,446 @@
+//! Structural checks of the inputs the guests decode, run on the raw containers before any tfhe
+//! entity is rebuilt from them.
+//!
+//! Every input is read from bytes the prover controls, so a check must hold for any decoded value:
+//! sizes are multiplied and added without overflow, and an input that passes the checks of its
+//! guest must rebuild into tfhe entities without tripping an assertion of tfhe-rs. A guest built in
+//! release mode would wrap where the host panics, and that divergence alone could let a malformed
+//! input be proven. The fuzz targets of the host decode arbitrary words and hold the checks to it.
+//!
+//! The guests turn a failed check into the rejection of their inputs with its
+//! [`ValidationFailure`].
+
+use crate::encoding::{Decoding, EncodingParams};
+use crate::io::{
+    GlweCiphertextData, GlweSecretKeyData, LweCiphertextData, LweCompactCiphertextListData,
+    LweKeyswitchKeyData, LweMultiBitBootstrapKeyHeader, LwePublicKeyData, LweSecretKeyData,
+    NttLweBootstrapKeyHeader,
+};
+use crate::params::ProofParams;
+use crate::validation::ValidationFailure;
+
+/// Modulus of the NTT bootstrapping key, the Solinas prime `blind_rotate_ntt64_assign` works on.
+pub const NTT64_CIPHERTEXT_MODULUS: u128 = (1 << 64) - (1 << 32) + 1;
+
+/// Largest grouping factor of a multi-bit bootstrapping key, the tfhe-rs multi-bit parameter sets
+/// use 2 to 4.
+pub const MAX_GROUPING_FACTOR: usize = 4;
+
+/// A check that holds or fails with the [`ValidationFailure`] the guest rejects its inputs with.
+pub type Check = Result<(), ValidationFailure>;
+
+fn ensure(condition: bool, failure: ValidationFailure) -> Check {
+    if condition {
+        Ok(())
+    } else {
+        Err(failure)
+    }
+}
+
+/// Dimension of the LWE key obtained by flattening the GLWE secret key, the key the PBS outputs
+/// are encrypted under, `None` when the parameters overflow it.
+pub fn checked_big_lwe_dimension(params: &ProofParams) -> Option<usize> {
+    params.glwe_dimension.checked_mul(params.polynomial_size)
+}
+
+fn big_lwe_dimension(params: &ProofParams) -> Result<usize, ValidationFailure> {
+    checked_big_lwe_dimension(params).ok_or(ValidationFailure::SecretKeyDimension)
+}
+
+/// Check the encoding against plaintexts of `plaintext_bits` bits.
+pub fn validate_encoding_for(encoding: &EncodingParams, plaintext_bits: u32) -> Check {
+    ensure(
+        encoding.is_valid_for(plaintext_bits),
+        ValidationFailure::InvalidEncoding,
+    )
+}
+
+/// Check that the parameters decode unsigned messages, for the guests that output no other
+/// decoding.
+pub fn validate_unsigned_decoding(params: &ProofParams) -> Check {
+    ensure(
+        params.decoding == Decoding::Unsigned,
+        ValidationFailure::InvalidEncoding,
+    )
+}
+
+pub fn validate_lwe_secret_key<Scalar>(
+    secret_key: &LweSecretKeyData<Scalar>,
+    lwe_dimension: usize,
+) -> Check {
+    ensure(
+        secret_key.data.len() == lwe_dimension,
+        ValidationFailure::SecretKeyDimension,
+    )
+}
+
+/// Check the ciphertext modulus of the parameters: native, or an odd non-native modulus below
+/// `2^64` leaving room for every encoded bit. An even modulus is refused, the non-native decoding
+/// rounds as the odd moduli tfhe-rs supports, and a power of two is the native modulus truncated.
+pub fn validate_ciphertext_modulus(params: &ProofParams) -> Check {
+    let modulus = params.ciphertext_modulus;
+    let encoded = params
+        .encoding
+        .checked_total_bits()
+        .and_then(|total_bits| 1u128.checked_shl(total_bits));
+    ensure(
+        modulus == 0
+            || (modulus % 2 == 1
+                && encoded.is_some_and(|encoded| modulus > encoded && modulus < 1 << 64)),
+        ValidationFailure::CiphertextModulus,
+    )
+}
+
+/// Check that the parameters use the native modulus, for the guests that support no other.
+pub fn validate_native_modulus(params: &ProofParams) -> Check {
+    ensure(params.ciphertext_modulus == 0, ValidationFailure::CiphertextModulus)
+}
+
+/// Check that `ciphertext` can be decrypted under a key of `lwe_dimension` and is defined over
+/// `ciphertext_modulus`, 0 standing for the native modulus. On a non-native modulus every
+/// coefficient must be reduced, as the modular arithmetic of tfhe-rs expects.
+pub fn validate_lwe_ciphertext<Scalar: Copy + Into<u128>>(
+    ciphertext: &LweCiphertextData<Scalar>,
+    lwe_dimension: usize,
+    ciphertext_modulus: u128,
+) -> Check {
+    ensure(
+        lwe_dimension.checked_add(1) == Some(ciphertext.data.len()),
+        ValidationFailure::CiphertextDimension,
+    )?;
+    ensure(
+        ciphertext.ciphertext_modulus == ciphertext_modulus,
+        ValidationFailure::CiphertextModulus,
+    )?;
+    ensure(
+        ciphertext_modulus == 0
+            || ciphertext
+                .data
+                .iter()
+                .all(|&coefficient| coefficient.into() < ciphertext_modulus),
+        ValidationFailure::CiphertextModulus,
+    )
+}
+
+/// Check that `public_key` holds at least one whole encryption of zero, on `ciphertext_modulus`
+/// and reduced as [`validate_lwe_ciphertext`] requires.
+pub fn validate_lwe_public_key(public_key: &LwePublicKeyData, ciphertext_modulus: u128) -> Check {
+    ensure(
+        public_key.lwe_size > 1
+            && !public_key.data.is_empty()
+            && public_key.data.len().is_multiple_of(public_key.lwe_size),
+        ValidationFailure::CiphertextDimension,
+    )?;
+    ensure(
+        public_key.ciphertext_modulus == ciphertext_modulus,
+        ValidationFailure::CiphertextModulus,
+    )?;
+    ensure(
+        ciphertext_modulus == 0
+            || public_key
+                .data
+                .iter()
+                .all(|&coefficient| u128::from(coefficient) < ciphertext_modulus),
+        ValidationFailure::CiphertextModulus,
+    )
+}
+
+/// Check that `list` holds at least one ciphertext under a key of `lwe_dimension`, a power of two
+/// as the expansion multiplies the masks in `X^n + 1`, with one mask per `lwe_dimension`
+/// ciphertexts followed by every body.
+pub fn validate_lwe_compact_ciphertext_list(
+    list: &LweCompactCiphertextListData,
+    lwe_dimension: usize,
+    ciphertext_modulus: u128,
+) -> Check {
+    ensure(
+        lwe_dimension.is_power_of_two()
+            && lwe_dimension.checked_add(1) == Some(list.lwe_size)
+            && list.ciphertext_count > 0,
+        ValidationFailure::CiphertextDimension,
+    )?;
+    let mask_count = list.ciphertext_count.div_ceil(lwe_dimension);
+    let expected_len = mask_count
+        .checked_mul(lwe_dimension)
+        .and_then(|masks_len| masks_len.checked_add(list.ciphertext_count));
+    ensure(
+        expected_len == Some(list.data.len()),
+        ValidationFailure::CiphertextDimension,
+    )?;
+    ensure(
+        list.ciphertext_modulus == ciphertext_modulus,
+        ValidationFailure::CiphertextModulus,
+    )
+}
+
+pub fn validate_glwe_secret_key(secret_key: &GlweSecretKeyData, params: &ProofParams) -> Check {
+    ensure(
+        secret_key.polynomial_size == params.polynomial_size,
+        ValidationFailure::PolynomialSize,
+    )?;
+    ensure(
+        secret_key.data.len() == big_lwe_dimension(params)?,
+        ValidationFailure::SecretKeyDimension,
+    )
+}
+
+pub fn validate_glwe_ciphertext(
+    ciphertext: &GlweCiphertextData,
+    params: &ProofParams,
+    ciphertext_modulus: u128,
+) -> Check {
+    ensure(
+        ciphertext.polynomial_size == params.polynomial_size,
+        ValidationFailure::PolynomialSize,
+    )?;
+    let expected_len = params
+        .glwe_dimension
+        .checked_add(1)
+        .and_then(|glwe_size| glwe_size.checked_mul(params.polynomial_size));
+    ensure(
+        params.polynomial_size > 0 && expected_len == Some(ciphertext.data.len()),
+        ValidationFailure::GlweSize,
+    )?;
+    ensure(
+        ciphertext.ciphertext_modulus == ciphertext_modulus,
+        ValidationFailure::CiphertextModulus,
+    )
+}
+
+/// Check that the keyswitching key goes from the big LWE key to the small one with the committed
+/// decomposition.
+pub fn validate_keyswitch_key(ksk: &LweKeyswitchKeyData, params: &ProofParams) -> Check {
+    ensure(
+        ksk.decomposition_base_log == params.ks_base_log
+            && ksk.decomposition_level_count == params.ks_level,
+        ValidationFailure::Decomposition,
+    )?;
+    validate_decomposition(ksk)?;
+    let expected_len = big_lwe_dimension(params)?
+        .checked_mul(ksk.decomposition_level_count)
+        .and_then(|len| len.checked_mul(ksk.output_lwe_size));
+    ensure(
+        params.lwe_dimension.checked_add(1) == Some(ksk.output_lwe_size)
+            && expected_len == Some(ksk.data.len()),
+        ValidationFailure::CiphertextDimension,
+    )?;
+    ensure(ksk.ciphertext_modulus == 0, ValidationFailure::CiphertextModulus)
+}
+
+/// Check that every coefficient of `secret_key` is 0 or 1, as the parameters generate them.
+pub fn validate_binary_secret_key<Scalar: Copy + Into<u128>>(
+    secret_key: &LweSecretKeyData<Scalar>,
+) -> Check {
+    validate_binary_coefficients(&secret_key.data)
+}
+
+/// Check that every coefficient of the GLWE `secret_key` is 0 or 1.
+pub fn validate_binary_glwe_secret_key(secret_key: &GlweSecretKeyData) -> Check {
+    validate_binary_coefficients(&secret_key.data)
+}
+
+fn validate_binary_coefficients<Scalar: Copy + Into<u128>>(coefficients: &[Scalar]) -> Check {
+    ensure(
+        coefficients.iter().all(|&coefficient| coefficient.into() <= 1),
+        ValidationFailure::SecretKeyDistribution,
+    )
+}
+
+/// A decomposition tfhe-rs can apply to a native 64 bits coefficient.
+fn validate_decomposition(ksk: &LweKeyswitchKeyData) -> Check {
+    let decomposed_bits = ksk
+        .decomposition_base_log
+        .checked_mul(ksk.decomposition_level_count);
+    ensure(
+        ksk.decomposition_level_count > 0
+            && ksk.decomposition_base_log > 0
+            && decomposed_bits.is_some_and(|bits| bits <= u64::BITS as usize),
+        ValidationFailure::Decomposition,
+    )
+}
+
+/// Check a keyswitching key against its own decomposition and the ciphertext it switches, for the
+/// guests that take the key as is rather than from the parameters. Both are on the native modulus.
+pub fn validate_keyswitch_input(ksk: &LweKeyswitchKeyData, input: &LweCiphertextData) -> Check {
+    validate_decomposition(ksk)?;
+    // A level count of at least 1 and an output size of at least 2 keep the element length
+    // nonzero, an overflowing one is refused
+    let input_key_element_len = ksk.decomposition_level_count.checked_mul(ksk.output_lwe_size);
+    ensure(
+        ksk.output_lwe_size > 1
+            && input_key_element_len.is_some_and(|element_len| {
+                ksk.data.len().is_multiple_of(element_len)
+                    && input.data.len() == ksk.data.len() / element_len + 1
+            }),
+        ValidationFailure::CiphertextDimension,
+    )?;
+    ensure(
+        ksk.ciphertext_modulus == 0 && input.ciphertext_modulus == 0,
+        ValidationFailure::CiphertextModulus,
+    )
+}
+
+/// Check a keyswitching key against the ciphertexts it is proven to switch, for the keyswitch
+/// guest that reads no parameters. Everything is on the native modulus.
+pub fn validate_keyswitch(
+    ksk: &LweKeyswitchKeyData,
+    input: &LweCiphertextData,
+    output: &LweCiphertextData,
+) -> Check {
+    validate_keyswitch_input(ksk, input)?;
+    ensure(
+        output.data.len() == ksk.output_lwe_size,
+        ValidationFailure::CiphertextDimension,
+    )?;
+    ensure(output.ciphertext_modulus == 0, ValidationFailure::CiphertextModulus)
+}
+
+/// Check the NTT bootstrapping key header against the committed parameters, its container is read
+/// afterwards with the length checked here.
+pub fn validate_ntt_bootstrap_key(bsk: &NttLweBootstrapKeyHeader, params: &ProofParams) -> Check {
+    let glwe_size = params.glwe_dimension.checked_add(1);
+    ensure(glwe_size == Some(bsk.glwe_size), ValidationFailure::GlweSize)?;
+    ensure(
+        bsk.polynomial_size == params.polynomial_size,
+        ValidationFailure::PolynomialSize,
+    )?;
+    ensure(
+        bsk.decomposition_base_log == params.pbs_base_log
+            && bsk.decomposition_level_count == params.pbs_level,
+        ValidationFailure::Decomposition,
+    )?;
+    ensure(
+        bsk.ciphertext_modulus == NTT64_CIPHERTEXT_MODULUS,
+        ValidationFailure::CiphertextModulus,
+    )?;
+    // One GGSW ciphertext per coefficient of the small LWE key
+    let expected_container_len = [
+        bsk.decomposition_level_count,
+        bsk.glwe_size,
+        bsk.glwe_size,
+        params.polynomial_size,
+    ]
+    .into_iter()
+    .try_fold(params.lwe_dimension, usize::checked_mul);
+    ensure(
+        expected_container_len.is_some_and(|len| bsk.container_len == len as u64),
+        ValidationFailure::SecretKeyDimension,
+    )
+}
+
+/// Check the multi-bit bootstrapping key header against the committed parameters, its container is
+/// read afterwards with the length checked here.
+pub fn validate_multi_bit_bootstrap_key(
+    bsk: &LweMultiBitBootstrapKeyHeader,
+    params: &ProofParams,
+) -> Check {
+    let glwe_size = params.glwe_dimension.checked_add(1);
+    ensure(glwe_size == Some(bsk.glwe_size), ValidationFailure::GlweSize)?;
+    ensure(
+        bsk.polynomial_size == params.polynomial_size,
+        ValidationFailure::PolynomialSize,
+    )?;
+    ensure(
+        bsk.decomposition_base_log == params.pbs_base_log
+            && bsk.decomposition_level_count == params.pbs_level,
+        ValidationFailure::Decomposition,
+    )?;
+    ensure(bsk.ciphertext_modulus == 0, ValidationFailure::CiphertextModulus)?;
+    // The small LWE key splits in whole groups
+    ensure(
+        bsk.input_lwe_dimension == params.lwe_dimension
+            && (1..=MAX_GROUPING_FACTOR).contains(&bsk.grouping_factor)
+            && bsk.input_lwe_dimension.is_multiple_of(bsk.grouping_factor),
+        ValidationFailure::SecretKeyDimension,
+    )?;
+    // 2^grouping_factor GGSW ciphertexts per group
+    let ggsw_count = (bsk.input_lwe_dimension / bsk.grouping_factor) << bsk.grouping_factor;
+    let expected_container_len = [
+        bsk.decomposition_level_count,
+        bsk.glwe_size,
+        bsk.glwe_size,
+        params.polynomial_size,
+    ]
+    .into_iter()
+    .try_fold(ggsw_count, usize::checked_mul);
+    ensure(
+        expected_container_len.is_some_and(|len| bsk.container_len == len as u64),
+        ValidationFailure::SecretKeyDimension,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{NoiseDistribution, PbsOrder};
+
+    fn params() -> ProofParams {
+        ProofParams {
+            name: "test".into(),
+            lwe_dimension: 742,
+            glwe_dimension: 1,
+            polynomial_size: 2048,
+            lwe_noise_distribution: NoiseDistribution::TUniform { bound_log2: 41 },
+            glwe_noise_distribution: NoiseDistribution::TUniform { bound_log2: 17 },
+            pbs_base_log: 23,
+            pbs_level: 1,
+            ks_base_log: 3,
+            ks_level: 5,
+            pbs_order: PbsOrder::KeyswitchBootstrap,
+            encoding: EncodingParams {
+                message_bits: 2,
+                carry_bits: 2,
+                padding_bits: 1,
+            },
+            decoding: Decoding::Unsigned,
+            ciphertext_modulus: 0,
+            security_bits: 0,
+        }
+    }
+
+    fn with_modulus(ciphertext_modulus: u128) -> ProofParams {
+        ProofParams {
+            ciphertext_modulus,
+            ..params()
+        }
+    }
+
+    #[test]
+    fn odd_moduli_are_accepted() {
+        for modulus in [0, 33, NTT64_CIPHERTEXT_MODULUS, (1 << 61) - 1, u64::MAX.into()] {
+            assert_eq!(validate_ciphertext_modulus(&with_modulus(modulus)), Ok(()), "{modulus}");
+        }
+    }
+
+    #[test]
+    fn even_and_out_of_range_moduli_are_rejected() {
+        for modulus in [1, 31, 32, 34, 1 << 40, (1 << 63) + 2, 1 << 64, (1 << 64) + 1] {
+            assert_eq!(
+                validate_ciphertext_modulus(&with_modulus(modulus)),
+                Err(ValidationFailure::CiphertextModulus),
+                "{modulus}"
+            );
+        }
+    }
+
+    #[test]
+    fn only_binary_keys_are_accepted() {
+        let binary = LweSecretKeyData { data: vec![0u64, 1, 1] };
+        assert_eq!(validate_binary_secret_key(&binary), Ok(()));
+        let ternary = LweSecretKeyData {
+            data: vec![0u128, 1, u128::MAX],
+        };
+        assert_eq!(
+            validate_binary_secret_key(&ternary),
+            Err(ValidationFailure::SecretKeyDistribution)
+        );
+        let glwe = GlweSecretKeyData {
+            data: vec![1, 2],
+            polynomial_size: 2,
+        };
+        assert_eq!(
+            validate_binary_glwe_secret_key(&glwe),
+            Err(ValidationFailure::SecretKeyDistribution)
+        );
+    }
+}
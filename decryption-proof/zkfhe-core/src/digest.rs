@@ -0,0 +1,28 @@
+//! Digests as verifiers exchange them: 32 bytes values, image IDs included, written as 64 hex
+//! characters.
+
+use alloc::format;
+use alloc::string::String;
+
+/// Parse a 32 bytes digest given as 64 hex characters, with or without a `0x` prefix.
+pub fn parse_digest(hex_digest: &str) -> Result<[u8; 32], String> {
+    let hex_digest = hex_digest.strip_prefix("0x").unwrap_or(hex_digest).as_bytes();
+    if hex_digest.len() != 64 {
+        return Err(format!(
+            "the digest is {} hex characters long, expected 64",
+            hex_digest.len()
+        ));
+    }
+    let mut digest = [0; 32];
+    for (byte, pair) in digest.iter_mut().zip(hex_digest.chunks(2)) {
+        *byte = (nibble(pair[0])? << 4) | nibble(pair[1])?;
+    }
+    Ok(digest)
+}
+
+fn nibble(character: u8) -> Result<u8, String> {
+    (character as char)
+        .to_digit(16)
+        .map(|digit| digit as u8)
+        .ok_or_else(|| format!("invalid hex character {:?}", character as char))
+}
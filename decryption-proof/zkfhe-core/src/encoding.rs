@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+
+/// How messages are encoded in the most significant bits of a native 64 or 128 bits plaintext.
+///
+/// From the MSB: `padding_bits` bits of padding, then `carry_bits` bits of carry and
+/// `message_bits` bits of message, the rest is left for the noise. This matches the shortint
+/// layout where a message modulus and a carry modulus share the same plaintext.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncodingParams {
+    pub message_bits: u32,
+    pub carry_bits: u32,
+    pub padding_bits: u32,
+}
+
+impl EncodingParams {
+    /// Number of bits holding the message and its carry
+    pub fn message_space_bits(&self) -> u32 {
+        self.message_bits + self.carry_bits
+    }
+
+    /// Number of most significant bits kept when decoding, padding included
+    pub fn total_bits(&self) -> u32 {
+        self.padding_bits + self.message_space_bits()
+    }
+
+    /// [`Self::total_bits`], `None` when the bit counts of a decoded encoding overflow it
+    pub fn checked_total_bits(&self) -> Option<u32> {
+        self.message_bits
+            .checked_add(self.carry_bits)
+            .and_then(|bits| bits.checked_add(self.padding_bits))
+    }
+
+    /// An encoding is usable when it keeps at least one bit and at most the whole plaintext
+    pub fn is_valid(&self) -> bool {
+        self.is_valid_for(u64::BITS)
+    }
+
+    /// Whether the encoding is usable on plaintexts of `plaintext_bits` bits
+    pub fn is_valid_for(&self, plaintext_bits: u32) -> bool {
+        self.message_bits > 0
+            && self
+                .checked_total_bits()
+                .is_some_and(|total_bits| total_bits <= plaintext_bits)
+    }
+
+    pub fn message_modulus(&self) -> u64 {
+        1u64 << self.message_bits
+    }
+
+    pub fn carry_modulus(&self) -> u64 {
+        1u64 << self.carry_bits
+    }
+
+    /// Size of the message space, message and carry included
+    pub fn message_space(&self) -> u64 {
+        1u64 << self.message_space_bits()
+    }
+
+    /// Whether the decoded `message` sets a padding bit, the overflow of a homomorphic computation
+    /// whose result left the message space
+    pub fn overflows(&self, message: u128) -> bool {
+        message >> self.message_space_bits() != 0
+    }
+
+    /// Scaling factor applied to a message to encode it
+    pub fn delta(&self) -> u64 {
+        1u64 << (u64::BITS - self.total_bits())
+    }
+
+    /// Scaling factor applied to a message to encode it in a 128 bits plaintext
+    pub fn delta_u128(&self) -> u128 {
+        1u128 << (u128::BITS - self.total_bits())
+    }
+
+    /// Encode `message` in a plaintext modulo the non-native `modulus`, the integer closest to
+    /// `message * modulus / 2^total_bits`. The arithmetic is exact, `modulus` is at most `2^64`.
+    pub fn encode_modulo(&self, message: u64, modulus: u128) -> u64 {
+        let total_bits = self.total_bits();
+        let scaled = message as u128 * modulus;
+        ((scaled + (1u128 << (total_bits - 1))) >> total_bits) as u64
+    }
+
+    /// Encode `message` in a plaintext on `ciphertext_modulus`, 0 standing for the native modulus.
+    pub fn encode_for_modulus(&self, message: u64, ciphertext_modulus: u128) -> u64 {
+        if ciphertext_modulus == 0 {
+            message.wrapping_mul(self.delta())
+        } else {
+            self.encode_modulo(message, ciphertext_modulus)
+        }
+    }
+
+    /// Round a plaintext modulo the non-native `modulus` to the closest multiple of
+    /// `modulus / 2^total_bits` and return the message with its carries, the counterpart of
+    /// [`Self::encode_modulo`].
+    pub fn decode_modulo(&self, plaintext: u64, modulus: u128) -> u64 {
+        let total_bits = self.total_bits();
+        let scaled = (plaintext as u128) << total_bits;
+        let rounded = (scaled + modulus / 2) / modulus;
+        (rounded % (1u128 << total_bits)) as u64
+    }
+
+    /// The message bits of the decoded `message` as a two's complement integer modulo the message
+    /// modulus, the carries are dropped.
+    pub fn signed_message(&self, message: u64) -> i64 {
+        let shift = u64::BITS - self.message_bits;
+        ((message << shift) as i64) >> shift
+    }
+
+    /// What a decryption outputs for the decoded `message`, with its carries, under `decoding`
+    /// on `ciphertext_modulus`, 0 standing for the native modulus.
+    pub fn output(&self, decoding: Decoding, message: u64, ciphertext_modulus: u128) -> u64 {
+        match decoding {
+            Decoding::Unsigned => message,
+            Decoding::Signed => self.signed_message(message) as u64,
+            Decoding::Torus => self.encode_for_modulus(message, ciphertext_modulus),
+        }
+    }
+
+    /// Base log of the single level decomposer rounding a decrypted plaintext to the padding, carry
+    /// and message bits
+    pub fn decomposer_base_log(&self) -> usize {
+        self.total_bits() as usize
+    }
+}
+
+/// What a decryption outputs once its plaintext is rounded, see [`EncodingParams::output`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decoding {
+    /// The message with its carries, as an unsigned integer
+    #[default]
+    Unsigned = 0,
+    /// The message alone, as a two's complement integer within the message modulus, sign
+    /// extended to 64 bits
+    Signed = 1,
+    /// The rounded plaintext itself, the message with its carries and padding scaled back on the
+    /// torus, for applications reading the phase rather than the message
+    Torus = 2,
+}
+
+/// Rounding of native plaintexts to the padding, carry and message bits of an encoding, computed
+/// once per encoding so a batch decodes every ciphertext with an add and a shift.
+///
+/// Adding half of delta carries into the kept bits exactly when the single level
+/// `SignedDecomposer::closest_representable` of tfhe-rs rounds up, and the wrapping add drops the
+/// overflow past the padding as the decomposer does, so `decode` returns the closest
+/// representable plaintext divided by delta without the division.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rounding<Scalar> {
+    half_delta: Scalar,
+    delta_log: u32,
+}
+
+impl Rounding<u64> {
+    /// The rounding of a valid encoding, see [`EncodingParams::is_valid`].
+    pub fn new(encoding: &EncodingParams) -> Self {
+        Self {
+            half_delta: encoding.delta() >> 1,
+            delta_log: u64::BITS - encoding.total_bits(),
+        }
+    }
+
+    /// The message with its carries encoded in `plaintext`.
+    pub fn decode(&self, plaintext: u64) -> u64 {
+        plaintext.wrapping_add(self.half_delta) >> self.delta_log
+    }
+}
+
+impl Rounding<u128> {
+    /// The rounding of an encoding valid on 128 bits plaintexts.
+    pub fn new(encoding: &EncodingParams) -> Self {
+        Self {
+            half_delta: encoding.delta_u128() >> 1,
+            delta_log: u128::BITS - encoding.total_bits(),
+        }
+    }
+
+    /// The message with its carries encoded in `plaintext`.
+    pub fn decode(&self, plaintext: u128) -> u128 {
+        plaintext.wrapping_add(self.half_delta) >> self.delta_log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODING: EncodingParams = EncodingParams {
+        message_bits: 2,
+        carry_bits: 2,
+        padding_bits: 1,
+    };
+
+    /// The smallest odd modulus above the encoded bits, a prime and the largest odd one.
+    const MODULI: [u128; 3] = [(1 << 5) + 1, (1 << 61) - 1, u64::MAX as u128];
+
+    #[test]
+    fn modulo_encoding_round_trips() {
+        for modulus in MODULI {
+            for message in 0..1 << ENCODING.total_bits() {
+                let plaintext = ENCODING.encode_modulo(message, modulus);
+                assert!(u128::from(plaintext) < modulus);
+                assert_eq!(ENCODING.decode_modulo(plaintext, modulus), message);
+            }
+        }
+    }
+
+    #[test]
+    fn modulo_decoding_rounds_to_the_closest_message() {
+        for modulus in MODULI {
+            // Half a step away from a message minus one still decodes to it
+            let margin = ((modulus >> (ENCODING.total_bits() + 1)) as u64).saturating_sub(1);
+            let plaintext = ENCODING.encode_modulo(3, modulus);
+            assert_eq!(ENCODING.decode_modulo(plaintext + margin, modulus), 3);
+            assert_eq!(ENCODING.decode_modulo(plaintext - margin, modulus), 3);
+        }
+    }
+
+    #[test]
+    fn modulo_decoding_wraps_around_the_modulus() {
+        // On the smallest modulus a step is a single plaintext, so `modulus - 1` is a whole step
+        // below the modulus and decodes to the largest message rather than wrapping to 0
+        assert_eq!(ENCODING.decode_modulo(32, MODULI[0]), 31);
+        for modulus in MODULI[1..].iter().copied() {
+            let largest = (modulus - 1) as u64;
+            assert_eq!(ENCODING.decode_modulo(largest, modulus), 0);
+            assert_eq!(ENCODING.decode_modulo(0, modulus), 0);
+        }
+    }
+}
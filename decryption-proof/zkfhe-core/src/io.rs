@@ -0,0 +1,374 @@
+//! Typed inputs exchanged between the host and the guests.
+//!
+//! TFHE entities are carried as their raw containers plus the metadata needed to rebuild them, so
+//! the host and the guests do not have to agree on a tfhe-rs version, and every input is serialized
+//! exactly once with the risc0 serde format through `env::write`/`env::read`.
+//!
+//! The NTT bootstrapping key of the PBS guest is the exception: only its header is serialized, its
+//! multi-megabyte container is sent raw right after the serialized input in frames of at most
+//! [`FRAME_BYTES`], each prefixed by its length in `u64` as a `u32`. The guest reads every frame
+//! straight into its buffer with `env::read_slice` and hashes it as it arrives, instead of
+//! deserializing the key element by element and hashing a copy of it.
+//!
+//! The decrypt guest keeps its public statement apart from its private inputs: the ciphertext is
+//! read from a public input frame, the length of its canonical encoding in bytes as a `u32` then
+//! the encoding padded with zeros to whole words, and only the secret key is serialized. The guest
+//! commits the SHA-256 of the frame's bytes, which anyone holding the ciphertext recomputes with
+//! [`crate::canonical`] alone.
+//!
+//! Ciphertext moduli are stored as a `u128`, 0 standing for the native modulus, as returned by
+//! `CiphertextModulus::get_custom_modulus`. LWE ciphertexts and secret keys are generic over their
+//! scalar, `u64` unless a 128 bits parameter set is proven.
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::budget::MemoryLayout;
+use crate::encoding::EncodingParams;
+use crate::policy::JournalPolicy;
+
+/// Size of the frames raw containers are streamed in, 1 MiB.
+pub const FRAME_BYTES: usize = 1 << 20;
+
+/// Number of `u64` in a full frame, the last frame of a container may be shorter.
+pub const FRAME_LEN: usize = FRAME_BYTES / core::mem::size_of::<u64>();
+
+/// Bound on the encoding a public input frame holds, in bytes, well over the LWE ciphertexts of
+/// every parameter set.
+pub const MAX_PUBLIC_INPUT_BYTES: usize = FRAME_BYTES;
+
+/// Number of bytes of a public input frame holding `len` bytes, without its length prefix: the
+/// encoding is padded with zeros to whole words.
+pub fn public_input_padded_len(len: usize) -> usize {
+    len.next_multiple_of(core::mem::size_of::<u32>())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LweCiphertextData<Scalar = u64> {
+    /// The mask followed by the body
+    pub data: Vec<Scalar>,
+    pub ciphertext_modulus: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LweSecretKeyData<Scalar = u64> {
+    pub data: Vec<Scalar>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlweSecretKeyData {
+    pub data: Vec<u64>,
+    pub polynomial_size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlweCiphertextData {
+    pub data: Vec<u64>,
+    pub polynomial_size: usize,
+    pub ciphertext_modulus: u128,
+}
+
+/// An LWE compact ciphertext list, as encrypted with an LWE compact public key: one mask per
+/// `lwe_size - 1` ciphertexts, then the body of every ciphertext.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LweCompactCiphertextListData {
+    pub data: Vec<u64>,
+    pub lwe_size: usize,
+    pub ciphertext_count: usize,
+    pub ciphertext_modulus: u128,
+}
+
+/// An LWE public key, its encryptions of zero one after the other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LwePublicKeyData {
+    pub data: Vec<u64>,
+    pub lwe_size: usize,
+    pub ciphertext_modulus: u128,
+}
+
+/// A bootstrapping key in the NTT domain of its (prime) ciphertext modulus, its `u64` container is
+/// sent raw in frames.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NttLweBootstrapKeyHeader {
+    pub container_len: u64,
+    pub glwe_size: usize,
+    pub polynomial_size: usize,
+    pub decomposition_base_log: usize,
+    pub decomposition_level_count: usize,
+    pub ciphertext_modulus: u128,
+}
+
+/// A multi-bit bootstrapping key in the standard domain, on the native modulus, its `u64`
+/// container is sent raw in frames like the NTT one. Every group of `grouping_factor` coefficients
+/// of the small LWE key has `2^grouping_factor` GGSW ciphertexts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LweMultiBitBootstrapKeyHeader {
+    pub container_len: u64,
+    pub input_lwe_dimension: usize,
+    pub glwe_size: usize,
+    pub polynomial_size: usize,
+    pub decomposition_base_log: usize,
+    pub decomposition_level_count: usize,
+    pub grouping_factor: usize,
+    pub ciphertext_modulus: u128,
+}
+
+/// The bootstrapping key the PBS guest blind rotates with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PbsBootstrapKeyHeader {
+    /// Classic key in the NTT domain, every ciphertext of the PBS lives on its prime modulus
+    Ntt(NttLweBootstrapKeyHeader),
+    /// Multi-bit key of the multi-bit parameter sets, the PBS runs on the native modulus
+    MultiBit(LweMultiBitBootstrapKeyHeader),
+}
+
+impl PbsBootstrapKeyHeader {
+    pub fn container_len(&self) -> u64 {
+        match self {
+            Self::Ntt(header) => header.container_len,
+            Self::MultiBit(header) => header.container_len,
+        }
+    }
+
+    /// Modulus of the accumulator and of every ciphertext of the PBS.
+    pub fn ciphertext_modulus(&self) -> u128 {
+        match self {
+            Self::Ntt(header) => header.ciphertext_modulus,
+            Self::MultiBit(header) => header.ciphertext_modulus,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LweKeyswitchKeyData {
+    pub data: Vec<u64>,
+    pub decomposition_base_log: usize,
+    pub decomposition_level_count: usize,
+    pub output_lwe_size: usize,
+    pub ciphertext_modulus: u128,
+}
+
+/// Material needed by the guest to re-perform the keyswitch of the PBS outputs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyswitchInput {
+    pub ksk: LweKeyswitchKeyData,
+    pub small_lwe_sk: LweSecretKeyData,
+}
+
+/// Input of the single ciphertext decryption guests other than the decrypt guest, read after the
+/// parameters whose encoding is used to decode the plaintext. The `u128` guest reads it with `u128`
+/// scalars, the modulus switch guest with the small LWE secret key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LweDecryptionInput<Scalar = u64> {
+    pub lwe_sk: LweSecretKeyData<Scalar>,
+    pub ciphertext: LweCiphertextData<Scalar>,
+}
+
+/// Input of the policy guest, read after the parameters: the ciphertext is decrypted and decoded
+/// as by the single ciphertext guest, the journal discloses what `policy` allows of its value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyDecryptionInput {
+    pub lwe_sk: LweSecretKeyData,
+    pub ciphertext: LweCiphertextData,
+    pub policy: JournalPolicy,
+}
+
+/// Input of the re-encryption guest, read after the parameters. The ciphertext is decrypted and
+/// its decoded message encrypted again under `recipient_public_key` as the subset sum of the
+/// encryptions of zero selected by `selection`, bit `i % 64` of word `i / 64` for the `i`-th
+/// encryption of zero. The selection is the randomness of the new encryption, it stays private.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReencryptionInput {
+    pub lwe_sk: LweSecretKeyData,
+    pub ciphertext: LweCiphertextData,
+    pub recipient_public_key: LwePublicKeyData,
+    pub selection: Vec<u64>,
+}
+
+/// Input of the consistency guest, read after the parameters: two ciphertexts, each with the secret
+/// key it is encrypted under, a big or a small key of the parameters. Both keys stay private.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistencyInput {
+    pub left: LweDecryptionInput,
+    pub right: LweDecryptionInput,
+}
+
+/// Input of the key rotation guest, read after the parameters: `old_ciphertext` is keyswitched
+/// under `ksk`, from the old key to `new_lwe_sk`, then decrypted and decoded with the encoding of
+/// the parameters. Only the commitment of the plaintext under `salt` is published.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRotationInput {
+    pub ksk: LweKeyswitchKeyData,
+    pub old_ciphertext: LweCiphertextData,
+    pub new_lwe_sk: LweSecretKeyData,
+    pub salt: [u8; 32],
+}
+
+/// Input of the keyswitch guest, no parameters are read: any keyswitching key on the native
+/// modulus can be proven, e.g. one rotating a key to its successor. `output` must be the keyswitch
+/// of `input` under `ksk`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyswitchProofInput {
+    pub ksk: LweKeyswitchKeyData,
+    pub input: LweCiphertextData,
+    pub output: LweCiphertextData,
+}
+
+/// Input of the key generation guest, read after the parameters: `ksk` must switch from
+/// `input_lwe_sk`, the flattened GLWE key, to `output_lwe_sk`, every encryption of a key element
+/// with a noise below `2^noise_bound_log2`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyGenerationInput {
+    pub ksk: LweKeyswitchKeyData,
+    pub input_lwe_sk: LweSecretKeyData,
+    pub output_lwe_sk: LweSecretKeyData,
+    pub noise_bound_log2: u32,
+}
+
+/// Everything the batch decryption guest reads before the batch, it is followed by the `batch_size`
+/// [`BatchItem`]s one by one. No evaluation key is needed to decrypt, the encoding is part of the
+/// parameters read first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecryptionInput {
+    pub big_lwe_sk: LweSecretKeyData,
+    pub keyswitch: Option<KeyswitchInput>,
+    /// Committed as is to the journal
+    pub seed_commitment: Option<[u8; 32]>,
+    /// Committed as is to the journal
+    pub circuit_digest: Option<[u8; 32]>,
+    pub batch_size: u32,
+}
+
+/// One PBS output of the batch along with its expected decrypted value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchItem {
+    pub cleartext_multiplication_result: u64,
+    pub pbs_multiplication_ct: LweCiphertextData,
+}
+
+/// Everything the PBS guest reads before the batch. It is followed by the raw container of the NTT
+/// bootstrapping key, then by the `batch_size` [`PbsBatchItem`]s one by one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PbsDecryptionInput {
+    pub accumulator: GlweCiphertextData,
+    /// Function table of the PBS, `lut[x]` for every `x` of the message space, the guest rebuilds
+    /// the accumulator from it and checks it matches `accumulator`
+    pub lut: Vec<u64>,
+    pub big_lwe_sk: LweSecretKeyData,
+    pub bsk: PbsBootstrapKeyHeader,
+    /// Committed as is to the journal
+    pub seed_commitment: Option<[u8; 32]>,
+    /// Committed as is to the journal
+    pub circuit_digest: Option<[u8; 32]>,
+    pub batch_size: u32,
+    pub memory: MemoryLayout,
+}
+
+/// One ciphertext of the batch along with its claimed PBS output and the expected decrypted value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PbsBatchItem {
+    pub lwe_ciphertext_in: LweCiphertextData,
+    pub cleartext_multiplication_result: u64,
+    pub pbs_multiplication_ct: LweCiphertextData,
+}
+
+/// Input of the threshold partial decryption guest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialDecryptionInput {
+    pub party_index: u32,
+    /// Additive share of the LWE secret key
+    pub key_share: Vec<u64>,
+    pub ciphertext: LweCiphertextData,
+    pub flooding_bound_log2: u32,
+    pub flooding_seed: [u8; 32],
+}
+
+/// One party of a distributed key generation: its additive share of the threshold secret key and
+/// its public key share, the body `<mask, key_share> + noise` of an encryption of zero under every
+/// common mask of the public key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgParty {
+    pub key_share: Vec<u64>,
+    pub public_key_share: Vec<u64>,
+}
+
+/// Input of the DKG guest, the transcript of a distributed generation of the threshold key:
+/// `public_key` is the aggregated key, its masks are common to every party and its bodies are the
+/// sums of the parties' bodies, so it encrypts zero under the sum of the key shares. The shares
+/// stay private, only their commitments are published.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgInput {
+    pub public_key: LwePublicKeyData,
+    /// Parties in the order of their index
+    pub parties: Vec<DkgParty>,
+    /// Bound on the noise of every public key share, `2^noise_bound_log2` excluded
+    pub noise_bound_log2: u32,
+}
+
+/// Input of the GLWE decryption guest, the whole polynomial plaintext of `ciphertext` is decoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlweDecryptionInput {
+    pub glwe_sk: GlweSecretKeyData,
+    pub ciphertext: GlweCiphertextData,
+}
+
+/// Input of the compact list decryption guest, the list is expanded in the guest and every
+/// ciphertext of it decrypted under `lwe_sk`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactListDecryptionInput {
+    pub lwe_sk: LweSecretKeyData,
+    pub list: LweCompactCiphertextListData,
+}
+
+/// Input of the sample extract guest, read after the parameters: `extracted` must be the LWE
+/// ciphertext of coefficient `monomial_degree` of `accumulator`, both on the native or the NTT
+/// modulus, e.g. the PBS output extracted from the blind rotated accumulator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampleExtractInput {
+    pub accumulator: GlweCiphertextData,
+    pub extracted: LweCiphertextData,
+    pub monomial_degree: u32,
+}
+
+/// Input of the shortint decryption guest, a shortint ciphertext lowered to its LWE ciphertext, the
+/// key it is encrypted under and its message/carry encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShortintDecryptionInput {
+    pub lwe_sk: LweSecretKeyData,
+    pub ciphertext: LweCiphertextData,
+    pub encoding: EncodingParams,
+}
+
+/// Input of the radix decryption guest, the little endian blocks of a radix integer ciphertext, all
+/// encrypted under `lwe_sk` with the same encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RadixDecryptionInput {
+    pub lwe_sk: LweSecretKeyData,
+    pub blocks: Vec<LweCiphertextData>,
+    pub encoding: EncodingParams,
+}
+
+/// A receipt verified by the aggregation or the chained decryption guest, identified by the image ID of the guest that
+/// produced it and its journal bytes. The receipt itself is added to the executor environment as
+/// an assumption.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregatedReceipt {
+    pub image_id: [u32; 8],
+    pub journal: Vec<u8>,
+}
+
+/// Input of the chained decryption guest, read after the parameters: `ciphertext` is decrypted as
+/// by the single ciphertext guest, and must be the output of the evaluation step `evaluation`
+/// attests. The evaluation receipt is verified in the guest as an assumption.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainedDecryptionInput {
+    pub lwe_sk: LweSecretKeyData,
+    pub ciphertext: LweCiphertextData,
+    pub evaluation: AggregatedReceipt,
+}
+
+/// Input of the aggregation guest, the inner receipts in the order of the Merkle tree leaves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregationInput {
+    pub receipts: Vec<AggregatedReceipt>,
+}
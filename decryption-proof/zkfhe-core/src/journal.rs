@@ -0,0 +1,426 @@
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::encoding::{Decoding, EncodingParams};
+use crate::params::{PbsOrder, ProofParams};
+use crate::policy::Disclosure;
+
+/// Version of the journal layout, the first field of every [`JournalEnvelope`]. It is bumped with
+/// any change to a journal struct, so a verifier decoding a journal it does not know the layout of
+/// fails on the version instead of misreading the payload.
+pub const JOURNAL_VERSION: u16 = 10;
+
+/// The guest a journal was committed by, one per guest binary.
+///
+/// The risc0 serde format encodes a kind as its declaration index, which the guests also commit as
+/// a raw word when rejecting their inputs, so every kind pins its index: a new kind is appended at
+/// the end and never renumbers the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum JournalKind {
+    Decrypt = 0,
+    DecryptU128 = 1,
+    PbsDecrypt = 2,
+    PartialDecrypt = 3,
+    BatchDecrypt = 4,
+    GlweDecrypt = 5,
+    ShortintDecrypt = 6,
+    RadixDecrypt = 7,
+    ModSwitchDecrypt = 8,
+    PolicyDecrypt = 9,
+    Reencrypt = 10,
+    Keyswitch = 11,
+    Aggregate = 12,
+    KeyGeneration = 13,
+    SampleExtract = 14,
+    CompactListDecrypt = 15,
+    /// Committed in place of its journal by any guest rejecting its inputs, the payload is the
+    /// [`crate::validation::ErrorCode`] of the failed check
+    Rejected = 16,
+    ChainedDecrypt = 17,
+    Consistency = 18,
+    KeyRotation = 19,
+    Dkg = 20,
+}
+
+/// What every guest commits: the layout version and the kind of the journal, then the journal
+/// itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEnvelope<Payload> {
+    pub version: u16,
+    pub kind: JournalKind,
+    pub payload: Payload,
+}
+
+impl<Payload> JournalEnvelope<Payload> {
+    /// The envelope of `payload` at the current [`JOURNAL_VERSION`].
+    pub fn new(kind: JournalKind, payload: Payload) -> Self {
+        Self {
+            version: JOURNAL_VERSION,
+            kind,
+            payload,
+        }
+    }
+}
+
+/// The leading fields of a [`JournalEnvelope`], decoded on their own to pick how the payload of a
+/// journal is decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalHeader {
+    pub version: u16,
+    pub kind: JournalKind,
+}
+
+/// Public output committed for every decrypted ciphertext.
+///
+/// Only a digest of the ciphertext is published, a verifier holding the ciphertext can recompute
+/// it, the ciphertext itself does not need to be part of the journal. The plaintext is a `u128` for
+/// decryptions on 128 bits parameter sets.
+///
+/// The noise is only committed as a budget, the bit length of the decoding bound, half the gap
+/// between two encoded messages, minus the bit length of the noise magnitude: a budget of 0 or 1
+/// is a borderline decryption, and a ciphertext whose noise reaches the bound cannot be proven.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecryptionJournal<Plaintext = u64> {
+    pub ct_digest: [u8; 32],
+    /// Key ID of the LWE secret key the ciphertext was decrypted with, the SHA-256 of its
+    /// container as recorded in the host key registry
+    pub secret_key_id: [u8; 32],
+    /// Digest of the ciphertext the decrypted one was derived from inside the guest, by a keyswitch
+    /// or a PBS, when provenance was proven
+    pub source_ct_digest: Option<[u8; 32]>,
+    pub plaintext: Plaintext,
+    /// Whether the plaintext sets a padding bit. The guests refuse to prove an overflow, a proven
+    /// journal commits `false`: its plaintext is the in range result of the computation
+    pub overflow: bool,
+    pub params_digest: [u8; 32],
+    /// The parameters `params_digest` commits to, as far as a verifier policy needs them
+    pub params: ParamsSummary,
+    /// Noise budget in bits, `None` for the decryptions of a batch, whose leaves carry no noise
+    pub noise_budget_bits: Option<u32>,
+    /// Opaque identifier of the external request the decryption answers, say the hash of an
+    /// on-chain request, echoed as the host passed it. A verifier answering a request checks it,
+    /// so the receipt cannot be replayed for another one
+    pub request_id: Option<[u8; 32]>,
+}
+
+/// The fields of a parameter set a verifier enforces a policy on, committed in readable form next
+/// to the digest of the whole set, so a verifier can refuse, say, any proof but a keyswitch first
+/// one at 128 bits of security without holding the parameters.
+///
+/// The guest derives it from the very parameters it hashes, it cannot disagree with the digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParamsSummary {
+    pub pbs_order: PbsOrder,
+    pub pbs_base_log: usize,
+    pub pbs_level: usize,
+    pub ks_base_log: usize,
+    pub ks_level: usize,
+    /// Modulus of the decrypted ciphertexts, 0 for the native modulus
+    pub ciphertext_modulus: u128,
+    /// Modulus of the messages and of their carries, as powers of two
+    pub message_bits: u32,
+    pub carry_bits: u32,
+    /// How the plaintext of the journal reads: a signed plaintext is an `i64` cast to `u64`
+    pub decoding: Decoding,
+    pub security_bits: u32,
+}
+
+impl From<&ProofParams> for ParamsSummary {
+    fn from(params: &ProofParams) -> Self {
+        Self {
+            pbs_order: params.pbs_order,
+            pbs_base_log: params.pbs_base_log,
+            pbs_level: params.pbs_level,
+            ks_base_log: params.ks_base_log,
+            ks_level: params.ks_level,
+            ciphertext_modulus: params.ciphertext_modulus,
+            message_bits: params.encoding.message_bits,
+            carry_bits: params.encoding.carry_bits,
+            decoding: params.decoding,
+            security_bits: params.security_bits,
+        }
+    }
+}
+
+/// Public output of the policy guest: the digests of a single decryption and what the journal
+/// policy discloses of its plaintext.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyDecryptionJournal {
+    pub ct_digest: [u8; 32],
+    /// Key ID of the LWE secret key the ciphertext was decrypted with
+    pub secret_key_id: [u8; 32],
+    pub disclosure: Disclosure,
+    pub params_digest: [u8; 32],
+}
+
+/// Public output of a re-encryption: the ciphertext `reencrypted_ct_digest` encrypts, under the
+/// public key `recipient_pk_digest`, the message `ct_digest` decrypts to under the secret key of ID
+/// `secret_key_id`. Neither the message nor the randomness of the new encryption is committed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReencryptionJournal {
+    pub ct_digest: [u8; 32],
+    pub secret_key_id: [u8; 32],
+    pub reencrypted_ct_digest: [u8; 32],
+    pub recipient_pk_digest: [u8; 32],
+    pub params_digest: [u8; 32],
+}
+
+/// Public output of a consistency proof: whether the ciphertexts `left_ct_digest` and
+/// `right_ct_digest`, under the secret keys of IDs `left_secret_key_id` and `right_secret_key_id`,
+/// decrypt to the same message. Neither message is committed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistencyJournal {
+    pub left_ct_digest: [u8; 32],
+    pub left_secret_key_id: [u8; 32],
+    pub right_ct_digest: [u8; 32],
+    pub right_secret_key_id: [u8; 32],
+    pub equal: bool,
+    pub params_digest: [u8; 32],
+}
+
+/// Public output of a key rotation proof: the ciphertext `new_ct_digest` is the keyswitch of
+/// `old_ct_digest` under the keyswitching key `ksk_id`, and decrypts under the secret key of ID
+/// `new_secret_key_id` to the plaintext `plaintext_commitment` commits to. Together with the key
+/// generation receipt of `ksk_id`, it shows the rotation preserved the plaintext.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRotationJournal {
+    /// Digest of the keyswitching key, also its key registry ID
+    pub ksk_id: [u8; 32],
+    pub old_ct_digest: [u8; 32],
+    pub new_ct_digest: [u8; 32],
+    pub new_secret_key_id: [u8; 32],
+    /// `SHA-256(salt || plaintext)`, opened with a [`crate::policy::PlaintextOpening`]
+    pub plaintext_commitment: [u8; 32],
+    pub params_digest: [u8; 32],
+}
+
+/// Public output of a keyswitch proof: the ciphertext `output_ct_digest` is the keyswitch of the
+/// ciphertext `input_ct_digest` under the keyswitching key `ksk_id`, no secret key is involved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyswitchJournal {
+    /// Digest of the keyswitching key, also its key registry ID
+    pub ksk_id: [u8; 32],
+    pub input_ct_digest: [u8; 32],
+    pub output_ct_digest: [u8; 32],
+}
+
+/// Public output of a key generation proof: every encryption of the keyswitching key `ksk_id`
+/// decrypts under the output secret key to its level of the matching input key element, with a
+/// noise below `2^noise_bound_log2`, so the key switches from `input_secret_key_id` to
+/// `output_secret_key_id` with the decomposition of the parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyGenerationJournal {
+    /// Digest of the keyswitching key, also its key registry ID
+    pub ksk_id: [u8; 32],
+    /// Key ID of the big LWE secret key, the flattened GLWE key the key switches from
+    pub input_secret_key_id: [u8; 32],
+    /// Key ID of the small LWE secret key the key switches to
+    pub output_secret_key_id: [u8; 32],
+    pub params_digest: [u8; 32],
+    pub noise_bound_log2: u32,
+}
+
+/// One decryption of a batch, a leaf of the Merkle tree whose root the batch journal commits.
+///
+/// Its digest is `SHA-256(0x00 || ct_digest || plaintext || source_ct_digest)`, the plaintext as 8
+/// little endian bytes and the source digest omitted when there is none. Nodes are
+/// `SHA-256(0x01 || left || right)`, a node without a sibling is carried up unchanged and the root
+/// of an empty tree is all zeros.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecryptionLeaf {
+    pub ct_digest: [u8; 32],
+    /// Digest of the ciphertext the decrypted one was derived from inside the guest, by a keyswitch
+    /// or a PBS, when provenance was proven
+    pub source_ct_digest: Option<[u8; 32]>,
+    pub plaintext: u64,
+}
+
+/// Public output committed for a whole batch, the parameters are published in full so verifiers
+/// know which security level was used.
+///
+/// The decryptions themselves are only committed through the Merkle root of their
+/// [`DecryptionLeaf`]s, so the journal does not grow with the batch and a verifier can check a
+/// single decryption with its inclusion proof.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchJournal {
+    pub params: ProofParams,
+    /// Key ID of the LWE secret key the batch was decrypted with, the SHA-256 of its container as
+    /// recorded in the host key registry
+    pub secret_key_id: [u8; 32],
+    /// Key ID of the keyswitching key, when the guest performed the keyswitch
+    pub ksk_id: Option<[u8; 32]>,
+    /// Digest of the NTT bootstrapping key, when the guest performed the PBS itself, the
+    /// decryption-only guest does not read any evaluation key. It is also its key registry ID
+    pub ntt_bsk_digest: Option<[u8; 32]>,
+    /// The NTT the guest bootstrapped with, when it performed the PBS itself
+    pub ntt_params: Option<NttParams>,
+    /// Digest of the multi-bit bootstrapping key, when the guest performed the PBS itself with one
+    /// in place of an NTT key. It is also its key registry ID
+    pub multi_bit_bsk_digest: Option<[u8; 32]>,
+    /// Grouping factor of the multi-bit bootstrapping key, its decomposition and polynomial size
+    /// are the ones of the parameters
+    pub grouping_factor: Option<u32>,
+    /// Function table of the PBS checked by the guest against the accumulator, when the guest
+    /// performed the PBS itself
+    pub lut: Option<Vec<u64>>,
+    /// Commitment to the seed all the keys and ciphertexts were derived from, when the host was
+    /// run with a user supplied seed
+    pub seed_commitment: Option<[u8; 32]>,
+    /// Digest of the homomorphic circuit that produced the decrypted ciphertexts, as attached by
+    /// the host. The guest does not evaluate it, the digest links the decryptions to the
+    /// attestation of the computation that produced them
+    pub circuit_digest: Option<[u8; 32]>,
+    /// Whether a ciphertext of the batch is a trivial encryption, its mask all zeros, as produced
+    /// by the host `--trivial` mode. Such a proof only checks the pipeline, anyone can produce it
+    /// without the secret key
+    pub trivial: bool,
+    pub decryption_count: u32,
+    pub decryptions_root: [u8; 32],
+}
+
+/// The integer NTT a PBS guest bootstraps with, in place of the floating point FFT of the host so
+/// the blind rotation is exact and deterministic in the zkVM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NttParams {
+    /// Prime modulus of the bootstrapping key, every ciphertext of the PBS lives on it
+    pub ciphertext_modulus: u128,
+    /// Length of the negacyclic NTT, the polynomial size
+    pub polynomial_size: usize,
+    pub decomposition_base_log: usize,
+    pub decomposition_level_count: usize,
+}
+
+/// Public output of the decryption of a blind rotation input after its modulus switch to `2N`.
+///
+/// Both ciphertexts are committed: `ct_digest` is the digest of the native ciphertext, and
+/// `switched_ct_digest` the digest of its coefficients switched to `2^log_modulus`, as `u64`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModulusSwitchJournal {
+    pub ct_digest: [u8; 32],
+    pub switched_ct_digest: [u8; 32],
+    /// Key ID of the small LWE secret key the switched ciphertext was decrypted with
+    pub secret_key_id: [u8; 32],
+    pub log_modulus: u32,
+    pub plaintext: u64,
+    pub params_digest: [u8; 32],
+}
+
+/// Public output of a GLWE decryption, one decoded value per coefficient of the plaintext
+/// polynomial.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlweDecryptionJournal {
+    pub ct_digest: [u8; 32],
+    /// Key ID of the GLWE secret key the ciphertext was decrypted with, the ID of its flattened
+    /// LWE secret key
+    pub secret_key_id: [u8; 32],
+    pub plaintexts: Vec<u64>,
+    pub params_digest: [u8; 32],
+}
+
+/// Public output of a compact list decryption, one decoded value per ciphertext of the expanded
+/// list, in order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactListDecryptionJournal {
+    pub list_digest: [u8; 32],
+    /// Key ID of the LWE secret key the expanded ciphertexts were decrypted with
+    pub secret_key_id: [u8; 32],
+    pub plaintexts: Vec<u64>,
+    pub params_digest: [u8; 32],
+}
+
+/// Public output of a sample extract proof: the LWE ciphertext `extracted_ct_digest` is coefficient
+/// `monomial_degree` of the GLWE ciphertext `accumulator_digest`, no secret key is involved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampleExtractJournal {
+    pub accumulator_digest: [u8; 32],
+    pub extracted_ct_digest: [u8; 32],
+    pub monomial_degree: u32,
+    pub params_digest: [u8; 32],
+}
+
+/// Public output of a chained decryption: `plaintext` is the decryption of `ct_digest`, the output
+/// of the evaluation step whose journal, of kind `evaluation_kind`, hashes to
+/// `evaluation_journal_digest` and was committed by the guest `evaluation_image_id`.
+///
+/// The guest verified that receipt as an assumption, the receipt of the decryption only holds
+/// along with it. The guest cannot know the image IDs of the other guests, verifiers must check
+/// `evaluation_image_id` is the guest they trust for the step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainedDecryptionJournal {
+    pub evaluation_image_id: [u32; 8],
+    pub evaluation_kind: JournalKind,
+    pub evaluation_journal_digest: [u8; 32],
+    pub ct_digest: [u8; 32],
+    /// Key ID of the LWE secret key the ciphertext was decrypted with
+    pub secret_key_id: [u8; 32],
+    pub plaintext: u64,
+    pub params_digest: [u8; 32],
+}
+
+/// Public output of a shortint decryption, `message` is the decrypted value with its carries
+/// dropped, as returned by `shortint::ClientKey::decrypt`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShortintDecryptionJournal {
+    pub ct_digest: [u8; 32],
+    /// Key ID of the LWE secret key of the client key the ciphertext decrypts under, for its PBS
+    /// order
+    pub secret_key_id: [u8; 32],
+    pub encoding: EncodingParams,
+    pub message: u64,
+}
+
+/// Public output of a radix integer decryption, the block messages are recombined into `value`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RadixDecryptionJournal {
+    /// Digest of every block, in little endian block order
+    pub block_digests: Vec<[u8; 32]>,
+    /// Key ID of the LWE secret key every block was decrypted with
+    pub secret_key_id: [u8; 32],
+    pub encoding: EncodingParams,
+    pub value: u128,
+}
+
+/// Public output of a threshold partial decryption.
+///
+/// Combining the partial decryptions of all the parties holding a share of the secret key gives
+/// `body - sum(partial_decryption)`, the encoded plaintext plus noise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialDecryptionJournal {
+    pub party_index: u32,
+    /// Digest of the secret key share used, to be matched against the published share commitments
+    pub key_share_digest: [u8; 32],
+    pub ct_digest: [u8; 32],
+    /// Inner product of the ciphertext mask and the key share plus flooding noise
+    pub partial_decryption: u64,
+    /// The flooding noise is drawn uniformly in `[-2^flooding_bound_log2, 2^flooding_bound_log2]`
+    pub flooding_bound_log2: u32,
+    /// Digest of the seed the flooding noise was derived from
+    pub flooding_seed_digest: [u8; 32],
+}
+
+/// Public output of the DKG guest: the threshold key whose additive shares are committed to in
+/// `share_commitments` is the key `public_key_digest` encrypts zero under, every party's public key
+/// share was generated from its committed share with a noise below `2^noise_bound_log2`. The
+/// partial decryptions of party `i` commit `share_commitments[i]` as their `key_share_digest`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgJournal {
+    /// Digest of the secret key share of every party, in the order of their index
+    pub share_commitments: Vec<[u8; 32]>,
+    /// SHA-256 of the share commitments one after the other, naming the threshold key
+    pub key_commitment: [u8; 32],
+    /// Digest of the aggregated LWE public key
+    pub public_key_digest: [u8; 32],
+    pub noise_bound_log2: u32,
+}
+
+/// Public output of the aggregation guest, which verified `receipt_count` inner receipts.
+///
+/// Leaf `i` of the Merkle tree is `SHA-256(0x00 || image_id || journal)` of the i-th inner receipt,
+/// the image ID in little endian words, and every node is `SHA-256(0x01 || left || right)`. A node
+/// without a sibling is carried up unchanged, the root of an empty tree is all zeros.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregationJournal {
+    pub receipt_count: u32,
+    pub journals_root: [u8; 32],
+}
@@ -0,0 +1,24 @@
+//! The zkFHE library core, shared by the host, the guests and the verifiers: parameters, message
+//! encoding, the canonical encodings the digests are computed over, guest inputs, journals, guest
+//! validation exit codes and the arithmetic and checks of the decryption statements. Defining them
+//! once here keeps every side from drifting apart.
+//!
+//! The crate is `no_std` and free of tfhe-rs and risc0 types, so a guest, a native service or a
+//! WebAssembly verifier can depend on it alike.
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+pub mod budget;
+pub mod canonical;
+pub mod checks;
+pub mod digest;
+pub mod encoding;
+pub mod io;
+pub mod journal;
+pub mod math;
+pub mod modulus_switch;
+pub mod params;
+pub mod policy;
+pub mod profile;
+pub mod validation;
@@ -0,0 +1,191 @@
+//! Modular arithmetic of the LWE decryption, the hot loop of the decryption guests.
+//!
+//! With key dimensions in the thousands the dot product of the mask and the secret key is most of
+//! the cycles of a decryption proof. The generic dot products of tfhe-rs go through the
+//! `UnsignedInteger` abstractions and, on a non-native modulus, reduce every product with a 128
+//! bits division, a software routine on the 32 bits zkVM. Here the products are accumulated in
+//! 128 bits and reduced once per dot product, with a Barrett constant computed once per modulus.
+//! On the native modulus the dot product wraps over plain slices.
+//!
+//! The secret keys of tfhe-rs are binary, a [`BinaryLweSecretKey`] keeps the indices of the ones
+//! of such a key so its dot products are sums of mask coefficients, without a multiplication.
+//! Ciphertexts are laid out as their mask then their body, with reduced coefficients on a
+//! non-native modulus. The host tests check every routine against `decrypt_lwe_ciphertext`.
+
+use alloc::vec::Vec;
+
+/// A ciphertext modulus with the constants of its reductions, 0 standing for the native modulus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Modulus {
+    value: u64,
+    /// `floor((2^128 - 1) / value)`
+    barrett: u128,
+    /// `2^128 mod value`
+    two_pow_128: u64,
+}
+
+impl Modulus {
+    /// The modulus `ciphertext_modulus` of the parameters, 0 or `2^64` standing for the native
+    /// modulus. Panics on a modulus below 2 or above `2^64`, which the validation rejects first.
+    pub fn new(ciphertext_modulus: u128) -> Self {
+        if ciphertext_modulus == 0 || ciphertext_modulus == 1 << 64 {
+            return Self {
+                value: 0,
+                barrett: 0,
+                two_pow_128: 0,
+            };
+        }
+        assert!(
+            (2..1 << 64).contains(&ciphertext_modulus),
+            "invalid ciphertext modulus"
+        );
+        let value = ciphertext_modulus as u64;
+        let mut modulus = Self {
+            value,
+            barrett: u128::MAX / ciphertext_modulus,
+            two_pow_128: 0,
+        };
+        // 2^128 = (2^128 - 1) + 1
+        modulus.two_pow_128 = modulus.add(modulus.reduce(u128::MAX), 1);
+        modulus
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.value == 0
+    }
+
+    /// `x mod value`: the quotient estimated from the high half of `x * barrett` is at most two
+    /// short of the exact quotient, so at most two subtractions remain.
+    pub fn reduce(&self, x: u128) -> u64 {
+        if self.is_native() {
+            return x as u64;
+        }
+        let quotient = mul_high(x, self.barrett);
+        let modulus = self.value as u128;
+        let mut remainder = x - quotient * modulus;
+        while remainder >= modulus {
+            remainder -= modulus;
+        }
+        remainder as u64
+    }
+
+    /// `(a + b) mod value` of reduced `a` and `b`.
+    pub fn add(&self, a: u64, b: u64) -> u64 {
+        if self.is_native() {
+            return a.wrapping_add(b);
+        }
+        let sum = a as u128 + b as u128;
+        match sum.checked_sub(self.value as u128) {
+            Some(reduced) => reduced as u64,
+            None => sum as u64,
+        }
+    }
+
+    /// `(a - b) mod value` of reduced `a` and `b`.
+    pub fn sub(&self, a: u64, b: u64) -> u64 {
+        if self.is_native() || a >= b {
+            a.wrapping_sub(b)
+        } else {
+            a.wrapping_sub(b).wrapping_add(self.value)
+        }
+    }
+
+    /// `(a * b) mod value`.
+    pub fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+
+    /// `sum(mask[i] * key[i]) mod value`, with lazy reduction: the 128 bits products are summed,
+    /// the carries out of the 128 bits sum counted, and both reduced once.
+    pub fn dot_product(&self, mask: &[u64], key: &[u64]) -> u64 {
+        debug_assert_eq!(mask.len(), key.len());
+        if self.is_native() {
+            return mask
+                .iter()
+                .zip(key)
+                .fold(0u64, |acc, (&a, &s)| acc.wrapping_add(a.wrapping_mul(s)));
+        }
+        let (mut sum, mut carries) = (0u128, 0u64);
+        for (&a, &s) in mask.iter().zip(key) {
+            let (next, carry) = sum.overflowing_add(a as u128 * s as u128);
+            sum = next;
+            carries += u64::from(carry);
+        }
+        let carried = self.mul(self.reduce(carries as u128), self.two_pow_128);
+        self.add(self.reduce(sum), carried)
+    }
+
+    /// `body - <mask, key> mod value` of an LWE ciphertext laid out as its mask then its body.
+    pub fn decrypt_lwe(&self, lwe_sk: &[u64], ciphertext: &[u64]) -> u64 {
+        let (body, mask) = ciphertext.split_last().expect("empty LWE ciphertext");
+        let body = self.reduce(*body as u128);
+        self.sub(body, self.dot_product(mask, lwe_sk))
+    }
+}
+
+/// High 128 bits of the 256 bits product `x * y`, from four 64 bits products.
+fn mul_high(x: u128, y: u128) -> u128 {
+    const LOW: u128 = u64::MAX as u128;
+    let (x_high, x_low) = (x >> 64, x & LOW);
+    let (y_high, y_low) = (y >> 64, y & LOW);
+    let low_low = x_low * y_low;
+    let low_high = x_low * y_high;
+    let high_low = x_high * y_low;
+    let high_high = x_high * y_high;
+    let middle = (low_low >> 64) + (low_high & LOW) + (high_low & LOW);
+    high_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64)
+}
+
+/// A binary LWE secret key, as the indices of its ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryLweSecretKey {
+    dimension: usize,
+    ones: Vec<usize>,
+}
+
+impl BinaryLweSecretKey {
+    /// The binary key of the elements `key`, `None` when an element is neither 0 nor 1.
+    pub fn new(key: &[u64]) -> Option<Self> {
+        let mut ones = Vec::with_capacity(key.len() / 2 + 1);
+        for (index, &element) in key.iter().enumerate() {
+            match element {
+                0 => {}
+                1 => ones.push(index),
+                _ => return None,
+            }
+        }
+        Some(Self {
+            dimension: key.len(),
+            ones,
+        })
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// `<mask, key> mod modulus`, the sum of the mask coefficients at the ones of the key. On a
+    /// non-native modulus the sum of at most `2^32` coefficients fits in 128 bits, it is reduced
+    /// once.
+    pub fn dot_product(&self, modulus: &Modulus, mask: &[u64]) -> u64 {
+        assert_eq!(mask.len(), self.dimension, "mask and key dimensions differ");
+        if modulus.is_native() {
+            return self
+                .ones
+                .iter()
+                .fold(0u64, |acc, &index| acc.wrapping_add(mask[index]));
+        }
+        let sum = self
+            .ones
+            .iter()
+            .fold(0u128, |acc, &index| acc + mask[index] as u128);
+        modulus.reduce(sum)
+    }
+
+    /// `body - <mask, key> mod modulus`, see [`Modulus::decrypt_lwe`].
+    pub fn decrypt_lwe(&self, modulus: &Modulus, ciphertext: &[u64]) -> u64 {
+        let (body, mask) = ciphertext.split_last().expect("empty LWE ciphertext");
+        let body = modulus.reduce(*body as u128);
+        modulus.sub(body, self.dot_product(modulus, mask))
+    }
+}
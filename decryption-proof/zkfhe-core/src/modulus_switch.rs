@@ -0,0 +1,16 @@
+//! The modulus switch performed before a blind rotation, from the native 64 bits modulus to `2N`.
+//!
+//! Defined here so the guest proving a decryption after the switch and the host checking its
+//! journal switch ciphertexts the exact same way.
+
+/// Log2 of the modulus a blind rotation input is switched to, `2N` for polynomials of size `N`.
+pub fn blind_rotation_modulus_log(polynomial_size: usize) -> u32 {
+    polynomial_size.ilog2() + 1
+}
+
+/// Round a native coefficient to the modulus `2^log_modulus`, as the blind rotation does: adding
+/// half of the dropped range before shifting rounds instead of flooring.
+pub fn modulus_switch(coefficient: u64, log_modulus: u32) -> u64 {
+    let rounding = 1u64 << (u64::BITS - log_modulus - 1);
+    coefficient.wrapping_add(rounding) >> (u64::BITS - log_modulus)
+}
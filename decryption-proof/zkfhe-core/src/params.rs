@@ -0,0 +1,61 @@
+use alloc::string::String;
+use serde::{Deserialize, Serialize};
+
+use crate::encoding::{Decoding, EncodingParams};
+
+/// Noise distribution of a parameter set, kept independent of the tfhe-rs serialization format so
+/// parameter files stay readable.
+///
+/// Externally tagged as bincode and the risc0 serde format cannot decode internally tagged enums.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseDistribution {
+    Gaussian { std_dev: f64 },
+    TUniform { bound_log2: u32 },
+}
+
+/// Order of the keyswitch and the PBS of a parameter set, as the `PBSOrder` of tfhe-rs shortint
+/// ciphertexts: `KeyswitchBootstrap` ciphertexts rest under the big key and are keyswitched before
+/// they are bootstrapped, `BootstrapKeyswitch` ones rest under the small key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PbsOrder {
+    #[default]
+    KeyswitchBootstrap = 0,
+    BootstrapKeyswitch = 1,
+}
+
+/// The TFHE parameters used to generate keys, encrypt, bootstrap and decode.
+///
+/// The whole struct is sent to the guest and committed in the journal so verifiers know which
+/// parameters, and therefore which security level, a proof was generated with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofParams {
+    pub name: String,
+    pub lwe_dimension: usize,
+    pub glwe_dimension: usize,
+    pub polynomial_size: usize,
+    pub lwe_noise_distribution: NoiseDistribution,
+    pub glwe_noise_distribution: NoiseDistribution,
+    pub pbs_base_log: usize,
+    pub pbs_level: usize,
+    pub ks_base_log: usize,
+    pub ks_level: usize,
+    /// Order of the keyswitch and the PBS, parameter files without one are keyswitched first
+    #[serde(default)]
+    pub pbs_order: PbsOrder,
+    /// How messages are laid out in the plaintext, the guest builds its decoder from it
+    pub encoding: EncodingParams,
+    /// What the decryption outputs, parameter files without one decode unsigned messages. Only
+    /// the single ciphertext decryption guest outputs other decodings
+    #[serde(default)]
+    pub decoding: Decoding,
+    /// Modulus of the decrypted ciphertexts, 0 for the native modulus. A non-native modulus is
+    /// only supported by the single ciphertext decryption guest
+    #[serde(default)]
+    pub ciphertext_modulus: u128,
+    /// Security level in bits the parameters claim, as estimated by the host for the known sets,
+    /// 0 when no estimate backs them
+    #[serde(default)]
+    pub security_bits: u32,
+}
@@ -0,0 +1,61 @@
+//! What a decryption journal discloses about the plaintext, so one guest serves deployments
+//! publishing their decryptions and deployments keeping them private.
+
+use serde::{Deserialize, Serialize};
+
+/// A statement about a decrypted value, proven in place of the value itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Predicate {
+    /// `plaintext < threshold`
+    LessThan(u64),
+    /// `plaintext == expected`
+    Equals(u64),
+    /// `min <= plaintext <= max`
+    InRange { min: u64, max: u64 },
+}
+
+impl Predicate {
+    /// Whether `plaintext` satisfies the predicate.
+    pub fn evaluate(&self, plaintext: u64) -> bool {
+        match *self {
+            Self::LessThan(threshold) => plaintext < threshold,
+            Self::Equals(expected) => plaintext == expected,
+            Self::InRange { min, max } => (min..=max).contains(&plaintext),
+        }
+    }
+}
+
+/// Private input of the policy guest choosing what its journal commits about the plaintext, the
+/// guest enforces it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JournalPolicy {
+    /// The decoded plaintext itself
+    Plaintext,
+    /// `SHA-256(salt || plaintext)`, the plaintext as 8 little endian bytes. The salt keeps the
+    /// few plaintexts of a small message space from being found by hashing them all, it is only
+    /// shared with the parties allowed to open the commitment
+    PlaintextHash { salt: [u8; 32] },
+    /// Whether the plaintext satisfies the predicate
+    Predicate(Predicate),
+    /// Nothing beyond the ciphertext and parameters digests, the proof only shows the ciphertext
+    /// decrypts under the key
+    DigestsOnly,
+}
+
+/// What the requester of a [`JournalPolicy::PlaintextHash`] proof reveals to open the committed
+/// hash later, e.g. once the bids of a sealed-bid auction are closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaintextOpening {
+    pub salt: [u8; 32],
+    pub plaintext: u64,
+}
+
+/// What the journal discloses under each [`JournalPolicy`], the salt of a plaintext hash is never
+/// committed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Disclosure {
+    Plaintext(u64),
+    PlaintextHash([u8; 32]),
+    Predicate { predicate: Predicate, holds: bool },
+    DigestsOnly,
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Cycles spent by the batch guest in each phase, summed over the whole batch.
+///
+/// The guest writes it to the host on stdout at the end of the execution, it is not part of the
+/// journal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CycleProfile {
+    /// Reading the inputs and rebuilding the tfhe entities
+    pub deserialization: u64,
+    /// Digests of the keys and ciphertexts
+    pub hashing: u64,
+    pub keyswitch: u64,
+    /// Rebuilding the PBS accumulator from the committed function table
+    pub lut_check: u64,
+    /// Building the NTT plan of the bootstrapping key, its twiddle factors are computed in the
+    /// guest
+    pub ntt_plan: u64,
+    pub blind_rotation: u64,
+    pub sample_extract: u64,
+    /// Decryption and decoding of the plaintexts
+    pub decryption: u64,
+}
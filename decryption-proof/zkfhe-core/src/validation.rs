@@ -0,0 +1,202 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Sanity check of the guest inputs against the committed parameters that failed.
+///
+/// The guests run these checks before any cryptographic operation, commit the check's
+/// [`ErrorCode`] and halt with its exit code, so the host can tell a malformed input from a wrong
+/// decryption. Exit code 0 is a
+/// successful run, the codes below are stable and must not be renumbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ValidationFailure {
+    /// The committed encoding keeps no message bit or more bits than the plaintext holds, or the
+    /// guest does not output its decoding
+    InvalidEncoding = 1,
+    /// A secret key does not have the dimension the parameters give it
+    SecretKeyDimension = 2,
+    /// A ciphertext does not have the dimension of the key it is decrypted with
+    CiphertextDimension = 3,
+    /// A GLWE ciphertext or the bootstrapping key has another GLWE size than the parameters
+    GlweSize = 4,
+    /// A GLWE ciphertext, a GLWE secret key or the bootstrapping key has another polynomial size
+    /// than the parameters
+    PolynomialSize = 5,
+    /// A ciphertext is not defined over the modulus its guest decrypts on
+    CiphertextModulus = 6,
+    /// The keyswitching or bootstrapping key decomposition differs from the parameters
+    Decomposition = 7,
+    /// A streamed container frame is empty, longer than `FRAME_LEN` or runs past the container
+    Framing = 8,
+    /// The inputs the parameters call for do not fit in the guest memory, or an allocation went
+    /// past the memory reserved for them
+    OutOfMemory = 9,
+    /// A secret key the parameters call binary holds a coefficient other than 0 or 1
+    SecretKeyDistribution = 10,
+    /// The claimed noise bound of a key reaches the top level of its decomposition, the key
+    /// elements could not be told apart from the noise
+    NoiseBound = 11,
+    /// The public input frame is longer than `MAX_PUBLIC_INPUT_BYTES`, is not padded with zeros or
+    /// is not the canonical encoding of the public statement, or a public input such as a function
+    /// table does not have the length the statement calls for
+    PublicInput = 12,
+}
+
+impl ValidationFailure {
+    pub const ALL: [ValidationFailure; 12] = [
+        ValidationFailure::InvalidEncoding,
+        ValidationFailure::SecretKeyDimension,
+        ValidationFailure::CiphertextDimension,
+        ValidationFailure::GlweSize,
+        ValidationFailure::PolynomialSize,
+        ValidationFailure::CiphertextModulus,
+        ValidationFailure::Decomposition,
+        ValidationFailure::Framing,
+        ValidationFailure::OutOfMemory,
+        ValidationFailure::SecretKeyDistribution,
+        ValidationFailure::NoiseBound,
+        ValidationFailure::PublicInput,
+    ];
+
+    pub fn exit_code(self) -> u8 {
+        self as u8
+    }
+
+    /// The check a guest exit code stands for, `None` for a successful run or an unknown code.
+    pub fn from_exit_code(exit_code: u32) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|failure| failure.exit_code() as u32 == exit_code)
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            ValidationFailure::InvalidEncoding => "invalid encoding",
+            ValidationFailure::SecretKeyDimension => {
+                "secret key dimension does not match the parameters"
+            }
+            ValidationFailure::CiphertextDimension => {
+                "ciphertext dimension does not match the secret key"
+            }
+            ValidationFailure::GlweSize => "GLWE size does not match the parameters",
+            ValidationFailure::PolynomialSize => "polynomial size does not match the parameters",
+            ValidationFailure::CiphertextModulus => "unexpected ciphertext modulus",
+            ValidationFailure::Decomposition => {
+                "key decomposition parameters do not match the parameters"
+            }
+            ValidationFailure::Framing => "malformed container frame",
+            ValidationFailure::OutOfMemory => "guest memory exhausted",
+            ValidationFailure::SecretKeyDistribution => "secret key is not binary",
+            ValidationFailure::NoiseBound => {
+                "noise bound reaches the top decomposition level of the key"
+            }
+            ValidationFailure::PublicInput => "malformed public input",
+        }
+    }
+}
+
+impl fmt::Display for ValidationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (exit code {})", self.description(), self.exit_code())
+    }
+}
+
+/// What a guest rejecting its inputs commits, in an envelope of kind
+/// [`crate::journal::JournalKind::Rejected`], before halting with the same exit code: the code of
+/// the failed [`ValidationFailure`]. A halted guest without it stopped for another reason than its
+/// inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorCode(pub u8);
+
+impl ErrorCode {
+    pub fn validation_failure(self) -> Option<ValidationFailure> {
+        ValidationFailure::from_exit_code(self.0.into())
+    }
+}
+
+impl From<ValidationFailure> for ErrorCode {
+    fn from(failure: ValidationFailure) -> Self {
+        Self(failure.exit_code())
+    }
+}
+
+/// Check of the decryption statement itself that failed, once the inputs passed validation.
+///
+/// Unlike a [`ValidationFailure`] the guest panics, so no receipt exists for a wrong decryption.
+/// The panic message is the [`fmt::Display`] of the failure, which carries its code, and
+/// [`DecryptionFailure::from_panic_message`] recovers it on the host. The codes are stable and
+/// must not be renumbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DecryptionFailure {
+    /// The decrypted value differs from the result the host claimed for the ciphertext
+    ResultMismatch = 1,
+    /// The noise of a ciphertext reaches the decoding bound, its decoding cannot be trusted
+    NoiseBound = 2,
+    /// A ciphertext the guest recomputes (PBS or keyswitch output) differs from the claimed one
+    RecomputationMismatch = 3,
+    /// An encryption of an evaluation key does not decrypt to its key element within the noise
+    /// bound, the key was not generated from the claimed secret keys
+    KeyNoise = 4,
+    /// A decoded plaintext sets a padding bit, a prior homomorphic computation overflowed the
+    /// message space and its result is out of range
+    PaddingOverflow = 5,
+}
+
+const DECRYPTION_FAILURE_PREFIX: &str = "decryption check failed (code ";
+
+impl DecryptionFailure {
+    pub const ALL: [DecryptionFailure; 5] = [
+        DecryptionFailure::ResultMismatch,
+        DecryptionFailure::NoiseBound,
+        DecryptionFailure::RecomputationMismatch,
+        DecryptionFailure::KeyNoise,
+        DecryptionFailure::PaddingOverflow,
+    ];
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|failure| failure.code() == code)
+    }
+
+    /// The failure a guest panic message reports, `None` for any other panic. The message may be
+    /// prefixed by the panic location.
+    pub fn from_panic_message(message: &str) -> Option<Self> {
+        let (_, rest) = message.split_once(DECRYPTION_FAILURE_PREFIX)?;
+        let (code, _) = rest.split_once(')')?;
+        Self::from_code(code.parse().ok()?)
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            DecryptionFailure::ResultMismatch => {
+                "decrypted value does not match the claimed result"
+            }
+            DecryptionFailure::NoiseBound => "the noise reaches the decoding bound",
+            DecryptionFailure::RecomputationMismatch => {
+                "recomputed ciphertext does not match the claimed one"
+            }
+            DecryptionFailure::KeyNoise => {
+                "key encryption does not decrypt to its key element within the noise bound"
+            }
+            DecryptionFailure::PaddingOverflow => {
+                "the decoded plaintext overflows into its padding"
+            }
+        }
+    }
+}
+
+impl fmt::Display for DecryptionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{DECRYPTION_FAILURE_PREFIX}{}): {}",
+            self.code(),
+            self.description()
+        )
+    }
+}